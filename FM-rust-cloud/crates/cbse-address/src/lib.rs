@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! 20-byte EVM account addresses
+//!
+//! `Address` is the one key type for accounts. It converts to and from the
+//! other forms an address shows up in: hex strings, 256-bit words and integers.
+//! Conversions from words and integers keep the low 160 bits, as the EVM does.
+//! `Display` gives the EIP-55 checksummed form Foundry prints.
+
+use cbse_bitvec::CbseBitVec;
+use cbse_exceptions::{CbseException, CbseResult};
+use cbse_hashes::keccak256;
+use num_bigint::BigUint;
+use std::borrow::Borrow;
+use std::fmt;
+use std::str::FromStr;
+
+/// EVM account address
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Address(pub [u8; 20]);
+
+impl Address {
+    pub const ZERO: Address = Address([0u8; 20]);
+
+    pub const fn new(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 20] {
+        &self.0
+    }
+
+    /// Parse 40 hex digits, with or without a 0x prefix, in any case
+    ///
+    /// The checksum is not verified.
+    pub fn from_hex(s: &str) -> CbseResult<Self> {
+        let digits = s.strip_prefix("0x").unwrap_or(s);
+        let invalid = || CbseException::Internal(format!("invalid address: {}", s));
+        if digits.len() != 40 {
+            return Err(invalid());
+        }
+        let bytes = hex::decode(digits).map_err(|_| invalid())?;
+        let mut addr = [0u8; 20];
+        addr.copy_from_slice(&bytes);
+        Ok(Self(addr))
+    }
+
+    /// Lowercase hex with a 0x prefix
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.0))
+    }
+
+    /// EIP-55 mixed-case checksum encoding
+    pub fn to_checksum(&self) -> String {
+        let lower = hex::encode(self.0);
+        let hash = keccak256(lower.as_bytes());
+        let digits: String = lower
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect();
+        format!("0x{}", digits)
+    }
+
+    /// Address made of the low 160 bits of `value`
+    pub fn from_biguint(value: &BigUint) -> Self {
+        let bytes = value.to_bytes_be();
+        let n = bytes.len().min(20);
+        let mut addr = [0u8; 20];
+        addr[20 - n..].copy_from_slice(&bytes[bytes.len() - n..]);
+        Self(addr)
+    }
+
+    pub fn to_biguint(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.0)
+    }
+
+    /// Address made of the low 160 bits of a concrete word
+    ///
+    /// Fails if `bv` is symbolic.
+    pub fn from_bitvec(bv: &CbseBitVec) -> CbseResult<Self> {
+        bv.as_biguint().map(|value| Self::from_biguint(&value))
+    }
+
+    /// The address as a concrete `size`-bit word
+    pub fn to_bitvec<'ctx>(&self, size: u32) -> CbseBitVec<'ctx> {
+        CbseBitVec::from_bytes(&self.0, size)
+    }
+}
+
+impl From<[u8; 20]> for Address {
+    fn from(bytes: [u8; 20]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8; 20]> for Address {
+    fn from(bytes: &[u8; 20]) -> Self {
+        Self(*bytes)
+    }
+}
+
+impl From<Address> for [u8; 20] {
+    fn from(addr: Address) -> Self {
+        addr.0
+    }
+}
+
+impl From<&BigUint> for Address {
+    fn from(value: &BigUint) -> Self {
+        Self::from_biguint(value)
+    }
+}
+
+impl From<Address> for BigUint {
+    fn from(addr: Address) -> Self {
+        addr.to_biguint()
+    }
+}
+
+impl<'ctx> TryFrom<&CbseBitVec<'ctx>> for Address {
+    type Error = CbseException;
+
+    fn try_from(bv: &CbseBitVec<'ctx>) -> CbseResult<Self> {
+        Self::from_bitvec(bv)
+    }
+}
+
+impl From<Address> for CbseBitVec<'_> {
+    /// The address as a 256-bit word
+    fn from(addr: Address) -> Self {
+        addr.to_bitvec(256)
+    }
+}
+
+impl PartialEq<[u8; 20]> for Address {
+    fn eq(&self, other: &[u8; 20]) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Lets maps keyed by `Address` be queried with raw bytes. Sound because the
+/// derived `Hash`, `Eq` and `Ord` only look at the wrapped array.
+impl Borrow<[u8; 20]> for Address {
+    fn borrow(&self) -> &[u8; 20] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Address {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for Address {
+    type Err = CbseException;
+
+    fn from_str(s: &str) -> CbseResult<Self> {
+        Self::from_hex(s)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_checksum())
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Address({})", self.to_checksum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_is_checksummed() {
+        // Test vectors from EIP-55
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let addr = Address::from_hex(&checksummed.to_lowercase()).unwrap();
+            assert_eq!(addr.to_string(), checksummed);
+            assert_eq!(addr.to_hex(), checksummed.to_lowercase());
+        }
+        assert_eq!(
+            Address::ZERO.to_string(),
+            "0x0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_from_hex_rejects_malformed_input() {
+        assert!(Address::from_hex("0x1234").is_err());
+        assert!(Address::from_hex(&format!("0x{}", "zz".repeat(20))).is_err());
+        assert_eq!(
+            "aa".repeat(20).parse::<Address>().unwrap(),
+            Address([0xaa; 20])
+        );
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let addr = Address::from_hex("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+
+        assert_eq!(Address::from_hex(&addr.to_string()).unwrap(), addr);
+        assert_eq!(Address::from(<[u8; 20]>::from(addr)), addr);
+        assert_eq!(Address::from(&addr.0), addr);
+        assert_eq!(Address::from(&BigUint::from(addr)), addr);
+        let word: CbseBitVec = addr.into();
+        assert_eq!(word.size(), 256);
+        assert_eq!(Address::try_from(&word).unwrap(), addr);
+    }
+
+    #[test]
+    fn test_words_keep_low_160_bits() {
+        let mut word = vec![0xff; 12];
+        word.extend([0x11; 20]);
+        let addr = Address::from_bitvec(&CbseBitVec::from_bytes(&word, 256)).unwrap();
+        assert_eq!(addr, [0x11; 20]);
+        assert_eq!(Address::from_biguint(&BigUint::from(7u8)).0[19], 7);
+    }
+}
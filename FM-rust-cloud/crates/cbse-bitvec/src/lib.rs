@@ -550,6 +550,11 @@ impl<'ctx> CbseBitVec<'ctx> {
         self.udiv_with_abstraction(other, ctx, None)
     }
 
+    /// Unsigned division, also returning whether the divisor may be zero
+    pub fn udiv_checked(&self, other: &Self, ctx: &'ctx Context) -> (Self, CbseBool<'ctx>) {
+        (self.udiv(other, ctx), other.is_zero(ctx))
+    }
+
     /// Unsigned division with optional abstraction
     pub fn udiv_with_abstraction(
         &self,
@@ -597,6 +602,11 @@ impl<'ctx> CbseBitVec<'ctx> {
         self.urem_with_abstraction(other, ctx, None)
     }
 
+    /// Unsigned modulo, also returning whether the divisor may be zero
+    pub fn urem_checked(&self, other: &Self, ctx: &'ctx Context) -> (Self, CbseBool<'ctx>) {
+        (self.urem(other, ctx), other.is_zero(ctx))
+    }
+
     /// Unsigned modulo with optional abstraction
     pub fn urem_with_abstraction(
         &self,
@@ -644,6 +654,11 @@ impl<'ctx> CbseBitVec<'ctx> {
         self.sdiv_with_abstraction(other, ctx, None)
     }
 
+    /// Signed division, also returning whether the divisor may be zero
+    pub fn sdiv_checked(&self, other: &Self, ctx: &'ctx Context) -> (Self, CbseBool<'ctx>) {
+        (self.sdiv(other, ctx), other.is_zero(ctx))
+    }
+
     /// Signed division with optional abstraction
     pub fn sdiv_with_abstraction(
         &self,
@@ -687,6 +702,11 @@ impl<'ctx> CbseBitVec<'ctx> {
         self.smod_with_abstraction(other, ctx, None)
     }
 
+    /// Signed modulo, also returning whether the divisor may be zero
+    pub fn smod_checked(&self, other: &Self, ctx: &'ctx Context) -> (Self, CbseBool<'ctx>) {
+        (self.smod(other, ctx), other.is_zero(ctx))
+    }
+
     /// Signed modulo with optional abstraction
     pub fn smod_with_abstraction(
         &self,
@@ -958,6 +978,59 @@ impl<'ctx> CbseBitVec<'ctx> {
         }
     }
 
+    /// Extract bits [hi:lo] (inclusive, LSB is bit 0); the result is hi - lo + 1 bits wide
+    pub fn extract_bits(&self, hi: u32, lo: u32, ctx: &'ctx Context) -> Self {
+        assert!(
+            lo <= hi && hi < self.size(),
+            "bit range [{}:{}] out of bounds for {}-bit value",
+            hi,
+            lo,
+            self.size()
+        );
+        let width = hi - lo + 1;
+
+        match self {
+            Self::Concrete { value, .. } => Self::from_biguint(value >> (lo as usize), width),
+            Self::Symbolic { .. } => Self::from_z3(self.as_z3(ctx).extract(hi, lo)),
+        }
+    }
+
+    /// Return a copy with bits [hi:lo] replaced by `value`, which must be hi - lo + 1 bits wide
+    pub fn set_bits(&self, hi: u32, lo: u32, value: &Self, ctx: &'ctx Context) -> Self {
+        let size = self.size();
+        assert!(
+            lo <= hi && hi < size,
+            "bit range [{}:{}] out of bounds for {}-bit value",
+            hi,
+            lo,
+            size
+        );
+        assert_eq!(
+            value.size(),
+            hi - lo + 1,
+            "replacement width does not match bit range"
+        );
+
+        match (self, value) {
+            (Self::Concrete { value: base, .. }, Self::Concrete { value: bits, .. }) => {
+                let field =
+                    ((BigUint::one() << value.size() as usize) - BigUint::one()) << (lo as usize);
+                let cleared = base & (mask(size) ^ field);
+                Self::from_biguint(cleared | (bits << (lo as usize)), size)
+            }
+            _ => {
+                let mut result = value.clone();
+                if hi + 1 < size {
+                    result = self.extract_bits(size - 1, hi + 1, ctx).concat(&result);
+                }
+                if lo > 0 {
+                    result = result.concat(&self.extract_bits(lo - 1, 0, ctx));
+                }
+                result
+            }
+        }
+    }
+
     /// Bitwise AND
     pub fn and(&self, other: &Self, ctx: &'ctx Context) -> Self {
         match (self, other) {
@@ -1132,29 +1205,14 @@ impl<'ctx> CbseBitVec<'ctx> {
                     })
                 }
                 Self::Symbolic { .. } => {
-                    // Extract from symbolic bitvector
-                    // Bits are indexed from LSB (0) to MSB (size-1)
-                    // But bytes are indexed from MSB
-                    let bit_len = self.size() as usize;
-                    let start_bit = (data_byte_len - offset - size_bytes) * 8;
-                    let end_bit = start_bit + (size_bytes * 8);
-
-                    if end_bit > bit_len {
-                        // Need zero padding
-                        let available_bits = bit_len - start_bit;
-                        let hi = (bit_len - start_bit - 1) as u32;
-                        let lo = 0u32;
-                        let extracted = self.as_z3(ctx).extract(hi, lo);
-                        let result = Self::from_z3(extracted);
-
-                        // Zero-extend to desired size
-                        let padding_bits = (size_bytes * 8) - available_bits;
-                        Ok(result.zero_extend(size_bits, ctx))
-                    } else {
-                        let hi = (end_bit - 1) as u32;
-                        let lo = start_bit as u32;
-                        Ok(Self::from_z3(self.as_z3(ctx).extract(hi, lo)))
-                    }
+                    // Bits are indexed from the LSB but bytes from the MSB. A
+                    // width that is not a whole number of bytes has its first
+                    // byte padded with leading zeros, as in to_bytes.
+                    let aligned = self.zero_extend((data_byte_len * 8) as u32, ctx);
+                    let lo = ((data_byte_len - offset - size_bytes) * 8) as u32;
+                    Ok(Self::from_z3(
+                        aligned.as_z3(ctx).extract(lo + size_bits - 1, lo),
+                    ))
                 }
             }
         } else {
@@ -1349,12 +1407,35 @@ impl<'ctx> CbseBitVec<'ctx> {
     }
 
     /// Create a bit vector from 32-byte slice (U256 format)
-    pub fn from_u256(_ctx: &'ctx Context, bytes: &[u8]) -> Self {
+    ///
+    /// Only the first 32 bytes are used; shorter slices are an error.
+    pub fn from_u256(_ctx: &'ctx Context, bytes: &[u8]) -> CbseResult<Self> {
         if bytes.len() < 32 {
-            return Self::from_u64(0, 256);
+            return Err(CbseException::Internal(format!(
+                "from_u256 expects 32 bytes, got {}",
+                bytes.len()
+            )));
         }
-        let value = BigUint::from_bytes_be(&bytes[0..32]);
-        Self::Concrete { value, size: 256 }
+        Ok(Self::from_bytes(&bytes[0..32], 256))
+    }
+
+    /// Create a 256-bit bit vector from an integer, keeping the low 256 bits
+    pub fn from_u256_biguint(value: BigUint) -> Self {
+        Self::from_biguint(value, 256)
+    }
+
+    /// Create a 160-bit bit vector from an address
+    pub fn from_address(addr: &[u8; 20], _ctx: &'ctx Context) -> Self {
+        Self::from_bytes(addr, 160)
+    }
+
+    /// Interpret the low 160 bits as an address (concrete values only)
+    pub fn as_address(&self) -> CbseResult<[u8; 20]> {
+        let value = self.as_biguint()? & mask(160);
+        let bytes = value.to_bytes_be();
+        let mut addr = [0u8; 20];
+        addr[20 - bytes.len()..].copy_from_slice(&bytes);
+        Ok(addr)
     }
 
     /// Convert to bytes (big-endian). Symbolic values are zero-filled to match the bit-width.
@@ -1409,6 +1490,157 @@ pub const ONE: u64 = 1;
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_extract_bits() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let word = CbseBitVec::from_u64(0x1234_5678, 256);
+        let nibble = word.extract_bits(11, 8, &ctx);
+        assert_eq!(nibble.size(), 4);
+        assert_eq!(nibble.as_u64().unwrap(), 0x6);
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let sym = x.extract_bits(11, 8, &ctx);
+        assert!(sym.is_symbolic());
+        assert_eq!(sym.size(), 4);
+    }
+
+    #[test]
+    fn test_set_bits() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let word = CbseBitVec::from_u64(0x1234_5678, 32);
+        let updated = word.set_bits(15, 8, &CbseBitVec::from_u64(0xab, 8), &ctx);
+        assert_eq!(updated.size(), 32);
+        assert_eq!(updated.as_u64().unwrap(), 0x1234_ab78);
+
+        // Symbolic base: the replaced range reads back, the rest is untouched
+        let x = CbseBitVec::symbolic(&ctx, "x", 32);
+        let updated = x.set_bits(15, 8, &CbseBitVec::from_u64(0xab, 8), &ctx);
+        assert_eq!(updated.size(), 32);
+        let solver = z3::Solver::new(&ctx);
+        solver.assert(
+            &updated
+                .extract_bits(15, 8, &ctx)
+                .as_z3(&ctx)
+                ._eq(&BV::from_u64(&ctx, 0xab, 8))
+                .not(),
+        );
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_extract_bytes_of_unaligned_symbolic_value() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // 12 bits occupy two bytes, the first padded with four leading zeros
+        let x = CbseBitVec::symbolic(&ctx, "x", 12);
+        let high = x.extract_bytes(0, 1, &ctx).unwrap();
+        let low = x.extract_bytes(1, 1, &ctx).unwrap();
+        assert_eq!((high.size(), low.size()), (8, 8));
+
+        let solver = z3::Solver::new(&ctx);
+        solver.assert(
+            &high
+                .as_z3(&ctx)
+                .concat(&low.as_z3(&ctx))
+                ._eq(&x.as_z3(&ctx).zero_ext(4))
+                .not(),
+        );
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_udiv_checked_flags_zero_divisor() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let a = CbseBitVec::from_u64(10, 256);
+
+        let (result, flag) = a.udiv_checked(&CbseBitVec::from_u64(0, 256), &ctx);
+        assert_eq!(result.as_u64().unwrap(), 0);
+        assert!(flag.is_true());
+
+        let (result, flag) = a.udiv_checked(&CbseBitVec::from_u64(3, 256), &ctx);
+        assert_eq!(result.as_u64().unwrap(), 3);
+        assert!(flag.is_false());
+
+        let y = CbseBitVec::symbolic(&ctx, "y", 256);
+        let (result, flag) = a.udiv_checked(&y, &ctx);
+        assert!(result.is_symbolic());
+        assert!(flag.is_symbolic());
+    }
+
+    #[test]
+    fn test_address_roundtrip() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let mut addr = [0u8; 20];
+        addr[0] = 0x7f;
+        addr[19] = 0x2a;
+        let bv = CbseBitVec::from_address(&addr, &ctx);
+        assert_eq!(bv.size(), 160);
+        assert_eq!(bv.as_address().unwrap(), addr);
+
+        // A 256-bit word keeps only its low 160 bits
+        let word = bv.zero_extend(256, &ctx).or(
+            &CbseBitVec::from_biguint(BigUint::one() << 200usize, 256),
+            &ctx,
+        );
+        assert_eq!(word.as_address().unwrap(), addr);
+
+        assert_eq!(
+            CbseBitVec::from_u64(0, 256).as_address().unwrap(),
+            [0u8; 20]
+        );
+    }
+
+    #[test]
+    fn test_symbolic_as_address_errors() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let x = CbseBitVec::symbolic(&ctx, "addr", 256);
+        assert!(matches!(x.as_address(), Err(CbseException::NotConcrete(_))));
+    }
+
+    #[test]
+    fn test_from_u256() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x80;
+        bytes[31] = 0x01;
+        let bv = CbseBitVec::from_u256(&ctx, &bytes).unwrap();
+        assert_eq!(bv.size(), 256);
+        assert_eq!(
+            bv.as_biguint().unwrap(),
+            (BigUint::one() << 255usize) + BigUint::one()
+        );
+
+        assert!(CbseBitVec::from_u256(&ctx, &[0u8; 20]).is_err());
+    }
+
+    #[test]
+    fn test_from_u256_biguint() {
+        let value = BigUint::from(0xdead_beefu64) << 200usize;
+        let bv = CbseBitVec::from_u256_biguint(value.clone());
+        assert_eq!(bv.size(), 256);
+        assert_eq!(bv.as_biguint().unwrap(), value);
+        assert_eq!(BigUint::from_bytes_be(&bv.to_bytes()), value);
+
+        // Wider values wrap to 256 bits
+        let wide = (BigUint::one() << 256usize) + BigUint::from(7u8);
+        assert_eq!(
+            CbseBitVec::from_u256_biguint(wide).as_biguint().unwrap(),
+            BigUint::from(7u8)
+        );
+    }
+
     #[test]
     fn test_is_power_of_two() {
         assert!(is_power_of_two(1));
@@ -94,6 +94,31 @@ fn defrag<'ctx>(data: Vec<UnwrappedBytes<'ctx>>) -> Vec<UnwrappedBytes<'ctx>> {
     output
 }
 
+/// Merge each run of adjacent concrete chunks, keeping symbolic chunks apart
+fn defrag_chunks<'ctx>(chunks: Vec<Chunk<'ctx>>) -> Vec<Chunk<'ctx>> {
+    let mut output = Vec::new();
+    let mut run: Vec<u8> = Vec::new();
+
+    for chunk in chunks {
+        match chunk {
+            Chunk::Concrete(c) => run.extend_from_slice(c.as_slice()),
+            symbolic => {
+                if !run.is_empty() {
+                    let bytes = std::mem::take(&mut run);
+                    output.push(Chunk::Concrete(ConcreteChunk::from_vec(bytes)));
+                }
+                output.push(symbolic);
+            }
+        }
+    }
+
+    if !run.is_empty() {
+        output.push(Chunk::Concrete(ConcreteChunk::from_vec(run)));
+    }
+
+    output
+}
+
 /// Concatenate a list of unwrapped bytes into a single value
 fn concat_unwrapped<'ctx>(
     data: Vec<UnwrappedBytes<'ctx>>,
@@ -235,9 +260,9 @@ impl<'ctx> Chunk<'ctx> {
     }
 
     /// Unwrap the chunk to raw data
-    pub fn unwrap(&self, ctx: &'ctx Context) -> UnwrappedBytes<'ctx> {
+    pub fn unwrap(&self, ctx: &'ctx Context) -> CbseResult<UnwrappedBytes<'ctx>> {
         match self {
-            Chunk::Concrete(c) => c.unwrap(),
+            Chunk::Concrete(c) => Ok(c.unwrap()),
             Chunk::Symbolic(s) => s.unwrap(ctx),
         }
     }
@@ -310,6 +335,17 @@ impl ConcreteChunk {
         }
     }
 
+    /// Wrap all of `data` (infallible counterpart of `new(data, 0, None)`)
+    fn from_vec(data: Vec<u8>) -> Self {
+        let length = data.len();
+        Self {
+            data,
+            start: 0,
+            length,
+            data_byte_length: length,
+        }
+    }
+
     /// Get a single byte at the given offset (O(1) operation)
     pub fn get_byte<'a>(&self, offset: usize) -> CbseResult<UnwrappedBytes<'a>> {
         if offset >= self.length {
@@ -332,6 +368,11 @@ impl ConcreteChunk {
         })
     }
 
+    /// Borrow the bytes in view
+    fn as_slice(&self) -> &[u8] {
+        &self.data[self.start..self.start + self.length]
+    }
+
     /// Unwrap to raw bytes (O(n) operation, actual copying happens here)
     pub fn unwrap<'a>(&self) -> UnwrappedBytes<'a> {
         if self.length == self.data_byte_length && self.start == 0 {
@@ -402,6 +443,13 @@ impl<'ctx> SymbolicChunk<'ctx> {
 
     /// Slice the chunk (O(1) operation, just creates a new view)
     pub fn slice(&self, start: usize, stop: usize) -> CbseResult<SymbolicChunk<'ctx>> {
+        if start > stop || stop > self.length {
+            return Err(CbseException::Internal(format!(
+                "Slice {}..{} out of bounds for a {}-byte chunk",
+                start, stop, self.length
+            )));
+        }
+
         Ok(SymbolicChunk {
             data: self.data.clone(),
             start: self.start + start,
@@ -411,19 +459,14 @@ impl<'ctx> SymbolicChunk<'ctx> {
     }
 
     /// Unwrap to raw bitvector (O(n) - involves Extract if not full data)
-    pub fn unwrap(&self, ctx: &'ctx Context) -> UnwrappedBytes<'ctx> {
+    ///
+    /// A partial view is extracted as a symbolic sub-bitvector of the data.
+    pub fn unwrap(&self, ctx: &'ctx Context) -> CbseResult<UnwrappedBytes<'ctx>> {
         if self.length == self.data_byte_length && self.start == 0 {
-            UnwrappedBytes::BitVec(self.data.clone())
-        } else {
-            // Extract the slice
-            match self.data.extract_bytes(self.start, self.length, ctx) {
-                Ok(extracted) => UnwrappedBytes::BitVec(extracted),
-                Err(_) => {
-                    // Fallback to zeros
-                    UnwrappedBytes::Bytes(vec![0; self.length])
-                }
-            }
+            return Ok(UnwrappedBytes::BitVec(self.data.clone()));
         }
+        let extracted = self.data.extract_bytes(self.start, self.length, ctx)?;
+        Ok(UnwrappedBytes::BitVec(extracted))
     }
 }
 
@@ -490,10 +533,16 @@ impl<'ctx> ChunkInfo<'ctx> {
 /// - set_word: write a 32-byte word
 /// - unwrap: returns the entire ByteVec as a single value
 /// - concretize: apply substitutions to symbolic values
+///
+/// Until the first symbolic write the contents are kept in a plain `Vec<u8>`
+/// ("dense" form), which is the common case for EVM memory. The chunked form
+/// is only built once a symbolic chunk has to be stored.
 pub struct ByteVec<'ctx> {
     /// Sorted map of start offset -> chunk
     /// BTreeMap is Rust's equivalent of Python's SortedDict
     chunks: BTreeMap<usize, Chunk<'ctx>>,
+    /// Contents while fully concrete; `chunks` is empty whenever this is set
+    dense: Option<Vec<u8>>,
     /// Total length in bytes
     length: usize,
     /// Z3 context (needed for symbolic operations)
@@ -505,6 +554,7 @@ impl<'ctx> ByteVec<'ctx> {
     pub fn new(ctx: &'ctx Context) -> Self {
         Self {
             chunks: BTreeMap::new(),
+            dense: Some(Vec::new()),
             length: 0,
             ctx,
         }
@@ -527,6 +577,7 @@ impl<'ctx> Clone for ByteVec<'ctx> {
     fn clone(&self) -> Self {
         Self {
             chunks: self.chunks.clone(),
+            dense: self.dense.clone(),
             length: self.length,
             ctx: self.ctx,
         }
@@ -541,9 +592,12 @@ impl<'ctx> ByteVec<'ctx> {
     }
 
     /// Create a ByteVec from a list of chunks
+    ///
+    /// Adjacent concrete chunks are merged up front, so the result holds one
+    /// chunk per concrete run even after a symbolic chunk.
     pub fn from_chunks(chunks: Vec<Chunk<'ctx>>, ctx: &'ctx Context) -> Self {
         let mut bv = Self::new(ctx);
-        for chunk in chunks {
+        for chunk in defrag_chunks(chunks) {
             bv.append_chunk(chunk);
         }
         bv
@@ -567,13 +621,42 @@ impl<'ctx> ByteVec<'ctx> {
 
     /// Get the number of chunks
     pub fn num_chunks(&self) -> usize {
-        self.chunks.len()
+        match self.dense {
+            Some(_) => usize::from(self.length > 0),
+            None => self.chunks.len(),
+        }
     }
 
     //
     // Internal methods
     //
 
+    /// Switch from the dense to the chunked form, keeping the contents
+    fn upgrade(&mut self) {
+        if let Some(bytes) = self.dense.take() {
+            if !bytes.is_empty() {
+                let chunk = ConcreteChunk::from_vec(bytes);
+                self.chunks.insert(0, Chunk::Concrete(chunk));
+            }
+        }
+    }
+
+    /// Write concrete `bytes` at `offset` in dense form, zero-filling any gap
+    ///
+    /// Returns false (writing nothing) if the ByteVec is no longer dense.
+    fn write_dense(&mut self, offset: usize, bytes: &[u8]) -> bool {
+        let Some(dense) = self.dense.as_mut() else {
+            return false;
+        };
+        let end = offset + bytes.len();
+        if end > dense.len() {
+            dense.resize(end, 0);
+        }
+        dense[offset..end].copy_from_slice(bytes);
+        self.length = dense.len();
+        true
+    }
+
     /// Locate the chunk that contains the given offset
     ///
     /// Complexity: O(log n) thanks to BTreeMap
@@ -631,6 +714,14 @@ impl<'ctx> ByteVec<'ctx> {
     ///
     /// Complexity: O(1)
     pub fn append_chunk(&mut self, chunk: Chunk<'ctx>) {
+        if let Chunk::Concrete(c) = &chunk {
+            if self.write_dense(self.length, c.as_slice()) {
+                return;
+            }
+        } else if !chunk.is_empty() {
+            self.upgrade();
+        }
+
         let start = self.length;
         if self.set_chunk(start, chunk.clone()) {
             self.length += chunk.len();
@@ -646,6 +737,10 @@ impl<'ctx> ByteVec<'ctx> {
 
     /// Append another ByteVec (unwraps and appends each chunk)
     pub fn append_bytevec(&mut self, other: &ByteVec<'ctx>) {
+        if let Some(bytes) = &other.dense {
+            self.append_chunk(Chunk::Concrete(ConcreteChunk::from_vec(bytes.clone())));
+            return;
+        }
         for chunk in other.chunks.values() {
             self.append_chunk(chunk.clone());
         }
@@ -656,6 +751,12 @@ impl<'ctx> ByteVec<'ctx> {
         let byte_chunk = Chunk::wrap(value)?;
         assert_eq!(byte_chunk.len(), 1, "Value must be a single byte");
 
+        match &byte_chunk {
+            Chunk::Concrete(c) if self.write_dense(offset, c.as_slice()) => return Ok(()),
+            Chunk::Concrete(_) => {}
+            Chunk::Symbolic(_) => self.upgrade(),
+        }
+
         if offset >= self.length {
             // Backfill with zeros
             let padding = vec![0u8; offset - self.length];
@@ -720,6 +821,12 @@ impl<'ctx> ByteVec<'ctx> {
             ));
         }
 
+        match &value_chunk {
+            Chunk::Concrete(c) if self.write_dense(start, c.as_slice()) => return Ok(()),
+            Chunk::Concrete(_) => {}
+            Chunk::Symbolic(_) => self.upgrade(),
+        }
+
         if start >= self.length {
             // Backfill with zeros
             let padding = vec![0u8; start - self.length];
@@ -806,6 +913,11 @@ impl<'ctx> ByteVec<'ctx> {
     ///
     /// Complexity: O(log n) + O(1) for concrete or O(n) for symbolic
     pub fn get_byte(&self, offset: usize) -> CbseResult<Byte<'ctx>> {
+        if let Some(dense) = &self.dense {
+            let byte = dense.get(offset).copied().unwrap_or(0);
+            return Ok(UnwrappedBytes::Bytes(vec![byte]));
+        }
+
         let chunk_info = self.load_chunk(offset);
         if !chunk_info.found() {
             return Ok(UnwrappedBytes::Bytes(vec![0])); // Out of bounds returns 0
@@ -829,6 +941,16 @@ impl<'ctx> ByteVec<'ctx> {
             return Ok(result);
         }
 
+        if let Some(dense) = &self.dense {
+            let mut bytes = vec![0u8; expected_length];
+            if start < dense.len() {
+                let end = stop.min(dense.len());
+                bytes[..end - start].copy_from_slice(&dense[start..end]);
+            }
+            result.write_dense(0, &bytes);
+            return Ok(result);
+        }
+
         let first_chunk = self.load_chunk(start);
         if !first_chunk.found() {
             // Entire slice is out of bounds
@@ -888,12 +1010,16 @@ impl<'ctx> ByteVec<'ctx> {
             return Ok(UnwrappedBytes::Bytes(Vec::new()));
         }
 
+        if let Some(dense) = &self.dense {
+            return Ok(UnwrappedBytes::Bytes(dense.clone()));
+        }
+
         // Unwrap all chunks
-        let unwrapped: Vec<UnwrappedBytes> = self
+        let unwrapped = self
             .chunks
             .values()
             .map(|chunk| chunk.unwrap(self.ctx))
-            .collect();
+            .collect::<CbseResult<Vec<UnwrappedBytes>>>()?;
 
         // Defragment: merge adjacent concrete bytes
         let defragged = defrag(unwrapped);
@@ -910,6 +1036,7 @@ impl<'ctx> ByteVec<'ctx> {
     pub fn copy(&self) -> Self {
         Self {
             chunks: self.chunks.clone(),
+            dense: self.dense.clone(),
             length: self.length,
             ctx: self.ctx,
         }
@@ -917,6 +1044,9 @@ impl<'ctx> ByteVec<'ctx> {
 
     /// Concretize all symbolic chunks with the given substitution
     pub fn concretize(&self, substitution: &BTreeMap<String, CbseBitVec<'ctx>>) -> Self {
+        if self.dense.is_some() {
+            return self.clone();
+        }
         let mut result = ByteVec::new(self.ctx);
         for chunk in self.chunks.values() {
             result.append_chunk(chunk.concretize(substitution, self.ctx));
@@ -926,20 +1056,26 @@ impl<'ctx> ByteVec<'ctx> {
 
     /// Dump the ByteVec for debugging (32 bytes per line)
     pub fn dump(&self) {
+        let _ = self.dump_to(&mut std::io::stdout());
+    }
+
+    /// Dump the ByteVec to `out` (32 bytes per line)
+    pub fn dump_to(&self, out: &mut dyn std::io::Write) -> std::io::Result<()> {
         for idx in (0..self.len()).step_by(32) {
             if let Ok(slice) = self.slice(idx, idx + 32) {
                 if let Ok(word) = slice.unwrap() {
                     match word {
                         UnwrappedBytes::Bytes(b) => {
-                            println!("{:04x}: 0x{}", idx, hex::encode(&b));
+                            writeln!(out, "{:04x}: 0x{}", idx, hex::encode(&b))?;
                         }
                         UnwrappedBytes::BitVec(_) => {
-                            println!("{:04x}: <symbolic>", idx);
+                            writeln!(out, "{:04x}: <symbolic>", idx)?;
                         }
                     }
                 }
             }
         }
+        Ok(())
     }
 }
 
@@ -967,6 +1103,9 @@ impl<'ctx> PartialEq for ByteVec<'ctx> {
 
 impl<'ctx> fmt::Debug for ByteVec<'ctx> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dense.is_some() {
+            return write!(f, "ByteVec(dense, length={})", self.length);
+        }
         write!(
             f,
             "ByteVec(chunks at {:?}, length={})",
@@ -979,6 +1118,7 @@ impl<'ctx> fmt::Debug for ByteVec<'ctx> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use z3::ast::Ast;
 
     #[test]
     fn test_concrete_chunk_creation() {
@@ -1015,4 +1155,155 @@ mod tests {
             _ => panic!("Expected concrete bytes"),
         }
     }
+
+    #[test]
+    fn test_from_chunks_merges_concrete_runs() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let byte_chunks = |range: std::ops::Range<u8>| {
+            range
+                .map(|b| Chunk::Concrete(ConcreteChunk::from_vec(vec![b])))
+                .collect::<Vec<_>>()
+        };
+
+        let bv = ByteVec::from_chunks(byte_chunks(0..100), &ctx);
+        assert_eq!(bv.num_chunks(), 1);
+        assert_eq!(concrete_slice(&bv, 0, 100), (0..100).collect::<Vec<u8>>());
+
+        // A symbolic chunk splits the runs but stays a chunk of its own
+        let x = CbseBitVec::symbolic(&ctx, "x", 16);
+        let mut chunks = byte_chunks(0..50);
+        chunks.push(Chunk::wrap(UnwrappedBytes::BitVec(x)).unwrap());
+        chunks.extend(byte_chunks(50..100));
+        let bv = ByteVec::from_chunks(chunks, &ctx);
+        assert_eq!(bv.num_chunks(), 3);
+        assert_eq!(bv.len(), 102);
+        assert_eq!(concrete_slice(&bv, 0, 50), (0..50).collect::<Vec<u8>>());
+        assert!(matches!(
+            bv.get_byte(50).unwrap(),
+            UnwrappedBytes::BitVec(_)
+        ));
+        assert_eq!(concrete_slice(&bv, 52, 102), (50..100).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_slice_inside_symbolic_word() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let mut bv = ByteVec::new(&ctx);
+        bv.set_word(0, UnwrappedBytes::BitVec(x.clone())).unwrap();
+
+        // Bytes 14..18 of the word are bits 143..112 of x
+        let middle = match bv.slice(14, 18).unwrap().unwrap().unwrap() {
+            UnwrappedBytes::BitVec(middle) => middle,
+            UnwrappedBytes::Bytes(b) => panic!("Expected symbolic bytes, got {:?}", b),
+        };
+        assert!(middle.is_symbolic());
+        assert_eq!(middle.size(), 32);
+
+        let solver = z3::Solver::new(&ctx);
+        solver.assert(
+            &middle
+                .as_z3(&ctx)
+                ._eq(&x.as_z3(&ctx).extract(143, 112))
+                .not(),
+        );
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+
+        // A view past the end of the chunk cannot be taken
+        let chunk = SymbolicChunk::new(x, 0, None).unwrap();
+        assert!(chunk.slice(30, 34).is_err());
+    }
+
+    /// Concrete bytes of `bv[start..stop]`
+    fn concrete_slice(bv: &ByteVec, start: usize, stop: usize) -> Vec<u8> {
+        match bv.slice(start, stop).unwrap().unwrap().unwrap() {
+            UnwrappedBytes::Bytes(b) => b,
+            UnwrappedBytes::BitVec(_) => panic!("Expected concrete bytes"),
+        }
+    }
+
+    #[test]
+    fn test_dense_and_chunked_agree() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let writes = |bv: &mut ByteVec| {
+            bv.set_word(0, UnwrappedBytes::Bytes(vec![0xaa; 32]))
+                .unwrap();
+            bv.set_byte(5, UnwrappedBytes::Bytes(vec![0x11])).unwrap();
+            bv.set_slice(30, 40, UnwrappedBytes::Bytes((0..10).collect()))
+                .unwrap();
+            bv.set_word(
+                64,
+                UnwrappedBytes::BitVec(CbseBitVec::from_u64(0x1234, 256)),
+            )
+            .unwrap();
+            bv.append(UnwrappedBytes::Bytes(vec![1, 2, 3])).unwrap();
+            bv.set_byte(120, UnwrappedBytes::Bytes(vec![0xff])).unwrap();
+        };
+
+        let mut dense = ByteVec::new(&ctx);
+        writes(&mut dense);
+        assert!(dense.dense.is_some());
+
+        let mut chunked = ByteVec::new(&ctx);
+        chunked.upgrade();
+        writes(&mut chunked);
+        assert!(chunked.dense.is_none());
+        assert!(chunked.num_chunks() > 1);
+
+        assert_eq!(dense.len(), chunked.len());
+        assert_eq!(dense, chunked);
+        for offset in 0..dense.len() + 8 {
+            assert_eq!(
+                dense.get_byte(offset).unwrap(),
+                chunked.get_byte(offset).unwrap()
+            );
+        }
+        assert_eq!(
+            concrete_slice(&dense, 28, 140),
+            concrete_slice(&chunked, 28, 140)
+        );
+
+        // The first symbolic write switches to chunks and keeps what was there
+        let x = CbseBitVec::symbolic(&ctx, "x", 8);
+        dense
+            .set_byte(7, UnwrappedBytes::BitVec(x.clone()))
+            .unwrap();
+        chunked.set_byte(7, UnwrappedBytes::BitVec(x)).unwrap();
+        assert!(dense.dense.is_none());
+        assert_eq!(concrete_slice(&dense, 0, 7), concrete_slice(&chunked, 0, 7));
+        assert_eq!(
+            concrete_slice(&dense, 8, 140),
+            concrete_slice(&chunked, 8, 140)
+        );
+        assert!(matches!(
+            dense.get_byte(7).unwrap(),
+            UnwrappedBytes::BitVec(_)
+        ));
+    }
+
+    #[test]
+    fn test_dense_64kb_of_concrete_writes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut bv = ByteVec::new(&ctx);
+
+        // Byte-by-byte writes would leave 64K chunks in the chunked form
+        for offset in 0..0x10000 {
+            bv.set_byte(offset, UnwrappedBytes::Bytes(vec![offset as u8]))
+                .unwrap();
+        }
+        for offset in (0..0x10000).step_by(32) {
+            let word = concrete_slice(&bv, offset, offset + 32);
+            bv.set_word(offset, UnwrappedBytes::Bytes(word)).unwrap();
+        }
+
+        assert_eq!(bv.len(), 0x10000);
+        assert_eq!(bv.num_chunks(), 1);
+        let expected: Vec<u8> = (0..0x10000).map(|i| i as u8).collect();
+        assert_eq!(concrete_slice(&bv, 0, 0x10000), expected);
+    }
 }
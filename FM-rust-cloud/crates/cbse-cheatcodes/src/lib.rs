@@ -7,12 +7,13 @@
 //! - Halmos SVM symbolic creation cheatcodes
 //! - Environment variable cheatcodes
 
-use z3::ast::BV;
+use z3::ast::{Ast, BV};
 use z3::{Context, FuncDecl, Sort};
 
 use cbse_bitvec::CbseBitVec;
 use cbse_bytevec::ByteVec;
 use cbse_exceptions::CbseException;
+use cbse_traces::DeployAddressMapper;
 
 /// Helper function to create a constant bitvector
 /// Helper function to create a concrete bitvector (matches Python con())
@@ -209,146 +210,193 @@ pub fn name_of(x: &str) -> String {
     x.split_whitespace().collect::<Vec<_>>().join("_")
 }
 
-/// Extract string argument from calldata at given argument index
-pub fn extract_string_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<String> {
-    // Get offset to string data (32 bytes per argument)
-    let offset_word = calldata.get_word(4 + 32 * arg_idx)?;
-    let offset_bv = match offset_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for offset"
-            )))
-        }
-    };
-    let offset = cbse_utils::unbox_int(&offset_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic offset for string argument".to_string())
-    })?;
-
-    // Get string length
-    let length_word = calldata.get_word((4 + offset) as usize)?;
-    let length_bv = match length_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
+/// Read a concrete word from calldata as an offset or length
+fn extract_usize_word<'ctx>(calldata: &ByteVec<'ctx>, offset: usize, what: &str) -> Result<usize> {
+    if calldata.len() < 32 || offset > calldata.len() - 32 {
+        return Err(malformed_calldata(
+            calldata,
+            format!("{} at {} is past the end", what, offset),
+        ));
+    }
+    let value = match calldata.get_word(offset)? {
         cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for length"
-            )))
+            // Anything above 64 bits cannot be a valid offset or length
+            if b[..24].iter().any(|&byte| byte != 0) {
+                return Err(malformed_calldata(
+                    calldata,
+                    format!("{} at {} is out of range", what, offset),
+                ));
+            }
+            cbse_utils::bytes_to_bv_value(&b)
         }
+        cbse_bytevec::UnwrappedBytes::BitVec(bv) => cbse_utils::unbox_int(&bv)
+            .ok_or_else(|| CbseException::NotConcrete(format!("symbolic {}", what)))?,
     };
-    let length = cbse_utils::unbox_int(&length_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic length for string argument".to_string())
-    })?;
+    Ok(value as usize)
+}
 
-    // Extract string bytes
-    let string_data_offset = (4 + offset + 32) as usize;
-    let string_slice = calldata.slice(string_data_offset, string_data_offset + length as usize)?;
-    let string_data = string_slice.unwrap()?;
+/// Error for offsets and lengths that point outside of calldata
+fn malformed_calldata(calldata: &ByteVec, detail: String) -> CbseException {
+    CbseException::AbiDecode(format!(
+        "malformed calldata ({} bytes): {}",
+        calldata.len(),
+        detail
+    ))
+}
 
-    let bytes = match string_data {
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => b,
+/// Extract `length` concrete bytes starting at `offset`
+fn extract_concrete_bytes<'ctx>(
+    calldata: &ByteVec<'ctx>,
+    offset: usize,
+    length: usize,
+) -> Result<Vec<u8>> {
+    if length == 0 {
+        return Ok(Vec::new());
+    }
+
+    match calldata.slice(offset, offset + length)?.unwrap()? {
+        cbse_bytevec::UnwrappedBytes::Bytes(b) => Ok(b),
         cbse_bytevec::UnwrappedBytes::BitVec(bv) => {
-            cbse_utils::bv_value_to_bytes(&bv).map_err(|e| CbseException::Internal(e))?
+            cbse_utils::bv_value_to_bytes(&bv).map_err(|e| CbseException::Internal(e))
         }
-    };
+    }
+}
 
-    String::from_utf8(bytes)
-        .map_err(|e| CbseException::Internal(format!("invalid UTF-8 in string argument: {}", e)))
+/// Extract the payload of a dynamic value whose length word is at `offset`
+///
+/// The length counts elements of `elem_size` bytes; the payload must fit
+/// within calldata.
+fn extract_dynamic_at<'ctx>(
+    calldata: &ByteVec<'ctx>,
+    offset: usize,
+    elem_size: usize,
+    what: &str,
+) -> Result<Vec<u8>> {
+    let length = extract_usize_word(calldata, offset, &format!("length for {}", what))?;
+    let start = offset + 32;
+    let size = length
+        .checked_mul(elem_size)
+        .filter(|size| *size <= calldata.len() - start)
+        .ok_or_else(|| {
+            malformed_calldata(
+                calldata,
+                format!(
+                    "{} of length {} at {} runs past the end",
+                    what, length, offset
+                ),
+            )
+        })?;
+    extract_concrete_bytes(calldata, start, size)
 }
 
-/// Extract bytes32 array argument from calldata
-pub fn extract_bytes32_array_argument<'ctx>(
+/// Extract the payload of the dynamic argument at `arg_idx`
+fn extract_dynamic_argument<'ctx>(
     calldata: &ByteVec<'ctx>,
     arg_idx: usize,
+    elem_size: usize,
+    what: &str,
 ) -> Result<Vec<u8>> {
-    // Get offset to array data
-    let offset_word = calldata.get_word(4 + 32 * arg_idx)?;
-    let offset_bv = match offset_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for offset"
-            )))
-        }
-    };
-    let offset = cbse_utils::unbox_int(&offset_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic offset for bytes32 array".to_string())
-    })?;
-
-    // Get array length
-    let length_word = calldata.get_word((4 + offset) as usize)?;
-    let length_bv = match length_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for length"
-            )))
-        }
-    };
-    let length = cbse_utils::unbox_int(&length_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic length for bytes32 array".to_string())
-    })?;
-
-    // Extract all array elements (32 bytes each)
-    let mut result = Vec::new();
-    for i in 0..length {
-        let element = calldata.get_word((4 + offset + 32 + i * 32) as usize)?;
-        let element_bytes = match element {
-            cbse_bytevec::UnwrappedBytes::BitVec(bv) => {
-                cbse_utils::bv_value_to_bytes(&bv).map_err(|e| CbseException::Internal(e))?
-            }
-            cbse_bytevec::UnwrappedBytes::Bytes(b) => b,
-        };
-        result.extend_from_slice(&element_bytes);
-    }
+    let offset = extract_usize_word(calldata, 4 + 32 * arg_idx, &format!("offset for {}", what))?;
+    extract_dynamic_at(calldata, offset.saturating_add(4), elem_size, what)
+}
 
-    Ok(result)
+/// Decode the `bytes` argument at `arg_idx`, bounds-checking its offset and
+/// length against the calldata size
+pub fn decode_dynamic_bytes<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<Vec<u8>> {
+    extract_dynamic_argument(calldata, arg_idx, 1, "bytes argument")
 }
 
-/// Extract bytes argument from calldata
-pub fn extract_bytes_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<Vec<u8>> {
-    // Get offset to bytes data
-    let offset_word = calldata.get_word(4 + 32 * arg_idx)?;
-    let offset_bv = match offset_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for offset"
-            )))
-        }
-    };
-    let offset = cbse_utils::unbox_int(&offset_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic offset for bytes argument".to_string())
-    })?;
-
-    // Get bytes length
-    let length_word = calldata.get_word((4 + offset) as usize)?;
-    let length_bv = match length_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(b) => {
-            return Err(CbseException::Internal(format!(
-                "unexpected concrete bytes for length"
-            )))
-        }
-    };
-    let length = cbse_utils::unbox_int(&length_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic length for bytes argument".to_string())
-    })?;
+/// Convert decoded string bytes, rejecting invalid UTF-8
+fn string_from_utf8(bytes: Vec<u8>) -> Result<String> {
+    String::from_utf8(bytes)
+        .map_err(|e| CbseException::AbiDecode(format!("invalid UTF-8 in string argument: {}", e)))
+}
 
-    // Extract bytes
-    let bytes_data_offset = (4 + offset + 32) as usize;
-    let bytes_slice = calldata.slice(bytes_data_offset, bytes_data_offset + length as usize)?;
-    let bytes_data = bytes_slice.unwrap()?;
+/// Extract string argument from calldata at given argument index
+pub fn extract_string_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<String> {
+    string_from_utf8(extract_dynamic_argument(
+        calldata,
+        arg_idx,
+        1,
+        "string argument",
+    )?)
+}
 
-    let result = match bytes_data {
+/// Extract address argument from calldata at given argument index
+pub fn extract_address_argument<'ctx>(
+    calldata: &ByteVec<'ctx>,
+    arg_idx: usize,
+) -> Result<[u8; 20]> {
+    let word = match calldata.get_word(4 + 32 * arg_idx)? {
         cbse_bytevec::UnwrappedBytes::Bytes(b) => b,
         cbse_bytevec::UnwrappedBytes::BitVec(bv) => {
+            if !bv.is_concrete() {
+                return Err(CbseException::NotConcrete(
+                    "symbolic address argument".to_string(),
+                ));
+            }
             cbse_utils::bv_value_to_bytes(&bv).map_err(|e| CbseException::Internal(e))?
         }
     };
 
+    let mut addr = [0u8; 20];
+    addr.copy_from_slice(&word[word.len() - 20..]);
+    Ok(addr)
+}
+
+/// Extract a concrete bool argument from calldata at given argument index
+pub fn extract_bool_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<bool> {
+    Ok(extract_usize_word(calldata, 4 + 32 * arg_idx, "bool argument")? != 0)
+}
+
+/// Extract a concrete uint argument that fits in 64 bits from calldata at given argument index
+pub fn extract_u64_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<u64> {
+    Ok(extract_usize_word(calldata, 4 + 32 * arg_idx, "uint64 argument")? as u64)
+}
+
+/// Extract string array argument from calldata at given argument index
+pub fn extract_string_array_argument<'ctx>(
+    calldata: &ByteVec<'ctx>,
+    arg_idx: usize,
+) -> Result<Vec<String>> {
+    let offset = extract_usize_word(calldata, 4 + 32 * arg_idx, "offset for string array")?;
+    let array_start = offset.saturating_add(4);
+    let length = extract_usize_word(calldata, array_start, "length for string array")?;
+
+    // Element offsets are relative to the first word after the array length
+    let elements_start = array_start + 32;
+    let mut result = Vec::with_capacity(length.min(calldata.len() / 32));
+    for i in 0..length {
+        let element_offset = extract_usize_word(
+            calldata,
+            elements_start + 32 * i,
+            "offset for string element",
+        )?;
+        let bytes = extract_dynamic_at(
+            calldata,
+            elements_start.saturating_add(element_offset),
+            1,
+            "string element",
+        )?;
+        result.push(string_from_utf8(bytes)?);
+    }
+
     Ok(result)
 }
 
+/// Extract bytes32 array argument from calldata
+pub fn extract_bytes32_array_argument<'ctx>(
+    calldata: &ByteVec<'ctx>,
+    arg_idx: usize,
+) -> Result<Vec<u8>> {
+    extract_dynamic_argument(calldata, arg_idx, 32, "bytes32 array")
+}
+
+/// Extract bytes argument from calldata
+pub fn extract_bytes_argument<'ctx>(calldata: &ByteVec<'ctx>, arg_idx: usize) -> Result<Vec<u8>> {
+    decode_dynamic_bytes(calldata, arg_idx)
+}
+
 /// Encode a single bytes value as tuple(bytes) for ABI return
 pub fn encode_tuple_bytes<'ctx>(data: &[u8], ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
     let length = data.len();
@@ -394,6 +442,49 @@ pub fn padded_bytes(val: &[u8], right_pad: bool) -> Vec<u8> {
     result
 }
 
+/// Encode a value of at most 256 bits as one left-padded 32-byte word
+pub fn encode_word<'ctx>(value: &CbseBitVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if value.size() > 256 {
+        return Err(CbseException::Internal(format!(
+            "cannot encode a {}-bit value in one word",
+            value.size()
+        )));
+    }
+    checked_word(word_result(uint256(value, ctx), ctx)?)
+}
+
+/// Encode an `n`-byte value as bytesN: left-aligned in a 32-byte word, right-padded with zeros
+pub fn encode_bytes_n<'ctx>(
+    value: &CbseBitVec<'ctx>,
+    n: u32,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    if n == 0 || n > 32 || value.size() != n * 8 {
+        return Err(CbseException::Internal(format!(
+            "cannot encode a {}-bit value as bytes{}",
+            value.size(),
+            n
+        )));
+    }
+    let word = if n == 32 {
+        value.clone()
+    } else {
+        value.concat(&con(0, 256 - n * 8, ctx))
+    };
+    checked_word(word_result(word, ctx)?)
+}
+
+/// Fail unless `encoded` is exactly one word
+fn checked_word(encoded: ByteVec<'_>) -> Result<ByteVec<'_>> {
+    if encoded.len() != 32 {
+        return Err(CbseException::Internal(format!(
+            "encoded word is {} bytes, expected 32",
+            encoded.len()
+        )));
+    }
+    Ok(encoded)
+}
+
 /// Encode array of word values (uint256, address, bool, bytes32, int256)
 pub fn abi_encode_array_words<'ctx>(
     values: &[CbseBitVec<'ctx>],
@@ -468,6 +559,32 @@ pub fn abi_encode_array_bytes<'ctx>(
 // Symbolic Creation Cheatcodes
 // ============================================================================
 
+/// Hands out monotonically increasing ids for cheatcode-created symbols
+///
+/// Every `create_*`/`random_*` call must take its id from the same counter so
+/// that two symbols with the same name never share a Z3 constant.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolCounter {
+    last: usize,
+}
+
+impl SymbolCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Next unused symbol id (starting from 1)
+    pub fn next_id(&mut self) -> usize {
+        self.last += 1;
+        self.last
+    }
+
+    /// Last id handed out, or 0 if none has been
+    pub fn current(&self) -> usize {
+        self.last
+    }
+}
+
 /// Create a generic symbolic value with given bit size
 pub fn create_generic<'ctx>(
     bits: u32,
@@ -493,29 +610,12 @@ pub fn create_uint<'ctx>(
     symbol_id: usize,
     ctx: &'ctx Context,
 ) -> Result<ByteVec<'ctx>> {
-    let bits_word = arg.get_word(4)?;
-    let bits_bv = match bits_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for bits".to_string(),
-            ))
-        }
-    };
-    let bits = cbse_utils::unbox_int(&bits_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic bit size for createUint".to_string())
-    })?;
-
-    if bits > 256 {
-        return Err(CbseException::Internal(
-            "createUint: bits must be <= 256".to_string(),
-        ));
-    }
+    let bits = extract_bits_argument(arg, 0, "createUint")?;
 
     let name = extract_string_argument(arg, 1)?;
     let name = name_of(&name);
 
-    let symbolic = create_generic(bits as u32, &name, &format!("uint{}", bits), symbol_id, ctx)?;
+    let symbolic = create_generic(bits, &name, &format!("uint{}", bits), symbol_id, ctx)?;
     let result = uint256(&symbolic, ctx);
 
     let mut bytevec = ByteVec::new(ctx);
@@ -548,25 +648,8 @@ pub fn create_uint256_min_max<'ctx>(
     let name = extract_string_argument(arg, 0)?;
     let name = name_of(&name);
 
-    let min_word = arg.get_word(4 + 32 * 1)?;
-    let min_bv = match min_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for min".to_string(),
-            ))
-        }
-    };
-
-    let max_word = arg.get_word(4 + 32 * 2)?;
-    let max_bv = match max_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for max".to_string(),
-            ))
-        }
-    };
+    let min_bv = extract_word_argument(arg, 1)?;
+    let max_bv = extract_word_argument(arg, 2)?;
 
     let symbolic = create_generic(256, &name, "uint256", symbol_id, ctx)?;
 
@@ -587,28 +670,12 @@ pub fn create_int<'ctx>(
     symbol_id: usize,
     ctx: &'ctx Context,
 ) -> Result<ByteVec<'ctx>> {
-    let bits_word = arg.get_word(4)?;
-    let bits_bv = match bits_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for bits".to_string(),
-            ))
-        }
-    };
-    let bits = cbse_utils::unbox_int(&bits_bv)
-        .ok_or_else(|| CbseException::NotConcrete("symbolic bit size for createInt".to_string()))?;
-
-    if bits > 256 {
-        return Err(CbseException::Internal(
-            "createInt: bits must be <= 256".to_string(),
-        ));
-    }
+    let bits = extract_bits_argument(arg, 0, "createInt")?;
 
     let name = extract_string_argument(arg, 1)?;
     let name = name_of(&name);
 
-    let symbolic = create_generic(bits as u32, &name, &format!("int{}", bits), symbol_id, ctx)?;
+    let symbolic = create_generic(bits, &name, &format!("int{}", bits), symbol_id, ctx)?;
     let result = uint256(&symbolic, ctx);
 
     let mut bytevec = ByteVec::new(ctx);
@@ -638,18 +705,7 @@ pub fn create_bytes<'ctx>(
     symbol_id: usize,
     ctx: &'ctx Context,
 ) -> Result<ByteVec<'ctx>> {
-    let byte_size_word = arg.get_word(4)?;
-    let byte_size_bv = match byte_size_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for size".to_string(),
-            ))
-        }
-    };
-    let byte_size = cbse_utils::unbox_int(&byte_size_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic byte size for createBytes".to_string())
-    })?;
+    let byte_size = extract_usize_word(arg, 4, "byte size for createBytes")?;
 
     let name = extract_string_argument(arg, 1)?;
     let name = name_of(&name);
@@ -659,24 +715,49 @@ pub fn create_bytes<'ctx>(
     encode_tuple_bytes(&bytes, ctx)
 }
 
+/// svm.createBytes(uint256 length, string name) with a symbolic `length`
+///
+/// Returns one candidate per entry of `lengths`: a fresh symbolic buffer of
+/// that size encoded as `bytes`, and a 1-bit flag that holds iff `length`
+/// equals it. The caller forks over (or picks among) the candidates.
+pub fn create_bytes_bounded<'ctx>(
+    name: &str,
+    length: &CbseBitVec<'ctx>,
+    symbol_id: usize,
+    lengths: &[usize],
+    ctx: &'ctx Context,
+) -> Result<Vec<(ByteVec<'ctx>, Vec<CbseBitVec<'ctx>>)>> {
+    let length = uint256(length, ctx);
+    lengths
+        .iter()
+        .map(|&n| {
+            let n_word = con(n as u64, 256, ctx);
+            let is_n = length.eq(&n_word, ctx).to_bitvec(ctx, 1);
+
+            let mut result = ByteVec::new(ctx);
+            result.append(cbse_bytevec::UnwrappedBytes::BitVec(con(32, 256, ctx)))?;
+            result.append(cbse_bytevec::UnwrappedBytes::BitVec(n_word))?;
+            if n > 0 {
+                let type_name = format!("bytes_{}", n);
+                let symbolic = create_generic((n * 8) as u32, name, &type_name, symbol_id, ctx)?;
+                result.append(cbse_bytevec::UnwrappedBytes::BitVec(symbolic))?;
+                let padding = (32 - n % 32) % 32;
+                if padding > 0 {
+                    result.append(cbse_bytevec::UnwrappedBytes::Bytes(vec![0u8; padding]))?;
+                }
+            }
+            Ok((result, vec![is_n]))
+        })
+        .collect()
+}
+
 /// svm.createString(uint256 length, string name)
 pub fn create_string<'ctx>(
     arg: &ByteVec<'ctx>,
     symbol_id: usize,
     ctx: &'ctx Context,
 ) -> Result<ByteVec<'ctx>> {
-    let byte_size_word = arg.get_word(4)?;
-    let byte_size_bv = match byte_size_word {
-        cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
-        cbse_bytevec::UnwrappedBytes::Bytes(_) => {
-            return Err(CbseException::Internal(
-                "unexpected concrete bytes for size".to_string(),
-            ))
-        }
-    };
-    let byte_size = cbse_utils::unbox_int(&byte_size_bv).ok_or_else(|| {
-        CbseException::NotConcrete("symbolic byte size for createString".to_string())
-    })?;
+    let byte_size = extract_usize_word(arg, 4, "byte size for createString")?;
 
     let name = extract_string_argument(arg, 1)?;
     let name = name_of(&name);
@@ -696,10 +777,7 @@ pub fn create_bytes4<'ctx>(
     let name = name_of(&name);
 
     let symbolic = create_generic(32, &name, "bytes4", symbol_id, ctx)?;
-    let mut result = ByteVec::new(ctx);
-    result.append(cbse_bytevec::UnwrappedBytes::BitVec(symbolic))?;
-    result.append(cbse_bytevec::UnwrappedBytes::Bytes(vec![0u8; 28]))?; // Pad right
-    Ok(result)
+    encode_bytes_n(&symbolic, 4, ctx)
 }
 
 /// svm.createBytes8(string name)
@@ -712,10 +790,7 @@ pub fn create_bytes8<'ctx>(
     let name = name_of(&name);
 
     let symbolic = create_generic(64, &name, "bytes8", symbol_id, ctx)?;
-    let mut result = ByteVec::new(ctx);
-    result.append(cbse_bytevec::UnwrappedBytes::BitVec(symbolic))?;
-    result.append(cbse_bytevec::UnwrappedBytes::Bytes(vec![0u8; 24]))?; // Pad right
-    Ok(result)
+    encode_bytes_n(&symbolic, 8, ctx)
 }
 
 /// svm.createBytes32(string name)
@@ -728,9 +803,7 @@ pub fn create_bytes32<'ctx>(
     let name = name_of(&name);
 
     let symbolic = create_generic(256, &name, "bytes32", symbol_id, ctx)?;
-    let mut result = ByteVec::new(ctx);
-    result.append(cbse_bytevec::UnwrappedBytes::BitVec(symbolic))?;
-    Ok(result)
+    encode_bytes_n(&symbolic, 32, ctx)
 }
 
 /// svm.createAddress(string name)
@@ -767,6 +840,714 @@ pub fn create_bool<'ctx>(
     Ok(bytevec)
 }
 
+// ============================================================================
+// Random Value Cheatcodes
+// ============================================================================
+//
+// Like Halmos, vm.random*() produce fresh symbolic values rather than actual
+// randomness, so every possible value is explored.
+
+/// Read a (possibly symbolic) word argument as a 256-bit bitvector
+fn extract_word_argument<'ctx>(arg: &ByteVec<'ctx>, arg_idx: usize) -> Result<CbseBitVec<'ctx>> {
+    match arg.get_word(4 + 32 * arg_idx)? {
+        cbse_bytevec::UnwrappedBytes::Bytes(b) => Ok(CbseBitVec::from_bytes(&b, 256)),
+        cbse_bytevec::UnwrappedBytes::BitVec(bv) => Ok(bv),
+    }
+}
+
+/// Read a concrete bit size argument in the range 1..=256
+fn extract_bits_argument<'ctx>(arg: &ByteVec<'ctx>, arg_idx: usize, fun: &str) -> Result<u32> {
+    let bits = extract_usize_word(arg, 4 + 32 * arg_idx, &format!("bit size for {}", fun))?;
+    if bits == 0 || bits > 256 {
+        return Err(CbseException::Internal(format!(
+            "{}: bits must be between 1 and 256, got {}",
+            fun, bits
+        )));
+    }
+    Ok(bits as u32)
+}
+
+/// Wrap a single word in a ByteVec
+fn word_result<'ctx>(word: CbseBitVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let mut result = ByteVec::new(ctx);
+    result.append(cbse_bytevec::UnwrappedBytes::BitVec(word))?;
+    Ok(result)
+}
+
+/// vm.randomUint()
+pub fn random_uint<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let symbolic = create_generic(256, "vmRandomUint", "uint256", symbol_id, ctx)?;
+    word_result(symbolic, ctx)
+}
+
+/// vm.randomUint(uint256 bits)
+pub fn random_uint_bits<'ctx>(
+    arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let bits = extract_bits_argument(arg, 0, "randomUint")?;
+    let symbolic = create_generic(
+        bits,
+        "vmRandomUint",
+        &format!("uint{}", bits),
+        symbol_id,
+        ctx,
+    )?;
+    word_result(uint256(&symbolic, ctx), ctx)
+}
+
+/// vm.randomUint(uint256 min, uint256 max)
+///
+/// Returns the value together with 1-bit flags that must hold for min <= value <= max.
+pub fn random_uint_min_max<'ctx>(
+    arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<(ByteVec<'ctx>, Vec<CbseBitVec<'ctx>>)> {
+    let min_bv = extract_word_argument(arg, 0)?;
+    let max_bv = extract_word_argument(arg, 1)?;
+
+    let symbolic = create_generic(256, "vmRandomUint", "uint256", symbol_id, ctx)?;
+    let constraints = vec![
+        symbolic.uge(&min_bv, ctx).to_bitvec(ctx, 1),
+        symbolic.ule(&max_bv, ctx).to_bitvec(ctx, 1),
+    ];
+
+    Ok((word_result(symbolic, ctx)?, constraints))
+}
+
+/// vm.randomInt()
+pub fn random_int<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let symbolic = create_generic(256, "vmRandomInt", "int256", symbol_id, ctx)?;
+    word_result(symbolic, ctx)
+}
+
+/// vm.randomInt(uint256 bits)
+pub fn random_int_bits<'ctx>(
+    arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let bits = extract_bits_argument(arg, 0, "randomInt")?;
+    let symbolic = create_generic(bits, "vmRandomInt", &format!("int{}", bits), symbol_id, ctx)?;
+    let extended = CbseBitVec::from_z3(symbolic.as_z3(ctx).sign_ext(256 - bits));
+    word_result(extended, ctx)
+}
+
+/// vm.randomAddress()
+pub fn random_address<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let symbolic = create_generic(160, "vmRandomAddress", "address", symbol_id, ctx)?;
+    word_result(uint256(&symbolic, ctx), ctx)
+}
+
+/// vm.randomBool()
+pub fn random_bool<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    // A single bit zero-extended to 256 bits can only be 0 or 1
+    let symbolic = create_generic(1, "vmRandomBool", "bool", symbol_id, ctx)?;
+    word_result(uint256(&symbolic, ctx), ctx)
+}
+
+/// vm.randomBytes(uint256 length)
+pub fn random_bytes<'ctx>(
+    arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let length = extract_usize_word(arg, 4, "length for randomBytes")?;
+
+    let mut result = ByteVec::new(ctx);
+    result.append(cbse_bytevec::UnwrappedBytes::BitVec(con(32, 256, ctx)))?;
+    result.append(cbse_bytevec::UnwrappedBytes::BitVec(con(
+        length as u64,
+        256,
+        ctx,
+    )))?;
+    if length > 0 {
+        let symbolic = create_generic(length as u32 * 8, "vmRandomBytes", "bytes", symbol_id, ctx)?;
+        result.append(cbse_bytevec::UnwrappedBytes::BitVec(symbolic))?;
+        let padding = (32 - length % 32) % 32;
+        if padding > 0 {
+            result.append(cbse_bytevec::UnwrappedBytes::Bytes(vec![0u8; padding]))?;
+        }
+    }
+    Ok(result)
+}
+
+/// Fresh bytesN value, right-padded to a full word
+fn random_fixed_bytes<'ctx>(
+    size: u32,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let type_name = format!("bytes{}", size);
+    let var_name = format!("vmRandomBytes{}", size);
+    let symbolic = create_generic(size * 8, &var_name, &type_name, symbol_id, ctx)?;
+    encode_bytes_n(&symbolic, size, ctx)
+}
+
+/// vm.randomBytes4()
+pub fn random_bytes4<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    random_fixed_bytes(4, symbol_id, ctx)
+}
+
+/// vm.randomBytes8()
+pub fn random_bytes8<'ctx>(
+    _arg: &ByteVec<'ctx>,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    random_fixed_bytes(8, symbol_id, ctx)
+}
+
+// ============================================================================
+// Symbolic Calldata
+// ============================================================================
+
+/// Solidity ABI parameter types supported by symbolic calldata generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolType {
+    Uint(u32),
+    Int(u32),
+    Address,
+    Bool,
+    FixedBytes(u32),
+    Bytes,
+    String,
+}
+
+impl SolType {
+    /// Returns true if the type is ABI-encoded in the tail (bytes, string)
+    pub fn is_dynamic(&self) -> bool {
+        matches!(self, SolType::Bytes | SolType::String)
+    }
+
+    /// Canonical ABI type name, e.g. "uint256"
+    pub fn name(&self) -> String {
+        match self {
+            SolType::Uint(bits) => format!("uint{}", bits),
+            SolType::Int(bits) => format!("int{}", bits),
+            SolType::Address => "address".to_string(),
+            SolType::Bool => "bool".to_string(),
+            SolType::FixedBytes(n) => format!("bytes{}", n),
+            SolType::Bytes => "bytes".to_string(),
+            SolType::String => "string".to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for SolType {
+    type Err = CbseException;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || CbseException::Internal(format!("Unsupported ABI type: {}", s));
+        let sized = |digits: &str, max: u32, step: u32| -> Result<u32> {
+            let n = if digits.is_empty() {
+                max
+            } else {
+                digits.parse::<u32>().map_err(|_| invalid())?
+            };
+            if n == 0 || n > max || n % step != 0 {
+                return Err(invalid());
+            }
+            Ok(n)
+        };
+
+        match s {
+            "address" => Ok(SolType::Address),
+            "bool" => Ok(SolType::Bool),
+            "bytes" => Ok(SolType::Bytes),
+            "string" => Ok(SolType::String),
+            _ => {
+                if let Some(bits) = s.strip_prefix("uint") {
+                    Ok(SolType::Uint(sized(bits, 256, 8)?))
+                } else if let Some(bits) = s.strip_prefix("int") {
+                    Ok(SolType::Int(sized(bits, 256, 8)?))
+                } else if let Some(n) = s.strip_prefix("bytes") {
+                    Ok(SolType::FixedBytes(sized(n, 32, 1)?))
+                } else {
+                    Err(invalid())
+                }
+            }
+        }
+    }
+}
+
+/// Parameter types of a function signature such as "transfer(address,uint256)"
+///
+/// A bare function name has no parameters.
+pub fn parse_param_types(signature: &str) -> Result<Vec<SolType>> {
+    let Some((_, params)) = signature.split_once('(') else {
+        return Ok(Vec::new());
+    };
+    let params = params
+        .strip_suffix(')')
+        .ok_or_else(|| CbseException::Internal(format!("Malformed signature: {}", signature)))?;
+    if params.is_empty() {
+        return Ok(Vec::new());
+    }
+    params
+        .split(',')
+        .map(|param| param.trim().parse())
+        .collect()
+}
+
+/// Build calldata for `selector` with a fresh symbolic value per parameter
+///
+/// Returns one calldata per length in `bytes_lengths` (as parsed from
+/// `--default-bytes-lengths`), giving every dynamic parameter (bytes, string)
+/// that length. Without dynamic parameters a single calldata is returned.
+pub fn build_symbolic_calldata<'ctx>(
+    selector: [u8; 4],
+    param_types: &[SolType],
+    symbol_id_base: usize,
+    bytes_lengths: &[usize],
+    ctx: &'ctx Context,
+) -> Result<Vec<ByteVec<'ctx>>> {
+    let lengths = if param_types.iter().any(SolType::is_dynamic) && !bytes_lengths.is_empty() {
+        bytes_lengths
+    } else {
+        &[0]
+    };
+    lengths
+        .iter()
+        .map(|&length| {
+            build_symbolic_calldata_with_length(selector, param_types, symbol_id_base, length, ctx)
+        })
+        .collect()
+}
+
+/// Create an ABI-encoded array of `length` fresh symbolic elements of `element_type`
+///
+/// Each element is a full 256-bit symbol; for element types narrower than a
+/// word the returned 1-bit flags constrain it to valid values. Callers take
+/// `length` from `--array-lengths` or `--default-array-lengths`.
+pub fn create_array<'ctx>(
+    element_type: &SolType,
+    length: usize,
+    name: &str,
+    symbol_id: usize,
+    ctx: &'ctx Context,
+) -> Result<(ByteVec<'ctx>, Vec<CbseBitVec<'ctx>>)> {
+    if element_type.is_dynamic() {
+        return Err(CbseException::Internal(format!(
+            "createArray: unsupported element type {}",
+            element_type.name()
+        )));
+    }
+
+    let type_name = format!("{}[]", element_type.name());
+    let mut elements = Vec::with_capacity(length);
+    let mut constraints = Vec::new();
+    for i in 0..length {
+        let var_name = format!("{}_{}", name_of(name), i);
+        let element = create_generic(256, &var_name, &type_name, symbol_id, ctx)?;
+        let value = element.as_z3(ctx);
+
+        let valid = match element_type {
+            SolType::Uint(bits) if *bits < 256 => Some(
+                value
+                    .extract(255, *bits)
+                    ._eq(&BV::from_u64(ctx, 0, 256 - bits)),
+            ),
+            SolType::Address => Some(value.extract(255, 160)._eq(&BV::from_u64(ctx, 0, 96))),
+            SolType::Bool => Some(value.extract(255, 1)._eq(&BV::from_u64(ctx, 0, 255))),
+            SolType::Int(bits) if *bits < 256 => {
+                Some(value._eq(&value.extract(bits - 1, 0).sign_ext(256 - bits)))
+            }
+            SolType::FixedBytes(n) if *n < 32 => {
+                let pad = 256 - n * 8;
+                Some(value.extract(pad - 1, 0)._eq(&BV::from_u64(ctx, 0, pad)))
+            }
+            _ => None,
+        };
+        if let Some(valid) = valid {
+            constraints.push(cbse_bitvec::CbseBool::from_z3(valid).to_bitvec(ctx, 1));
+        }
+        elements.push(element);
+    }
+
+    Ok((abi_encode_array_words(&elements, ctx)?, constraints))
+}
+
+/// Same as `build_symbolic_calldata`, with an explicit length for dynamic parameters
+pub fn build_symbolic_calldata_with_length<'ctx>(
+    selector: [u8; 4],
+    param_types: &[SolType],
+    symbol_id_base: usize,
+    bytes_length: usize,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    let mut head = ByteVec::new(ctx);
+    let mut tail = ByteVec::new(ctx);
+    let head_size = 32 * param_types.len();
+
+    for (i, typ) in param_types.iter().enumerate() {
+        let var_name = format!("p{}", i);
+        let type_name = typ.name();
+        let symbol_id = symbol_id_base + i;
+
+        let word = match typ {
+            SolType::Uint(bits) | SolType::Int(bits) => {
+                let symbolic = create_generic(*bits, &var_name, &type_name, symbol_id, ctx)?;
+                match typ {
+                    SolType::Int(_) => uint256(&symbolic, ctx).signextend(bits / 8 - 1, ctx),
+                    _ => uint256(&symbolic, ctx),
+                }
+            }
+            SolType::Address => uint256(
+                &create_generic(160, &var_name, &type_name, symbol_id, ctx)?,
+                ctx,
+            ),
+            SolType::Bool => uint256(
+                &create_generic(1, &var_name, &type_name, symbol_id, ctx)?,
+                ctx,
+            ),
+            SolType::FixedBytes(n) => {
+                let symbolic = create_generic(n * 8, &var_name, &type_name, symbol_id, ctx)?;
+                // bytesN values are left-aligned in their word
+                let shift = CbseBitVec::from_u64((256 - n * 8) as u64, 256);
+                uint256(&symbolic, ctx).shl(&shift, ctx)
+            }
+            SolType::Bytes | SolType::String => {
+                let offset = head_size + tail.len();
+                tail.append(cbse_bytevec::UnwrappedBytes::BitVec(con(
+                    bytes_length as u64,
+                    256,
+                    ctx,
+                )))?;
+                if bytes_length > 0 {
+                    let data = create_generic(
+                        (bytes_length * 8) as u32,
+                        &var_name,
+                        &type_name,
+                        symbol_id,
+                        ctx,
+                    )?;
+                    tail.append(cbse_bytevec::UnwrappedBytes::BitVec(data))?;
+                    let padding = (32 - bytes_length % 32) % 32;
+                    if padding > 0 {
+                        tail.append(cbse_bytevec::UnwrappedBytes::Bytes(vec![0u8; padding]))?;
+                    }
+                }
+                con(offset as u64, 256, ctx)
+            }
+        };
+        head.append(cbse_bytevec::UnwrappedBytes::BitVec(word))?;
+    }
+
+    let mut calldata = ByteVec::from_bytes(selector.to_vec(), ctx)?;
+    calldata.append_bytevec(&head);
+    calldata.append_bytevec(&tail);
+    Ok(calldata)
+}
+
+// ============================================================================
+// Labels
+// ============================================================================
+
+/// vm.label(address, string) - name an address in rendered traces
+pub fn label<'ctx>(arg: &ByteVec<'ctx>, mapper: &mut DeployAddressMapper) -> Result<()> {
+    let addr = extract_address_argument(arg, 0)?;
+    let name = extract_string_argument(arg, 1)?;
+
+    // Same format as cbse_traces::rendered_address, so the label replaces the address
+    let digits = hex::encode(addr);
+    let digits = digits.trim_start_matches('0');
+    let key = format!("0x{}", if digits.is_empty() { "0" } else { digits });
+    mapper.add_deployed_contract(key, name);
+    Ok(())
+}
+
+// ============================================================================
+// External Commands
+// ============================================================================
+
+/// vm.ffi(string[]) - run an external command and return its stdout as bytes
+///
+/// Only allowed when the ffi option is enabled. Like Foundry, output starting
+/// with 0x is hex-decoded; any other output is returned as raw bytes.
+pub fn ffi<'ctx>(
+    arg: &ByteVec<'ctx>,
+    ffi_enabled: bool,
+    ctx: &'ctx Context,
+) -> Result<ByteVec<'ctx>> {
+    if !ffi_enabled {
+        return Err(CbseException::Internal(
+            "cheatcode vm.ffi() requires the --ffi flag".to_string(),
+        ));
+    }
+
+    let cmd = extract_string_array_argument(arg, 0)?;
+    let (program, args) = cmd
+        .split_first()
+        .ok_or_else(|| CbseException::Internal("vm.ffi(): empty command".to_string()))?;
+
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| {
+            CbseException::Internal(format!("vm.ffi(): failed to run {}: {}", program, e))
+        })?;
+
+    if !output.stderr.is_empty() {
+        cbse_logs::warn(
+            &format!(
+                "vm.ffi({}) stderr: {}",
+                cmd.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            true,
+        );
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let out_bytes = match stdout.trim().strip_prefix("0x") {
+        Some(hex_str) => hex::decode(hex_str)
+            .map_err(|e| CbseException::Internal(format!("vm.ffi(): invalid hex output: {}", e)))?,
+        None => stdout.trim().as_bytes().to_vec(),
+    };
+
+    encode_tuple_bytes(&out_bytes, ctx)
+}
+
+// ============================================================================
+// Environment Variable Cheatcodes
+// ============================================================================
+
+/// Convert a cbse_env lookup failure into a cheatcode error
+fn env_error(key: &str, err: String) -> CbseException {
+    if cbse_env::exists(key) {
+        CbseException::Internal(format!(
+            "failed to parse environment variable {}: {}",
+            key, err
+        ))
+    } else {
+        CbseException::Internal(format!("environment variable {} not found", key))
+    }
+}
+
+/// Left-pad a big-endian value to a single ABI word
+fn encode_be_word<'ctx>(value: &[u8], ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    ByteVec::from_bytes(padded_bytes(value, false), ctx)
+}
+
+/// Sign-extend an integer to a 32-byte two's complement word
+fn int_word(value: i64) -> [u8; 32] {
+    let mut word = if value < 0 { [0xFF; 32] } else { [0u8; 32] };
+    word[24..32].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// vm.envExists(string)
+pub fn env_exists<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    encode_be_word(&[cbse_env::exists(&key) as u8], ctx)
+}
+
+/// vm.envInt(string)
+pub fn env_int<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_int(&key).map_err(|e| env_error(&key, e))?;
+    encode_be_word(&int_word(value), ctx)
+}
+
+/// vm.envUint(string)
+pub fn env_uint<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_uint(&key).map_err(|e| env_error(&key, e))?;
+    encode_be_word(&value.to_be_bytes(), ctx)
+}
+
+/// vm.envAddress(string)
+pub fn env_address<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_address(&key).map_err(|e| env_error(&key, e))?;
+    encode_be_word(&value, ctx)
+}
+
+/// vm.envBool(string)
+pub fn env_bool<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_bool(&key).map_err(|e| env_error(&key, e))?;
+    encode_be_word(&[value as u8], ctx)
+}
+
+/// vm.envBytes32(string)
+pub fn env_bytes32<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_bytes32(&key).map_err(|e| env_error(&key, e))?;
+    encode_be_word(&value, ctx)
+}
+
+/// vm.envString(string)
+pub fn env_string<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_string(&key, None).map_err(|e| env_error(&key, e))?;
+    encode_tuple_bytes(value.as_bytes(), ctx)
+}
+
+/// vm.envBytes(string)
+pub fn env_bytes<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let key = extract_string_argument(arg, 0)?;
+    let value = cbse_env::get_bytes(&key).map_err(|e| env_error(&key, e))?;
+    encode_tuple_bytes(&value, ctx)
+}
+
+/// Variable name and delimiter of an env(string, string) array call
+fn env_array_args<'ctx>(arg: &ByteVec<'ctx>) -> Result<(String, String)> {
+    Ok((
+        extract_string_argument(arg, 0)?,
+        extract_string_argument(arg, 1)?,
+    ))
+}
+
+/// vm.envInt(string, string) -> int256[]
+pub fn env_int_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_int_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let words: Vec<_> = values
+        .iter()
+        .map(|v| CbseBitVec::from_bytes(v, 256))
+        .collect();
+    abi_encode_array_words(&words, ctx)
+}
+
+/// vm.envUint(string, string) -> uint256[]
+pub fn env_uint_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_uint_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let words: Vec<_> = values
+        .iter()
+        .map(|v| CbseBitVec::from_bytes(v, 256))
+        .collect();
+    abi_encode_array_words(&words, ctx)
+}
+
+/// vm.envAddress(string, string) -> address[]
+pub fn env_address_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_address_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let words: Vec<_> = values
+        .iter()
+        .map(|v| CbseBitVec::from_bytes(v, 160))
+        .collect();
+    abi_encode_array_words(&words, ctx)
+}
+
+/// vm.envBool(string, string) -> bool[]
+pub fn env_bool_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_bool_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let words: Vec<_> = values.iter().map(|v| con(*v as u64, 256, ctx)).collect();
+    abi_encode_array_words(&words, ctx)
+}
+
+/// vm.envBytes32(string, string) -> bytes32[]
+pub fn env_bytes32_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_bytes32_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let words: Vec<_> = values
+        .iter()
+        .map(|v| CbseBitVec::from_bytes(v, 256))
+        .collect();
+    abi_encode_array_words(&words, ctx)
+}
+
+/// vm.envString(string, string) -> string[]
+pub fn env_string_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_string_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    let bytes: Vec<_> = values.into_iter().map(String::into_bytes).collect();
+    abi_encode_array_bytes(&bytes, ctx)
+}
+
+/// vm.envBytes(string, string) -> bytes[]
+pub fn env_bytes_array<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    let (key, delimiter) = env_array_args(arg)?;
+    let values = cbse_env::get_bytes_array(&key, &delimiter).map_err(|e| env_error(&key, e))?;
+    abi_encode_array_bytes(&values, ctx)
+}
+
+/// Default word (second argument) of an envOr(string, T) call with a static T
+fn env_or_default_word<'ctx>(arg: &ByteVec<'ctx>) -> Result<ByteVec<'ctx>> {
+    arg.slice(4 + 32, 4 + 64)
+}
+
+/// vm.envOr(string, uint256)
+pub fn env_or_uint<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_uint(arg, ctx)
+    } else {
+        env_or_default_word(arg)
+    }
+}
+
+/// vm.envOr(string, address)
+pub fn env_or_address<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_address(arg, ctx)
+    } else {
+        env_or_default_word(arg)
+    }
+}
+
+/// vm.envOr(string, bool)
+pub fn env_or_bool<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_bool(arg, ctx)
+    } else {
+        env_or_default_word(arg)
+    }
+}
+
+/// vm.envOr(string, bytes32)
+pub fn env_or_bytes32<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_bytes32(arg, ctx)
+    } else {
+        env_or_default_word(arg)
+    }
+}
+
+/// vm.envOr(string, string)
+pub fn env_or_string<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_string(arg, ctx)
+    } else {
+        encode_tuple_bytes(extract_string_argument(arg, 1)?.as_bytes(), ctx)
+    }
+}
+
+/// vm.envOr(string, bytes)
+pub fn env_or_bytes<'ctx>(arg: &ByteVec<'ctx>, ctx: &'ctx Context) -> Result<ByteVec<'ctx>> {
+    if cbse_env::exists(&extract_string_argument(arg, 0)?) {
+        env_bytes(arg, ctx)
+    } else {
+        encode_tuple_bytes(&extract_bytes_argument(arg, 1)?, ctx)
+    }
+}
+
 // ============================================================================
 // Cheatcode Selectors
 // ============================================================================
@@ -821,6 +1602,20 @@ pub mod hevm_cheat_code {
     pub const GET_BLOCK_NUMBER: u32 = 0x42CBB15C;
     pub const SNAPSHOT_STATE: u32 = 0x9CD23835;
     pub const SET_ARBITRARY_STORAGE: u32 = 0xE1631837;
+    pub const EXPECT_EMIT: u32 = 0x440ED10D;
+    pub const EXPECT_EMIT_ADDR: u32 = 0x86B9620D;
+    pub const EXPECT_EMIT_CHECKS: u32 = 0x491CC7C2;
+    pub const EXPECT_EMIT_CHECKS_ADDR: u32 = 0x81BAD6F3;
+    pub const MOCK_CALL: u32 = 0xB96213E4;
+    pub const CLEAR_MOCKED_CALLS: u32 = 0x3FDF4E15;
+
+    // Assertion cheatcodes
+    pub const ASSERT_TRUE: u32 = 0x0C9FD581;
+    pub const ASSERT_EQ_UINT256: u32 = 0x98296C54;
+    pub const ASSERT_EQ_ADDRESS: u32 = 0x515361F6;
+    pub const ASSERT_NOT_EQ_UINT256: u32 = 0xB7909320;
+    pub const ASSERT_GT_UINT256: u32 = 0xDB07FCD2;
+    pub const ASSERT_LT_UINT256: u32 = 0xB12FC005;
 
     // Random value cheatcodes
     pub const RANDOM_INT: u32 = 0x111F1202;
@@ -885,6 +1680,587 @@ mod tests {
         assert_eq!(name_of("test"), "test");
     }
 
+    #[test]
+    fn test_sol_type_parse() {
+        assert_eq!("uint256".parse::<SolType>().unwrap(), SolType::Uint(256));
+        assert_eq!("uint".parse::<SolType>().unwrap(), SolType::Uint(256));
+        assert_eq!("int8".parse::<SolType>().unwrap(), SolType::Int(8));
+        assert_eq!("bytes4".parse::<SolType>().unwrap(), SolType::FixedBytes(4));
+        assert_eq!("bytes".parse::<SolType>().unwrap(), SolType::Bytes);
+        assert!("uint7".parse::<SolType>().is_err());
+        assert!("bytes33".parse::<SolType>().is_err());
+        assert!("tuple".parse::<SolType>().is_err());
+    }
+
+    #[test]
+    fn test_build_symbolic_calldata_transfer() {
+        let ctx = Context::new(&z3::Config::new());
+        // transfer(address,uint256)
+        let selector = [0xa9, 0x05, 0x9c, 0xbb];
+        let params = [SolType::Address, SolType::Uint(256)];
+        let calldatas = build_symbolic_calldata(selector, &params, 0, &[0, 65], &ctx).unwrap();
+
+        // Lengths only matter for dynamic parameters
+        assert_eq!(calldatas.len(), 1);
+        let calldata = &calldatas[0];
+        assert_eq!(calldata.len(), 4 + 32 * 2);
+        match calldata.slice(0, 4).unwrap().unwrap().unwrap() {
+            cbse_bytevec::UnwrappedBytes::Bytes(bytes) => assert_eq!(bytes, selector.to_vec()),
+            _ => panic!("selector should be concrete"),
+        }
+        for offset in [4, 36] {
+            match calldata.get_word(offset).unwrap() {
+                cbse_bytevec::UnwrappedBytes::BitVec(bv) => assert!(!bv.is_concrete()),
+                _ => panic!("argument word should be symbolic"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_build_symbolic_calldata_dynamic() {
+        let ctx = Context::new(&z3::Config::new());
+        let calldata =
+            build_symbolic_calldata_with_length([0; 4], &[SolType::Bytes], 0, 65, &ctx).unwrap();
+
+        // selector + offset + length + 65 bytes padded to 96
+        assert_eq!(calldata.len(), 4 + 32 + 32 + 96);
+        match calldata.get_word(4).unwrap() {
+            cbse_bytevec::UnwrappedBytes::Bytes(bytes) => assert_eq!(bytes[31], 32),
+            _ => panic!("offset should be concrete"),
+        }
+    }
+
+    #[test]
+    fn test_build_symbolic_calldata_covers_each_length() {
+        let ctx = Context::new(&z3::Config::new());
+        let calldatas =
+            build_symbolic_calldata([0; 4], &[SolType::String], 0, &[0, 65, 1024], &ctx).unwrap();
+
+        let lengths: Vec<usize> = calldatas.iter().map(|calldata| calldata.len()).collect();
+        assert_eq!(lengths, vec![4 + 64, 4 + 64 + 96, 4 + 64 + 1024]);
+    }
+
+    #[test]
+    fn test_parse_param_types() {
+        assert_eq!(parse_param_types("check_noargs()").unwrap(), vec![]);
+        assert_eq!(parse_param_types("check_noargs").unwrap(), vec![]);
+        assert_eq!(
+            parse_param_types("transfer(address,uint256)").unwrap(),
+            vec![SolType::Address, SolType::Uint(256)]
+        );
+        assert!(parse_param_types("check(uint256[])").is_err());
+        assert!(parse_param_types("check(uint256").is_err());
+    }
+
+    /// ABI-encode `ffi(string[])` calldata for `cmd`
+    fn ffi_calldata<'ctx>(cmd: &[&str], ctx: &'ctx Context) -> ByteVec<'ctx> {
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        let mut data = (hevm_cheat_code::FFI).to_be_bytes().to_vec();
+        data.extend(word(32));
+        data.extend(word(cmd.len()));
+        let mut tail = Vec::new();
+        for s in cmd {
+            data.extend(word(32 * cmd.len() + tail.len()));
+            tail.extend(word(s.len()));
+            tail.extend(padded_bytes(s.as_bytes(), true));
+        }
+        data.extend(tail);
+        ByteVec::from_bytes(data, ctx).unwrap()
+    }
+
+    #[test]
+    fn test_extract_string_array_argument() {
+        let ctx = Context::new(&z3::Config::new());
+        let calldata = ffi_calldata(&["echo", "hello world"], &ctx);
+        assert_eq!(
+            extract_string_array_argument(&calldata, 0).unwrap(),
+            vec!["echo".to_string(), "hello world".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_decode_dynamic_bytes() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        let mut data = vec![0u8; 4];
+        data.extend(word(32));
+        data.extend(word(3));
+        data.extend(padded_bytes(b"abc", true));
+        let calldata = ByteVec::from_bytes(data.clone(), &ctx).unwrap();
+        assert_eq!(decode_dynamic_bytes(&calldata, 0).unwrap(), b"abc".to_vec());
+
+        // Length claims more bytes than the buffer holds
+        data.truncate(4 + 64 + 2);
+        data[4 + 63] = 200;
+        let truncated = ByteVec::from_bytes(data.clone(), &ctx).unwrap();
+        let err = decode_dynamic_bytes(&truncated, 0).unwrap_err().to_string();
+        assert!(err.contains("malformed calldata (70 bytes)"), "{}", err);
+        assert!(err.contains("runs past the end"), "{}", err);
+
+        // Offset points beyond the buffer
+        data[4 + 31] = 0xff;
+        let bad_offset = ByteVec::from_bytes(data, &ctx).unwrap();
+        let err = extract_bytes32_array_argument(&bad_offset, 0)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("length for bytes32 array"), "{}", err);
+        assert!(err.contains("past the end"), "{}", err);
+
+        // Argument head itself is missing
+        let err = extract_string_argument(&calldata, 3)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("offset for string argument"), "{}", err);
+    }
+
+    #[test]
+    fn test_malformed_string_argument_is_abi_decode_error() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        // Length says 5 bytes but only 2 follow
+        let mut data = vec![0u8; 4];
+        data.extend(word(32));
+        data.extend(word(5));
+        data.extend(b"ab");
+        let truncated = ByteVec::from_bytes(data, &ctx).unwrap();
+        assert!(matches!(
+            extract_string_argument(&truncated, 0),
+            Err(CbseException::AbiDecode(_))
+        ));
+
+        let mut data = vec![0u8; 4];
+        data.extend(word(32));
+        data.extend(word(2));
+        data.extend(padded_bytes(&[0xff, 0xfe], true));
+        let invalid_utf8 = ByteVec::from_bytes(data, &ctx).unwrap();
+        match extract_string_argument(&invalid_utf8, 0) {
+            Err(CbseException::AbiDecode(msg)) => assert!(msg.contains("invalid UTF-8"), "{}", msg),
+            other => panic!("expected an ABI decoding error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_label() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        // label(address(0x1234), "Alice")
+        let mut data = (hevm_cheat_code::LABEL).to_be_bytes().to_vec();
+        data.extend(word(0x1234));
+        data.extend(word(64));
+        data.extend(word(5));
+        data.extend(padded_bytes(b"Alice", true));
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        let mut mapper = DeployAddressMapper::new();
+        label(&calldata, &mut mapper).unwrap();
+        assert_eq!(mapper.get_deployed_contract("0x1234"), "Alice");
+        assert_eq!(cbse_traces::rendered_address(0x1234, &mapper), "Alice");
+    }
+
+    /// ABI-encode calldata for a cheatcode taking string arguments
+    fn string_args_calldata<'ctx>(
+        selector: u32,
+        args: &[&str],
+        ctx: &'ctx Context,
+    ) -> ByteVec<'ctx> {
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        let mut data = selector.to_be_bytes().to_vec();
+        let mut tail = Vec::new();
+        for s in args {
+            data.extend(word(32 * args.len() + tail.len()));
+            tail.extend(word(s.len()));
+            tail.extend(padded_bytes(s.as_bytes(), true));
+        }
+        data.extend(tail);
+        ByteVec::from_bytes(data, ctx).unwrap()
+    }
+
+    /// Concrete bytes of an encoded return value
+    fn concrete_bytes(data: &ByteVec) -> Vec<u8> {
+        match data.slice(0, data.len()).unwrap().unwrap().unwrap() {
+            cbse_bytevec::UnwrappedBytes::Bytes(b) => b,
+            cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv.to_bytes(),
+        }
+    }
+
+    #[test]
+    fn test_env_word_types() {
+        let ctx = Context::new(&z3::Config::new());
+        let call = |selector: u32, key: &str| string_args_calldata(selector, &[key], &ctx);
+
+        std::env::set_var("CBSE_TEST_ENV_UINT", "0x2a");
+        let result = env_uint(&call(hevm_cheat_code::ENV_UINT, "CBSE_TEST_ENV_UINT"), &ctx);
+        assert_eq!(
+            concrete_bytes(&result.unwrap()),
+            padded_bytes(&[0x2a], false)
+        );
+
+        std::env::set_var("CBSE_TEST_ENV_INT", "-1");
+        let result = env_int(&call(hevm_cheat_code::ENV_INT, "CBSE_TEST_ENV_INT"), &ctx);
+        assert_eq!(concrete_bytes(&result.unwrap()), vec![0xFF; 32]);
+
+        std::env::set_var("CBSE_TEST_ENV_BOOL", "true");
+        let result = env_bool(&call(hevm_cheat_code::ENV_BOOL, "CBSE_TEST_ENV_BOOL"), &ctx);
+        assert_eq!(concrete_bytes(&result.unwrap()), padded_bytes(&[1], false));
+
+        let addr = "0x7109709ecfa91a80626ff3989d68f67f5b1dd12d";
+        std::env::set_var("CBSE_TEST_ENV_ADDRESS", addr);
+        let result = env_address(
+            &call(hevm_cheat_code::ENV_ADDRESS, "CBSE_TEST_ENV_ADDRESS"),
+            &ctx,
+        );
+        assert_eq!(
+            concrete_bytes(&result.unwrap()),
+            padded_bytes(&HEVM_ADDRESS, false)
+        );
+
+        std::env::set_var("CBSE_TEST_ENV_BYTES32", format!("0x{}", "ab".repeat(32)));
+        let result = env_bytes32(
+            &call(hevm_cheat_code::ENV_BYTES32, "CBSE_TEST_ENV_BYTES32"),
+            &ctx,
+        );
+        assert_eq!(concrete_bytes(&result.unwrap()), vec![0xab; 32]);
+    }
+
+    #[test]
+    fn test_env_string() {
+        let ctx = Context::new(&z3::Config::new());
+        std::env::set_var("CBSE_TEST_ENV_STRING", "hello");
+        let calldata =
+            string_args_calldata(hevm_cheat_code::ENV_STRING, &["CBSE_TEST_ENV_STRING"], &ctx);
+
+        let result = env_string(&calldata, &ctx).unwrap();
+        assert_eq!(
+            extract_string_argument(&prefix_selector(&result, &ctx), 0).unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn test_env_exists() {
+        let ctx = Context::new(&z3::Config::new());
+        let call = |key: &str| string_args_calldata(hevm_cheat_code::ENV_EXISTS, &[key], &ctx);
+
+        std::env::set_var("CBSE_TEST_ENV_EXISTS", "1");
+        let result = env_exists(&call("CBSE_TEST_ENV_EXISTS"), &ctx).unwrap();
+        assert_eq!(concrete_bytes(&result), padded_bytes(&[1], false));
+
+        std::env::remove_var("CBSE_TEST_ENV_NOT_EXISTS");
+        let result = env_exists(&call("CBSE_TEST_ENV_NOT_EXISTS"), &ctx).unwrap();
+        assert_eq!(concrete_bytes(&result), vec![0u8; 32]);
+    }
+
+    #[test]
+    fn test_env_missing_var() {
+        let ctx = Context::new(&z3::Config::new());
+        std::env::remove_var("CBSE_TEST_ENV_MISSING");
+        let calldata =
+            string_args_calldata(hevm_cheat_code::ENV_UINT, &["CBSE_TEST_ENV_MISSING"], &ctx);
+
+        let err = env_uint(&calldata, &ctx).unwrap_err();
+        assert!(err.to_string().contains("CBSE_TEST_ENV_MISSING not found"));
+    }
+
+    #[test]
+    fn test_env_or_word() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        // envOr("CBSE_TEST_ENV_OR_UINT", 7)
+        let mut data = hevm_cheat_code::ENV_OR_UINT.to_be_bytes().to_vec();
+        data.extend(word(64));
+        data.extend(word(7));
+        data.extend(word("CBSE_TEST_ENV_OR_UINT".len()));
+        data.extend(padded_bytes(b"CBSE_TEST_ENV_OR_UINT", true));
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        std::env::remove_var("CBSE_TEST_ENV_OR_UINT");
+        let result = env_or_uint(&calldata, &ctx).unwrap();
+        assert_eq!(concrete_bytes(&result), word(7));
+
+        std::env::set_var("CBSE_TEST_ENV_OR_UINT", "42");
+        let result = env_or_uint(&calldata, &ctx).unwrap();
+        assert_eq!(concrete_bytes(&result), word(42));
+        std::env::remove_var("CBSE_TEST_ENV_OR_UINT");
+    }
+
+    #[test]
+    fn test_env_or_string() {
+        let ctx = Context::new(&z3::Config::new());
+        let calldata = string_args_calldata(
+            hevm_cheat_code::ENV_OR_STRING,
+            &["CBSE_TEST_ENV_OR_STRING", "fallback"],
+            &ctx,
+        );
+
+        std::env::remove_var("CBSE_TEST_ENV_OR_STRING");
+        let result = env_or_string(&calldata, &ctx).unwrap();
+        let decoded = extract_string_argument(&prefix_selector(&result, &ctx), 0).unwrap();
+        assert_eq!(decoded, "fallback");
+
+        std::env::set_var("CBSE_TEST_ENV_OR_STRING", "from env");
+        let result = env_or_string(&calldata, &ctx).unwrap();
+        let decoded = extract_string_argument(&prefix_selector(&result, &ctx), 0).unwrap();
+        assert_eq!(decoded, "from env");
+        std::env::remove_var("CBSE_TEST_ENV_OR_STRING");
+    }
+
+    #[test]
+    fn test_env_uint_array() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+        std::env::set_var("CBSE_TEST_ENV_UINT_ARRAY", "1,2,3");
+        let calldata = string_args_calldata(
+            hevm_cheat_code::ENV_UINT_ARRAY,
+            &["CBSE_TEST_ENV_UINT_ARRAY", ","],
+            &ctx,
+        );
+
+        let result = env_uint_array(&calldata, &ctx).unwrap();
+        let expected: Vec<u8> = [32, 3, 1, 2, 3].iter().flat_map(|v| word(*v)).collect();
+        assert_eq!(concrete_bytes(&result), expected);
+    }
+
+    #[test]
+    fn test_env_string_array() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+        std::env::set_var("CBSE_TEST_ENV_STRING_ARRAY", "foo;bar");
+        let calldata = string_args_calldata(
+            hevm_cheat_code::ENV_STRING_ARRAY,
+            &["CBSE_TEST_ENV_STRING_ARRAY", ";"],
+            &ctx,
+        );
+
+        let result = env_string_array(&calldata, &ctx).unwrap();
+        let mut expected: Vec<u8> = [32, 2, 64, 128].iter().flat_map(|v| word(*v)).collect();
+        expected.extend(word(3));
+        expected.extend(padded_bytes(b"foo", true));
+        expected.extend(word(3));
+        expected.extend(padded_bytes(b"bar", true));
+        assert_eq!(concrete_bytes(&result), expected);
+    }
+
+    #[test]
+    fn test_ffi_echo() {
+        let ctx = Context::new(&z3::Config::new());
+
+        let result = ffi(&ffi_calldata(&["echo", "0x1234"], &ctx), true, &ctx).unwrap();
+        assert_eq!(
+            extract_bytes_argument(&prefix_selector(&result, &ctx), 0).unwrap(),
+            vec![0x12, 0x34]
+        );
+
+        let result = ffi(&ffi_calldata(&["echo", "hello"], &ctx), true, &ctx).unwrap();
+        assert_eq!(
+            extract_bytes_argument(&prefix_selector(&result, &ctx), 0).unwrap(),
+            b"hello".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_ffi_disabled() {
+        let ctx = Context::new(&z3::Config::new());
+        let err = ffi(&ffi_calldata(&["echo", "hello"], &ctx), false, &ctx).unwrap_err();
+        assert!(err.to_string().contains("--ffi"));
+    }
+
+    /// Prepend a dummy selector so return data can be read back with the extract_* helpers
+    fn prefix_selector<'ctx>(data: &ByteVec<'ctx>, ctx: &'ctx Context) -> ByteVec<'ctx> {
+        let mut result = ByteVec::from_bytes(vec![0; 4], ctx).unwrap();
+        result.append_bytevec(data);
+        result
+    }
+
+    /// Single symbolic word returned by a cheatcode
+    fn result_word<'ctx>(result: &ByteVec<'ctx>) -> CbseBitVec<'ctx> {
+        match result.get_word(0).unwrap() {
+            cbse_bytevec::UnwrappedBytes::BitVec(bv) => bv,
+            _ => panic!("expected a bitvector word"),
+        }
+    }
+
+    /// Whether bits [high:low] of `word` simplify to zero
+    fn high_bits_zero(word: &CbseBitVec, high: u32, low: u32, ctx: &Context) -> bool {
+        word.as_z3(ctx).extract(high, low).simplify().as_u64() == Some(0)
+    }
+
+    #[test]
+    fn test_random_values_are_fresh() {
+        let ctx = Context::new(&z3::Config::new());
+        let empty =
+            ByteVec::from_bytes(hevm_cheat_code::RANDOM_UINT.to_be_bytes().to_vec(), &ctx).unwrap();
+
+        let a = result_word(&random_uint(&empty, 1, &ctx).unwrap());
+        let b = result_word(&random_uint(&empty, 2, &ctx).unwrap());
+        assert!(a.is_symbolic() && b.is_symbolic());
+        assert_eq!(a.size(), 256);
+        assert_ne!(a.as_z3(&ctx).to_string(), b.as_z3(&ctx).to_string());
+
+        let addr = result_word(&random_address(&empty, 3, &ctx).unwrap());
+        assert!(addr.is_symbolic());
+        assert!(high_bits_zero(&addr, 255, 160, &ctx));
+
+        let flag = result_word(&random_bool(&empty, 4, &ctx).unwrap());
+        assert!(high_bits_zero(&flag, 255, 1, &ctx));
+
+        // bytes4 is left-aligned: the low 28 bytes are padding
+        let b4 = result_word(&random_bytes4(&empty, 5, &ctx).unwrap());
+        assert!(high_bits_zero(&b4, 223, 0, &ctx));
+        assert!(!high_bits_zero(&b4, 255, 224, &ctx));
+    }
+
+    #[test]
+    fn test_encode_word_layout() {
+        let ctx = Context::new(&z3::Config::new());
+
+        let encoded = encode_word(&CbseBitVec::from_u64(0x1234, 16), &ctx).unwrap();
+        let mut expected = vec![0u8; 30];
+        expected.extend([0x12, 0x34]);
+        assert_eq!(concrete_bytes(&encoded), expected);
+
+        let full = encode_word(&CbseBitVec::from_bytes(&[0xab; 32], 256), &ctx).unwrap();
+        assert_eq!(concrete_bytes(&full), vec![0xab; 32]);
+
+        assert!(encode_word(&CbseBitVec::from_u64(1, 264), &ctx).is_err());
+    }
+
+    #[test]
+    fn test_encode_bytes_n_layout() {
+        let ctx = Context::new(&z3::Config::new());
+
+        let value = CbseBitVec::from_bytes(&[0xde, 0xad, 0xbe, 0xef], 32);
+        let encoded = encode_bytes_n(&value, 4, &ctx).unwrap();
+        let mut expected = vec![0xde, 0xad, 0xbe, 0xef];
+        expected.resize(32, 0);
+        assert_eq!(concrete_bytes(&encoded), expected);
+
+        let full = CbseBitVec::from_bytes(&[0xcd; 32], 256);
+        assert_eq!(
+            concrete_bytes(&encode_bytes_n(&full, 32, &ctx).unwrap()),
+            vec![0xcd; 32]
+        );
+
+        let four = CbseBitVec::from_u64(1, 32);
+        assert!(encode_bytes_n(&four, 8, &ctx).is_err());
+        assert!(encode_bytes_n(&four, 0, &ctx).is_err());
+        assert!(encode_bytes_n(&full, 33, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_create_fixed_bytes_layout() {
+        let ctx = Context::new(&z3::Config::new());
+        let call = |selector: u32| string_args_calldata(selector, &["x"], &ctx);
+
+        // bytes4 and bytes8 are symbolic in their high bytes and zero below
+        let b4 = create_bytes4(&call(halmos_cheat_code::CREATE_BYTES4), 1, &ctx).unwrap();
+        assert_eq!(b4.len(), 32);
+        let b4 = result_word(&b4);
+        assert!(high_bits_zero(&b4, 223, 0, &ctx));
+        assert!(!high_bits_zero(&b4, 255, 224, &ctx));
+
+        let b8 = create_bytes8(&call(0), 2, &ctx).unwrap();
+        assert_eq!(b8.len(), 32);
+        let b8 = result_word(&b8);
+        assert!(high_bits_zero(&b8, 191, 0, &ctx));
+        assert!(!high_bits_zero(&b8, 255, 192, &ctx));
+
+        let b32 = create_bytes32(&call(halmos_cheat_code::CREATE_BYTES32), 3, &ctx).unwrap();
+        assert_eq!(b32.len(), 32);
+        let b32 = result_word(&b32);
+        assert!(b32.is_symbolic());
+        assert!(!high_bits_zero(&b32, 7, 0, &ctx));
+    }
+
+    #[test]
+    fn test_create_bytes_bounded() {
+        let ctx = Context::new(&z3::Config::new());
+        let length = CbseBitVec::symbolic(&ctx, "len", 256);
+
+        let candidates = create_bytes_bounded("data", &length, 1, &[0, 65, 1024], &ctx).unwrap();
+        assert_eq!(candidates.len(), 3);
+        for ((encoded, constraints), n) in candidates.iter().zip([0usize, 65, 1024]) {
+            // offset, length, then the data padded to whole words
+            assert_eq!(encoded.len(), 64 + (n + 31) / 32 * 32);
+            match encoded.get_word(32).unwrap() {
+                cbse_bytevec::UnwrappedBytes::Bytes(b) => {
+                    assert_eq!(b, padded_bytes(&(n as u64).to_be_bytes(), false))
+                }
+                cbse_bytevec::UnwrappedBytes::BitVec(bv) => {
+                    assert_eq!(bv.as_u64().unwrap(), n as u64)
+                }
+            }
+            if n > 0 {
+                match encoded.slice(64, 64 + n).unwrap().unwrap().unwrap() {
+                    cbse_bytevec::UnwrappedBytes::BitVec(bv) => assert!(bv.is_symbolic()),
+                    _ => panic!("buffer should be symbolic"),
+                }
+            }
+            assert_eq!(constraints.len(), 1);
+            assert_eq!(constraints[0].size(), 1);
+            assert!(constraints[0].is_symbolic());
+        }
+    }
+
+    #[test]
+    fn test_random_uint_bits_and_range() {
+        let ctx = Context::new(&z3::Config::new());
+        let word = |v: usize| padded_bytes(&(v as u64).to_be_bytes(), false);
+
+        let mut data = hevm_cheat_code::RANDOM_UINT_UINT256.to_be_bytes().to_vec();
+        data.extend(word(8));
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+        let value = result_word(&random_uint_bits(&calldata, 1, &ctx).unwrap());
+        assert!(high_bits_zero(&value, 255, 8, &ctx));
+
+        let mut data = hevm_cheat_code::RANDOM_UINT_MIN_MAX.to_be_bytes().to_vec();
+        data.extend(word(10));
+        data.extend(word(20));
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+        let (value, constraints) = random_uint_min_max(&calldata, 2, &ctx).unwrap();
+        assert!(result_word(&value).is_symbolic());
+        assert_eq!(constraints.len(), 2);
+        assert!(constraints.iter().all(|c| c.size() == 1));
+    }
+
+    #[test]
+    fn test_create_array() {
+        let ctx = Context::new(&z3::Config::new());
+        let (array, constraints) = create_array(&SolType::Uint(256), 3, "xs", 1, &ctx).unwrap();
+
+        assert_eq!(array.len(), 32 * 5);
+        assert!(constraints.is_empty());
+        assert_eq!(
+            result_word(&array.slice(0, 32).unwrap()).as_u64().unwrap(),
+            32
+        );
+        assert_eq!(
+            result_word(&array.slice(32, 64).unwrap()).as_u64().unwrap(),
+            3
+        );
+
+        let elements: Vec<String> = (0..3)
+            .map(|i| {
+                let offset = 64 + 32 * i;
+                let word = result_word(&array.slice(offset, offset + 32).unwrap());
+                assert!(word.is_symbolic());
+                word.as_z3(&ctx).to_string()
+            })
+            .collect();
+        assert_ne!(elements[0], elements[1]);
+        assert_ne!(elements[1], elements[2]);
+
+        // Narrow element types come with one validity flag per element
+        let (_, constraints) = create_array(&SolType::Address, 2, "as", 2, &ctx).unwrap();
+        assert_eq!(constraints.len(), 2);
+        assert!(create_array(&SolType::Bytes, 1, "bs", 3, &ctx).is_err());
+    }
+
     #[test]
     fn test_prank_context() {
         let ctx = Context::new(&z3::Config::new());
@@ -9,7 +9,7 @@ use anyhow::{Context, Result};
 use clap::Parser;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 
 // Configuration source priority (matches Python ConfigSource IntEnum)
@@ -145,6 +145,26 @@ pub struct Config {
     #[serde(default)]
     pub depth: usize,
 
+    /// Max size in bytes memory may expand to before the path halts
+    #[clap(long, default_value = "1048576")]
+    #[serde(default = "default_max_memory_size")]
+    pub max_memory_size: usize,
+
+    /// Chain id returned by CHAINID (1 = Ethereum mainnet)
+    #[clap(long, default_value = "1")]
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+
+    /// Gas each test call starts with
+    #[clap(long, default_value = "30000000")]
+    #[serde(default = "default_gas_limit")]
+    pub gas_limit: u64,
+
+    /// Path exploration strategy (dfs or bfs)
+    #[clap(long, default_value = "dfs")]
+    #[serde(default = "default_exploration")]
+    pub exploration: String,
+
     /// Array lengths specification
     #[clap(long)]
     pub array_lengths: Option<String>,
@@ -215,7 +235,7 @@ pub struct Config {
     #[serde(default)]
     pub debug_config: bool,
 
-    /// Profile instruction execution frequencies
+    /// Profile instruction execution frequencies into <contract>.profile.json
     #[clap(long)]
     #[serde(default)]
     pub profile_instructions: bool,
@@ -404,6 +424,17 @@ pub struct Config {
     #[clap(long)]
     pub output: Option<PathBuf>,
 
+    /// Comma-separated function selectors of calls to addresses without code
+    /// that return fresh symbolic data instead of failing
+    #[clap(long, default_value = "0x150b7a02,0x1626ba7e,0xf23a6e61,0xbc197c81")]
+    #[serde(default = "default_uninterpreted")]
+    pub uninterpreted_unknown_calls: String,
+
+    /// Size in bytes of the symbolic data returned by uninterpreted unknown calls
+    #[clap(long, default_value = "32")]
+    #[serde(default = "default_return_size")]
+    pub return_size_of_unknown_calls: usize,
+
     // === Deprecated options ===
     /// (Deprecated) Run tests in parallel
     #[clap(long)]
@@ -418,16 +449,25 @@ pub struct Config {
     /// (Deprecated) Log execution steps in JSON
     #[clap(long)]
     pub log: Option<PathBuf>,
+}
 
-    /// (Deprecated) Uninterpreted unknown calls
-    #[clap(long, default_value = "0x150b7a02,0x1626ba7e,0xf23a6e61,0xbc197c81")]
-    #[serde(default = "default_uninterpreted")]
-    pub uninterpreted_unknown_calls: String,
+/// Compiled contract and test selection regexes (see `Config::compile_filters`)
+#[derive(Debug, Clone)]
+pub struct Filters {
+    contract: Regex,
+    test: Regex,
+}
 
-    /// (Deprecated) Return size of unknown calls
-    #[clap(long, default_value = "32")]
-    #[serde(default = "default_return_size")]
-    pub return_size_of_unknown_calls: usize,
+impl Filters {
+    /// True if tests of the contract `name` should run
+    pub fn matches_contract(&self, name: &str) -> bool {
+        self.contract.is_match(name)
+    }
+
+    /// True if the test function `name` should run
+    pub fn matches_test(&self, name: &str) -> bool {
+        self.test.is_match(name)
+    }
 }
 
 // Default value functions
@@ -451,6 +491,22 @@ fn default_loop() -> usize {
     2
 }
 
+fn default_max_memory_size() -> usize {
+    1 << 20
+}
+
+fn default_chain_id() -> u64 {
+    1
+}
+
+fn default_gas_limit() -> u64 {
+    30_000_000
+}
+
+fn default_exploration() -> String {
+    "dfs".to_string()
+}
+
 fn default_array_lengths() -> String {
     "0,1,2".to_string()
 }
@@ -517,6 +573,10 @@ impl Default for Config {
             loop_bound: default_loop(),
             width: 0,
             depth: 0,
+            max_memory_size: default_max_memory_size(),
+            chain_id: default_chain_id(),
+            gas_limit: default_gas_limit(),
+            exploration: default_exploration(),
             array_lengths: None,
             prover_mode: false,
             private_key: None,
@@ -621,6 +681,18 @@ impl Config {
         if other.depth > 0 {
             self.depth = other.depth;
         }
+        if other.max_memory_size != default_max_memory_size() {
+            self.max_memory_size = other.max_memory_size;
+        }
+        if other.chain_id != default_chain_id() {
+            self.chain_id = other.chain_id;
+        }
+        if other.gas_limit != default_gas_limit() {
+            self.gas_limit = other.gas_limit;
+        }
+        if other.exploration != default_exploration() {
+            self.exploration = other.exploration;
+        }
         if other.solver_timeout_assertion != default_solver_timeout_assertion() {
             self.solver_timeout_assertion = other.solver_timeout_assertion;
         }
@@ -630,6 +702,95 @@ impl Config {
         // Add more fields as needed
     }
 
+    /// Fields that differ between two configurations
+    ///
+    /// Returns (field, self value, other value) tuples sorted by field name.
+    pub fn diff(&self, other: &Config) -> Vec<(String, String, String)> {
+        let theirs = other.field_values();
+        self.field_values()
+            .into_iter()
+            .filter_map(|(field, value)| {
+                let other_value = theirs.get(&field).cloned().unwrap_or_default();
+                (value != other_value).then_some((field, value, other_value))
+            })
+            .collect()
+    }
+
+    /// Fields overridden from their defaults, as (field, default, value) tuples
+    pub fn non_default_fields(&self) -> Vec<(String, String, String)> {
+        Config::default().diff(self)
+    }
+
+    /// Every field rendered as a string, keyed by its serialized name
+    fn field_values(&self) -> BTreeMap<String, String> {
+        let fields = match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(fields)) => fields,
+            _ => return BTreeMap::new(),
+        };
+        fields
+            .into_iter()
+            .map(|(field, value)| {
+                let value = match value {
+                    // Never echo the signing key into logs
+                    serde_json::Value::String(_) if field == "private_key" => {
+                        "<redacted>".to_string()
+                    }
+                    serde_json::Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (field, value)
+            })
+            .collect()
+    }
+
+    /// Check option combinations that clap alone cannot enforce
+    pub fn validate(&self) -> Result<()> {
+        if self.prover_mode {
+            let key = self
+                .private_key
+                .as_deref()
+                .map(|key| key.trim_start_matches("0x"))
+                .unwrap_or_default();
+            if key.is_empty() {
+                return Err(anyhow::anyhow!("--prover-mode requires --private-key"));
+            }
+            if key.len() != 64 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow::anyhow!(
+                    "--private-key must be a 32-byte hex string"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Compile the contract and test selection regexes
+    ///
+    /// `contract` selects one contract by exact name and takes precedence over
+    /// `match_contract`; `match_test` takes precedence over `function`. An
+    /// empty pattern matches everything. All three patterns are checked, so
+    /// an invalid one fails here even if it would not be used.
+    pub fn compile_filters(&self) -> Result<Filters> {
+        let compile = |flag: &str, pattern: &str| {
+            let pattern = if pattern.is_empty() { ".*" } else { pattern };
+            Regex::new(pattern).with_context(|| format!("invalid --{} regex: {}", flag, pattern))
+        };
+        let match_contract = compile("match-contract", &self.match_contract)?;
+        let match_test = compile("match-test", &self.match_test)?;
+        let function = compile("function", &self.function)?;
+
+        let contract = if self.contract.is_empty() {
+            match_contract
+        } else {
+            Regex::new(&format!("^{}$", regex::escape(&self.contract)))?
+        };
+        let test = if self.match_test.is_empty() {
+            function
+        } else {
+            match_test
+        };
+        Ok(Filters { contract, test })
+    }
+
     /// Parse array lengths specification
     /// Format: name1={1,2,3},name2=5
     pub fn parse_array_lengths(&self) -> Result<HashMap<String, Vec<usize>>> {
@@ -671,6 +832,23 @@ impl Config {
         Ok(codes)
     }
 
+    /// Parse the selectors of uninterpreted unknown calls
+    pub fn parse_uninterpreted_unknown_calls(&self) -> Result<Vec<u32>> {
+        parse_csv(&self.uninterpreted_unknown_calls)
+            .iter()
+            .map(|part| {
+                let digits = part
+                    .strip_prefix("0x")
+                    .or_else(|| part.strip_prefix("0X"))
+                    .unwrap_or(part);
+                if digits.is_empty() || digits.len() > 8 {
+                    return Err(anyhow::anyhow!("Invalid function selector: {}", part));
+                }
+                Ok(u32::from_str_radix(digits, 16)?)
+            })
+            .collect()
+    }
+
     /// Parse trace events
     pub fn parse_trace_events(&self) -> Result<Vec<TraceEvent>> {
         if let Some(events_str) = &self.trace_events {
@@ -795,10 +973,20 @@ impl TomlConfig {
                 "function" => config.function = parse_toml_string(&value)?,
                 "match_test" => config.match_test = parse_toml_string(&value)?,
                 "panic_error_codes" => config.panic_error_codes = parse_toml_string(&value)?,
+                "uninterpreted_unknown_calls" => {
+                    config.uninterpreted_unknown_calls = parse_toml_string(&value)?
+                }
+                "return_size_of_unknown_calls" => {
+                    config.return_size_of_unknown_calls = parse_toml_usize(&value)?
+                }
                 "invariant_depth" => config.invariant_depth = parse_toml_usize(&value)?,
                 "loop_bound" | "loop" => config.loop_bound = parse_toml_usize(&value)?,
                 "width" => config.width = parse_toml_usize(&value)?,
                 "depth" => config.depth = parse_toml_usize(&value)?,
+                "max_memory_size" => config.max_memory_size = parse_toml_usize(&value)?,
+                "chain_id" => config.chain_id = parse_toml_u64(&value)?,
+                "gas_limit" => config.gas_limit = parse_toml_u64(&value)?,
+                "exploration" => config.exploration = parse_toml_string(&value)?,
                 "array_lengths" => config.array_lengths = Some(parse_toml_string(&value)?),
                 "default_array_lengths" => {
                     config.default_array_lengths = parse_toml_string(&value)?
@@ -1001,6 +1189,23 @@ mod tests {
         assert_eq!(config.loop_bound, 2);
         assert_eq!(config.solver, "yices");
         assert_eq!(config.function, "(check|invariant)_");
+        assert_eq!(config.exploration, "dfs");
+        assert_eq!(config.chain_id, 1);
+        assert_eq!(config.gas_limit, 30_000_000);
+    }
+
+    #[test]
+    fn test_diff_reports_changed_fields() {
+        let config = Config::default();
+        let other = Config {
+            solver: "z3".to_string(),
+            ..Config::default()
+        };
+
+        let expected = vec![("solver".to_string(), "yices".to_string(), "z3".to_string())];
+        assert_eq!(config.diff(&other), expected);
+        assert_eq!(other.non_default_fields(), expected);
+        assert!(config.non_default_fields().is_empty());
     }
 
     #[test]
@@ -1041,6 +1246,21 @@ mod tests {
         assert_eq!(codes, vec![]); // Empty means match all
     }
 
+    #[test]
+    fn test_parse_uninterpreted_unknown_calls() {
+        let mut config = Config::default();
+        assert_eq!(
+            config.parse_uninterpreted_unknown_calls().unwrap(),
+            vec![0x150b7a02, 0x1626ba7e, 0xf23a6e61, 0xbc197c81]
+        );
+
+        config.uninterpreted_unknown_calls = "".to_string();
+        assert_eq!(config.parse_uninterpreted_unknown_calls().unwrap(), vec![]);
+
+        config.uninterpreted_unknown_calls = "0x123456789".to_string();
+        assert!(config.parse_uninterpreted_unknown_calls().is_err());
+    }
+
     #[test]
     fn test_trace_event_parse() {
         assert_eq!("LOG".parse::<TraceEvent>().unwrap(), TraceEvent::Log);
@@ -1131,4 +1351,51 @@ mod tests {
         let cmd = config.resolved_solver_command().unwrap();
         assert_eq!(cmd, vec!["z3", "-in", "-smt2"]);
     }
+
+    #[test]
+    fn test_validate_prover_mode_requires_key() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.prover_mode = true;
+        assert!(config.validate().is_err());
+
+        config.private_key = Some("0x1234".to_string());
+        assert!(config.validate().is_err());
+
+        config.private_key = Some(format!("0x{}", "11".repeat(32)));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_compile_filters() {
+        let config = Config {
+            match_test: "check_.*".to_string(),
+            ..Config::default()
+        };
+        let filters = config.compile_filters().unwrap();
+        assert!(filters.matches_test("check_foo"));
+        assert!(!filters.matches_test("test_bar"));
+        assert!(filters.matches_contract("AnyTest"));
+
+        // Without match_test the function prefix applies; contract is an exact name
+        let config = Config {
+            contract: "Token.t".to_string(),
+            match_contract: "Nope".to_string(),
+            ..Config::default()
+        };
+        let filters = config.compile_filters().unwrap();
+        assert!(filters.matches_test("invariant_supply"));
+        assert!(!filters.matches_test("test_bar"));
+        assert!(filters.matches_contract("Token.t"));
+        assert!(!filters.matches_contract("TokenXt"));
+        assert!(!filters.matches_contract("Nope"));
+
+        let invalid = Config {
+            match_test: "check_(".to_string(),
+            ..Config::default()
+        };
+        let err = invalid.compile_filters().unwrap_err();
+        assert!(err.to_string().contains("--match-test"));
+    }
 }
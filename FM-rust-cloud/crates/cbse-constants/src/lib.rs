@@ -26,6 +26,15 @@ pub const HASH_SIZE: usize = 32;
 /// Maximum call depth for EVM execution
 pub const MAX_CALL_DEPTH: usize = 1024;
 
+/// Maximum number of items on the EVM stack
+pub const STACK_LIMIT: usize = 1024;
+
+/// Maximum deployed code size in bytes (EIP-170)
+pub const MAX_CODE_SIZE: usize = 0x6000;
+
+/// Maximum init code size in bytes (EIP-3860)
+pub const MAX_INITCODE_SIZE: usize = 2 * MAX_CODE_SIZE;
+
 /// Empty Keccak-256 hash (keccak256(""))
 pub const EMPTY_KECCAK: [u8; 32] = [
     0xC5, 0xD2, 0x46, 0x01, 0x86, 0xF7, 0x23, 0x3C, 0x92, 0x7E, 0x7D, 0xB2, 0xDC, 0xC7, 0x03, 0xC0,
@@ -73,6 +82,12 @@ mod tests {
         assert_eq!(MAX_MEMORY_SIZE, 1048576);
     }
 
+    #[test]
+    fn test_code_size_limits() {
+        assert_eq!(MAX_CODE_SIZE, 24576);
+        assert_eq!(MAX_INITCODE_SIZE, 49152);
+    }
+
     #[test]
     fn test_verbosity_levels() {
         assert_eq!(VERBOSITY_TRACE_COUNTEREXAMPLE, 2);
@@ -87,6 +102,7 @@ mod tests {
         assert_eq!(ADDRESS_SIZE, 20);
         assert_eq!(HASH_SIZE, 32);
         assert_eq!(MAX_CALL_DEPTH, 1024);
+        assert_eq!(STACK_LIMIT, 1024);
     }
 
     #[test]
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Static gas costs for the Cancun gas schedule.
+//!
+//! Only the constant portion of each opcode's cost is covered here. Memory
+//! expansion, per-word copy costs and EIP-2929 warm/cold surcharges are
+//! accounted for by the interpreter on top of `base_cost`.
+
+use crate::*;
+
+/// Sentinel returned for opcodes whose cost is entirely dynamic
+/// (account/storage access, calls) or undefined.
+pub const GAS_DYNAMIC: u64 = u64::MAX;
+
+// Yellow paper fee tiers
+pub const G_ZERO: u64 = 0;
+pub const G_JUMPDEST: u64 = 1;
+pub const G_BASE: u64 = 2;
+pub const G_VERYLOW: u64 = 3;
+pub const G_LOW: u64 = 5;
+pub const G_MID: u64 = 8;
+pub const G_HIGH: u64 = 10;
+pub const G_EXT: u64 = 20;
+
+pub const G_EXP: u64 = 10;
+pub const G_EXP_BYTE: u64 = 50;
+pub const G_KECCAK256: u64 = 30;
+pub const G_KECCAK256_WORD: u64 = 6;
+pub const G_COPY: u64 = 3;
+pub const G_LOG: u64 = 375;
+pub const G_LOG_TOPIC: u64 = 375;
+pub const G_LOG_DATA: u64 = 8;
+pub const G_CREATE: u64 = 32000;
+pub const G_SELFDESTRUCT: u64 = 5000;
+
+// EIP-2929 access costs
+pub const G_WARM_ACCESS: u64 = 100;
+pub const G_COLD_SLOAD: u64 = 2100;
+pub const G_COLD_ACCOUNT_ACCESS: u64 = 2600;
+
+// EIP-2200/3529 storage costs
+pub const G_SSTORE_SET: u64 = 20000;
+pub const G_SSTORE_RESET: u64 = 2900;
+pub const R_SSTORE_CLEAR: u64 = 4800;
+/// The refund applied at the end of a transaction is capped at
+/// gas_used / REFUND_QUOTIENT
+pub const REFUND_QUOTIENT: u64 = 5;
+
+/// Returns the static gas cost of `opcode`, or `GAS_DYNAMIC` if the cost
+/// depends entirely on execution state.
+pub fn base_cost(opcode: u8) -> u64 {
+    match opcode {
+        OP_STOP | OP_RETURN | OP_REVERT => G_ZERO,
+
+        OP_JUMPDEST => G_JUMPDEST,
+
+        OP_ADDRESS | OP_ORIGIN | OP_CALLER | OP_CALLVALUE | OP_CALLDATASIZE | OP_CODESIZE
+        | OP_GASPRICE | OP_RETURNDATASIZE | OP_COINBASE | OP_TIMESTAMP | OP_NUMBER
+        | OP_DIFFICULTY | OP_GASLIMIT | OP_CHAINID | OP_BASEFEE | OP_POP | OP_PC | OP_MSIZE
+        | OP_GAS | OP_PUSH0 => G_BASE,
+        // BLOBBASEFEE
+        0x4a => G_BASE,
+
+        OP_ADD | OP_SUB | OP_NOT | OP_LT | OP_GT | OP_SLT | OP_SGT | OP_EQ | OP_ISZERO | OP_AND
+        | OP_OR | OP_XOR | OP_BYTE | OP_SHL | OP_SHR | OP_SAR | OP_CALLDATALOAD | OP_MLOAD
+        | OP_MSTORE | OP_MSTORE8 => G_VERYLOW,
+        // BLOBHASH
+        0x49 => G_VERYLOW,
+        // copy opcodes: static part only, per-word cost is dynamic
+        OP_CALLDATACOPY | OP_CODECOPY | OP_RETURNDATACOPY | OP_MCOPY => G_VERYLOW,
+        OP_PUSH1..=OP_PUSH32 | OP_DUP1..=OP_DUP16 | OP_SWAP1..=OP_SWAP16 => G_VERYLOW,
+
+        OP_MUL | OP_DIV | OP_SDIV | OP_MOD | OP_SMOD | OP_SIGNEXTEND | OP_SELFBALANCE => G_LOW,
+
+        OP_ADDMOD | OP_MULMOD | OP_JUMP => G_MID,
+
+        OP_JUMPI => G_HIGH,
+        OP_EXP => G_EXP,
+
+        OP_BLOCKHASH => G_EXT,
+
+        OP_SHA3 => G_KECCAK256,
+
+        OP_TLOAD | OP_TSTORE => G_WARM_ACCESS,
+
+        OP_LOG0..=OP_LOG4 => G_LOG + G_LOG_TOPIC * (opcode - OP_LOG0) as u64,
+
+        OP_CREATE | OP_CREATE2 => G_CREATE,
+        OP_SELFDESTRUCT => G_SELFDESTRUCT,
+
+        // warm/cold account or storage access
+        OP_BALANCE | OP_EXTCODESIZE | OP_EXTCODECOPY | OP_EXTCODEHASH | OP_SLOAD | OP_SSTORE
+        | OP_CALL | OP_CALLCODE | OP_DELEGATECALL | OP_STATICCALL => GAS_DYNAMIC,
+
+        // INVALID and undefined opcodes consume all remaining gas
+        _ => GAS_DYNAMIC,
+    }
+}
+
+/// Returns true if `opcode` has no static cost component
+pub fn is_dynamic(opcode: u8) -> bool {
+    base_cost(opcode) == GAS_DYNAMIC
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_cost_tiers() {
+        assert_eq!(base_cost(OP_STOP), 0);
+        assert_eq!(base_cost(OP_ADD), 3);
+        assert_eq!(base_cost(OP_MUL), 5);
+        assert_eq!(base_cost(OP_ADDMOD), 8);
+        assert_eq!(base_cost(OP_JUMPI), 10);
+        assert_eq!(base_cost(OP_JUMPDEST), 1);
+        assert_eq!(base_cost(OP_CALLER), 2);
+        assert_eq!(base_cost(OP_PUSH0), 2);
+        assert_eq!(base_cost(OP_PUSH32), 3);
+        assert_eq!(base_cost(OP_SWAP16), 3);
+    }
+
+    #[test]
+    fn test_base_cost_special() {
+        assert_eq!(base_cost(OP_SHA3), 30);
+        assert_eq!(base_cost(OP_EXP), 10);
+        assert_eq!(base_cost(OP_BLOCKHASH), 20);
+        assert_eq!(base_cost(OP_LOG0), 375);
+        assert_eq!(base_cost(OP_LOG4), 1875);
+        assert_eq!(base_cost(OP_CREATE2), 32000);
+        assert_eq!(base_cost(OP_TSTORE), 100);
+    }
+
+    #[test]
+    fn test_base_cost_dynamic() {
+        assert!(is_dynamic(OP_SLOAD));
+        assert!(is_dynamic(OP_SSTORE));
+        assert!(is_dynamic(OP_CALL));
+        assert!(is_dynamic(OP_BALANCE));
+        assert!(is_dynamic(OP_INVALID));
+        assert!(is_dynamic(0x0c));
+        assert!(!is_dynamic(OP_ADD));
+    }
+}
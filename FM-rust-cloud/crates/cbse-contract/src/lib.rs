@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: AGPL-3.0
 
 use once_cell::sync::Lazy;
+use std::cell::OnceCell;
 use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 
@@ -8,9 +9,12 @@ use cbse_bitvec::CbseBitVec;
 use cbse_bytevec::{ByteVec, UnwrappedBytes};
 use cbse_constants::MAX_MEMORY_SIZE;
 use cbse_exceptions::CbseException;
+use cbse_hashes::keccak256;
 use cbse_utils::{hexify, stripped};
 use z3::Context;
 
+pub mod gas;
+
 /// Helper function to convert bitvector to 256 bits
 fn uint256<'ctx>(value: &CbseBitVec<'ctx>, ctx: &'ctx Context) -> CbseBitVec<'ctx> {
     let current_size = value.size();
@@ -355,7 +359,11 @@ impl<'ctx> Instruction<'ctx> {
     }
 }
 
+/// Length in hex characters of a library address placeholder
+const PLACEHOLDER_LEN: usize = 40;
+
 /// Abstraction over contract bytecode with instruction decoding
+#[derive(Clone)]
 pub struct Contract<'ctx> {
     code: ByteVec<'ctx>,
     fastcode: Option<Vec<u8>>,
@@ -363,6 +371,12 @@ pub struct Contract<'ctx> {
     jumpdests: Option<HashSet<usize>>,
     ctx: &'ctx Context,
 
+    /// Library placeholders still in the code: (byte offset, placeholder text)
+    unlinked: Vec<(usize, String)>,
+
+    /// keccak256 of the code, computed on first use
+    code_hash: OnceCell<[u8; 32]>,
+
     pub contract_name: Option<String>,
     pub filename: Option<String>,
     pub source_map: Option<String>,
@@ -379,11 +393,23 @@ impl<'ctx> Contract<'ctx> {
     ) -> Self {
         let len = code.len();
 
-        // Extract concrete prefix for fast access - try to unwrap and get concrete bytes
-        let fastcode = code.unwrap().ok().and_then(|unwrapped| match unwrapped {
-            UnwrappedBytes::Bytes(bytes) => Some(bytes),
-            _ => None,
-        });
+        // Extract concrete prefix for fast access - the whole code if it unwraps to
+        // concrete bytes, otherwise the bytes before the first symbolic one
+        let fastcode = match code.unwrap() {
+            Ok(UnwrappedBytes::Bytes(bytes)) => Some(bytes),
+            _ => {
+                let mut prefix = Vec::new();
+                for i in 0..len {
+                    match code.get_byte(i) {
+                        Ok(UnwrappedBytes::Bytes(bytes)) if !bytes.is_empty() => {
+                            prefix.push(bytes[0])
+                        }
+                        _ => break,
+                    }
+                }
+                (!prefix.is_empty()).then_some(prefix)
+            }
+        };
 
         Self {
             code,
@@ -391,6 +417,8 @@ impl<'ctx> Contract<'ctx> {
             insn: vec![None; len],
             jumpdests: None,
             ctx,
+            unlinked: Vec::new(),
+            code_hash: OnceCell::new(),
             contract_name,
             filename,
             source_map,
@@ -406,22 +434,103 @@ impl<'ctx> Contract<'ctx> {
             )));
         }
 
-        if hexcode.contains("__") {
-            eprintln!("Warning: contract hexcode contains library placeholder");
+        // Library placeholders are zeroed until link_libraries fills them in
+        let mut stripped_hex = stripped(hexcode).to_string();
+        let mut unlinked = Vec::new();
+        let mut i = 0;
+        while i + 2 <= stripped_hex.len() {
+            if stripped_hex.as_bytes()[i..i + 2] == *b"__"
+                && stripped_hex.is_char_boundary(i + PLACEHOLDER_LEN)
+            {
+                unlinked.push((i / 2, stripped_hex[i..i + PLACEHOLDER_LEN].to_string()));
+                stripped_hex.replace_range(i..i + PLACEHOLDER_LEN, &"0".repeat(PLACEHOLDER_LEN));
+                i += PLACEHOLDER_LEN;
+            } else {
+                i += 2;
+            }
         }
 
-        let stripped_hex = stripped(hexcode);
-        let bytes = hex::decode(stripped_hex).map_err(|e| {
+        let bytes = hex::decode(&stripped_hex).map_err(|e| {
             CbseException::Internal(format!("Invalid hex: {} (hexcode={})", e, hexcode))
         })?;
 
-        Ok(Self::new(
-            ByteVec::from_bytes(bytes, ctx)?,
-            ctx,
-            None,
-            None,
-            None,
-        ))
+        let mut contract = Self::new(ByteVec::from_bytes(bytes, ctx)?, ctx, None, None, None);
+        contract.unlinked = unlinked;
+        Ok(contract)
+    }
+
+    /// Placeholder solc leaves in place of the address of library `name`
+    ///
+    /// `name` is the fully qualified name, e.g. "src/Lib.sol:Lib".
+    pub fn library_placeholder(name: &str) -> String {
+        let hash = hex::encode(keccak256(name.as_bytes()));
+        format!("__${}$__", &hash[..34])
+    }
+
+    /// Overwrite library placeholders with the addresses in `links`
+    ///
+    /// `links` maps fully qualified library names to their deployed address.
+    /// Both current (`__$hash$__`) and legacy (`__path:Name___`) placeholders
+    /// are recognized. Placeholders of libraries not in `links` are left for
+    /// `check_linked` to report.
+    pub fn link_libraries(
+        &mut self,
+        links: &HashMap<String, [u8; 20]>,
+    ) -> Result<(), CbseException> {
+        let mut bytes = match self.code.unwrap()? {
+            UnwrappedBytes::Bytes(bytes) => bytes,
+            UnwrappedBytes::BitVec(_) => {
+                return Err(CbseException::NotConcrete(
+                    "cannot link libraries into symbolic code".to_string(),
+                ))
+            }
+        };
+
+        let placeholders: Vec<(String, &[u8; 20])> = links
+            .iter()
+            .flat_map(|(name, address)| {
+                let legacy: String = format!("__{:_<38}", name)
+                    .chars()
+                    .take(PLACEHOLDER_LEN)
+                    .collect();
+                [
+                    (Self::library_placeholder(name), address),
+                    (legacy, address),
+                ]
+            })
+            .collect();
+
+        let mut remaining = Vec::new();
+        for (offset, placeholder) in std::mem::take(&mut self.unlinked) {
+            match placeholders.iter().find(|(p, _)| *p == placeholder) {
+                Some((_, address)) => bytes[offset..offset + 20].copy_from_slice(*address),
+                None => remaining.push((offset, placeholder)),
+            }
+        }
+
+        let linked = Self::new(
+            ByteVec::from_bytes(bytes, self.ctx)?,
+            self.ctx,
+            self.contract_name.take(),
+            self.filename.take(),
+            self.source_map.take(),
+        );
+        *self = Self {
+            unlinked: remaining,
+            ..linked
+        };
+        Ok(())
+    }
+
+    /// Error naming the first library placeholder that was never linked, if any
+    pub fn check_linked(&self) -> Result<(), CbseException> {
+        match self.unlinked.first() {
+            Some((offset, placeholder)) => Err(CbseException::Internal(format!(
+                "unlinked library placeholder {} at byte {}",
+                placeholder, offset
+            ))),
+            None => Ok(()),
+        }
     }
 
     /// Scans the bytecode for valid jump destinations
@@ -627,6 +736,37 @@ impl<'ctx> Contract<'ctx> {
         self.code.len()
     }
 
+    /// Returns the number of leading bytes that are concrete
+    pub fn concrete_prefix_len(&self) -> usize {
+        self.fastcode.as_ref().map_or(0, |fastcode| fastcode.len())
+    }
+
+    /// Returns true if some of the code after the concrete prefix is symbolic
+    pub fn has_symbolic_tail(&self) -> bool {
+        self.concrete_prefix_len() < self.len()
+    }
+
+    /// keccak256 of the code, as returned by EXTCODEHASH
+    ///
+    /// Computed once and cached; fails if any byte of the code is symbolic.
+    pub fn code_hash(&self) -> Result<[u8; 32], CbseException> {
+        if let Some(hash) = self.code_hash.get() {
+            return Ok(*hash);
+        }
+        if self.has_symbolic_tail() {
+            return Err(CbseException::NotConcrete(
+                "cannot hash symbolic code".to_string(),
+            ));
+        }
+        let code = self.fastcode.as_deref().unwrap_or_default();
+        Ok(*self.code_hash.get_or_init(|| keccak256(code)))
+    }
+
+    /// Returns the Z3 context the bytecode was created in
+    pub fn ctx(&self) -> &'ctx Context {
+        self.ctx
+    }
+
     /// Returns the set of valid jump destinations
     pub fn valid_jumpdests(&mut self) -> &HashSet<usize> {
         if self.jumpdests.is_none() {
@@ -759,6 +899,113 @@ impl CoverageReporter {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_concrete_prefix_fully_concrete() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let contract = Contract::from_hexcode("6001005b", &ctx).unwrap();
+
+        assert_eq!(contract.concrete_prefix_len(), 4);
+        assert!(!contract.has_symbolic_tail());
+    }
+
+    #[test]
+    fn test_concrete_prefix_symbolic_tail() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // PUSH1 1; JUMPDEST; <symbolic byte>
+        let mut code = ByteVec::from_bytes(vec![OP_PUSH1, 0x01, OP_JUMPDEST], &ctx).unwrap();
+        code.append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(
+            z3::ast::BV::new_const(&ctx, "tail", 8),
+        )))
+        .unwrap();
+        let mut contract = Contract::new(code, &ctx, None, None, None);
+
+        assert_eq!(contract.len(), 4);
+        assert_eq!(contract.concrete_prefix_len(), 3);
+        assert!(contract.has_symbolic_tail());
+        assert_eq!(contract.get_byte(2).unwrap(), OP_JUMPDEST);
+        assert!(contract.get_byte(3).is_err());
+        assert!(contract.valid_jumpdests().contains(&2));
+    }
+
+    #[test]
+    fn test_code_hash_is_cached() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let contract = Contract::from_hexcode("6001005b", &ctx).unwrap();
+
+        assert!(contract.code_hash.get().is_none());
+        let hash = contract.code_hash().unwrap();
+        assert_eq!(hash, keccak256(&[0x60, 0x01, 0x00, 0x5b]));
+        assert_eq!(contract.code_hash.get(), Some(&hash));
+        assert_eq!(contract.code_hash().unwrap(), hash);
+
+        let empty = Contract::from_hexcode("", &ctx).unwrap();
+        assert_eq!(empty.code_hash().unwrap(), keccak256(&[]));
+    }
+
+    #[test]
+    fn test_code_hash_of_symbolic_code_fails() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut code = ByteVec::from_bytes(vec![OP_PUSH1, 0x01], &ctx).unwrap();
+        code.append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(
+            z3::ast::BV::new_const(&ctx, "tail", 8),
+        )))
+        .unwrap();
+        let contract = Contract::new(code, &ctx, None, None, None);
+
+        assert!(contract.code_hash().is_err());
+        assert!(contract.code_hash.get().is_none());
+    }
+
+    #[test]
+    fn test_linking_replaces_code_hash() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let placeholder = Contract::library_placeholder("src/Lib.sol:Lib");
+        let mut contract = Contract::from_hexcode(&format!("73{}3b00", placeholder), &ctx).unwrap();
+        let unlinked = contract.code_hash().unwrap();
+
+        let links = HashMap::from([("src/Lib.sol:Lib".to_string(), [0xabu8; 20])]);
+        contract.link_libraries(&links).unwrap();
+
+        let mut linked_code = vec![OP_PUSH20];
+        linked_code.extend([0xab; 20]);
+        linked_code.extend([0x3b, 0x00]);
+        assert_ne!(contract.code_hash().unwrap(), unlinked);
+        assert_eq!(contract.code_hash().unwrap(), keccak256(&linked_code));
+    }
+
+    #[test]
+    fn test_link_library_placeholder() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let placeholder = Contract::library_placeholder("src/Lib.sol:Lib");
+        assert_eq!(placeholder.len(), 40);
+
+        // PUSH20 <Lib>; EXTCODESIZE; STOP
+        let mut contract = Contract::from_hexcode(&format!("73{}3b00", placeholder), &ctx).unwrap();
+        assert_eq!(contract.len(), 23);
+        assert!(contract.check_linked().is_err());
+
+        // Unrelated libraries leave the placeholder in place
+        let other = HashMap::from([("src/Other.sol:Other".to_string(), [0x11u8; 20])]);
+        contract.link_libraries(&other).unwrap();
+        assert!(contract.check_linked().is_err());
+
+        let links = HashMap::from([("src/Lib.sol:Lib".to_string(), [0xabu8; 20])]);
+        contract.link_libraries(&links).unwrap();
+        assert!(contract.check_linked().is_ok());
+        assert_eq!(contract.get_byte(0).unwrap(), OP_PUSH20);
+        for i in 1..21 {
+            assert_eq!(contract.get_byte(i).unwrap(), 0xab);
+        }
+        assert_eq!(contract.get_byte(21).unwrap(), 0x3b);
+    }
+
     #[test]
     fn test_insn_len() {
         assert_eq!(insn_len(OP_STOP), 1);
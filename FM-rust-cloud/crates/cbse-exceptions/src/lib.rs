@@ -190,18 +190,29 @@ pub enum CbseException {
     #[error("Internal error: {0}")]
     Internal(String),
 
+    /// Malformed ABI-encoded input, such as bad cheatcode calldata
+    #[error("ABI decoding error: {0}")]
+    AbiDecode(String),
+
     #[error("Infeasible path: {0}")]
     InfeasiblePath(String),
 
     #[error("Revert")]
     Revert,
 
+    /// Ends the current path like an EVM exceptional halt, consuming all gas
+    #[error("Exceptional halt: {0}")]
+    Halt(ExceptionalHalt),
+
     #[error("Fail cheatcode invoked")]
     FailCheatcode,
 
     #[error("Solver timeout")]
     SolverTimeout,
 
+    #[error("Solver returned unknown: {0}")]
+    SolverUnknown(String),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -289,5 +300,11 @@ mod tests {
     fn test_cbse_exception_display() {
         let err = CbseException::NotConcrete("test".to_string());
         assert_eq!(err.to_string(), "Value is not concrete: test");
+
+        let err = CbseException::SolverUnknown("incomplete quantifiers".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Solver returned unknown: incomplete quantifiers"
+        );
     }
 }
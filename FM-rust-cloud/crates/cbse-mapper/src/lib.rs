@@ -387,6 +387,63 @@ impl BuildOut {
 
         Ok(placeholders)
     }
+
+    /// Creation bytecode of the contract identified by `path`, as read by `vm.getCode`
+    ///
+    /// `path` is "File.sol:Name", "File.sol" for the contract named after the
+    /// file, or a bare "Name" looked up in every file. Files are matched by
+    /// their base name. Bytecode with unlinked library placeholders is rejected.
+    pub fn get_creation_code(&self, path: &str) -> Result<Vec<u8>, String> {
+        let (file, name) = match path.rsplit_once(':') {
+            Some((file, name)) => (Some(file), name),
+            None => match path.strip_suffix(".sol") {
+                Some(stem) => (Some(path), stem.rsplit('/').next().unwrap_or(stem)),
+                None => (None, path),
+            },
+        };
+        let file = file.map(|f| f.rsplit('/').next().unwrap_or(f));
+
+        let build_out = self.build_out_map.lock().unwrap();
+        let files = build_out
+            .as_ref()
+            .and_then(|b| b.as_object())
+            .ok_or_else(|| "build output not loaded".to_string())?;
+
+        let mut matches = files
+            .iter()
+            .filter(|(filename, _)| file.map_or(true, |f| f == filename.as_str()))
+            .filter_map(|(_, contracts)| contracts.get(name));
+        let artifact = matches
+            .next()
+            .ok_or_else(|| format!("contract not found: {}", path))?;
+        if matches.next().is_some() {
+            return Err(format!(
+                "ambiguous contract name {}, use File.sol:Name",
+                path
+            ));
+        }
+
+        let bytecode = artifact
+            .get("bytecode")
+            .ok_or_else(|| format!("no bytecode for {}", path))?;
+        let object = bytecode
+            .get("object")
+            .and_then(|o| o.as_str())
+            .ok_or_else(|| format!("no bytecode for {}", path))?;
+        let has_links = bytecode
+            .get("linkReferences")
+            .and_then(|l| l.as_object())
+            .map_or(false, |l| !l.is_empty());
+        if has_links || object.contains("__") {
+            return Err(format!(
+                "{} has unlinked library placeholders, which getCode does not support",
+                path
+            ));
+        }
+
+        hex::decode(object.strip_prefix("0x").unwrap_or(object))
+            .map_err(|e| format!("invalid bytecode for {}: {}", path, e))
+    }
 }
 
 /// Deploy address mapper
@@ -883,6 +940,44 @@ mod tests {
         assert_eq!(placeholders[1], (50, 82));
     }
 
+    #[test]
+    fn test_get_creation_code_by_path() {
+        let build_out = BuildOut::new();
+        build_out.set_build_out(serde_json::json!({
+            "Counter.sol": {
+                "Counter": {"bytecode": {"object": "0x6001", "linkReferences": {}}},
+                "Helper": {"bytecode": {"object": "0x6002"}}
+            },
+            "Other.sol": {
+                "Helper": {"bytecode": {"object": "0x6003"}},
+                "Linked": {"bytecode": {
+                    "object": "0x73__$0123456789abcdef0123456789abcdef01$__",
+                    "linkReferences": {"Lib.sol": {"Lib": [{"start": 1, "length": 20}]}}
+                }}
+            }
+        }));
+
+        assert_eq!(
+            build_out.get_creation_code("Counter.sol:Counter"),
+            Ok(vec![0x60, 0x01])
+        );
+        assert_eq!(
+            build_out.get_creation_code("src/Counter.sol"),
+            Ok(vec![0x60, 0x01])
+        );
+        assert_eq!(build_out.get_creation_code("Counter"), Ok(vec![0x60, 0x01]));
+        assert_eq!(
+            build_out.get_creation_code("Other.sol:Helper"),
+            Ok(vec![0x60, 0x03])
+        );
+        assert!(build_out.get_creation_code("Helper").is_err());
+        assert!(build_out.get_creation_code("Missing").is_err());
+        assert!(build_out
+            .get_creation_code("Other.sol:Linked")
+            .unwrap_err()
+            .contains("library"));
+    }
+
     #[test]
     fn test_build_out_placeholders_links() {
         let build_out = BuildOut::instance();
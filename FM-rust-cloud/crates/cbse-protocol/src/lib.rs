@@ -75,6 +75,37 @@ impl VerificationAttestation {
             payload: result,
         })
     }
+
+    /// Check that the attestation was signed by `prover_address` over `payload`
+    ///
+    /// Recomputes the result hash from the payload and recovers the signer's
+    /// public key from the 65-byte signature.
+    pub fn verify(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let result_hash = self.payload.hash();
+        if result_hash != self.result_hash {
+            return Err("result hash does not match payload".into());
+        }
+
+        if self.signature.len() != 65 {
+            return Err(
+                format!("expected 65 signature bytes, got {}", self.signature.len()).into(),
+            );
+        }
+        let signature = Signature::from_slice(&self.signature[..64])?;
+        let recovery_id = self.signature[64]
+            .checked_sub(27)
+            .and_then(RecoveryId::from_byte)
+            .ok_or("invalid recovery id")?;
+
+        let hash_bytes = hex::decode(&result_hash)?;
+        let verifying_key =
+            VerifyingKey::recover_from_prehash(&hash_bytes, &signature, recovery_id)?;
+
+        if !eth_address_from_pubkey(&verifying_key).eq_ignore_ascii_case(&self.prover_address) {
+            return Err("signature was not made by the prover address".into());
+        }
+        Ok(())
+    }
 }
 
 fn eth_address_from_pubkey(pubkey: &VerifyingKey) -> String {
@@ -85,3 +116,57 @@ fn eth_address_from_pubkey(pubkey: &VerifyingKey) -> String {
     let address_bytes = &hash[12..];
     format!("0x{}", hex::encode(address_bytes))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    fn passing_result() -> VerificationResult {
+        VerificationResult {
+            passed: true,
+            contract_bytecode_hash: format!("0x{}", "00".repeat(32)),
+            spec_hash: format!("0x{}", "01".repeat(32)),
+            timestamp: 1_700_000_000,
+            details: r#"{"test/Counter.t.sol:CounterTest":["check_increment"]}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let attestation =
+            VerificationAttestation::sign(passing_result(), PRIVATE_KEY, "0.1.0".to_string())
+                .unwrap();
+        assert_eq!(attestation.signature.len(), 65);
+
+        let signing_key =
+            SigningKey::from_slice(&hex::decode(PRIVATE_KEY.trim_start_matches("0x")).unwrap())
+                .unwrap();
+        assert_eq!(
+            attestation.prover_address,
+            eth_address_from_pubkey(signing_key.verifying_key())
+        );
+        attestation.verify().unwrap();
+
+        // Survives the JSON round trip the coordinator does
+        let json = serde_json::to_string(&attestation).unwrap();
+        let parsed: VerificationAttestation = serde_json::from_str(&json).unwrap();
+        parsed.verify().unwrap();
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let mut attestation =
+            VerificationAttestation::sign(passing_result(), PRIVATE_KEY, "0.1.0".to_string())
+                .unwrap();
+        attestation.payload.passed = false;
+        assert!(attestation.verify().is_err());
+
+        let mut attestation =
+            VerificationAttestation::sign(passing_result(), PRIVATE_KEY, "0.1.0".to_string())
+                .unwrap();
+        attestation.prover_address = format!("0x{}", "22".repeat(20));
+        assert!(attestation.verify().is_err());
+    }
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Uninterpreted functions abstracting nonlinear 256-bit arithmetic
+//!
+//! Matches Python's f_mul/f_div/f_mod/f_sdiv/f_smod in halmos/sevm.py. Keeping
+//! symbolic-by-symbolic multiplication and division uninterpreted stops the
+//! solver from bit-blasting them on every branch check.
+
+use z3::{Context, FuncDecl, Sort};
+
+/// Abstraction declarations passed to CbseBitVec's `*_with_abstraction` methods
+///
+/// Created once per SEVM so every step reuses the same declarations.
+#[derive(Debug)]
+pub struct ArithAbstractions<'ctx> {
+    pub mul: FuncDecl<'ctx>,
+    pub udiv: FuncDecl<'ctx>,
+    pub urem: FuncDecl<'ctx>,
+    pub sdiv: FuncDecl<'ctx>,
+    pub smod: FuncDecl<'ctx>,
+}
+
+impl<'ctx> ArithAbstractions<'ctx> {
+    pub fn new(ctx: &'ctx Context) -> Self {
+        let binop = |name: &str| {
+            let word = Sort::bitvector(ctx, 256);
+            FuncDecl::new(ctx, name, &[&word, &word], &word)
+        };
+
+        Self {
+            mul: binop("f_evm_bvmul_256"),
+            udiv: binop("f_evm_bvudiv_256"),
+            urem: binop("f_evm_bvurem_256"),
+            sdiv: binop("f_evm_bvsdiv_256"),
+            smod: binop("f_evm_bvsrem_256"),
+        }
+    }
+}
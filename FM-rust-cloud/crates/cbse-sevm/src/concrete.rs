@@ -0,0 +1,363 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Fast interpreter for fully concrete instructions
+//!
+//! Arithmetic, stack and memory opcodes whose operands are all concrete are run
+//! directly on BigUint and bytes, without building Z3 terms. Anything else, and
+//! any instruction that reads a symbolic value, is left to `execute_opcode`.
+
+use super::opcodes::{
+    OP_ADD, OP_ADDMOD, OP_AND, OP_BYTE, OP_DIV, OP_DUP1, OP_DUP16, OP_EQ, OP_GT, OP_ISZERO,
+    OP_JUMPDEST, OP_LT, OP_MLOAD, OP_MOD, OP_MSTORE, OP_MSTORE8, OP_MUL, OP_MULMOD, OP_NOT, OP_OR,
+    OP_POP, OP_PUSH0, OP_PUSH32, OP_SAR, OP_SDIV, OP_SGT, OP_SHL, OP_SHR, OP_SLT, OP_SMOD, OP_SUB,
+    OP_SWAP1, OP_SWAP16, OP_XOR,
+};
+use super::{ExecState, SEVM};
+use cbse_bitvec::CbseBitVec;
+use cbse_bytevec::UnwrappedBytes;
+use cbse_contract::Contract;
+use cbse_exceptions::CbseResult;
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+
+/// 2^256, the modulus of EVM word arithmetic
+fn word_modulus() -> BigUint {
+    BigUint::one() << 256
+}
+
+/// Whether the word has its sign bit set
+fn is_negative(value: &BigUint) -> bool {
+    value.bit(255)
+}
+
+/// Two's complement negation modulo 2^256
+fn negate(value: &BigUint) -> BigUint {
+    if value.is_zero() {
+        BigUint::zero()
+    } else {
+        word_modulus() - value
+    }
+}
+
+/// Absolute value of a word read as a signed integer
+fn abs(value: &BigUint) -> BigUint {
+    if is_negative(value) {
+        negate(value)
+    } else {
+        value.clone()
+    }
+}
+
+fn from_bool(value: bool) -> BigUint {
+    if value {
+        BigUint::one()
+    } else {
+        BigUint::zero()
+    }
+}
+
+/// Signed less-than on words
+fn slt(a: &BigUint, b: &BigUint) -> bool {
+    match (is_negative(a), is_negative(b)) {
+        (true, false) => true,
+        (false, true) => false,
+        _ => a < b,
+    }
+}
+
+/// Number of concrete stack operands read by each opcode `concrete_step` handles
+fn arity(opcode: u8) -> Option<usize> {
+    match opcode {
+        OP_ADD..=OP_SMOD => Some(2),
+        OP_ADDMOD | OP_MULMOD => Some(3),
+        OP_LT..=OP_EQ => Some(2),
+        OP_ISZERO => Some(1),
+        OP_AND..=OP_XOR => Some(2),
+        OP_NOT => Some(1),
+        OP_BYTE..=OP_SAR => Some(2),
+        // POP, DUP and SWAP only move values around, so they never need their
+        // operands to be concrete
+        OP_POP | OP_DUP1..=OP_SWAP16 => Some(0),
+        OP_MLOAD => Some(1),
+        OP_MSTORE | OP_MSTORE8 => Some(2),
+        OP_JUMPDEST | OP_PUSH0..=OP_PUSH32 => Some(0),
+        _ => None,
+    }
+}
+
+impl<'ctx> SEVM<'ctx> {
+    /// The top `n` stack items, top first, if they are all concrete
+    fn concrete_operands(state: &ExecState<'ctx>, n: usize) -> Option<Vec<BigUint>> {
+        if state.stack.len() < n {
+            return None;
+        }
+        state
+            .stack
+            .iter()
+            .rev()
+            .take(n)
+            .map(|item| item.as_biguint().ok())
+            .collect()
+    }
+
+    /// Execute `opcode` on concrete values if it is one the fast interpreter
+    /// handles and every operand it reads is concrete
+    ///
+    /// Returns false, leaving the state untouched, when the instruction must go
+    /// through `execute_opcode` instead.
+    pub(crate) fn concrete_step(
+        &mut self,
+        opcode: u8,
+        state: &mut ExecState<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<bool> {
+        let Some(n) = arity(opcode) else {
+            return Ok(false);
+        };
+        let Some(args) = Self::concrete_operands(state, n) else {
+            return Ok(false);
+        };
+
+        let modulus = word_modulus();
+        let result = match opcode {
+            OP_ADD => (&args[0] + &args[1]) % &modulus,
+            OP_MUL => (&args[0] * &args[1]) % &modulus,
+            OP_SUB => (&args[0] + &modulus - &args[1]) % &modulus,
+            OP_DIV if args[1].is_zero() => BigUint::zero(),
+            OP_DIV => &args[0] / &args[1],
+            OP_SDIV if args[1].is_zero() => BigUint::zero(),
+            OP_SDIV => {
+                let quotient = abs(&args[0]) / abs(&args[1]);
+                if is_negative(&args[0]) != is_negative(&args[1]) {
+                    negate(&quotient)
+                } else {
+                    quotient
+                }
+            }
+            OP_MOD if args[1].is_zero() => BigUint::zero(),
+            OP_MOD => &args[0] % &args[1],
+            OP_SMOD if args[1].is_zero() => BigUint::zero(),
+            OP_SMOD => {
+                // The remainder takes the sign of the dividend
+                let remainder = abs(&args[0]) % abs(&args[1]);
+                if is_negative(&args[0]) {
+                    negate(&remainder)
+                } else {
+                    remainder
+                }
+            }
+            OP_ADDMOD | OP_MULMOD if args[2].is_zero() => BigUint::zero(),
+            OP_ADDMOD => (&args[0] + &args[1]) % &args[2],
+            OP_MULMOD => (&args[0] * &args[1]) % &args[2],
+            OP_LT => from_bool(args[0] < args[1]),
+            OP_GT => from_bool(args[0] > args[1]),
+            OP_SLT => from_bool(slt(&args[0], &args[1])),
+            OP_SGT => from_bool(slt(&args[1], &args[0])),
+            OP_EQ => from_bool(args[0] == args[1]),
+            OP_ISZERO => from_bool(args[0].is_zero()),
+            OP_AND => &args[0] & &args[1],
+            OP_OR => &args[0] | &args[1],
+            OP_XOR => &args[0] ^ &args[1],
+            OP_NOT => &modulus - BigUint::one() - &args[0],
+            OP_BYTE => match args[0].to_u32() {
+                Some(i) if i < 32 => (&args[1] >> (8 * (31 - i))) & BigUint::from(0xffu8),
+                _ => BigUint::zero(),
+            },
+            OP_SHL => match args[0].to_u32() {
+                Some(shift) if shift < 256 => (&args[1] << shift) % &modulus,
+                _ => BigUint::zero(),
+            },
+            OP_SHR => match args[0].to_u32() {
+                Some(shift) if shift < 256 => &args[1] >> shift,
+                _ => BigUint::zero(),
+            },
+            OP_SAR => {
+                let fill = if is_negative(&args[1]) {
+                    &modulus - BigUint::one()
+                } else {
+                    BigUint::zero()
+                };
+                match args[0].to_u32() {
+                    Some(shift) if shift < 256 => {
+                        let high = (&fill << (256 - shift)) % &modulus;
+                        (&args[1] >> shift) | high
+                    }
+                    _ => fill,
+                }
+            }
+            _ => return self.concrete_move(opcode, state, contract, &args),
+        };
+
+        for _ in 0..n {
+            self.pop(state)?;
+        }
+        self.push(state, CbseBitVec::from_biguint(result, 256))?;
+        state.pc += 1;
+        Ok(true)
+    }
+
+    /// Stack and memory opcodes of `concrete_step`, which do not compute a word
+    fn concrete_move(
+        &mut self,
+        opcode: u8,
+        state: &mut ExecState<'ctx>,
+        contract: &Contract<'ctx>,
+        args: &[BigUint],
+    ) -> CbseResult<bool> {
+        match opcode {
+            OP_POP => {
+                self.pop(state)?;
+            }
+
+            OP_MLOAD => {
+                let Some(offset) = args[0].to_u64() else {
+                    return Ok(false);
+                };
+                let word = match state.memory.get_word(offset as usize)? {
+                    UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+                    UnwrappedBytes::BitVec(bv) if bv.is_concrete() => bv,
+                    UnwrappedBytes::BitVec(_) => return Ok(false),
+                };
+                // Memory past the limit is left for execute_opcode to halt on
+                if !self.touch_memory(state, offset, 32) {
+                    return Ok(false);
+                }
+                self.pop(state)?;
+                self.push(state, word)?;
+            }
+
+            OP_MSTORE | OP_MSTORE8 => {
+                let Some(offset) = args[0].to_u64() else {
+                    return Ok(false);
+                };
+                let size = if opcode == OP_MSTORE { 32 } else { 1 };
+                if !self.touch_memory(state, offset, size) {
+                    return Ok(false);
+                }
+                self.pop(state)?;
+                self.pop(state)?;
+
+                let bytes = args[1].to_bytes_be();
+                if opcode == OP_MSTORE {
+                    let mut word = vec![0u8; 32 - bytes.len()];
+                    word.extend_from_slice(&bytes);
+                    state
+                        .memory
+                        .set_word(offset as usize, UnwrappedBytes::Bytes(word))?;
+                } else {
+                    let low = bytes.last().copied().unwrap_or(0);
+                    state
+                        .memory
+                        .set_byte(offset as usize, UnwrappedBytes::Bytes(vec![low]))?;
+                }
+            }
+
+            OP_JUMPDEST => {}
+
+            OP_PUSH0..=OP_PUSH32 => {
+                let n = (opcode - OP_PUSH0) as usize;
+                let mut bytes = Vec::with_capacity(n);
+                for i in 1..=n {
+                    if state.pc + i < contract.len() {
+                        bytes.push(contract.get_byte(state.pc + i)?);
+                    } else {
+                        bytes.push(0);
+                    }
+                }
+                self.push(state, CbseBitVec::from_bytes(&bytes, 256))?;
+                state.pc += n;
+            }
+
+            OP_DUP1..=OP_DUP16 => {
+                let value = self.peek(state, (opcode - OP_DUP1 + 1) as usize)?;
+                self.push(state, value)?;
+            }
+
+            OP_SWAP1..=OP_SWAP16 => {
+                let n = (opcode - OP_SWAP1 + 1) as usize;
+                let len = state.stack.len();
+                if len < n + 1 {
+                    return Ok(false);
+                }
+                state.stack.swap(len - 1, len - 1 - n);
+            }
+
+            _ => return Ok(false),
+        }
+
+        state.pc += 1;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbse_traces::{CallContext, CallMessage, CallOutput};
+    use std::rc::Rc;
+    use z3::{Context, Solver};
+
+    /// Run `hexcode` as a top-level call and return (success, returndata, solver calls)
+    fn run<'ctx>(ctx: &'ctx Context, hexcode: &str, concrete: bool) -> (bool, Vec<u8>, usize) {
+        let mut sevm = SEVM::new(ctx);
+        sevm.concrete_execution = concrete;
+        let target = [1u8; 20];
+        sevm.deploy_contract(target, Contract::from_hexcode(hexcode, ctx).unwrap());
+        let (success, returndata, _, _) = sevm
+            .execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                false,
+            )
+            .unwrap();
+        (success, returndata, sevm.run_stats().solver.calls)
+    }
+
+    #[test]
+    fn test_concrete_program_matches_symbolic_interpreter() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // mstore(0, (7 + 5) * 3 - 4); mstore(32, 32 << 1);
+        // mstore(64, sgt(0, 0 - 1)); return(0, 96)
+        let code = concat!(
+            "6007600501600302600490038060005260011b602052",
+            "60016000036000136040526060",
+            "6000f3"
+        );
+
+        let (fast_success, fast_data, fast_calls) = run(&ctx, code, true);
+        let (slow_success, slow_data, _) = run(&ctx, code, false);
+
+        let mut expected = vec![0u8; 96];
+        expected[31] = 32;
+        expected[63] = 64;
+        expected[95] = 1;
+        assert!(fast_success && slow_success);
+        assert_eq!(fast_data, expected);
+        assert_eq!(fast_data, slow_data);
+        assert_eq!(fast_calls, 0);
+    }
+
+    #[test]
+    fn test_symbolic_operand_falls_back() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("01", &ctx).unwrap();
+
+        let message = CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false);
+        let context = CallContext::new(message, CallOutput::new(None, None, None), 1);
+        let mut state = ExecState::new(&ctx, context, Rc::new(Solver::new(&ctx)));
+        state.stack.push(CbseBitVec::symbolic(&ctx, "x", 256));
+        state.stack.push(CbseBitVec::from_u64(1, 256));
+
+        assert!(!sevm.concrete_step(OP_ADD, &mut state, &contract).unwrap());
+        assert_eq!(state.stack.len(), 2);
+        assert_eq!(state.pc, 0);
+    }
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Expected event logs for `vm.expectEmit`
+//!
+//! `vm.expectEmit` arms an expectation. The next log the test emits is taken as
+//! the template rather than recorded, and the next call the test makes must
+//! emit a log matching it. Topic 0 (the event signature) is always compared;
+//! topics 1-3 and the data only if their check flag is set.
+
+use cbse_traces::{CallContext, EventLog, TraceElement};
+
+/// Armed `vm.expectEmit` expectation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedEmit {
+    /// checkTopic1, checkTopic2, checkTopic3
+    pub check_topics: [bool; 3],
+    pub check_data: bool,
+    /// Address that must emit the log, if given
+    pub emitter: Option<[u8; 20]>,
+    /// Log to match, once the test has emitted it
+    pub template: Option<(Vec<Vec<u8>>, Vec<u8>)>,
+}
+
+impl ExpectedEmit {
+    pub fn new(check_topics: [bool; 3], check_data: bool, emitter: Option<[u8; 20]>) -> Self {
+        Self {
+            check_topics,
+            check_data,
+            emitter,
+            template: None,
+        }
+    }
+
+    /// Expectation comparing every topic and the data (`vm.expectEmit()`)
+    pub fn all(emitter: Option<[u8; 20]>) -> Self {
+        Self::new([true; 3], true, emitter)
+    }
+
+    /// True if `log` matches the template in every checked field
+    pub fn matches(&self, log: &EventLog) -> bool {
+        let (topics, data) = match &self.template {
+            Some(template) => template,
+            None => return false,
+        };
+
+        if self.emitter.map_or(false, |emitter| emitter != log.address) {
+            return false;
+        }
+        if topics.len() != log.topics.len() {
+            return false;
+        }
+        let checked = |i: usize| i == 0 || self.check_topics[i - 1];
+        let topics_match = (0..topics.len()).all(|i| !checked(i) || topics[i] == log.topics[i]);
+        topics_match && (!self.check_data || *data == log.data)
+    }
+
+    /// True if a matching log was emitted anywhere in `context`, subcalls included
+    pub fn is_met_by(&self, context: &CallContext) -> bool {
+        context.trace.iter().any(|element| match element {
+            TraceElement::Log(log) => self.matches(log),
+            TraceElement::Call(subcall) => self.is_met_by(subcall),
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(address: [u8; 20], topics: &[u8], data: &[u8]) -> EventLog {
+        let topics = topics.iter().map(|&t| vec![t; 32]).collect();
+        EventLog::new(address, topics, data.to_vec())
+    }
+
+    #[test]
+    fn test_matches_checked_fields_only() {
+        let mut expected = ExpectedEmit::new([true, false, true], false, None);
+        assert!(!expected.matches(&log([1; 20], &[0xaa, 1, 2, 3], b"x")));
+
+        expected.template = Some((log([0; 20], &[0xaa, 1, 2, 3], b"x").topics, b"x".to_vec()));
+        assert!(expected.matches(&log([1; 20], &[0xaa, 1, 2, 3], b"x")));
+        // Topic 2 and the data are not checked
+        assert!(expected.matches(&log([1; 20], &[0xaa, 1, 9, 3], b"y")));
+        // Topic 0 is always checked
+        assert!(!expected.matches(&log([1; 20], &[0xbb, 1, 2, 3], b"x")));
+        assert!(!expected.matches(&log([1; 20], &[0xaa, 9, 2, 3], b"x")));
+        assert!(!expected.matches(&log([1; 20], &[0xaa, 1, 2], b"x")));
+
+        expected.emitter = Some([2; 20]);
+        assert!(!expected.matches(&log([1; 20], &[0xaa, 1, 2, 3], b"x")));
+        assert!(expected.matches(&log([2; 20], &[0xaa, 1, 2, 3], b"x")));
+        // Emitters are compared on all 20 bytes, not just the low ones
+        let mut same_low_bytes = [2; 20];
+        same_low_bytes[0] = 9;
+        assert!(!expected.matches(&log(same_low_bytes, &[0xaa, 1, 2, 3], b"x")));
+    }
+}
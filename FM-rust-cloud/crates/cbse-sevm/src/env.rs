@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Block and transaction environment read by the environment opcodes
+//!
+//! Unset values are left symbolic, so a test holds for any coinbase or gas price.
+
+use cbse_bitvec::CbseBitVec;
+use cbse_constants::GAS_LIMIT_DEFAULT;
+use z3::Context;
+
+/// Number of recent blocks whose hashes BLOCKHASH can return
+pub const BLOCKHASH_WINDOW: u64 = 256;
+
+/// Values pushed by NUMBER, COINBASE, GASLIMIT and CHAINID, and used to bound BLOCKHASH
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockEnv {
+    /// Current block number
+    pub number: u64,
+    /// Block beneficiary; symbolic when unset
+    pub coinbase: Option<[u8; 20]>,
+    /// Block gas limit; symbolic when unset
+    pub gas_limit: Option<u64>,
+    /// Chain id, set with `--chain-id` or vm.chainId
+    pub chainid: u64,
+}
+
+impl Default for BlockEnv {
+    fn default() -> Self {
+        Self {
+            number: 1,
+            coinbase: None,
+            gas_limit: Some(GAS_LIMIT_DEFAULT),
+            chainid: 1,
+        }
+    }
+}
+
+impl BlockEnv {
+    /// Value pushed by COINBASE
+    pub fn coinbase<'ctx>(&self, ctx: &'ctx Context) -> CbseBitVec<'ctx> {
+        match self.coinbase {
+            Some(address) => CbseBitVec::from_bytes(&address, 160),
+            None => CbseBitVec::symbolic(ctx, "block_coinbase", 160),
+        }
+    }
+
+    /// Value pushed by GASLIMIT
+    pub fn gas_limit<'ctx>(&self, ctx: &'ctx Context) -> CbseBitVec<'ctx> {
+        match self.gas_limit {
+            Some(gas_limit) => CbseBitVec::from_u64(gas_limit, 256),
+            None => CbseBitVec::symbolic(ctx, "block_gaslimit", 256),
+        }
+    }
+
+    /// True if `number` is one of the BLOCKHASH_WINDOW blocks before the current one
+    pub fn in_blockhash_window(&self, number: u64) -> bool {
+        number < self.number && self.number - number <= BLOCKHASH_WINDOW
+    }
+}
+
+/// Transaction-level values pushed by GASPRICE
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TxEnv {
+    /// Gas price of the transaction; symbolic when unset
+    pub gas_price: Option<u64>,
+}
+
+impl TxEnv {
+    /// Value pushed by GASPRICE
+    pub fn gas_price<'ctx>(&self, ctx: &'ctx Context) -> CbseBitVec<'ctx> {
+        match self.gas_price {
+            Some(gas_price) => CbseBitVec::from_u64(gas_price, 256),
+            None => CbseBitVec::symbolic(ctx, "tx_gasprice", 256),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blockhash_window() {
+        let env = BlockEnv {
+            number: 1000,
+            ..BlockEnv::default()
+        };
+        assert!(env.in_blockhash_window(999));
+        assert!(env.in_blockhash_window(744));
+        assert!(!env.in_blockhash_window(743));
+        assert!(!env.in_blockhash_window(1000));
+        assert!(!env.in_blockhash_window(1001));
+    }
+}
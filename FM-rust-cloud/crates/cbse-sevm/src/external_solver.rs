@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Assertion solving through an external SMT solver process
+
+use crate::path::Path;
+use cbse_config::Config;
+use cbse_exceptions::{CbseException, CbseResult};
+use cbse_solver::{solve_stdin, SolverOutput};
+use std::time::Duration;
+
+/// Solves path queries with an external binary (yices, cvc5, bitwuzla, ...)
+///
+/// The query is the path's `to_smt2` export, piped to the solver's stdin
+/// together with `(check-sat)` and `(get-model)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSolver {
+    /// Solver binary followed by its arguments
+    pub command: Vec<String>,
+    /// Wall-clock limit per query, None for no limit
+    pub timeout: Option<Duration>,
+    /// Address-space limit for the solver process in MB, 0 for no limit
+    pub max_memory_mb: usize,
+}
+
+impl ExternalSolver {
+    pub fn new(command: Vec<String>, timeout: Option<Duration>, max_memory_mb: usize) -> Self {
+        Self {
+            command,
+            timeout,
+            max_memory_mb,
+        }
+    }
+
+    /// Build the solver selected by `config`
+    ///
+    /// Returns None when assertions should be solved with the in-process Z3,
+    /// i.e. the solver is `z3` and no explicit `solver_command` is given.
+    pub fn from_config(config: &Config) -> CbseResult<Option<Self>> {
+        if config.solver == "z3" && config.solver_command.is_empty() {
+            return Ok(None);
+        }
+
+        let command = config
+            .resolved_solver_command()
+            .map_err(|e| CbseException::Internal(e.to_string()))?;
+        if command.is_empty() {
+            return Err(CbseException::Internal("empty solver command".to_string()));
+        }
+
+        let timeout = match config.solver_timeout_assertion {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        };
+
+        Ok(Some(Self::new(command, timeout, config.solver_max_memory)))
+    }
+
+    /// The command line actually spawned
+    ///
+    /// With a memory limit, the solver is started through `sh` so the limit
+    /// can be applied with `ulimit -v` before exec'ing it.
+    fn invocation(&self) -> Vec<String> {
+        if self.max_memory_mb == 0 || !cfg!(unix) {
+            return self.command.clone();
+        }
+
+        let mut invocation = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "ulimit -v \"$0\" && exec \"$@\"".to_string(),
+            (self.max_memory_mb * 1024).to_string(),
+        ];
+        invocation.extend(self.command.iter().cloned());
+        invocation
+    }
+
+    /// Solve an SMT-LIB2 query; `smt2` holds declarations and assertions only
+    pub fn solve_smt2(&self, smt2: &str) -> SolverOutput {
+        let mut query = String::from("(set-option :produce-models true)\n");
+        query.push_str("(set-logic QF_AUFBV)\n");
+        query.push_str(smt2);
+        if !smt2.is_empty() && !smt2.ends_with('\n') {
+            query.push('\n');
+        }
+        query.push_str("(check-sat)\n(get-model)\n");
+
+        solve_stdin(&self.invocation(), &query, self.timeout, 0)
+    }
+
+    /// Check the constraints of `path`, returning a model when satisfiable
+    pub fn check(&self, path: &Path<'_>) -> SolverOutput {
+        self.solve_smt2(&path.to_smt2(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cbse_solver::SatResult;
+    use std::rc::Rc;
+    use z3::{ast::Ast, Context, Solver};
+
+    #[test]
+    fn test_from_config_selection() {
+        let mut config = Config::default();
+        config.solver = "z3".to_string();
+        assert_eq!(ExternalSolver::from_config(&config).unwrap(), None);
+
+        config.solver = "cvc5".to_string();
+        config.solver_timeout_assertion = 5;
+        let solver = ExternalSolver::from_config(&config).unwrap().unwrap();
+        assert_eq!(solver.command[0], "cvc5");
+        assert_eq!(solver.timeout, Some(Duration::from_secs(5)));
+
+        config.solver = "z3".to_string();
+        config.solver_command = "z3 -in -smt2".to_string();
+        let solver = ExternalSolver::from_config(&config).unwrap().unwrap();
+        assert_eq!(solver.command, vec!["z3", "-in", "-smt2"]);
+    }
+
+    #[test]
+    fn test_invocation_applies_memory_limit() {
+        let solver = ExternalSolver::new(vec!["yices-smt2".to_string()], None, 0);
+        assert_eq!(solver.invocation(), vec!["yices-smt2"]);
+
+        if cfg!(unix) {
+            let solver = ExternalSolver::new(vec!["yices-smt2".to_string()], None, 512);
+            let invocation = solver.invocation();
+            assert_eq!(invocation[0], "sh");
+            assert_eq!(invocation[3], "524288");
+            assert_eq!(invocation.last().unwrap(), "yices-smt2");
+        }
+    }
+
+    #[test]
+    fn test_external_z3_roundtrip() {
+        // Only runs where a z3 binary is installed
+        let Ok(status) = std::process::Command::new("z3").arg("-version").output() else {
+            return;
+        };
+        if !status.status.success() {
+            return;
+        }
+
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut path = Path::new(Rc::new(Solver::new(&ctx)));
+        let x = z3::ast::BV::new_const(&ctx, "halmos_x_uint256", 256);
+        let five = z3::ast::BV::from_u64(&ctx, 5, 256);
+        path.append(x._eq(&five), true).unwrap();
+
+        let solver = ExternalSolver::new(
+            vec!["z3".to_string(), "-in".to_string()],
+            Some(Duration::from_secs(10)),
+            0,
+        );
+        let output = solver.check(&path);
+        assert_eq!(output.result, SatResult::Sat);
+        let model = output.model.unwrap().model;
+        assert_eq!(model["halmos_x_uint256"].value, 5u32.into());
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Cache of keccak256 digests of concrete SHA3 inputs
+//!
+//! Loops often hash the same buffer again and again. Inputs are keyed by their
+//! xxhash3, which is far cheaper to compute than keccak256, and the least
+//! recently used digest is dropped once the cache is full.
+
+use cbse_hashes::{keccak256, xxhash3};
+use std::collections::{BTreeMap, HashMap};
+
+/// Digests kept by a default `KeccakCache`
+pub const KECCAK_CACHE_CAPACITY: usize = 4096;
+
+/// LRU map from the xxhash3 of a SHA3 input to its keccak256 digest
+#[derive(Debug, Clone)]
+pub struct KeccakCache {
+    capacity: usize,
+    /// Digest and last use of each cached input
+    entries: HashMap<u64, ([u8; 32], u64)>,
+    /// Cached inputs by last use, least recent first
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl Default for KeccakCache {
+    fn default() -> Self {
+        Self::new(KECCAK_CACHE_CAPACITY)
+    }
+}
+
+impl KeccakCache {
+    /// Cache holding at most `capacity` digests; 0 disables caching
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// keccak256 of `data`, computed only if it is not cached
+    pub fn digest(&mut self, data: &[u8]) -> [u8; 32] {
+        let key = xxhash3(data);
+        self.clock += 1;
+
+        if let Some((digest, last_used)) = self.entries.get_mut(&key) {
+            self.recency.remove(last_used);
+            *last_used = self.clock;
+            self.recency.insert(self.clock, key);
+            self.hits += 1;
+            return *digest;
+        }
+
+        self.misses += 1;
+        let digest = keccak256(data);
+        if self.capacity == 0 {
+            return digest;
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (digest, self.clock));
+        self.recency.insert(self.clock, key);
+        digest
+    }
+
+    /// Lookups answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that had to compute the digest
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_least_recently_used_digest_is_evicted() {
+        let mut cache = KeccakCache::new(2);
+        let (a, b, c) = (&[1u8][..], &[2u8][..], &[3u8][..]);
+
+        assert_eq!(cache.digest(a), keccak256(a));
+        cache.digest(b);
+        // Using `a` again leaves `b` as the least recently used
+        assert_eq!(cache.digest(a), keccak256(a));
+        cache.digest(c);
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 3, 2));
+
+        cache.digest(a);
+        cache.digest(c);
+        assert_eq!(cache.hits(), 3);
+        assert_eq!(cache.digest(b), keccak256(b));
+        assert_eq!(cache.misses(), 4);
+    }
+}
@@ -2,8 +2,12 @@
 
 //! Keccak hash registry for tracking SHA3 operations in symbolic execution
 
+use cbse_bitvec::CbseBitVec;
+use cbse_hashes::{get_keccak256_256_preimage, keccak256};
+use num_bigint::BigUint;
 use std::collections::HashMap;
-use z3::ast::BV as Z3BV;
+use z3::ast::{Ast, Bool as Z3Bool, BV as Z3BV};
+use z3::{Context, FuncDecl, Sort};
 
 /// Registry for tracking Keccak hash expressions and their values
 ///
@@ -11,12 +15,30 @@ use z3::ast::BV as Z3BV;
 /// 1. Hash expression tracking with unique IDs
 /// 2. Reverse lookup from hash values to original expressions
 /// 3. Support for offset-based lookups (hash + delta)
+/// 4. One `f_sha3_<bits>` uninterpreted function per preimage width, shared by
+///    every SHA3 so equal preimages always produce the same hash term
 pub struct KeccakRegistry<'ctx> {
     /// Maps hash expressions to unique IDs
     hash_ids: HashMap<String, usize>,
     /// Maps hash values to their generating expressions
     hash_values: OffsetMap<'ctx>,
     next_id: usize,
+    /// Hash function declarations keyed by preimage width in bits
+    decls: HashMap<u32, FuncDecl<'ctx>>,
+    /// (preimage, hash term, preimage is concrete) of every distinct SHA3 seen so far
+    applications: Vec<(Z3BV<'ctx>, Z3BV<'ctx>, bool)>,
+    /// Concrete preimages keyed by their keccak256 digest
+    preimages: HashMap<[u8; 32], Z3BV<'ctx>>,
+}
+
+/// Declare `f_sha3_<bits>`; redeclaring it yields the same Z3 symbol
+fn sha3_function(ctx: &Context, bits: u32) -> FuncDecl<'_> {
+    FuncDecl::new(
+        ctx,
+        format!("f_sha3_{}", bits),
+        &[&Sort::bitvector(ctx, bits)],
+        &Sort::bitvector(ctx, 256),
+    )
 }
 
 /// Map that supports lookups with offsets
@@ -88,6 +110,9 @@ impl<'ctx> KeccakRegistry<'ctx> {
             hash_ids: HashMap::new(),
             hash_values: OffsetMap::new(),
             next_id: 0,
+            decls: HashMap::new(),
+            applications: Vec::new(),
+            preimages: HashMap::new(),
         }
     }
 
@@ -135,6 +160,175 @@ impl<'ctx> KeccakRegistry<'ctx> {
         }
     }
 
+    /// The hash function for `bits`-bit preimages, declared on first use
+    pub fn sha3_decl(&mut self, ctx: &'ctx Context, bits: u32) -> &FuncDecl<'ctx> {
+        self.decls
+            .entry(bits)
+            .or_insert_with(|| sha3_function(ctx, bits))
+    }
+
+    /// The word SHA3 produces for `data`, without recording it in a registry
+    ///
+    /// Used to rebuild storage locations from decoded mapping keys.
+    pub fn hash_term(ctx: &'ctx Context, data: &CbseBitVec<'ctx>) -> CbseBitVec<'ctx> {
+        if data.is_concrete() {
+            return CbseBitVec::from_bytes(&keccak256(&data.to_bytes()), 256);
+        }
+        let term = sha3_function(ctx, data.size())
+            .apply(&[&data.as_z3(ctx)])
+            .as_bv()
+            .expect("f_sha3 returns a bitvector");
+        CbseBitVec::from_z3(term)
+    }
+
+    /// Hash `data`, returning the hash and the constraints to add to the path
+    ///
+    /// A concrete preimage hashes to its real keccak256 and its term is pinned
+    /// to that value. A new preimage is also constrained to hash differently
+    /// from every earlier one (keccak is assumed injective), so the solver
+    /// cannot make two distinct preimages collide.
+    pub fn sha3(
+        &mut self,
+        ctx: &'ctx Context,
+        data: &CbseBitVec<'ctx>,
+    ) -> (CbseBitVec<'ctx>, Vec<Z3Bool<'ctx>>) {
+        let preimage = data.as_z3(ctx);
+        let term = self
+            .sha3_decl(ctx, data.size())
+            .apply(&[&preimage])
+            .as_bv()
+            .expect("f_sha3 returns a bitvector");
+        let digest = data.is_concrete().then(|| keccak256(&data.to_bytes()));
+        self.register(term.to_string(), digest.as_ref().map(|hash| &hash[..]));
+        if let Some(hash) = digest {
+            self.preimages
+                .entry(hash)
+                .or_insert_with(|| preimage.clone());
+        }
+        let concrete = digest.map(|hash| CbseBitVec::from_bytes(&hash, 256));
+
+        let mut constraints = Vec::new();
+        if let Some(hash) = &concrete {
+            if !self
+                .applications
+                .iter()
+                .any(|(seen, _, _)| *seen == preimage)
+            {
+                constraints.push(term._eq(&hash.as_z3(ctx)));
+            }
+        }
+        constraints.extend(self.record_application(preimage, term.clone(), concrete.is_some()));
+
+        let hash = concrete.unwrap_or_else(|| CbseBitVec::from_z3(term));
+        (hash, constraints)
+    }
+
+    /// Hash concrete `data` without building an `f_sha3` application
+    ///
+    /// The digest is recorded as `sha3` would record it: as a preimage for
+    /// storage decoding, and as a hash that symbolic preimages hashed before
+    /// or after must not collide with. Returns the hash and the constraints
+    /// to add to the path, which are empty unless symbolic hashes were seen.
+    pub fn sha3_concrete(
+        &mut self,
+        ctx: &'ctx Context,
+        data: &[u8],
+    ) -> (CbseBitVec<'ctx>, Vec<Z3Bool<'ctx>>) {
+        self.sha3_with_digest(ctx, data, keccak256(data))
+    }
+
+    /// `sha3_concrete` for `data` whose keccak256 `digest` is already known,
+    /// e.g. from a `KeccakCache`
+    pub fn sha3_with_digest(
+        &mut self,
+        ctx: &'ctx Context,
+        data: &[u8],
+        digest: [u8; 32],
+    ) -> (CbseBitVec<'ctx>, Vec<Z3Bool<'ctx>>) {
+        let preimage = CbseBitVec::from_bytes(data, (data.len() * 8) as u32).as_z3(ctx);
+        let hash = CbseBitVec::from_bytes(&digest, 256);
+        self.register(format!("keccak256({})", preimage), Some(&digest));
+        self.preimages
+            .entry(digest)
+            .or_insert_with(|| preimage.clone());
+        let constraints = self.record_application(preimage, hash.as_z3(ctx), true);
+        (hash, constraints)
+    }
+
+    /// Remember that `term` hashes `preimage`, returning the constraints that
+    /// keep it from colliding with every earlier, distinct preimage
+    ///
+    /// Nothing is returned for a preimage that was already recorded.
+    fn record_application(
+        &mut self,
+        preimage: Z3BV<'ctx>,
+        term: Z3BV<'ctx>,
+        concrete: bool,
+    ) -> Vec<Z3Bool<'ctx>> {
+        if self
+            .applications
+            .iter()
+            .any(|(seen, _, _)| *seen == preimage)
+        {
+            return Vec::new();
+        }
+        let mut constraints = Vec::new();
+        for (other_preimage, other_term, other_concrete) in &self.applications {
+            // Two pinned concrete hashes are already distinct
+            if concrete && *other_concrete {
+                continue;
+            }
+            let same_hash = term._eq(other_term);
+            constraints.push(if other_preimage.get_size() == preimage.get_size() {
+                same_hash.implies(&preimage._eq(other_preimage))
+            } else {
+                same_hash.not()
+            });
+        }
+        self.applications.push((preimage, term, concrete));
+        constraints
+    }
+
+    /// The concrete preimage hashed to `hash` by an earlier SHA3, if any
+    pub fn preimage(&self, hash: &[u8; 32]) -> Option<&Z3BV<'ctx>> {
+        self.preimages.get(hash)
+    }
+
+    /// The concrete preimage whose hash lies closest below `value`, with the
+    /// offset of `value` from it
+    ///
+    /// Only offsets that fit in 64 bits are considered, which covers array
+    /// elements and struct fields placed after a keccak location. Hashes of
+    /// single-byte words that were never hashed during execution (e.g. folded
+    /// into constants by the compiler) are recognized when `value` is exactly
+    /// such a hash.
+    pub fn preimage_near(&self, ctx: &'ctx Context, value: &BigUint) -> Option<(Z3BV<'ctx>, u64)> {
+        let near = self
+            .preimages
+            .iter()
+            .filter_map(|(hash, preimage)| {
+                let hash = BigUint::from_bytes_be(hash);
+                if &hash > value {
+                    return None;
+                }
+                u64::try_from(value - hash)
+                    .ok()
+                    .map(|delta| (preimage, delta))
+            })
+            .min_by_key(|&(_, delta)| delta);
+        if let Some((preimage, delta)) = near {
+            return Some((preimage.clone(), delta));
+        }
+
+        let bytes = value.to_bytes_be();
+        if bytes.len() > 32 {
+            return None;
+        }
+        let mut hash = [0u8; 32];
+        hash[32 - bytes.len()..].copy_from_slice(&bytes);
+        get_keccak256_256_preimage(&hash).map(|x| (Z3BV::from_u64(ctx, x as u64, 256), 0))
+    }
+
     /// Reverse lookup: find the expression that produced a hash value
     ///
     /// Returns the expression that, when hashed and potentially offset,
@@ -173,6 +367,11 @@ impl<'ctx> KeccakRegistry<'ctx> {
             hash_ids: self.hash_ids.clone(),
             hash_values: self.hash_values.copy(),
             next_id: self.next_id,
+            // Redeclaring f_sha3_<bits> yields the same Z3 symbol, so the
+            // copy can rebuild its declarations lazily
+            decls: HashMap::new(),
+            applications: self.applications.clone(),
+            preimages: self.preimages.clone(),
         }
     }
 
@@ -261,6 +460,92 @@ mod tests {
         assert!(copy.contains("sha3_256(data)"));
     }
 
+    #[test]
+    fn test_same_symbolic_preimage_same_hash() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+
+        let data = CbseBitVec::symbolic(&ctx, "data", 512);
+        let (first, _) = registry.sha3(&ctx, &data);
+        let (second, constraints) = registry.sha3(&ctx, &data);
+
+        assert!(first.is_symbolic());
+        assert_eq!(first.as_z3(&ctx), second.as_z3(&ctx));
+        assert!(constraints.is_empty());
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_distinct_preimages_do_not_collide() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+
+        let one = CbseBitVec::from_u64(1, 256);
+        let two = CbseBitVec::from_u64(2, 256);
+        let (hash, mut constraints) = registry.sha3(&ctx, &one);
+        constraints.extend(registry.sha3(&ctx, &two).1);
+        assert_eq!(hash.to_bytes(), keccak256(&one.to_bytes()).to_vec());
+
+        // The uninterpreted terms themselves cannot be made equal
+        let f = registry.sha3_decl(&ctx, 256);
+        let f_one = f.apply(&[&one.as_z3(&ctx)]).as_bv().unwrap();
+        let f_two = f.apply(&[&two.as_z3(&ctx)]).as_bv().unwrap();
+        let solver = z3::Solver::new(&ctx);
+        for constraint in &constraints {
+            solver.assert(constraint);
+        }
+        solver.assert(&f_one._eq(&f_two));
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+
+        // Nor can a symbolic preimage share a hash with a different one
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let (hash_x, constraints) = registry.sha3(&ctx, &x);
+        let solver = z3::Solver::new(&ctx);
+        for constraint in &constraints {
+            solver.assert(constraint);
+        }
+        solver.assert(&hash_x.as_z3(&ctx)._eq(&f_one));
+        solver.assert(&x.as_z3(&ctx)._eq(&two.as_z3(&ctx)));
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_concrete_hash_does_not_collide_with_symbolic() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let (hash_x, _) = registry.sha3(&ctx, &x);
+        let one = CbseBitVec::from_u64(1, 256);
+        let (hash_one, constraints) = registry.sha3_concrete(&ctx, &one.to_bytes());
+        assert_eq!(hash_one.to_bytes(), keccak256(&one.to_bytes()).to_vec());
+
+        // hash(x) == hash(1) forces x == 1
+        let solver = z3::Solver::new(&ctx);
+        for constraint in &constraints {
+            solver.assert(constraint);
+        }
+        solver.assert(&hash_x.as_z3(&ctx)._eq(&hash_one.as_z3(&ctx)));
+        solver.assert(&x.as_z3(&ctx)._eq(&one.as_z3(&ctx)).not());
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_preimage_near_recognizes_precomputed_hashes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let registry = KeccakRegistry::new();
+
+        let hash = BigUint::from_bytes_be(&keccak256(&[0u8; 32]));
+        let (preimage, delta) = registry.preimage_near(&ctx, &hash).unwrap();
+        assert_eq!(preimage.as_u64(), Some(0));
+        assert_eq!(delta, 0);
+        assert!(registry.preimage_near(&ctx, &(hash + 1u8)).is_none());
+    }
+
     #[test]
     fn test_offset_map() {
         let mut map: OffsetMap = OffsetMap::new();
@@ -283,7 +568,7 @@ mod tests {
 ///
 /// This uses the cbse-hashes crate which contains precomputed
 /// keccak256(x) values for x in 0..256
-fn check_precomputed_registry(hash_value: u64) -> Option<String> {
+fn check_precomputed_registry(_hash_value: u64) -> Option<String> {
     // Convert hash_value (first 8 bytes) to full 32-byte hash
     // and check against precomputed values
 
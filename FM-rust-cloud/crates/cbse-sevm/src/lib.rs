@@ -5,24 +5,64 @@
 //! This module provides the main symbolic execution engine that interprets EVM bytecode
 //! and tracks execution paths through the program.
 
+use cbse_address::Address;
 use cbse_bitvec::CbseBitVec;
 use cbse_bytevec::{ByteVec, UnwrappedBytes};
-use cbse_contract::Contract;
-use cbse_exceptions::{CbseException, CbseResult};
-use cbse_traces::{CallContext, CallMessage, CallOutput};
-use std::collections::HashMap;
+use cbse_cheatcodes::{halmos_cheat_code, hevm_cheat_code, SymbolCounter};
+use cbse_constants::{
+    GAS_LIMIT_DEFAULT, MAX_CALL_DEPTH, MAX_CODE_SIZE, MAX_INITCODE_SIZE, MAX_MEMORY_SIZE,
+    STACK_LIMIT,
+};
+use cbse_contract::{gas::REFUND_QUOTIENT, mnemonic, Contract, Instruction};
+use cbse_exceptions::{CbseException, CbseResult, ExceptionalHalt};
+use cbse_mapper::{BuildOut, Mapper};
+use cbse_traces::{
+    CallContext, CallMessage, CallOutput, DeployAddressMapper, StorageWrite, TraceElement,
+    TraceEvent,
+};
+use num_bigint::BigUint;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Write;
+use std::path::Path as FsPath;
 use std::rc::Rc;
-use z3::{Context, Solver};
+use std::time::Instant;
+use z3::{Context, FuncDecl, Solver, Sort};
 
+mod arith;
+mod concrete;
+mod emit;
+mod env;
+mod external_solver;
+mod keccak_cache;
+mod keccak_registry;
+mod mock;
 mod opcodes;
+mod parallel;
 mod path;
+mod revert;
+mod runner;
 mod state;
+mod stats;
 mod storage;
+mod unknown;
 mod worklist;
 
+pub use arith::*;
+pub use emit::*;
+pub use env::*;
+pub use external_solver::*;
+pub use keccak_cache::*;
+pub use keccak_registry::*;
+pub use mock::*;
+pub use parallel::*;
 pub use path::*;
+pub use revert::*;
+pub use runner::*;
 pub use state::*;
+pub use stats::*;
 pub use storage::*;
+pub use unknown::*;
 pub use worklist::*;
 
 /// Message passed between contract calls
@@ -37,6 +77,127 @@ pub struct Message<'ctx> {
     pub is_static: bool,
 }
 
+/// Concrete bytes of `bytevec`, with symbolic bytes shown as zero
+fn concrete_bytes(bytevec: &ByteVec) -> CbseResult<Vec<u8>> {
+    let mut result = Vec::new();
+    for i in 0..bytevec.len() {
+        let byte = bytevec.get_byte(i)?;
+        match byte {
+            UnwrappedBytes::Bytes(bytes) => {
+                if !bytes.is_empty() {
+                    result.push(bytes[0]);
+                } else {
+                    result.push(0);
+                }
+            }
+            UnwrappedBytes::BitVec(bv) => {
+                let val = bv.as_u64().unwrap_or(0) as u8;
+                result.push(val);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Call value as recorded in traces, which hold a u64; larger values saturate
+fn trace_value(value: &BigUint) -> u64 {
+    u64::try_from(value).unwrap_or(u64::MAX)
+}
+
+/// Fluent constructor for a Message and the CallContext that traces it
+///
+/// Unset fields default to the zero address, no value, empty calldata, no gas,
+/// a non-static CALL.
+pub struct MessageBuilder<'ctx> {
+    message: Message<'ctx>,
+    call_value: u64,
+    call_scheme: u8,
+    depth: usize,
+}
+
+impl<'ctx> MessageBuilder<'ctx> {
+    pub fn new(ctx: &'ctx Context) -> Self {
+        Self {
+            message: Message {
+                target: [0u8; 20],
+                caller: [0u8; 20],
+                origin: [0u8; 20],
+                value: CbseBitVec::from_u64(0, 256),
+                data: ByteVec::new(ctx),
+                gas: 0,
+                is_static: false,
+            },
+            call_value: 0,
+            call_scheme: 0xF1, // CALL
+            depth: 0,
+        }
+    }
+
+    pub fn target(mut self, target: [u8; 20]) -> Self {
+        self.message.target = target;
+        self
+    }
+
+    pub fn caller(mut self, caller: [u8; 20]) -> Self {
+        self.message.caller = caller;
+        self
+    }
+
+    pub fn origin(mut self, origin: [u8; 20]) -> Self {
+        self.message.origin = origin;
+        self
+    }
+
+    pub fn value(mut self, value: BigUint) -> Self {
+        self.call_value = trace_value(&value);
+        self.message.value = CbseBitVec::from_biguint(value, 256);
+        self
+    }
+
+    pub fn data(mut self, data: ByteVec<'ctx>) -> Self {
+        self.message.data = data;
+        self
+    }
+
+    pub fn gas(mut self, gas: u64) -> Self {
+        self.message.gas = gas;
+        self
+    }
+
+    pub fn is_static(mut self, is_static: bool) -> Self {
+        self.message.is_static = is_static;
+        self
+    }
+
+    /// Opcode recorded in the trace, e.g. 0xF4 for DELEGATECALL
+    pub fn call_scheme(mut self, call_scheme: u8) -> Self {
+        self.call_scheme = call_scheme;
+        self
+    }
+
+    /// Call depth of the frame, 1 for a top-level call
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// The message plus a CallContext for its trace, with no output yet
+    pub fn build(self) -> CbseResult<(Message<'ctx>, CallContext)> {
+        let message = self.message;
+        let call_message = CallMessage::new(
+            SEVM::address_to_u64(&message.target),
+            SEVM::address_to_u64(&message.caller),
+            self.call_value,
+            concrete_bytes(&message.data)?,
+            self.call_scheme,
+            message.is_static,
+        );
+        let call_context =
+            CallContext::new(call_message, CallOutput::new(None, None, None), self.depth);
+        Ok((message, call_context))
+    }
+}
+
 /// Execution state for a single contract call
 ///
 /// This corresponds to Python's Exec class in halmos/sevm.py
@@ -46,6 +207,11 @@ pub struct ExecState<'ctx> {
     pub stack: Vec<CbseBitVec<'ctx>>,
     pub memory: ByteVec<'ctx>,
 
+    // Memory high-water mark: the end of the highest region ever read or
+    // written, rounded up to a word. MSIZE and memory expansion gas use this
+    // rather than the ByteVec length, which reads do not grow.
+    pub memory_size: usize,
+
     // Program counter and gas
     pub pc: usize,
     pub gas: u64,
@@ -53,7 +219,7 @@ pub struct ExecState<'ctx> {
     // Call context
     pub caller: [u8; 20],
     pub address: [u8; 20],
-    pub value: u64,
+    pub value: BigUint,
 
     // Return data from last call
     pub last_return_data: Option<ByteVec<'ctx>>,
@@ -66,6 +232,31 @@ pub struct ExecState<'ctx> {
 
     // Jump tracking for loop detection (matches Python's Exec.jumpis)
     pub jumpis: HashMap<(usize, Vec<String>), HashMap<bool, usize>>,
+
+    // Warm addresses and storage slots for the current transaction (EIP-2929)
+    pub access_set: AccessSet,
+
+    // Log the next call must emit (vm.expectEmit)
+    pub expected_emit: Option<ExpectedEmit>,
+
+    // SSTORE gas refund accrued by this frame and its successful subcalls
+    // (EIP-3529). A frame can undo a refund granted by its caller, so this
+    // may be negative.
+    pub refund: i64,
+
+    // Contracts that executed SELFDESTRUCT on this path or in its successful
+    // subcalls; their code and storage are removed once the top-level call
+    // completes
+    pub selfdestructs: Vec<[u8; 20]>,
+
+    // Storage of every contract as of when this path was set aside (forked,
+    // completed or suspended); SEVM::storage is the live copy while it runs.
+    // Clones share structure, so branching does not copy storage.
+    pub storage: StorageMap<'ctx>,
+
+    // Balances as of when this path was set aside; SEVM::balance is the
+    // live copy while it runs
+    pub balance: HashMap<Address, BigUint>,
 }
 
 impl<'ctx> ExecState<'ctx> {
@@ -74,17 +265,83 @@ impl<'ctx> ExecState<'ctx> {
         Self {
             stack: Vec::new(),
             memory: ByteVec::new(ctx),
+            memory_size: 0,
             pc: 0,
-            gas: 30_000_000, // Default gas limit
+            gas: GAS_LIMIT_DEFAULT,
             caller: [0u8; 20],
             address: [0u8; 20],
-            value: 0,
+            value: BigUint::default(),
             last_return_data: None,
             context: call_context,
             path: Path::new(solver),
             jumpis: HashMap::new(),
+            access_set: AccessSet::new(),
+            expected_emit: None,
+            refund: 0,
+            selfdestructs: Vec::new(),
+            storage: StorageMap::new(),
+            balance: HashMap::new(),
         }
     }
+
+    /// Push onto the stack; pushing onto a full stack halts the path
+    pub fn push(&mut self, value: CbseBitVec<'ctx>) -> CbseResult<()> {
+        if self.stack.len() >= STACK_LIMIT {
+            return Err(CbseException::Halt(ExceptionalHalt::StackOverflow));
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// Pop the top of the stack; popping an empty stack halts the path
+    pub fn pop(&mut self) -> CbseResult<CbseBitVec<'ctx>> {
+        self.stack
+            .pop()
+            .ok_or(CbseException::Halt(ExceptionalHalt::StackUnderflow))
+    }
+
+    /// Pop the top `n` items of the stack, topmost first
+    ///
+    /// Nothing is popped if the stack holds fewer than `n` items.
+    pub fn pop_n(&mut self, n: usize) -> CbseResult<Vec<CbseBitVec<'ctx>>> {
+        if self.stack.len() < n {
+            return Err(CbseException::Halt(ExceptionalHalt::StackUnderflow));
+        }
+        let mut items = self.stack.split_off(self.stack.len() - n);
+        items.reverse();
+        Ok(items)
+    }
+}
+
+/// A CREATE or CREATE2 whose init code is about to run, set by the opcode
+/// handler and picked up by `SEVM::execute_call_frame`
+#[derive(Debug)]
+pub(crate) struct PendingCreate {
+    pub address: [u8; 20],
+    pub init_code: Vec<u8>,
+    pub value: BigUint,
+    /// 0xF0 for CREATE, 0xF5 for CREATE2, as recorded in the trace
+    pub scheme: u8,
+    /// Balances to restore if creation fails, taken before the value moved
+    pub balances_before: HashMap<Address, BigUint>,
+}
+
+/// Code, message and paths of a frame run by `SEVM::execute_call_frame`
+struct Frame<'ctx> {
+    contract: Contract<'ctx>,
+    message: Message<'ctx>,
+    worklist: Worklist<ExecState<'ctx>>,
+    /// Completed paths in the order they finished; the first is the frame's result
+    completed_states: Vec<ExecState<'ctx>>,
+    steps: usize,
+}
+
+/// A frame waiting for the init code of a contract it creates
+struct SuspendedFrame<'ctx> {
+    frame: Frame<'ctx>,
+    /// The path that ran CREATE/CREATE2, resumed once the init code has run
+    creator: ExecState<'ctx>,
+    create: PendingCreate,
 }
 
 /// Result of executing a contract
@@ -95,6 +352,20 @@ pub struct ExecutionResult<'ctx> {
     pub gas_used: u64,
 }
 
+/// One completed path of a call, as returned by `SEVM::explore`
+#[derive(Debug)]
+pub struct PathResult<'ctx> {
+    pub success: bool,
+    pub return_data: Vec<u8>,
+    pub gas_used: u64,
+    /// Constraints the path collected, in the order they were added
+    pub constraints: Vec<z3::ast::Bool<'ctx>>,
+    /// Last value written to each storage slot by the frame on this path, as
+    /// recorded in the trace (empty if SSTORE is not in `SEVM::trace_filter`)
+    pub storage_diff: Vec<StorageWrite>,
+    pub context: CallContext,
+}
+
 /// Symbolic EVM - Main execution engine
 pub struct SEVM<'ctx> {
     /// Z3 context for symbolic operations
@@ -104,17 +375,172 @@ pub struct SEVM<'ctx> {
     pub solver: Rc<Solver<'ctx>>,
 
     /// Contract bytecode storage
-    pub contracts: HashMap<[u8; 20], Contract<'ctx>>,
+    pub contracts: HashMap<Address, Contract<'ctx>>,
 
     /// Storage for each contract address using Z3 Arrays for symbolic keys
     /// This matches Python's ex.storage dictionary with StorageData
-    pub storage: HashMap<[u8; 20], StorageData<'ctx>>,
+    ///
+    /// Holds the storage of the path being run; paths waiting in a worklist
+    /// keep their own copy in `ExecState::storage`.
+    pub storage: StorageMap<'ctx>,
 
     /// Balance for each address
-    pub balance: HashMap<[u8; 20], u64>,
+    pub balance: HashMap<Address, BigUint>,
+
+    /// Path exploration strategy used by the worklist in execute_call
+    pub strategy: Strategy,
+
+    /// Whether vm.ffi() may run external commands
+    pub ffi: bool,
+
+    /// Address names registered with vm.label(), used when rendering traces
+    pub labels: DeployAddressMapper,
+
+    /// Print every executed instruction (Config::print_steps)
+    pub print_steps: bool,
+
+    /// Dump memory along with each traced step (Config::print_mem)
+    pub print_mem: bool,
+
+    /// Destination for step traces, stdout by default
+    pub step_output: Box<dyn Write>,
+
+    /// Sample the call stack on every step for flamegraph output (Config::flamegraph)
+    pub flamegraph: bool,
+
+    /// Frames of the calls currently executing, outermost first
+    flame_frames: Vec<String>,
+
+    /// Step counts per folded call stack
+    flame_samples: BTreeMap<String, usize>,
+
+    /// Count executed opcodes for the instruction profile (Config::profile_instructions)
+    pub profile_instructions: bool,
+
+    /// Executions per opcode
+    instruction_counts: BTreeMap<u8, usize>,
+
+    /// Run instructions whose operands are all concrete without building solver terms
+    pub concrete_execution: bool,
+
+    /// Path, step and timing counters, reported with Config::statistics
+    stats: RunStats,
+
+    /// Solver checks made by every path this SEVM explores
+    solver_stats: Rc<RefCell<SolverStats>>,
+
+    /// Number of execute_call frames currently running
+    active_calls: usize,
+
+    /// Allocates ids for cheatcode-created symbols
+    pub symbols: SymbolCounter,
+
+    /// Per-contract storage snapshots, indexed by the handle returned to the caller
+    storage_snapshots: Vec<(Address, StorageData<'ctx>)>,
 
     /// Address counter for CREATE opcode (matches Python's new_address())
     address_counter: u64,
+
+    /// Counterexample from the most recent call that failed an assertion
+    pub counterexample: Option<String>,
+
+    /// Set when the solver could not decide the counterexample query of a
+    /// failing path, so the failure may be spurious
+    pub inconclusive: bool,
+
+    /// Solver for counterexample queries; None uses the in-process Z3
+    pub external_solver: Option<ExternalSolver>,
+
+    /// Time limits for branching and assertion solver checks
+    pub solver_timeouts: SolverTimeouts,
+
+    /// Uninterpreted functions for symbolic MUL/DIV/MOD/SDIV/SMOD
+    arith: ArithAbstractions<'ctx>,
+
+    /// Block number and other values read by the block information opcodes
+    pub block: BlockEnv,
+
+    /// Transaction values read by GASPRICE
+    pub tx: TxEnv,
+
+    /// Uninterpreted hash of recent blocks, so BLOCKHASH(n) is the same term for the same n
+    blockhash_fn: FuncDecl<'ctx>,
+
+    /// Shared f_sha3 functions and the preimages hashed so far
+    pub keccak_registry: KeccakRegistry<'ctx>,
+
+    /// Recent keccak256 digests of concrete SHA3 inputs
+    pub keccak_cache: KeccakCache,
+
+    /// Worker threads for solving independent assertion queries (Config::get_solver_threads)
+    pub solver_threads: usize,
+
+    /// Gas a top-level test call starts with (Config::gas_limit)
+    pub gas_limit: u64,
+
+    /// Bytes memory may expand to before the path halts out of gas (Config::max_memory_size)
+    pub max_memory_size: usize,
+
+    /// Deepest call frame allowed; calls and creations beyond it fail
+    pub max_call_depth: usize,
+
+    /// Panic codes counted as assertion failures (Config::parse_panic_error_codes)
+    pub panic_codes: PanicCodes,
+
+    /// Calls stubbed with vm.mockCall
+    pub mocks: MockRegistry,
+
+    /// Calls to addresses without code that return symbolic data
+    pub unknown_calls: UnknownCalls,
+
+    /// Candidate lengths for svm.createBytes with a symbolic length
+    /// (Config::parse_default_bytes_lengths)
+    pub bytes_lengths: Vec<usize>,
+
+    /// Trace events recorded during execution (Config::parse_trace_events)
+    pub trace_filter: HashSet<TraceEvent>,
+
+    /// Set once vm.expectEmit is used: the check reads logs from the trace,
+    /// so they are then recorded even if filtered out
+    emit_expected: bool,
+
+    /// EIP-170 limit on deployed code size; raise it for chains without the limit
+    pub max_code_size: usize,
+
+    /// EIP-3860 limit on CREATE/CREATE2 init code size
+    pub max_initcode_size: usize,
+
+    /// Code address for the next execute_call when it runs on behalf of a
+    /// DELEGATECALL (the call target then only supplies the storage context)
+    delegate_code: Option<[u8; 20]>,
+
+    /// Access set handed from a CALL to the subcall it is about to execute
+    inherited_access: Option<AccessSet>,
+
+    /// Access set of the most recently completed subcall, picked up by the caller
+    returned_access: Option<AccessSet>,
+
+    /// Refund accrued by the most recently completed subcall, picked up by the caller
+    returned_refund: Option<i64>,
+
+    /// Self-destructs scheduled by the most recently completed call, picked up
+    /// by the caller or, for a top-level call, applied
+    returned_selfdestructs: Option<Vec<[u8; 20]>>,
+
+    /// Creation whose init code the current frame should run next
+    pending_create: Option<PendingCreate>,
+
+    /// Paths forked by the cheatcode being handled, each with the result it
+    /// returns on that path; completed by the calling opcode
+    cheatcode_forks: Vec<(ExecState<'ctx>, ByteVec<'ctx>)>,
+
+    /// Paths forked by the current opcode, pushed to the frame's worklist
+    /// once the opcode completes
+    forked_states: Vec<ExecState<'ctx>>,
+
+    /// Value of each concrete slot written in the current transaction as it
+    /// was before the first write (the EIP-2200 original value)
+    original_storage: HashMap<(Address, BigUint), CbseBitVec<'ctx>>,
 }
 
 impl<'ctx> SEVM<'ctx> {
@@ -126,71 +552,254 @@ impl<'ctx> SEVM<'ctx> {
             ctx,
             solver,
             contracts: HashMap::new(),
-            storage: HashMap::new(),
+            storage: StorageMap::new(),
             balance: HashMap::new(),
+            strategy: Strategy::default(),
+            ffi: false,
+            labels: DeployAddressMapper::new(),
+            print_steps: false,
+            print_mem: false,
+            step_output: Box::new(std::io::stdout()),
+            flamegraph: false,
+            flame_frames: Vec::new(),
+            flame_samples: BTreeMap::new(),
+            profile_instructions: false,
+            instruction_counts: BTreeMap::new(),
+            concrete_execution: true,
+            stats: RunStats::default(),
+            solver_stats: Rc::new(RefCell::new(SolverStats::default())),
+            active_calls: 0,
+            symbols: SymbolCounter::new(),
+            storage_snapshots: Vec::new(),
             address_counter: 0x1000, // Start at 0x1000 for created contracts
+            counterexample: None,
+            inconclusive: false,
+            external_solver: None,
+            solver_timeouts: SolverTimeouts::default(),
+            arith: ArithAbstractions::new(ctx),
+            block: BlockEnv::default(),
+            tx: TxEnv::default(),
+            blockhash_fn: FuncDecl::new(
+                ctx,
+                "f_blockhash",
+                &[&Sort::bitvector(ctx, 256)],
+                &Sort::bitvector(ctx, 256),
+            ),
+            keccak_registry: KeccakRegistry::new(),
+            keccak_cache: KeccakCache::default(),
+            solver_threads: 1,
+            gas_limit: GAS_LIMIT_DEFAULT,
+            max_memory_size: MAX_MEMORY_SIZE,
+            max_call_depth: MAX_CALL_DEPTH,
+            panic_codes: PanicCodes::default(),
+            mocks: MockRegistry::new(),
+            unknown_calls: UnknownCalls::default(),
+            bytes_lengths: vec![0, 65, 1024],
+            trace_filter: HashSet::from([TraceEvent::Log, TraceEvent::Sload, TraceEvent::Sstore]),
+            emit_expected: false,
+            max_code_size: MAX_CODE_SIZE,
+            max_initcode_size: MAX_INITCODE_SIZE,
+            delegate_code: None,
+            inherited_access: None,
+            returned_access: None,
+            returned_refund: None,
+            returned_selfdestructs: None,
+            pending_create: None,
+            cheatcode_forks: Vec::new(),
+            forked_states: Vec::new(),
+            original_storage: HashMap::new(),
         }
     }
 
     /// Deploy a contract at the given address
-    pub fn deploy_contract(&mut self, address: [u8; 20], contract: Contract<'ctx>) {
-        self.contracts.insert(address, contract);
+    pub fn deploy_contract(&mut self, address: impl Into<Address>, contract: Contract<'ctx>) {
+        self.contracts.insert(address.into(), contract);
     }
 
-    /// Set storage value for a contract (SSTORE)
+    /// Replace the code at `address`, as runtime code installation does
     ///
-    /// Uses Z3 Array Store operation for symbolic storage keys.
-    /// Matches Python's SolidityStorage.store() at sevm.py:1804-1825
-    pub fn set_storage(
+    /// The contract is rebuilt from `code`, so nothing derived from earlier
+    /// code (decoded instructions, jump destinations, code hash, names and
+    /// source map) carries over.
+    pub fn set_code(&mut self, address: impl Into<Address>, code: ByteVec<'ctx>) {
+        let contract = Contract::new(code, self.ctx, None, None, None);
+        self.deploy_contract(address, contract);
+    }
+
+    /// Deploy a contract by running its init code at `address`
+    ///
+    /// `constructor_args` are appended to the init code, where Solidity
+    /// constructors read them with CODECOPY. The RETURN output becomes the
+    /// runtime code, and storage written by the constructor is kept.
+    pub fn deploy_from_initcode(
         &mut self,
         address: [u8; 20],
-        slot: CbseBitVec<'ctx>,
-        value: CbseBitVec<'ctx>,
-        path_conditions: &mut Vec<z3::ast::Bool<'ctx>>,
+        initcode: ByteVec<'ctx>,
+        constructor_args: ByteVec<'ctx>,
     ) -> CbseResult<()> {
-        // For now, treat slot directly as the storage location (scalar storage)
-        // In full implementation, this would decode the slot using SolidityStorage::decode
-        // and handle nested mappings/arrays
-
-        // Initialize storage if needed
-        SolidityStorage::init(&mut self.storage, address, 0, 0, 0, self.ctx)?;
+        let mut code = initcode;
+        code.append_bytevec(&constructor_args);
+        self.deploy_contract(address, Contract::new(code, self.ctx, None, None, None));
+        self.storage.entry_mut(address.into());
 
-        // Store with symbolic array operations
-        SolidityStorage::store(
-            &mut self.storage,
+        let (success, runtime, _, context) = self.execute_call(
             address,
-            0,       // base slot (would be decoded from slot in full implementation)
-            &[slot], // keys - treating slot as the key
-            value,
-            self.ctx,
+            TEST_CALLER,
+            TEST_CALLER,
+            BigUint::default(),
+            Vec::new(),
+            self.gas_limit,
+            false,
         )?;
+        if !success {
+            self.contracts.remove(&address);
+            return Err(CbseException::Internal(format!(
+                "constructor for {} failed: {}",
+                cbse_traces::hexify(&address),
+                context.output.error.as_deref().unwrap_or("reverted")
+            )));
+        }
 
+        self.set_code(address, ByteVec::from_bytes(runtime, self.ctx)?);
         Ok(())
     }
 
+    /// Set storage value for a contract (SSTORE)
+    ///
+    /// Mapping entries and array elements decoded by SolidityStorage::decode
+    /// go to the array of their base slot, keyed by the decoded keys; every
+    /// other slot is an index into one flat array, so concrete and symbolic
+    /// slots alias as they should.
+    /// Matches Python's SolidityStorage.store() at sevm.py:1804-1825
+    pub fn set_storage(
+        &mut self,
+        address: impl Into<Address>,
+        slot: CbseBitVec<'ctx>,
+        value: CbseBitVec<'ctx>,
+        _path_conditions: &mut Vec<z3::ast::Bool<'ctx>>,
+    ) -> CbseResult<()> {
+        let address = address.into();
+        match SolidityStorage::decode(&slot, &self.keccak_registry, self.ctx) {
+            Some((base, keys)) if !keys.is_empty() => {
+                SolidityStorage::store(&mut self.storage, address, base, &keys, value, self.ctx)
+            }
+            _ => GenericStorage::store(&mut self.storage, address, &slot, value, self.ctx),
+        }
+    }
+
     /// Get storage value for a contract (SLOAD)
     ///
-    /// Uses Z3 Array Select operation for symbolic storage keys.
+    /// Slots are decoded the same way as in `set_storage`.
     /// Matches Python's SolidityStorage.load() at sevm.py:1779-1802
-    pub fn get_storage(&mut self, address: [u8; 20], slot: &CbseBitVec<'ctx>) -> CbseBitVec<'ctx> {
-        // Initialize storage if needed
-        if SolidityStorage::init(&mut self.storage, address, 0, 0, 0, self.ctx).is_err() {
-            return CbseBitVec::from_u64(0, 256);
+    pub fn get_storage(
+        &mut self,
+        address: impl Into<Address>,
+        slot: &CbseBitVec<'ctx>,
+    ) -> CbseBitVec<'ctx> {
+        let address = address.into();
+        if !self.storage.contains_key(&address) {
+            self.storage.insert(address, StorageData::new());
+        }
+
+        let value = match SolidityStorage::decode(slot, &self.keccak_registry, self.ctx) {
+            Some((base, keys)) if !keys.is_empty() => {
+                SolidityStorage::load(&self.storage, address, base, &keys, self.ctx)
+            }
+            _ => GenericStorage::load(&self.storage, address, slot, self.ctx),
+        };
+        value.unwrap_or_else(|_| CbseBitVec::from_u64(0, 256))
+    }
+
+    /// Make the storage of `address` fully symbolic (svm.enableSymbolicStorage)
+    ///
+    /// Any previously written values are discarded. Afterwards every slot that
+    /// has not been written holds an unconstrained symbolic value.
+    pub fn enable_symbolic_storage(&mut self, address: impl Into<Address>) {
+        let address = address.into();
+        self.storage.insert(address, StorageData::new_symbolic());
+    }
+
+    /// Capture the storage of `address` (svm.snapshotStorage)
+    ///
+    /// Unlike a full state snapshot only this one contract's storage is saved.
+    /// Returns a handle for `restore_storage`.
+    pub fn snapshot_storage(&mut self, address: impl Into<Address>) -> usize {
+        let address = address.into();
+        let data = self.storage.get(&address).cloned().unwrap_or_default();
+        self.storage_snapshots.push((address, data));
+        self.storage_snapshots.len() - 1
+    }
+
+    /// Restore the contract storage captured by `snapshot_storage`
+    ///
+    /// Storage of every other contract is left untouched.
+    pub fn restore_storage(&mut self, handle: usize) -> CbseResult<()> {
+        let (address, data) = self.storage_snapshots.get(handle).cloned().ok_or_else(|| {
+            CbseException::Internal(format!("unknown storage snapshot: {}", handle))
+        })?;
+        self.storage.insert(address, data);
+        Ok(())
+    }
+
+    /// Slots of `address` changed since the `snapshot_storage` call that
+    /// returned `handle`, as (slot, old, new)
+    pub fn storage_diff(
+        &self,
+        address: impl Into<Address>,
+        handle: usize,
+    ) -> CbseResult<Vec<(CbseBitVec<'ctx>, CbseBitVec<'ctx>, CbseBitVec<'ctx>)>> {
+        let address = address.into();
+        let (snapshot_address, before) = self.storage_snapshots.get(handle).ok_or_else(|| {
+            CbseException::Internal(format!("unknown storage snapshot: {}", handle))
+        })?;
+        if *snapshot_address != address {
+            return Err(CbseException::Internal(format!(
+                "storage snapshot {} was not taken of {}",
+                handle, address
+            )));
         }
+        let after = self.storage.get(&address).cloned().unwrap_or_default();
+        Ok(after.diff(before, self.ctx))
+    }
+
+    /// Set balance for an address, in wei
+    pub fn set_balance(&mut self, address: impl Into<Address>, balance: impl Into<BigUint>) {
+        self.balance.insert(address.into(), balance.into());
+    }
 
-        // Load with symbolic array operations
-        SolidityStorage::load(&self.storage, address, 0, &[slot.clone()], self.ctx)
-            .unwrap_or_else(|_| CbseBitVec::from_u64(0, 256))
+    /// Get balance for an address, in wei
+    pub fn get_balance(&self, address: impl Into<Address>) -> BigUint {
+        self.balance
+            .get(&address.into())
+            .cloned()
+            .unwrap_or_default()
     }
 
-    /// Set balance for an address
-    pub fn set_balance(&mut self, address: [u8; 20], balance: u64) {
-        self.balance.insert(address, balance);
+    /// Balance of an address as a u64, saturating at u64::MAX
+    pub fn get_balance_u64(&self, address: impl Into<Address>) -> u64 {
+        u64::try_from(self.get_balance(address)).unwrap_or(u64::MAX)
     }
 
-    /// Get balance for an address
-    pub fn get_balance(&self, address: &[u8; 20]) -> u64 {
-        self.balance.get(address).copied().unwrap_or(0)
+    /// Move `amount` wei from `from` to `to`
+    ///
+    /// Returns false, moving nothing, if `from` holds less than `amount`.
+    pub(crate) fn transfer(
+        &mut self,
+        from: impl Into<Address>,
+        to: impl Into<Address>,
+        amount: &BigUint,
+    ) -> bool {
+        let (from, to) = (from.into(), to.into());
+        let from_balance = self.get_balance(from);
+        if &from_balance < amount {
+            return false;
+        }
+        if from != to && *amount > BigUint::default() {
+            self.set_balance(from, from_balance - amount);
+            let to_balance = self.get_balance(to);
+            self.set_balance(to, to_balance + amount);
+        }
+        true
     }
 
     /// Generate a new contract address for CREATE opcode
@@ -237,14 +846,21 @@ impl<'ctx> SEVM<'ctx> {
             stack: state.stack.clone(),
             memory: ByteVec::new(self.ctx), // Create fresh memory - will be populated during execution
             pc: target_pc,                  // Set to target PC for the branch
+            memory_size: state.memory_size,
             gas: state.gas,
             caller: state.caller,
             address: state.address,
-            value: state.value,
+            value: state.value.clone(),
             last_return_data: None, // Reset return data for new branch
             context: state.context.clone(),
             path: new_path,
             jumpis: state.jumpis.clone(),
+            access_set: state.access_set.clone(),
+            expected_emit: state.expected_emit.clone(),
+            refund: state.refund,
+            selfdestructs: state.selfdestructs.clone(),
+            storage: self.storage.clone(),
+            balance: self.balance.clone(),
         };
 
         Ok(new_state)
@@ -259,88 +875,193 @@ impl<'ctx> SEVM<'ctx> {
         target: [u8; 20],
         caller: [u8; 20],
         origin: [u8; 20],
-        value: u64,
+        value: BigUint,
         calldata: Vec<u8>,
         gas: u64,
         is_static: bool,
     ) -> CbseResult<(bool, Vec<u8>, u64, CallContext)> {
+        let data = ByteVec::from_bytes(calldata, self.ctx)?;
+        self.execute_call_with_calldata(target, caller, origin, value, data, gas, is_static)
+    }
+
+    /// Execute a call whose calldata may contain symbolic bytes
+    ///
+    /// Same as `execute_call`, but takes the calldata as a ByteVec so that
+    /// symbolic arguments reach CALLDATALOAD/CALLDATACOPY unchanged.
+    pub fn execute_call_with_calldata(
+        &mut self,
+        target: [u8; 20],
+        caller: [u8; 20],
+        origin: [u8; 20],
+        value: BigUint,
+        data: ByteVec<'ctx>,
+        gas: u64,
+        is_static: bool,
+    ) -> CbseResult<(bool, Vec<u8>, u64, CallContext)> {
+        let paths = self.explore(target, caller, origin, value, data, gas, is_static)?;
+        let first = paths
+            .into_iter()
+            .next()
+            .ok_or_else(|| CbseException::Internal("call completed no paths".to_string()))?;
+        Ok((
+            first.success,
+            first.return_data,
+            first.gas_used,
+            first.context,
+        ))
+    }
+
+    /// Execute a call and return every path that ran to completion
+    ///
+    /// `execute_call` reports only the first of these. Storage is left as the
+    /// first path wrote it; other effects on the SEVM (deployed contracts,
+    /// statistics) are those of running all of the paths.
+    pub fn explore(
+        &mut self,
+        target: [u8; 20],
+        caller: [u8; 20],
+        origin: [u8; 20],
+        value: BigUint,
+        data: ByteVec<'ctx>,
+        gas: u64,
+        is_static: bool,
+    ) -> CbseResult<Vec<PathResult<'ctx>>> {
+        // Only the outermost frame contributes wall time, subcalls are nested in it
+        let started = Instant::now();
+        let depth = self.active_calls;
+        self.active_calls += 1;
+        let result = self.execute_call_frame(target, caller, origin, value, data, gas, is_static);
+        // Also leaves any creation frames the call was in when it failed
+        self.active_calls = depth;
+
+        if self.active_calls == 0 {
+            self.stats.wall_time += started.elapsed();
+            // Only the path whose storage the SEVM keeps deletes anything,
+            // and only if it succeeded
+            let selfdestructs = self.returned_selfdestructs.take().unwrap_or_default();
+            if matches!(&result, Ok(paths) if paths[0].success) {
+                self.apply_selfdestructs(selfdestructs);
+            }
+        }
+        result
+    }
+
+    /// Remove the code and storage of every contract in `selfdestructs`
+    fn apply_selfdestructs(&mut self, selfdestructs: Vec<[u8; 20]>) {
+        for address in selfdestructs {
+            self.contracts.remove(&address);
+            self.storage.remove(&address);
+        }
+    }
+
+    /// Run the worklist loop for a single call frame
+    fn execute_call_frame(
+        &mut self,
+        target: [u8; 20],
+        caller: [u8; 20],
+        origin: [u8; 20],
+        value: BigUint,
+        data: ByteVec<'ctx>,
+        gas: u64,
+        is_static: bool,
+    ) -> CbseResult<Vec<PathResult<'ctx>>> {
+        // Subcalls continue the caller's access set; a top-level call starts a
+        // new transaction with only the sender, recipient and precompiles warm,
+        // and no storage written yet
+        let mut access_set = self.inherited_access.take().unwrap_or_else(|| {
+            self.original_storage.clear();
+            let mut access_set = AccessSet::new();
+            access_set.access_address(origin);
+            for i in 1..=10u8 {
+                let mut precompile = [0u8; 20];
+                precompile[19] = i;
+                access_set.access_address(precompile);
+            }
+            access_set
+        });
+        access_set.access_address(caller);
+        access_set.access_address(target);
+        self.returned_access = None;
+        self.returned_refund = None;
+        self.returned_selfdestructs = None;
+
+        // A DELEGATECALL runs another contract's code against the target's storage
+        let (code_address, call_scheme) = match self.delegate_code.take() {
+            Some(code_address) => (code_address, 0xF4), // DELEGATECALL
+            None => (target, 0xF1),                     // CALL
+        };
+
+        let (message, call_context) = MessageBuilder::new(self.ctx)
+            .target(target)
+            .caller(caller)
+            .origin(origin) // Track original transaction origin through nested calls
+            .value(value.clone())
+            .data(data)
+            .gas(gas)
+            .is_static(is_static)
+            .call_scheme(call_scheme)
+            .depth(self.active_calls)
+            .build()?;
+        // Traces only record concrete calldata; symbolic bytes show up as zero
+        let calldata = call_context.message.data.clone();
+
+        // Library-using code cannot run until every placeholder is linked
+        if let Some(contract) = self.contracts.get(&code_address) {
+            contract.check_linked()?;
+        }
+
         // Temporarily remove contract from HashMap to avoid borrow checker issues
         // This matches Python's pattern where Exec owns contracts separately
-        let contract = match self.contracts.remove(&target) {
+        let mut contract = match self.contracts.remove(&code_address) {
             Some(c) => c,
             None => {
                 // No contract at address - return empty
-                let empty_message = CallMessage::new(
-                    Self::address_to_u64(&target),
-                    Self::address_to_u64(&caller),
-                    value,
-                    calldata,
-                    0xF1, // CALL
-                    is_static,
-                );
-                let empty_output = CallOutput::new(Some(Vec::new()), None, Some(0xF3)); // RETURN
-                let empty_context = CallContext::new(empty_message, empty_output, 0);
-                return Ok((false, Vec::new(), 0, empty_context));
+                let mut empty_context = call_context;
+                empty_context.output = CallOutput::new(Some(Vec::new()), None, Some(0xF3)); // RETURN
+                return Ok(vec![PathResult {
+                    success: false,
+                    return_data: Vec::new(),
+                    gas_used: 0,
+                    constraints: Vec::new(),
+                    storage_diff: Vec::new(),
+                    context: empty_context,
+                }]);
             }
         };
 
-        // Create CallMessage for trace
-        let call_message = CallMessage::new(
-            Self::address_to_u64(&target),
-            Self::address_to_u64(&caller),
-            value,
-            calldata.clone(),
-            0xF1, // CALL opcode
-            is_static,
-        );
-
-        // Create CallOutput (will be updated after execution)
-        let call_output = CallOutput::new(None, None, None);
-
-        // Create CallContext
-        let call_context = CallContext::new(call_message, call_output, 0);
-
-        // Create message
-        let message = Message {
-            target,
-            caller,
-            origin, // Track original transaction origin through nested calls
-            value: CbseBitVec::from_u64(value, 256),
-            data: ByteVec::from_bytes(calldata.clone(), self.ctx)?,
-            gas,
-            is_static,
-        };
+        if self.flamegraph {
+            let frame = self.flame_frame_name(&contract, &target, &calldata);
+            self.flame_frames.push(frame);
+        }
 
         // Create initial execution state
-        let initial_state = ExecState {
-            stack: Vec::new(),
-            memory: ByteVec::new(self.ctx),
-            pc: 0,
-            gas,
-            caller,
-            address: target,
-            value,
-            last_return_data: None,
-            context: call_context,
-            path: Path::new(Rc::clone(&self.solver)),
-            jumpis: HashMap::new(),
-        };
+        let initial_state =
+            self.frame_state(call_context, caller, target, value.clone(), gas, access_set);
+        let storage_before = self.storage.clone();
+        let balance_before = self.balance.clone();
 
-        // Initialize worklist with the initial state
-        let mut worklist: Worklist<ExecState<'ctx>> = Worklist::new();
+        let mut frame = Frame {
+            contract,
+            message,
+            worklist: Worklist::with_strategy(self.strategy),
+            completed_states: Vec::new(),
+            steps: 0,
+        };
         let mut next_state: Option<ExecState> = Some(initial_state);
-
-        // Execution statistics
-        let mut steps = 0;
         const MAX_STEPS: usize = 100_000; // Prevent infinite loops
 
-        // Track completed paths - for now we'll just use the first completed path
-        let mut completed_state: Option<ExecState> = None;
+        // Frames waiting on a CREATE/CREATE2. Init code runs in this loop rather
+        // than a nested call, so chains of creations do not grow the native stack.
+        let mut suspended: Vec<SuspendedFrame<'ctx>> = Vec::new();
 
         // Main execution loop - matches Python's while (ex := next_ex or stack.pop()) is not None
-        while let Some(mut state) = next_state.take().or_else(|| worklist.pop()) {
-            steps += 1;
-            if steps > MAX_STEPS {
+        while let Some(mut state) = match next_state.take() {
+            Some(state) => Some(state),
+            None => self.next_path(&mut frame, &mut suspended)?,
+        } {
+            frame.steps += 1;
+            self.stats.steps += 1;
+            if frame.steps > MAX_STEPS {
                 return Err(CbseException::Internal(
                     "Maximum execution steps exceeded".to_string(),
                 ));
@@ -350,50 +1071,115 @@ impl<'ctx> SEVM<'ctx> {
             state.path.activate();
 
             // Check path feasibility - terminate early if infeasible
-            // This matches Python's ex.check() and prevents exploring impossible paths
-            if !state.path.is_feasible() {
+            // This matches Python's ex.check() and prevents exploring impossible paths.
+            // A path the solver cannot decide is kept: a failure it reaches is
+            // re-checked by the counterexample query, which reports it as inconclusive.
+            // A path without constraints of its own is as feasible as the frame
+            // that started it, so fully concrete runs never reach the solver.
+            if !state.path.conditions.is_empty() && matches!(state.path.is_feasible(), Ok(false)) {
                 // Path is infeasible (UNSAT) - terminate this path
-                worklist.completed_paths += 1;
+                frame.worklist.completed_paths += 1;
+                self.stats.infeasible_paths += 1;
                 continue;
             }
 
             // Check if PC is out of bounds
-            let code_len = contract.len();
+            let code_len = frame.contract.len();
             if state.pc >= code_len {
                 // Execution fell off the end - treat as STOP
-                if completed_state.is_none() {
-                    completed_state = Some(state);
-                }
-                worklist.completed_paths += 1;
+                state.context.output.return_scheme = Some(0x00); // STOP
+                self.set_aside(&mut state);
+                frame.completed_states.push(state);
+                frame.worklist.completed_paths += 1;
+                self.stats.completed_paths += 1;
                 continue;
             }
 
             // Fetch opcode
-            let opcode = contract.get_byte(state.pc)?;
+            let opcode = frame.contract.get_byte(state.pc)?;
+
+            if self.print_steps {
+                let insn = frame.contract.decode_instruction(state.pc, self.ctx)?;
+                self.trace_step(&state, &insn)?;
+            }
+
+            if self.flamegraph {
+                *self
+                    .flame_samples
+                    .entry(self.flame_frames.join(";"))
+                    .or_insert(0) += 1;
+            }
+
+            if self.profile_instructions {
+                *self.instruction_counts.entry(opcode).or_insert(0) += 1;
+            }
 
-            // Special handling for JUMPI - it creates multiple paths
-            if opcode == 0x57 {
-                // OP_JUMPI
-                let branches = self.handle_jumpi(&state, &message)?;
+            let concrete = if self.concrete_execution {
+                self.concrete_step(opcode, &mut state, &frame.contract)
+            } else {
+                Ok(false)
+            };
 
-                // Push all branches to the worklist (handle_jumpi already checks feasibility)
-                for branch in branches {
-                    worklist.push(branch);
+            let step = match concrete {
+                Err(e) => Err(e),
+                Ok(true) => {
+                    next_state = Some(state);
+                    continue;
                 }
+                // Special handling for JUMPI - it creates multiple paths
+                Ok(false) if opcode == 0x57 => {
+                    match self.handle_jumpi(&state, &frame.message) {
+                        Ok(branches) => {
+                            // Push all branches to the worklist (handle_jumpi already checks feasibility)
+                            for mut branch in branches {
+                                self.set_aside(&mut branch);
+                                frame.worklist.push(branch);
+                            }
 
-                // Continue to next iteration (don't use next_state fast path)
-                continue;
+                            // Continue to next iteration (don't use next_state fast path)
+                            continue;
+                        }
+                        Err(e) => Err(e),
+                    }
+                }
+                // Execute the opcode (state.context will be updated with traces)
+                Ok(false) => {
+                    self.execute_opcode(opcode, &mut state, &frame.message, &frame.contract)
+                }
+            };
+
+            // Paths forked by the opcode, such as a cheatcode forking over
+            // candidate values, are explored like JUMPI branches
+            for mut fork in std::mem::take(&mut self.forked_states) {
+                self.set_aside(&mut fork);
+                frame.worklist.push(fork);
             }
 
-            // Execute the opcode (state.context will be updated with traces)
-            let should_halt = self.execute_opcode(opcode, &mut state, &message, &contract)?;
+            // An exceptional halt, such as a stack underflow, ends only this path
+            let should_halt = match step {
+                Err(CbseException::Halt(halt)) => self.exceptional_halt(&mut state, halt),
+                step => step?,
+            };
 
             if should_halt {
                 // Path completed (RETURN, REVERT, STOP, etc.)
-                if completed_state.is_none() {
-                    completed_state = Some(state);
-                }
-                worklist.completed_paths += 1;
+                self.set_aside(&mut state);
+                frame.completed_states.push(state);
+                frame.worklist.completed_paths += 1;
+                self.stats.completed_paths += 1;
+                continue;
+            }
+
+            // The init code of a new contract runs in a frame of its own
+            if let Some(create) = self.pending_create.take() {
+                let (init, init_state) = self.create_frame(&state, &frame.message, &create)?;
+                self.set_aside(&mut state);
+                suspended.push(SuspendedFrame {
+                    frame: std::mem::replace(&mut frame, init),
+                    creator: state,
+                    create,
+                });
+                next_state = Some(init_state);
                 continue;
             }
 
@@ -402,34 +1188,274 @@ impl<'ctx> SEVM<'ctx> {
             next_state = Some(state);
         }
 
-        // Use the first completed state, or create a default one if none completed
-        let mut final_state = completed_state.unwrap_or_else(|| ExecState {
+        let Frame {
+            contract,
+            worklist,
+            mut completed_states,
+            ..
+        } = frame;
+
+        // Create a default state if no path completed
+        if completed_states.is_empty() {
+            completed_states.push(ExecState {
+                stack: Vec::new(),
+                memory: ByteVec::new(self.ctx),
+                memory_size: 0,
+                pc: 0,
+                gas: 0,
+                caller,
+                address: target,
+                value: value.clone(),
+                last_return_data: None,
+                context: CallContext::new(
+                    CallMessage::new(
+                        Self::address_to_u64(&target),
+                        Self::address_to_u64(&caller),
+                        trace_value(&value),
+                        calldata,
+                        0xF1,
+                        is_static,
+                    ),
+                    CallOutput::new(Some(Vec::new()), None, Some(0xF3)),
+                    0,
+                ),
+                path: Path::new(Rc::clone(&self.solver)),
+                jumpis: HashMap::new(),
+                access_set: AccessSet::new(),
+                expected_emit: None,
+                refund: 0,
+                selfdestructs: Vec::new(),
+                storage: storage_before,
+                balance: balance_before,
+            });
+        }
+
+        // Later paths' writes and transfers must not leak into the first one
+        self.resume(&completed_states[0]);
+
+        // Check every completed path for assertion failures
+        let (has_assertion_failure, counterexample) = self.check_assertions(&completed_states);
+        if has_assertion_failure {
+            // Print counterexample to stderr for visibility
+            eprintln!("❌ Assertion Failure Detected!");
+            eprintln!("{}", counterexample);
+            eprintln!("Completed paths explored: {}", worklist.completed_paths);
+            self.counterexample = Some(counterexample);
+        }
+
+        // Put the contract back into the HashMap
+        self.contracts.insert(code_address.into(), contract);
+
+        // Hand the access set back to the calling frame (if any)
+        self.returned_access = Some(completed_states[0].access_set.clone());
+        self.returned_refund = Some(completed_states[0].refund);
+        self.returned_selfdestructs = Some(completed_states[0].selfdestructs.clone());
+
+        // Refunds are paid out once, when the outermost frame returns
+        let settle_refund = self.active_calls == 1;
+
+        if self.flamegraph {
+            self.flame_frames.pop();
+        }
+
+        Ok(completed_states
+            .into_iter()
+            .map(|state| Self::path_result(state, gas, settle_refund))
+            .collect())
+    }
+
+    /// State a path of a new frame starts in
+    fn frame_state(
+        &self,
+        context: CallContext,
+        caller: [u8; 20],
+        address: [u8; 20],
+        value: BigUint,
+        gas: u64,
+        access_set: AccessSet,
+    ) -> ExecState<'ctx> {
+        ExecState {
             stack: Vec::new(),
             memory: ByteVec::new(self.ctx),
+            memory_size: 0,
             pc: 0,
-            gas: 0,
+            gas,
             caller,
-            address: target,
+            address,
             value,
             last_return_data: None,
-            context: CallContext::new(
-                CallMessage::new(
-                    Self::address_to_u64(&target),
-                    Self::address_to_u64(&caller),
-                    value,
-                    calldata,
-                    0xF1,
-                    is_static,
-                ),
-                CallOutput::new(Some(Vec::new()), None, Some(0xF3)),
-                0,
-            ),
-            path: Path::new(Rc::clone(&self.solver)),
+            context,
+            path: Path::with_stats(Rc::clone(&self.solver), Rc::clone(&self.solver_stats))
+                .with_timeouts(self.solver_timeouts),
             jumpis: HashMap::new(),
-        });
+            access_set,
+            expected_emit: None,
+            refund: 0,
+            selfdestructs: Vec::new(),
+            storage: self.storage.clone(),
+            balance: self.balance.clone(),
+        }
+    }
+
+    /// Record the live storage and balances in `state`, which is being set
+    /// aside (forked, completed or suspended)
+    fn set_aside(&self, state: &mut ExecState<'ctx>) {
+        state.storage = self.storage.clone();
+        state.balance = self.balance.clone();
+    }
+
+    /// Make the storage and balances of `state` live again, to run or report it
+    fn resume(&mut self, state: &ExecState<'ctx>) {
+        self.storage = state.storage.clone();
+        self.balance = state.balance.clone();
+    }
+
+    /// The next path of `frame` to run. Once init code has no paths left, the
+    /// contract is deployed and the frame that created it resumes.
+    fn next_path(
+        &mut self,
+        frame: &mut Frame<'ctx>,
+        suspended: &mut Vec<SuspendedFrame<'ctx>>,
+    ) -> CbseResult<Option<ExecState<'ctx>>> {
+        if let Some(state) = frame.worklist.pop() {
+            self.resume(&state);
+            return Ok(Some(state));
+        }
+        let Some(parent) = suspended.pop() else {
+            return Ok(None);
+        };
+        let init = std::mem::replace(frame, parent.frame);
+        let mut creator = parent.creator;
+        self.finish_create(&mut creator, init, parent.create)?;
+        Ok(Some(creator))
+    }
+
+    /// Frame running the init code of `create` on behalf of `creator`, and the
+    /// state its first path starts in
+    fn create_frame(
+        &mut self,
+        creator: &ExecState<'ctx>,
+        message: &Message<'ctx>,
+        create: &PendingCreate,
+    ) -> CbseResult<(Frame<'ctx>, ExecState<'ctx>)> {
+        self.active_calls += 1;
+        let (init_message, mut call_context) = MessageBuilder::new(self.ctx)
+            .target(create.address)
+            .caller(creator.address)
+            .origin(message.origin)
+            .value(create.value.clone())
+            .gas(creator.gas)
+            .call_scheme(create.scheme)
+            .depth(self.active_calls)
+            .build()?;
+        // The constructor sees empty calldata, but the trace shows the init code
+        call_context.message.data = create.init_code.clone();
+
+        let init_code = ByteVec::from_bytes(create.init_code.clone(), self.ctx)?;
+        let contract = Contract::new(init_code, self.ctx, None, None, None);
+        if self.flamegraph {
+            let frame = self.flame_frame_name(&contract, &create.address, &[]);
+            self.flame_frames.push(frame);
+        }
+
+        // The new account is warm from the start (EIP-2929)
+        let mut access_set = creator.access_set.clone();
+        access_set.access_address(create.address);
+        let state = self.frame_state(
+            call_context,
+            creator.address,
+            create.address,
+            create.value.clone(),
+            creator.gas,
+            access_set,
+        );
+
+        let frame = Frame {
+            contract,
+            message: init_message,
+            worklist: Worklist::with_strategy(self.strategy),
+            completed_states: Vec::new(),
+            steps: 0,
+        };
+        Ok((frame, state))
+    }
+
+    /// Deploy the code returned by the init code of `create`, run as `init`,
+    /// and push the new address onto `creator`'s stack, or 0 if the init code
+    /// failed or returned code over the EIP-170 limit
+    fn finish_create(
+        &mut self,
+        creator: &mut ExecState<'ctx>,
+        init: Frame<'ctx>,
+        create: PendingCreate,
+    ) -> CbseResult<()> {
+        self.active_calls -= 1;
+        if self.flamegraph {
+            self.flame_frames.pop();
+        }
+
+        // A creation in which no path completed fails; otherwise the first
+        // completed path decides it, as for an ordinary call
+        let deployed = match init.completed_states.into_iter().next() {
+            Some(state) => {
+                let access_set = state.access_set.clone();
+                let refund = state.refund;
+                let selfdestructs = state.selfdestructs.clone();
+                let storage = state.storage.clone();
+                let balance = state.balance.clone();
+                let result = Self::path_result(state, init.message.gas, false);
+                creator
+                    .context
+                    .trace
+                    .push(TraceElement::Call(result.context));
+                if result.success && result.return_data.len() <= self.max_code_size {
+                    // The constructor's writes are those of the path whose code is deployed
+                    self.storage = storage;
+                    self.balance = balance;
+                    self.set_aside(creator);
+                    let code = ByteVec::from_bytes(result.return_data, self.ctx)?;
+                    self.set_code(create.address, code);
+                    creator.access_set = access_set;
+                    creator.refund += refund;
+                    creator.selfdestructs.extend(selfdestructs);
+                    creator.last_return_data = Some(ByteVec::new(self.ctx));
+                    true
+                } else {
+                    // Only a revert hands its output back to the creator
+                    let returndata = if result.success {
+                        Vec::new()
+                    } else {
+                        result.return_data
+                    };
+                    creator.last_return_data = Some(ByteVec::from_bytes(returndata, self.ctx)?);
+                    false
+                }
+            }
+            None => {
+                creator.last_return_data = Some(ByteVec::new(self.ctx));
+                false
+            }
+        };
+
+        if deployed {
+            creator.push(CbseBitVec::from_bytes(&create.address, 256))
+        } else {
+            // Writes of failed init code are discarded with the contract
+            self.storage = creator.storage.clone();
+            self.contracts.remove(&create.address);
+            self.storage.remove(&create.address);
+            self.balance = create.balances_before;
+            self.set_aside(creator);
+            creator.push(CbseBitVec::from_u64(0, 256))
+        }
+    }
 
+    /// Summarize a completed path of a frame that started with `gas`
+    ///
+    /// With `settle_refund`, the path's refund is deducted from the gas used.
+    fn path_result(mut state: ExecState<'ctx>, gas: u64, settle_refund: bool) -> PathResult<'ctx> {
         // Extract return data
-        let return_data = if let Some(ref data) = final_state.last_return_data {
+        let return_data = if let Some(ref data) = state.last_return_data {
             // Convert ByteVec to Vec<u8>
             // Try to unwrap the ByteVec to get concrete bytes
             match data.unwrap() {
@@ -448,123 +1474,644 @@ impl<'ctx> SEVM<'ctx> {
             Vec::new()
         };
 
+        // Check if execution was successful (no exceptional halt or revert)
+        let success = state.context.output.error.is_none()
+            && state.context.output.return_scheme != Some(0xFD); // REVERT
+
         // Calculate gas used (simplified - just return remaining gas)
-        let gas_used = gas.saturating_sub(final_state.gas);
+        let mut gas_used = gas.saturating_sub(state.gas);
 
-        // Check if execution was successful (no revert)
-        let success = !return_data.starts_with(&[0x4e, 0x48, 0x7b, 0x71]); // Not Panic selector
+        // A reverted transaction loses its refund; otherwise at most a fifth
+        // of the gas used comes back (EIP-3529)
+        if settle_refund && success && state.refund > 0 {
+            gas_used -= (state.refund as u64).min(gas_used / REFUND_QUOTIENT);
+        }
 
-        // Check for assertion failures and generate counterexample if needed
-        let (has_assertion_failure, counterexample) = self.check_assertions(&final_state)?;
-        if has_assertion_failure {
-            // Print counterexample to stderr for visibility
-            eprintln!("❌ Assertion Failure Detected!");
-            eprintln!("{}", counterexample);
-            eprintln!("Completed paths explored: {}", worklist.completed_paths);
+        // Persistent writes of this frame, keeping the last value per slot
+        let mut storage_diff: Vec<StorageWrite> = Vec::new();
+        for element in &state.context.trace {
+            if let TraceElement::Write(write) = element {
+                if write.transient {
+                    continue;
+                }
+                match storage_diff.iter_mut().find(|w| w.slot == write.slot) {
+                    Some(existing) => existing.value = write.value.clone(),
+                    None => storage_diff.push(write.clone()),
+                }
+            }
         }
 
         // Update CallContext output
-        final_state.context.output.data = Some(return_data.clone());
-        final_state.context.output.return_scheme = Some(if success { 0xF3 } else { 0xFD }); // RETURN or REVERT
+        state.context.output.data = Some(return_data.clone());
 
-        // Put the contract back into the HashMap
-        self.contracts.insert(target, contract);
+        PathResult {
+            success,
+            return_data,
+            gas_used,
+            constraints: state
+                .path
+                .conditions
+                .iter()
+                .map(|(cond, _)| cond.clone())
+                .collect(),
+            storage_diff,
+            context: state.context,
+        }
+    }
+
+    /// Print the instruction about to execute along with the top of the stack,
+    /// followed by a memory dump when `print_mem` is set
+    fn trace_step(&mut self, state: &ExecState<'ctx>, insn: &Instruction<'ctx>) -> CbseResult<()> {
+        let stack = state
+            .stack
+            .iter()
+            .rev()
+            .take(4)
+            .map(|item| match item.as_biguint() {
+                Ok(value) => format!("{:#x}", value),
+                Err(_) => "<symbolic>".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let io_error = |e: std::io::Error| CbseException::Internal(format!("step trace: {}", e));
+        writeln!(
+            self.step_output,
+            "{:#06x} {} [{}]",
+            state.pc,
+            mnemonic(insn.opcode),
+            stack
+        )
+        .map_err(io_error)?;
+        if self.print_mem {
+            state
+                .memory
+                .dump_to(&mut self.step_output)
+                .map_err(io_error)?;
+        }
+        Ok(())
+    }
+
+    /// Statistics accumulated since the SEVM was created or last reset
+    pub fn run_stats(&self) -> RunStats {
+        RunStats {
+            solver: self.solver_stats.borrow().clone(),
+            ..self.stats.clone()
+        }
+    }
+
+    /// Clear the accumulated statistics, e.g. before running the next test
+    pub fn reset_stats(&mut self) {
+        self.stats = RunStats::default();
+        *self.solver_stats.borrow_mut() = SolverStats::default();
+    }
+
+    /// Flamegraph frame name for a call: `Contract::function`, falling back to
+    /// the address label and the raw selector when they are unknown
+    fn flame_frame_name(
+        &self,
+        contract: &Contract<'ctx>,
+        target: &[u8; 20],
+        calldata: &[u8],
+    ) -> String {
+        let contract_name = contract.contract_name.clone().unwrap_or_else(|| {
+            let addr_str = format!("{:#x}", Self::address_to_biguint(target));
+            self.labels.get_deployed_contract(&addr_str)
+        });
+        let function = if calldata.len() >= 4 {
+            let selector = cbse_traces::hexify(&calldata[..4]);
+            Mapper::instance().lookup_selector(&selector, Some(&contract_name))
+        } else {
+            "fallback".to_string()
+        };
+        format!("{}::{}", contract_name, function)
+    }
 
-        Ok((success, return_data, gas_used, final_state.context))
+    /// Collected flamegraph samples in folded-stack format (`a;b;c count`)
+    pub fn folded_stacks(&self) -> String {
+        self.flame_samples
+            .iter()
+            .map(|(stack, count)| format!("{} {}\n", stack, count))
+            .collect()
+    }
+
+    /// Write the collected samples as folded stacks for flamegraph.pl or inferno
+    pub fn write_flamegraph(&self, path: impl AsRef<FsPath>) -> CbseResult<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.folded_stacks()).map_err(|e| {
+            CbseException::Internal(format!("failed to write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Executed opcodes with their counts, most frequent first
+    pub fn instruction_profile(&self) -> Vec<InstructionProfile> {
+        let mut profile: Vec<_> = self
+            .instruction_counts
+            .iter()
+            .map(|(&opcode, &count)| InstructionProfile {
+                opcode,
+                mnemonic: mnemonic(opcode),
+                count,
+            })
+            .collect();
+        // Stable, so equal counts stay in opcode order
+        profile.sort_by(|a, b| b.count.cmp(&a.count));
+        profile
+    }
+
+    /// Write the instruction profile for offline analysis
+    pub fn write_instruction_profile(
+        &self,
+        path: impl AsRef<FsPath>,
+        format: ProfileFormat,
+    ) -> CbseResult<()> {
+        let path = path.as_ref();
+        let profile = self.instruction_profile();
+        let contents = match format {
+            ProfileFormat::Json => serde_json::to_string_pretty(&profile).map_err(|e| {
+                CbseException::Internal(format!("failed to encode instruction profile: {}", e))
+            })?,
+            ProfileFormat::Csv => std::iter::once("opcode,mnemonic,count\n".to_string())
+                .chain(
+                    profile
+                        .iter()
+                        .map(|p| format!("{},{},{}\n", p.opcode, p.mnemonic, p.count)),
+                )
+                .collect(),
+        };
+        std::fs::write(path, contents).map_err(|e| {
+            CbseException::Internal(format!("failed to write {}: {}", path.display(), e))
+        })
     }
 
     /// Convert address to u64 for trace
+    ///
+    /// Lossy: only the low 8 bytes are kept, so this must not be used to
+    /// identify accounts. Use `address_to_biguint` for that.
     fn address_to_u64(addr: &[u8; 20]) -> u64 {
         let mut bytes = [0u8; 8];
         bytes.copy_from_slice(&addr[12..20]); // Use last 8 bytes
         u64::from_be_bytes(bytes)
     }
 
+    /// Convert an address to its full 160-bit integer value
+    pub fn address_to_biguint(addr: &[u8; 20]) -> BigUint {
+        BigUint::from_bytes_be(addr)
+    }
+
+    /// Convert an integer to an address, keeping the low 160 bits
+    pub fn biguint_to_address(value: &BigUint) -> [u8; 20] {
+        let bytes = value.to_bytes_be();
+        let n = bytes.len().min(20);
+        let mut addr = [0u8; 20];
+        addr[20 - n..].copy_from_slice(&bytes[bytes.len() - n..]);
+        addr
+    }
+
     /// Handle cheatcode calls
-    pub fn handle_cheatcode(&mut self, selector: [u8; 4], data: &[u8]) -> CbseResult<Vec<u8>> {
+    ///
+    /// `arg` is the full calldata, selector included. Symbolic arguments and
+    /// results are passed through as-is. Calldata the cheatcode cannot decode
+    /// is reported as an `AbiDecode` error.
+    pub fn handle_cheatcode(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        arg: &ByteVec<'ctx>,
+    ) -> CbseResult<ByteVec<'ctx>> {
+        self.dispatch_cheatcode(state, arg).map_err(|e| match e {
+            CbseException::AbiDecode(msg) => {
+                CbseException::AbiDecode(format!("bad cheatcode calldata: {}", msg))
+            }
+            e => e,
+        })
+    }
+
+    fn dispatch_cheatcode(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        arg: &ByteVec<'ctx>,
+    ) -> CbseResult<ByteVec<'ctx>> {
+        let selector = match arg.slice(0, 4)?.unwrap()? {
+            UnwrappedBytes::Bytes(bytes) => bytes,
+            UnwrappedBytes::BitVec(bv) if bv.is_concrete() => bv.to_bytes(),
+            UnwrappedBytes::BitVec(_) => {
+                return Err(CbseException::NotConcrete(
+                    "symbolic cheatcode selector".to_string(),
+                ))
+            }
+        };
+        let selector = u32::from_be_bytes([selector[0], selector[1], selector[2], selector[3]]);
+
         // vm.assume(bool condition) - selector: 0x4c63e562
-        if selector == [0x4c, 0x63, 0xe5, 0x62] {
+        if selector == hevm_cheat_code::ASSUME {
             // Extract condition from calldata (first 32 bytes after selector)
-            if data.len() >= 32 {
-                let mut cond_bytes = [0u8; 32];
-                cond_bytes.copy_from_slice(&data[0..32]);
-                let cond = CbseBitVec::from_bytes(&cond_bytes, 256);
-
-                // Check if condition is zero (false) or non-zero (true)
-                let is_zero = cond.is_zero(self.ctx);
-
-                match is_zero {
-                    cbse_bitvec::CbseBool::Concrete(true) => {
-                        // Assuming false - path is infeasible
-                        return Err(CbseException::Internal(
-                            "vm.assume(false) makes path infeasible".to_string(),
-                        ));
-                    }
-                    cbse_bitvec::CbseBool::Concrete(false) => {
-                        // Assuming true - always satisfied, no constraint needed
-                    }
-                    cbse_bitvec::CbseBool::Symbolic(z3_bool) => {
-                        // Add symbolic constraint that condition is NOT zero (i.e., true)
-                        self.solver.assert(&z3_bool.not());
-                    }
-                }
+            if arg.len() >= 36 {
+                let cond = match arg.get_word(4)? {
+                    UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+                    UnwrappedBytes::BitVec(bv) => bv,
+                };
+
+                // vm.assume(cond) constrains the path to cond != 0
+                self.assert_cheatcode_constraint(
+                    state,
+                    &cond,
+                    "vm.assume(false) makes path infeasible",
+                )?;
             }
-            return Ok(Vec::new()); // vm.assume returns nothing
+            return Ok(ByteVec::new(self.ctx)); // vm.assume returns nothing
+        }
+
+        // vm.assertTrue/assertEq/... fail the path if the negation is satisfiable
+        if let Some(holds) = self.assertion_cheatcode_condition(selector, arg)? {
+            self.check_assertion_cheatcode(state, holds)?;
+            return Ok(ByteVec::new(self.ctx));
         }
 
         // vm.prank(address) - selector: 0xca669fa7
         // TODO: Implement prank functionality
-        if selector == [0xca, 0x66, 0x9f, 0xa7] {
+        if selector == hevm_cheat_code::PRANK {
             // For now, just return success
-            return Ok(Vec::new());
+            return Ok(ByteVec::new(self.ctx));
         }
 
-        // For other cheatcodes, return empty result
-        // TODO: Implement remaining cheatcodes (prank, deal, store, load, etc.)
-        Ok(Vec::new())
-    }
+        let result = match selector {
+            hevm_cheat_code::LABEL => {
+                cbse_cheatcodes::label(arg, &mut self.labels)?;
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::FFI => cbse_cheatcodes::ffi(arg, self.ffi, self.ctx)?,
+            hevm_cheat_code::GET_CODE => {
+                let path = cbse_cheatcodes::extract_string_argument(arg, 0)?;
+                let code = BuildOut::instance()
+                    .get_creation_code(&path)
+                    .map_err(|e| CbseException::Internal(format!("vm.getCode: {}", e)))?;
+                cbse_cheatcodes::encode_tuple_bytes(&code, self.ctx)?
+            }
+            hevm_cheat_code::EXPECT_EMIT => {
+                state.expected_emit = Some(ExpectedEmit::all(None));
+                self.emit_expected = true;
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::EXPECT_EMIT_ADDR => {
+                let emitter = cbse_cheatcodes::extract_address_argument(arg, 0)?;
+                state.expected_emit = Some(ExpectedEmit::all(Some(emitter)));
+                self.emit_expected = true;
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::EXPECT_EMIT_CHECKS | hevm_cheat_code::EXPECT_EMIT_CHECKS_ADDR => {
+                let check_topics = [
+                    cbse_cheatcodes::extract_bool_argument(arg, 0)?,
+                    cbse_cheatcodes::extract_bool_argument(arg, 1)?,
+                    cbse_cheatcodes::extract_bool_argument(arg, 2)?,
+                ];
+                let check_data = cbse_cheatcodes::extract_bool_argument(arg, 3)?;
+                let emitter = if selector == hevm_cheat_code::EXPECT_EMIT_CHECKS_ADDR {
+                    Some(cbse_cheatcodes::extract_address_argument(arg, 4)?)
+                } else {
+                    None
+                };
+                state.expected_emit = Some(ExpectedEmit::new(check_topics, check_data, emitter));
+                self.emit_expected = true;
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::CHAINID => {
+                self.block.chainid = cbse_cheatcodes::extract_u64_argument(arg, 0)?;
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::DEAL => {
+                let address = cbse_cheatcodes::extract_address_argument(arg, 0)?;
+                let amount = match arg.get_word(36)? {
+                    UnwrappedBytes::Bytes(bytes) => BigUint::from_bytes_be(&bytes),
+                    UnwrappedBytes::BitVec(bv) => bv.as_biguint().map_err(|_| {
+                        CbseException::NotConcrete("symbolic vm.deal amount".to_string())
+                    })?,
+                };
+                self.set_balance(address, amount);
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::MOCK_CALL => {
+                let address = cbse_cheatcodes::extract_address_argument(arg, 0)?;
+                let calldata = cbse_cheatcodes::extract_bytes_argument(arg, 1)?;
+                let returndata = cbse_cheatcodes::extract_bytes_argument(arg, 2)?;
+                self.mocks.insert(address, calldata, returndata);
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::CLEAR_MOCKED_CALLS => {
+                self.mocks.clear();
+                ByteVec::new(self.ctx)
+            }
+            hevm_cheat_code::ENV_EXISTS => cbse_cheatcodes::env_exists(arg, self.ctx)?,
+            hevm_cheat_code::ENV_INT => cbse_cheatcodes::env_int(arg, self.ctx)?,
+            hevm_cheat_code::ENV_UINT => cbse_cheatcodes::env_uint(arg, self.ctx)?,
+            hevm_cheat_code::ENV_ADDRESS => cbse_cheatcodes::env_address(arg, self.ctx)?,
+            hevm_cheat_code::ENV_BOOL => cbse_cheatcodes::env_bool(arg, self.ctx)?,
+            hevm_cheat_code::ENV_BYTES32 => cbse_cheatcodes::env_bytes32(arg, self.ctx)?,
+            hevm_cheat_code::ENV_STRING => cbse_cheatcodes::env_string(arg, self.ctx)?,
+            hevm_cheat_code::ENV_BYTES => cbse_cheatcodes::env_bytes(arg, self.ctx)?,
 
-    /// Convert ByteVec to concrete bytes
-    fn bytevec_to_bytes(&self, bytevec: &ByteVec<'ctx>) -> CbseResult<Vec<u8>> {
-        let mut result = Vec::new();
-        for i in 0..bytevec.len() {
-            let byte = bytevec.get_byte(i)?;
-            match byte {
-                UnwrappedBytes::Bytes(bytes) => {
-                    if !bytes.is_empty() {
-                        result.push(bytes[0]);
-                    } else {
-                        result.push(0);
+            hevm_cheat_code::ENV_INT_ARRAY => cbse_cheatcodes::env_int_array(arg, self.ctx)?,
+            hevm_cheat_code::ENV_UINT_ARRAY => cbse_cheatcodes::env_uint_array(arg, self.ctx)?,
+            hevm_cheat_code::ENV_ADDRESS_ARRAY => {
+                cbse_cheatcodes::env_address_array(arg, self.ctx)?
+            }
+            hevm_cheat_code::ENV_BOOL_ARRAY => cbse_cheatcodes::env_bool_array(arg, self.ctx)?,
+            hevm_cheat_code::ENV_BYTES32_ARRAY => {
+                cbse_cheatcodes::env_bytes32_array(arg, self.ctx)?
+            }
+            hevm_cheat_code::ENV_STRING_ARRAY => cbse_cheatcodes::env_string_array(arg, self.ctx)?,
+            hevm_cheat_code::ENV_BYTES_ARRAY => cbse_cheatcodes::env_bytes_array(arg, self.ctx)?,
+
+            hevm_cheat_code::ENV_OR_UINT => cbse_cheatcodes::env_or_uint(arg, self.ctx)?,
+            hevm_cheat_code::ENV_OR_ADDRESS => cbse_cheatcodes::env_or_address(arg, self.ctx)?,
+            hevm_cheat_code::ENV_OR_BOOL => cbse_cheatcodes::env_or_bool(arg, self.ctx)?,
+            hevm_cheat_code::ENV_OR_BYTES32 => cbse_cheatcodes::env_or_bytes32(arg, self.ctx)?,
+            hevm_cheat_code::ENV_OR_STRING => cbse_cheatcodes::env_or_string(arg, self.ctx)?,
+            hevm_cheat_code::ENV_OR_BYTES => cbse_cheatcodes::env_or_bytes(arg, self.ctx)?,
+
+            hevm_cheat_code::RANDOM_UINT => {
+                cbse_cheatcodes::random_uint(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_UINT_UINT256 => {
+                cbse_cheatcodes::random_uint_bits(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_UINT_MIN_MAX => {
+                let (value, constraints) =
+                    cbse_cheatcodes::random_uint_min_max(arg, self.symbols.next_id(), self.ctx)?;
+                for constraint in &constraints {
+                    self.assert_cheatcode_constraint(
+                        state,
+                        constraint,
+                        "vm.randomUint(min, max) with an empty range",
+                    )?;
+                }
+                value
+            }
+            hevm_cheat_code::RANDOM_INT => {
+                cbse_cheatcodes::random_int(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_INT_UINT256 => {
+                cbse_cheatcodes::random_int_bits(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_ADDRESS => {
+                cbse_cheatcodes::random_address(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_BOOL => {
+                cbse_cheatcodes::random_bool(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_BYTES => {
+                cbse_cheatcodes::random_bytes(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_BYTES4 => {
+                cbse_cheatcodes::random_bytes4(arg, self.symbols.next_id(), self.ctx)?
+            }
+            hevm_cheat_code::RANDOM_BYTES8 => {
+                cbse_cheatcodes::random_bytes8(arg, self.symbols.next_id(), self.ctx)?
+            }
+
+            halmos_cheat_code::SYMBOLIC_STORAGE => {
+                let address = cbse_cheatcodes::extract_address_argument(arg, 0)?;
+                self.enable_symbolic_storage(address);
+                ByteVec::new(self.ctx)
+            }
+            halmos_cheat_code::SNAPSHOT_STORAGE => {
+                let address = cbse_cheatcodes::extract_address_argument(arg, 0)?;
+                let handle = self.snapshot_storage(address);
+                ByteVec::from_bytes(
+                    CbseBitVec::from_u64(handle as u64, 256).to_bytes(),
+                    self.ctx,
+                )?
+            }
+            halmos_cheat_code::CREATE_UINT => {
+                cbse_cheatcodes::create_uint(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_UINT256 => {
+                cbse_cheatcodes::create_uint256(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_INT => {
+                cbse_cheatcodes::create_int(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_INT256 => {
+                cbse_cheatcodes::create_int256(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_BYTES => {
+                match cbse_cheatcodes::create_bytes(arg, self.symbols.next_id(), self.ctx) {
+                    Err(CbseException::NotConcrete(_)) => {
+                        self.create_bytes_symbolic_length(state, arg)?
                     }
+                    result => result?,
                 }
-                UnwrappedBytes::BitVec(bv) => {
-                    let val = bv.as_u64().unwrap_or(0) as u8;
-                    result.push(val);
+            }
+            halmos_cheat_code::CREATE_STRING => {
+                cbse_cheatcodes::create_string(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_BYTES4 => {
+                cbse_cheatcodes::create_bytes4(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_BYTES32 => {
+                cbse_cheatcodes::create_bytes32(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_ADDRESS => {
+                cbse_cheatcodes::create_address(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_BOOL => {
+                cbse_cheatcodes::create_bool(arg, self.symbols.next_id(), self.ctx)?
+            }
+            halmos_cheat_code::CREATE_UINT256_MIN_MAX => {
+                let (value, constraints) =
+                    cbse_cheatcodes::create_uint256_min_max(arg, self.symbols.next_id(), self.ctx)?;
+                for constraint in &constraints {
+                    self.assert_cheatcode_constraint(
+                        state,
+                        constraint,
+                        "svm.createUint256(name, min, max) with an empty range",
+                    )?;
                 }
+                value
             }
-        }
+
+            // For other cheatcodes, return empty result
+            // TODO: Implement remaining cheatcodes (prank, deal, store, load, etc.)
+            _ => ByteVec::new(self.ctx),
+        };
         Ok(result)
     }
 
+    /// Condition asserted by an assertion cheatcode, or None for other selectors
+    fn assertion_cheatcode_condition(
+        &self,
+        selector: u32,
+        arg: &ByteVec<'ctx>,
+    ) -> CbseResult<Option<cbse_bitvec::CbseBool<'ctx>>> {
+        let word = |offset: usize| -> CbseResult<CbseBitVec<'ctx>> {
+            Ok(match arg.get_word(offset)? {
+                UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+                UnwrappedBytes::BitVec(bv) => bv,
+            })
+        };
+
+        let holds = match selector {
+            hevm_cheat_code::ASSERT_TRUE => word(4)?.is_zero(self.ctx).not(self.ctx),
+            hevm_cheat_code::ASSERT_EQ_UINT256 | hevm_cheat_code::ASSERT_EQ_ADDRESS => {
+                word(4)?.eq(&word(36)?, self.ctx)
+            }
+            hevm_cheat_code::ASSERT_NOT_EQ_UINT256 => {
+                word(4)?.eq(&word(36)?, self.ctx).not(self.ctx)
+            }
+            hevm_cheat_code::ASSERT_GT_UINT256 => word(4)?.ugt(&word(36)?, self.ctx),
+            hevm_cheat_code::ASSERT_LT_UINT256 => word(4)?.ult(&word(36)?, self.ctx),
+            _ => return Ok(None),
+        };
+        Ok(Some(holds))
+    }
+
+    /// Record a counterexample if `holds` can be false on this path
+    ///
+    /// The path then continues under the assumption that the assertion holds,
+    /// so later assertions are only checked on inputs that got past this one.
+    fn check_assertion_cheatcode(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        holds: cbse_bitvec::CbseBool<'ctx>,
+    ) -> CbseResult<()> {
+        let holds = match holds {
+            cbse_bitvec::CbseBool::Concrete(true) => return Ok(()),
+            cbse_bitvec::CbseBool::Concrete(false) => {
+                self.counterexample = Some("Counterexample: ∅".to_string());
+                return Ok(());
+            }
+            cbse_bitvec::CbseBool::Symbolic(holds) => holds,
+        };
+
+        match state.path.model_with(&holds.not()) {
+            Ok(Some(model)) => {
+                let mut entries: Vec<String> = model
+                    .to_string()
+                    .lines()
+                    .map(|line| line.replace(" -> ", " = "))
+                    .collect();
+                entries.sort();
+                self.counterexample = Some(format!("Counterexample:\n    {}", entries.join(", ")));
+            }
+            Ok(None) => {}
+            Err(err @ (CbseException::SolverTimeout | CbseException::SolverUnknown(_))) => {
+                self.inconclusive = true;
+                self.counterexample = Some(format!("Counterexample: unknown ({})", err));
+            }
+            Err(err) => return Err(err),
+        }
+        state.path.append(holds, false)
+    }
+
+    /// Constrain the path to `flag != 0`, failing with `infeasible_msg` if it is
+    /// concretely zero
+    fn assert_cheatcode_constraint(
+        &self,
+        state: &mut ExecState<'ctx>,
+        flag: &CbseBitVec<'ctx>,
+        infeasible_msg: &str,
+    ) -> CbseResult<()> {
+        match flag.is_zero(self.ctx) {
+            cbse_bitvec::CbseBool::Concrete(true) => {
+                Err(CbseException::Internal(infeasible_msg.to_string()))
+            }
+            // Always satisfied, no constraint needed
+            cbse_bitvec::CbseBool::Concrete(false) => Ok(()),
+            cbse_bitvec::CbseBool::Symbolic(z3_bool) => state.path.append(z3_bool.not(), false),
+        }
+    }
+
+    /// svm.createBytes with a symbolic length: one of `bytes_lengths`
+    ///
+    /// The path is forked over every candidate length it allows, each fork
+    /// constrained to its length. `state` takes the first candidate; the
+    /// others are left in `cheatcode_forks` for the calling opcode.
+    fn create_bytes_symbolic_length(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        arg: &ByteVec<'ctx>,
+    ) -> CbseResult<ByteVec<'ctx>> {
+        let length = match arg.get_word(4)? {
+            UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+            UnwrappedBytes::BitVec(bv) => bv,
+        };
+        let name = cbse_cheatcodes::name_of(&cbse_cheatcodes::extract_string_argument(arg, 1)?);
+        let candidates = cbse_cheatcodes::create_bytes_bounded(
+            &name,
+            &length,
+            self.symbols.next_id(),
+            &self.bytes_lengths,
+            self.ctx,
+        )?;
+
+        let conds: Vec<z3::ast::Bool<'ctx>> = candidates
+            .iter()
+            .map(|(_, flags)| {
+                let holds: Vec<_> = flags
+                    .iter()
+                    .map(|flag| flag.is_zero(self.ctx).as_z3(self.ctx).not())
+                    .collect();
+                z3::ast::Bool::and(self.ctx, &holds.iter().collect::<Vec<_>>())
+            })
+            .collect();
+        let feasible = state.path.filter_feasible(&conds);
+        let mut branches = candidates
+            .into_iter()
+            .zip(conds)
+            .zip(feasible)
+            .filter_map(|(branch, feasible)| feasible.then_some(branch));
+
+        let ((encoded, _), cond) = branches.next().ok_or_else(|| {
+            CbseException::Internal("svm.createBytes: no candidate length is feasible".to_string())
+        })?;
+        for ((encoded, _), cond) in branches {
+            let mut fork = state.clone();
+            fork.path.append(cond, true)?;
+            self.cheatcode_forks.push((fork, encoded));
+        }
+        state.path.append(cond, true)?;
+        Ok(encoded)
+    }
+
+    /// End the path of `state` with an exceptional halt: the frame fails with
+    /// no return data and all of its gas consumed. Always returns true, the
+    /// path having halted.
+    fn exceptional_halt(&self, state: &mut ExecState<'ctx>, halt: ExceptionalHalt) -> bool {
+        state.context.output.error = Some(halt.to_string());
+        state.last_return_data = Some(ByteVec::new(self.ctx));
+        state.gas = 0;
+        true
+    }
+
+    /// Whether `event` is recorded in the trace
+    fn records(&self, event: TraceEvent) -> bool {
+        self.trace_filter.contains(&event) || (event == TraceEvent::Log && self.emit_expected)
+    }
+
     /// Stack operations
     fn push(&self, state: &mut ExecState<'ctx>, value: CbseBitVec<'ctx>) -> CbseResult<()> {
-        if state.stack.len() >= 1024 {
-            return Err(CbseException::Internal("Stack overflow".to_string()));
-        }
-        state.stack.push(value);
-        Ok(())
+        state.push(value)
     }
 
     fn pop(&self, state: &mut ExecState<'ctx>) -> CbseResult<CbseBitVec<'ctx>> {
-        state
-            .stack
-            .pop()
-            .ok_or_else(|| CbseException::Internal("Stack underflow".to_string()))
+        state.pop()
+    }
+
+    /// Pop a value and zero-extend it to a full 256-bit word
+    ///
+    /// Some values (e.g. 160-bit addresses) are pushed at their natural width;
+    /// binary operations on them need both operands to be words.
+    fn pop_word(&self, state: &mut ExecState<'ctx>) -> CbseResult<CbseBitVec<'ctx>> {
+        let value = self.pop(state)?;
+        Ok(if value.size() < 256 {
+            value.zero_extend(256, self.ctx)
+        } else {
+            value
+        })
     }
 
     fn peek(&self, state: &ExecState<'ctx>, n: usize) -> CbseResult<CbseBitVec<'ctx>> {
         if state.stack.len() < n {
-            return Err(CbseException::Internal("Stack underflow".to_string()));
+            return Err(CbseException::Halt(ExceptionalHalt::StackUnderflow));
         }
         Ok(state.stack[state.stack.len() - n].clone())
     }
@@ -572,52 +2119,58 @@ impl<'ctx> SEVM<'ctx> {
     /// Check if an execution state represents an assertion failure
     ///
     /// Detects Panic errors, which indicate assertion violations in Solidity.
-    /// Returns true if the state contains a Panic(0x01) error (assertion failure).
+    /// Returns true if the state reverted with Panic(uint256) and the code is
+    /// one of `panic_codes` (Panic(0x01) by default).
     pub fn is_assertion_failure(&self, state: &ExecState<'ctx>) -> bool {
-        if let Some(ref return_data) = state.last_return_data {
-            // Check for Panic signature: 0x4e487b71
-            // Panic(uint256) selector
-            if return_data.len() >= 36 {
-                // Get first 4 bytes for selector
-                let mut selector = [0u8; 4];
-                for i in 0..4 {
-                    if let Ok(byte) = return_data.get_byte(i) {
-                        match byte {
-                            UnwrappedBytes::Bytes(bytes) if !bytes.is_empty() => {
-                                selector[i] = bytes[0];
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-
-                // Check if it's Panic selector
-                if selector == [0x4e, 0x48, 0x7b, 0x71] {
-                    // Get panic code (next 32 bytes)
-                    // Panic(0x01) = assertion failure
-                    // Panic(0x11) = arithmetic overflow
-                    // Panic(0x12) = divide by zero
-                    // etc.
-                    if let Ok(byte) = return_data.get_byte(35) {
-                        if let UnwrappedBytes::Bytes(bytes) = byte {
-                            if !bytes.is_empty() && bytes[0] == 0x01 {
-                                return true; // Panic(0x01) - assertion failure
-                            }
-                        }
-                    }
-                }
-            }
+        match state.last_return_data {
+            Some(ref return_data) => concrete_bytes(return_data)
+                .map_or(false, |bytes| self.panic_codes.matches_returndata(&bytes)),
+            None => false,
         }
-        false
     }
 
     /// Generate and display a counterexample for an assertion failure
     ///
     /// This extracts a satisfying model from the solver showing concrete values
-    /// for symbolic variables that cause the assertion to fail.
+    /// for symbolic variables that cause the assertion to fail. If the solver
+    /// cannot decide the query, SolverTimeout or SolverUnknown is returned.
     ///
     /// Matches Python's counterexample generation in __main__.py lines 791-1000
     pub fn generate_counterexample(&self, state: &ExecState<'ctx>) -> CbseResult<String> {
+        if let Some(external) = &self.external_solver {
+            let output = external.check(&state.path);
+            match output.result {
+                cbse_solver::SatResult::Sat => {
+                    let mut entries: Vec<String> = output
+                        .model
+                        .map(|model| model.model)
+                        .unwrap_or_default()
+                        .into_values()
+                        .map(|var| format!("{} = 0x{:x}", var.full_name, var.value))
+                        .collect();
+                    if entries.is_empty() {
+                        return Ok("Counterexample: ∅".to_string());
+                    }
+                    entries.sort();
+                    return Ok(format!("Counterexample:\n    {}", entries.join(", ")));
+                }
+                cbse_solver::SatResult::Unsat => {
+                    return Ok("No counterexample found (path may be infeasible)".to_string());
+                }
+                cbse_solver::SatResult::Unknown => {
+                    return Err(CbseException::SolverUnknown(
+                        output.error.unwrap_or_else(|| "solver gave up".to_string()),
+                    ));
+                }
+                // The solver could not be run; fall back to the in-process Z3
+                cbse_solver::SatResult::Error => eprintln!(
+                    "warning: external solver {} failed: {}",
+                    external.command[0],
+                    output.error.unwrap_or_default()
+                ),
+            }
+        }
+
         // Extract model from the path's solver
         let model = state.path.get_model()?;
 
@@ -630,18 +2183,57 @@ impl<'ctx> SEVM<'ctx> {
         Ok(format!("Counterexample:\n    {}", formatted))
     }
 
-    /// Check for assertion failures and generate counterexamples
+    /// Solve the assertion queries of several completed paths concurrently
+    ///
+    /// Uses up to `solver_threads` workers, each query bounded by the assertion
+    /// timeout; results are in the order of `paths`.
+    pub fn solve_assertion_paths(&self, paths: &[&Path<'ctx>]) -> Vec<SolvedQuery> {
+        let queries: Vec<String> = paths.iter().map(|path| path.to_smt2(false)).collect();
+        solve_queries(
+            &queries,
+            self.solver_threads,
+            self.external_solver.as_ref(),
+            self.solver_timeouts.assertion,
+        )
+    }
+
+    /// Check completed paths for assertion failures and generate counterexamples
     ///
     /// This is called after execution completes to check if any assertion failed.
-    /// If a failure is detected, it extracts and displays the counterexample.
+    /// The counterexample queries of all failing paths are solved together (see
+    /// `solve_assertion_paths`), and the first counterexample found is reported.
+    /// A failing path whose query is unsat cannot be reached and is ignored.
+    ///
+    /// If no query is sat but one could not be decided, the failure is still
+    /// reported but `inconclusive` is set.
     ///
     /// Returns (has_failure, counterexample_message)
-    pub fn check_assertions(&self, state: &ExecState<'ctx>) -> CbseResult<(bool, String)> {
-        if self.is_assertion_failure(state) {
-            let counterexample = self.generate_counterexample(state)?;
-            Ok((true, counterexample))
-        } else {
-            Ok((false, String::new()))
+    pub fn check_assertions(&mut self, states: &[ExecState<'ctx>]) -> (bool, String) {
+        let failing: Vec<&Path<'ctx>> = states
+            .iter()
+            .filter(|state| self.is_assertion_failure(state))
+            .map(|state| &state.path)
+            .collect();
+        if failing.is_empty() {
+            return (false, String::new());
+        }
+
+        let solved = self.solve_assertion_paths(&failing);
+        if let Some(query) = solved
+            .iter()
+            .find(|query| query.result == cbse_solver::SatResult::Sat)
+        {
+            return (true, query.describe());
+        }
+        match solved
+            .iter()
+            .find(|query| query.result != cbse_solver::SatResult::Unsat)
+        {
+            Some(query) => {
+                self.inconclusive = true;
+                (true, query.describe())
+            }
+            None => (false, String::new()),
         }
     }
 }
@@ -649,6 +2241,9 @@ impl<'ctx> SEVM<'ctx> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cbse_constants::PANIC_SELECTOR;
+    use std::time::Duration;
+    use z3::ast::Ast;
 
     #[test]
     fn test_sevm_creation() {
@@ -699,4 +2294,2192 @@ mod tests {
 
         assert!(sevm.is_assertion_failure(&state));
     }
+
+    #[test]
+    fn test_assertion_failure_uses_configured_panic_codes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let solver = Rc::new(Solver::new(&ctx));
+
+        let message = CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false);
+        let output = CallOutput::new(None, None, None);
+        let call_context = CallContext::new(message, output, 0);
+        let mut state = ExecState::new(&ctx, call_context, solver);
+
+        // Panic(0x11): arithmetic overflow
+        let mut panic_data = PANIC_SELECTOR.to_vec();
+        panic_data.extend(vec![0u8; 31]);
+        panic_data.push(0x11);
+        state.last_return_data = Some(ByteVec::from_bytes(panic_data, &ctx).unwrap());
+
+        assert!(!sevm.is_assertion_failure(&state));
+
+        sevm.panic_codes = PanicCodes::new(vec![0x01, 0x11]);
+        assert!(sevm.is_assertion_failure(&state));
+
+        sevm.panic_codes = PanicCodes::new(Vec::new());
+        assert!(sevm.is_assertion_failure(&state));
+    }
+
+    /// Execution state for calling cheatcodes directly
+    fn cheatcode_state<'ctx>(sevm: &SEVM<'ctx>) -> ExecState<'ctx> {
+        let message = CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false);
+        let output = CallOutput::new(None, None, None);
+        let call_context = CallContext::new(message, output, 0);
+        ExecState::new(sevm.ctx, call_context, Rc::clone(&sevm.solver))
+    }
+
+    /// Writer whose contents stay readable after it is handed to the SEVM
+    struct SharedBuffer(Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_step_output() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let buffer = Rc::new(std::cell::RefCell::new(Vec::new()));
+        sevm.step_output = Box::new(SharedBuffer(Rc::clone(&buffer)));
+        sevm.print_steps = true;
+
+        // PUSH1 0x2a; STOP
+        let target = [1u8; 20];
+        sevm.deploy_contract(target, Contract::from_hexcode("602a00", &ctx).unwrap());
+        sevm.execute_call(
+            target,
+            [2u8; 20],
+            [2u8; 20],
+            BigUint::default(),
+            Vec::new(),
+            1_000_000,
+            false,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(buffer.borrow().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines, vec!["0x0000 PUSH1 []", "0x0002 STOP [0x2a]"]);
+    }
+
+    #[test]
+    fn test_trace_step_disabled_by_default() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let buffer = Rc::new(std::cell::RefCell::new(Vec::new()));
+        sevm.step_output = Box::new(SharedBuffer(Rc::clone(&buffer)));
+
+        let target = [1u8; 20];
+        sevm.deploy_contract(target, Contract::from_hexcode("602a00", &ctx).unwrap());
+        sevm.execute_call(
+            target,
+            [2u8; 20],
+            [2u8; 20],
+            BigUint::default(),
+            Vec::new(),
+            1_000_000,
+            false,
+        )
+        .unwrap();
+
+        assert!(buffer.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_run_stats_counts_paths_and_steps() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // if (calldataload(0)) { stop } else { stop }
+        let target = [1u8; 20];
+        sevm.deploy_contract(
+            target,
+            Contract::from_hexcode("600035600757005b00", &ctx).unwrap(),
+        );
+
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(
+                z3::ast::BV::new_const(&ctx, "cond", 256),
+            )))
+            .unwrap();
+        sevm.execute_call_with_calldata(
+            target,
+            [2u8; 20],
+            [2u8; 20],
+            BigUint::default(),
+            calldata,
+            1_000_000,
+            false,
+        )
+        .unwrap();
+
+        let stats = sevm.run_stats();
+        assert_eq!(stats.completed_paths, 2);
+        assert_eq!(stats.infeasible_paths, 0);
+        assert!(stats.steps > 0);
+        assert!(stats.solver.calls > 0);
+        assert_eq!(stats.solver.timings.len(), stats.solver.calls);
+        assert!(stats.to_string().contains("2 completed"));
+
+        sevm.reset_stats();
+        assert_eq!(sevm.run_stats(), RunStats::default());
+    }
+
+    #[test]
+    fn test_flamegraph_folded_stacks() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.flamegraph = true;
+
+        let mapper = Mapper::instance();
+        for (contract, function, selector) in [
+            ("FlameOuter", "run", "0x11111111"),
+            ("FlameInner", "ping", "0x22222222"),
+        ] {
+            mapper.add_node(
+                Some(contract),
+                cbse_mapper::AstNode::new(
+                    "FunctionDefinition".to_string(),
+                    function.to_string(),
+                    selector.to_string(),
+                ),
+            );
+        }
+
+        // PUSH1 1; PUSH1 2; ADD; STOP
+        let inner_address = [0xbbu8; 20];
+        let mut inner = Contract::from_hexcode("600160020100", &ctx).unwrap();
+        inner.contract_name = Some("FlameInner".to_string());
+        sevm.deploy_contract(inner_address, inner);
+
+        // mstore(0, 0x22222222 << 224)
+        // call(1000000, inner, 0, 0, 4, 0, 0); stop
+        let outer_code = format!(
+            "7f22222222{}600052\
+             6000600060046000600073{}620f4240f100",
+            "00".repeat(28),
+            "bb".repeat(20)
+        );
+        let outer_address = [0xaau8; 20];
+        let mut outer = Contract::from_hexcode(&outer_code, &ctx).unwrap();
+        outer.contract_name = Some("FlameOuter".to_string());
+        sevm.deploy_contract(outer_address, outer);
+
+        sevm.execute_call(
+            outer_address,
+            [2u8; 20],
+            [2u8; 20],
+            BigUint::default(),
+            vec![0x11, 0x11, 0x11, 0x11],
+            10_000_000,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            sevm.folded_stacks(),
+            "FlameOuter::run 12\nFlameOuter::run;FlameInner::ping 4\n"
+        );
+    }
+
+    #[test]
+    fn test_deploy_from_initcode_keeps_runtime_and_storage() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // runtime: return(sload(0))
+        let runtime = vec![
+            0x60, 0x00, 0x54, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+        ];
+        // sstore(0, 42); codecopy(0, 17, 11); return(0, 11)
+        let mut initcode = vec![
+            0x60, 0x2a, 0x60, 0x00, 0x55, 0x60, 0x0b, 0x60, 0x11, 0x60, 0x00, 0x39, 0x60, 0x0b,
+            0x60, 0x00, 0xf3,
+        ];
+        initcode.extend(&runtime);
+
+        let address = [0xccu8; 20];
+        sevm.deploy_from_initcode(
+            address,
+            ByteVec::from_bytes(initcode, &ctx).unwrap(),
+            ByteVec::new(&ctx),
+        )
+        .unwrap();
+
+        let code = &sevm.contracts[&address];
+        let deployed: Vec<u8> = (0..code.len()).map(|i| code.get_byte(i).unwrap()).collect();
+        assert_eq!(deployed, runtime);
+
+        let slot = CbseBitVec::from_u64(0, 256);
+        assert_eq!(sevm.get_storage(address, &slot).as_u64().unwrap(), 42);
+
+        let (success, returndata, _, _) = sevm
+            .execute_call(
+                address,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                false,
+            )
+            .unwrap();
+        assert!(success);
+        assert_eq!(returndata[31], 42);
+    }
+
+    #[test]
+    fn test_set_code_recomputes_jumpdests() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+        let call = |sevm: &mut SEVM<'_>| {
+            sevm.execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                false,
+            )
+            .unwrap()
+            .0
+        };
+
+        // PUSH1 4; JUMP; STOP; JUMPDEST; STOP
+        let old = ByteVec::from_bytes(vec![0x60, 0x04, 0x56, 0x00, 0x5b, 0x00], &ctx).unwrap();
+        sevm.set_code(target, old);
+        assert!(call(&mut sevm));
+        let key = Address::from(target);
+        let contract = sevm.contracts.get_mut(&key).unwrap();
+        assert!(contract.valid_jumpdests().contains(&4));
+
+        // PUSH1 4; JUMP; JUMPDEST; STOP - offset 4 is no longer a jump destination
+        let new = ByteVec::from_bytes(vec![0x60, 0x04, 0x56, 0x5b, 0x00], &ctx).unwrap();
+        sevm.set_code(target, new);
+        let contract = sevm.contracts.get_mut(&key).unwrap();
+        assert_eq!(contract.len(), 5);
+        assert_eq!(contract.valid_jumpdests(), &HashSet::from([3]));
+        assert!(!call(&mut sevm));
+    }
+
+    #[test]
+    fn test_write_instruction_profile() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.profile_instructions = true;
+
+        // mstore(0, 1 + 2); return(0, 32)
+        run_code(&mut sevm, "600160020160005260206000f3");
+
+        let expected = [
+            (0x60, "PUSH1", 5),
+            (0x01, "ADD", 1),
+            (0x52, "MSTORE", 1),
+            (0xf3, "RETURN", 1),
+        ];
+        let dir = std::env::temp_dir();
+        let pid = std::process::id();
+
+        let json_path = dir.join(format!("cbse-profile-{}.json", pid));
+        sevm.write_instruction_profile(&json_path, ProfileFormat::Json)
+            .unwrap();
+        let json = std::fs::read_to_string(&json_path).unwrap();
+        std::fs::remove_file(&json_path).unwrap();
+        let parsed: Vec<InstructionProfile> = serde_json::from_str(&json).unwrap();
+        let rows: Vec<_> = parsed
+            .iter()
+            .map(|p| (p.opcode, p.mnemonic.as_str(), p.count))
+            .collect();
+        assert_eq!(rows, expected);
+
+        let csv_path = dir.join(format!("cbse-profile-{}.csv", pid));
+        sevm.write_instruction_profile(&csv_path, ProfileFormat::Csv)
+            .unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        std::fs::remove_file(&csv_path).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("opcode,mnemonic,count"));
+        let rows: Vec<_> = lines
+            .map(|line| {
+                let fields: Vec<_> = line.split(',').collect();
+                (
+                    fields[0].parse::<u8>().unwrap(),
+                    fields[1],
+                    fields[2].parse::<usize>().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn test_selfdestruct_transfers_balance_and_clears_contract() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // selfdestruct(0xbb..bb)
+        let target = [0xaau8; 20];
+        let beneficiary = [0xbbu8; 20];
+        let code = format!("73{}ff", "bb".repeat(20));
+        sevm.deploy_contract(target, Contract::from_hexcode(&code, &ctx).unwrap());
+        sevm.set_balance(target, 100u64);
+        sevm.set_balance(beneficiary, 5u64);
+        sevm.set_storage(
+            target,
+            CbseBitVec::from_u64(0, 256),
+            CbseBitVec::from_u64(42, 256),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        let (success, _, _, _) = sevm
+            .execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        assert!(success);
+        assert_eq!(sevm.get_balance_u64(&target), 0);
+        assert_eq!(sevm.get_balance_u64(&beneficiary), 105);
+        assert!(!sevm.contracts.contains_key(&target));
+        assert!(!sevm.storage.contains_key(&target));
+    }
+
+    #[test]
+    fn test_selfdestruct_to_symbolic_beneficiary_halts_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // selfdestruct(coinbase)
+        let target = [0xaau8; 20];
+        sevm.deploy_contract(target, Contract::from_hexcode("41ff", &ctx).unwrap());
+        sevm.set_balance(target, 100u64);
+
+        let (success, _, gas_used, _) = sevm
+            .execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        // Only the path halts; the run itself goes on
+        assert!(!success);
+        assert_eq!(gas_used, 1_000_000);
+        assert_eq!(sevm.get_balance_u64(&target), 100);
+        assert_eq!(sevm.get_balance_u64(&[0u8; 20]), 0);
+    }
+
+    #[test]
+    fn test_extcode_of_symbolic_address_halts_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // extcodesize(coinbase), extcodecopy(coinbase, 0, 0, 0), extcodehash(coinbase)
+        for code in ["413b00", "600060006000413c00", "413f00"] {
+            let mut sevm = SEVM::new(&ctx);
+            let target = [0xaau8; 20];
+            sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+            let (success, _, gas_used, _) = sevm
+                .execute_call(
+                    target,
+                    [2u8; 20],
+                    [2u8; 20],
+                    BigUint::default(),
+                    Vec::new(),
+                    1_000_000,
+                    false,
+                )
+                .unwrap();
+
+            assert!(!success, "{}", code);
+            assert_eq!(gas_used, 1_000_000, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_selfdestruct_forbidden_in_static_call() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let target = [0xaau8; 20];
+        let code = format!("73{}ff", "bb".repeat(20));
+        sevm.deploy_contract(target, Contract::from_hexcode(&code, &ctx).unwrap());
+        sevm.set_balance(target, 100u64);
+
+        let (success, _, gas_used, _) = sevm
+            .execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                1_000_000,
+                true,
+            )
+            .unwrap();
+
+        // A write in a static context is an exceptional halt (EIP-214)
+        assert!(!success);
+        assert_eq!(gas_used, 1_000_000);
+        assert_eq!(sevm.get_balance_u64(&target), 100);
+        assert!(sevm.contracts.contains_key(&target));
+    }
+
+    /// Code that CALLs 0x22..22 with `value` wei and returns the callee's first
+    /// return word followed by the CALL status
+    fn call_with_value_code(value: u8) -> String {
+        format!(
+            "602060006000600060{:02x}73{}61fffff160205260406000f3",
+            value,
+            "22".repeat(20)
+        )
+    }
+
+    #[test]
+    fn test_call_transfers_value() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // callee: return(selfbalance())
+        let callee = [0x22u8; 20];
+        let code = "4760005260206000f3";
+        sevm.deploy_contract(callee, Contract::from_hexcode(code, &ctx).unwrap());
+        sevm.set_balance([1u8; 20], 100u64);
+
+        let (success, returndata, _, _) = run_code(&mut sevm, &call_with_value_code(40));
+
+        assert!(success);
+        assert_eq!(returndata[31], 40);
+        assert_eq!(returndata[63], 1);
+        assert_eq!(sevm.get_balance_u64(&[1u8; 20]), 60);
+        assert_eq!(sevm.get_balance_u64(&callee), 40);
+    }
+
+    #[test]
+    fn test_call_forwards_symbolic_calldata() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        // callee: sstore(0, calldataload(0))
+        let callee = [0x22u8; 20];
+        sevm.deploy_contract(
+            callee,
+            Contract::from_hexcode("60003560005500", &ctx).unwrap(),
+        );
+        // calldatacopy(0, 0, 32); pop(call(gas(), callee, 0, 0, 32, 0, 0))
+        let code = format!(
+            "602060006000376000600060206000600073{}5af15000",
+            "22".repeat(20)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(&code, &ctx).unwrap());
+
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::symbolic(
+                &ctx, "arg", 256,
+            )))
+            .unwrap();
+        let (success, _, _, _) = sevm
+            .execute_call_with_calldata(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+        assert!(success);
+
+        // The callee stored the symbolic argument, not a concretized 0
+        let stored = sevm.get_storage(callee, &CbseBitVec::from_u64(0, 256));
+        assert!(stored.as_u64().is_err());
+    }
+
+    #[test]
+    fn test_value_transfer_is_not_seen_by_sibling_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+        let callee = [0x22u8; 20];
+        sevm.deploy_contract(callee, Contract::from_hexcode("00", &ctx).unwrap());
+        sevm.set_balance(target, 100u64);
+
+        // if calldataload(0) { pop(call(gas(), callee, 40, 0, 0, 0, 0)) }
+        // else { return(selfbalance()) }
+        let code = format!(
+            "600035600f57{}5b{}73{}5af15000",
+            "4760005260206000f3",
+            "60006000600060006028",
+            "22".repeat(20)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(&code, &ctx).unwrap());
+
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::symbolic(
+                &ctx, "cond", 256,
+            )))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+        assert_eq!(paths.len(), 2);
+
+        // Whichever ran first, the returning path sees the undebited balance
+        let returned = paths
+            .iter()
+            .find(|path| !path.return_data.is_empty())
+            .unwrap();
+        assert_eq!(returned.return_data[31], 100);
+    }
+
+    #[test]
+    fn test_call_value_above_u64_reaches_callee() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // callee: return(callvalue())
+        let callee = [0x22u8; 20];
+        sevm.deploy_contract(
+            callee,
+            Contract::from_hexcode("3460005260206000f3", &ctx).unwrap(),
+        );
+        let value = BigUint::from(1u32) << 64;
+        sevm.set_balance([1u8; 20], value.clone() * 2u32);
+
+        // call(0xffff, callee, 2**64, 0, 0, 0, 32); return(0, 32)
+        let code = format!(
+            "60206000600060006801000000000000000073{}61fffff15060206000f3",
+            "22".repeat(20)
+        );
+        let (success, returndata, _, _) = run_code(&mut sevm, &code);
+
+        assert!(success);
+        assert_eq!(BigUint::from_bytes_be(&returndata), value);
+        assert_eq!(sevm.get_balance(callee), value);
+    }
+
+    #[test]
+    fn test_call_forwards_at_most_63_64ths_of_remaining_gas() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // callee: return(gas())
+        let callee = [0x22u8; 20];
+        sevm.deploy_contract(
+            callee,
+            Contract::from_hexcode("5a60005260206000f3", &ctx).unwrap(),
+        );
+
+        // call(not(0), callee, 0, 0, 0, 0, 32); return(0, 32)
+        let code = format!(
+            "6020600060006000600073{}600019f15060206000f3",
+            "22".repeat(20)
+        );
+        let (success, returndata, _, _) = run_code(&mut sevm, &code);
+
+        assert!(success);
+        let forwarded = u64::from_be_bytes(returndata[24..32].try_into().unwrap());
+        assert!(forwarded > 0);
+        assert!(forwarded <= 1_000_000 - 1_000_000 / 64);
+    }
+
+    #[test]
+    fn test_underfunded_call_fails() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let callee = [0x22u8; 20];
+        let code = "4760005260206000f3";
+        sevm.deploy_contract(callee, Contract::from_hexcode(code, &ctx).unwrap());
+        sevm.set_balance([1u8; 20], 10u64);
+
+        let (success, returndata, _, context) = run_code(&mut sevm, &call_with_value_code(40));
+
+        assert!(success);
+        assert_eq!(returndata[63], 0);
+        assert_eq!(sevm.get_balance_u64(&[1u8; 20]), 10);
+        assert_eq!(sevm.get_balance_u64(&callee), 0);
+        // The callee never ran
+        assert!(context.trace.is_empty());
+    }
+
+    #[test]
+    fn test_reverted_call_returns_value() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // callee: revert(0, 0)
+        let callee = [0x22u8; 20];
+        sevm.deploy_contract(callee, Contract::from_hexcode("60006000fd", &ctx).unwrap());
+        sevm.set_balance([1u8; 20], 100u64);
+
+        let (success, returndata, _, _) = run_code(&mut sevm, &call_with_value_code(40));
+
+        assert!(success);
+        assert_eq!(returndata[63], 0);
+        assert_eq!(sevm.get_balance_u64(&[1u8; 20]), 100);
+        assert_eq!(sevm.get_balance_u64(&callee), 0);
+    }
+
+    /// vm.deal(who, amount) calldata
+    fn deal_calldata<'ctx>(ctx: &'ctx Context, who: [u8; 20], amount: &BigUint) -> ByteVec<'ctx> {
+        let mut data = hevm_cheat_code::DEAL.to_be_bytes().to_vec();
+        data.extend([0u8; 12]);
+        data.extend(who);
+        data.extend(CbseBitVec::from_biguint(amount.clone(), 256).to_bytes());
+        ByteVec::from_bytes(data, ctx).unwrap()
+    }
+
+    fn thousand_ether() -> BigUint {
+        BigUint::from(1000u32) * BigUint::from(10u32).pow(18)
+    }
+
+    #[test]
+    fn test_chain_id_cheatcode_overrides_chainid() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        // return(chainid())
+        let code = "4660005260206000f3";
+
+        let (_, returndata, _, _) = run_code(&mut sevm, code);
+        assert_eq!(returndata[31], 1);
+
+        // vm.chainId(42)
+        let mut data = hevm_cheat_code::CHAINID.to_be_bytes().to_vec();
+        data.extend(CbseBitVec::from_u64(42, 256).to_bytes());
+        let mut state = cheatcode_state(&sevm);
+        sevm.handle_cheatcode(&mut state, &ByteVec::from_bytes(data, &ctx).unwrap())
+            .unwrap();
+
+        let (_, returndata, _, _) = run_code(&mut sevm, code);
+        assert_eq!(returndata[31], 42);
+        assert_eq!(sevm.block.chainid, 42);
+    }
+
+    #[test]
+    fn test_deal_balances_beyond_u64() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let callee = [0x22u8; 20];
+        let code = "4760005260206000f3";
+        sevm.deploy_contract(callee, Contract::from_hexcode(code, &ctx).unwrap());
+        let mut state = cheatcode_state(&sevm);
+        for who in [[1u8; 20], callee] {
+            sevm.handle_cheatcode(&mut state, &deal_calldata(&ctx, who, &thousand_ether()))
+                .unwrap();
+        }
+
+        let (success, returndata, _, _) = run_code(&mut sevm, &call_with_value_code(40));
+
+        assert!(success);
+        assert_eq!(returndata[63], 1);
+        // The callee sees its dealt balance plus the value, well past u64
+        let callee_balance = thousand_ether() + 40u32;
+        assert_eq!(
+            BigUint::from_bytes_be(&returndata[..32]),
+            callee_balance.clone()
+        );
+        assert_eq!(sevm.get_balance(&[1u8; 20]), thousand_ether() - 40u32);
+        assert_eq!(sevm.get_balance(&callee), callee_balance);
+
+        let total = sevm.get_balance(&[1u8; 20]) + sevm.get_balance(&callee);
+        assert_eq!(total, thousand_ether() * 2u32);
+        assert!(total > BigUint::from(u64::MAX));
+        assert_eq!(sevm.get_balance_u64(&callee), u64::MAX);
+    }
+
+    #[test]
+    fn test_balance_opcode_reads_wide_balance() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let account = [0x33u8; 20];
+        sevm.set_balance(account, thousand_ether() * 20u32);
+
+        // return(balance(0x33..33))
+        let code = format!("73{}3160005260206000f3", "33".repeat(20));
+        let (success, returndata, _, _) = run_code(&mut sevm, &code);
+
+        assert!(success);
+        assert_eq!(
+            BigUint::from_bytes_be(&returndata),
+            thousand_ether() * 20u32
+        );
+    }
+
+    #[test]
+    fn test_sstore_refund_is_capped() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // sstore(0, 1) in one transaction, then sstore(0, 0) in the next
+        run_code(&mut sevm, "6001600055");
+        let (success, _, gas_used, _) = run_code(&mut sevm, "6000600055");
+
+        // The 4800 refund is capped at a fifth of the 2100 spent on SSTORE
+        assert!(success);
+        assert_eq!(gas_used, 2100 - 2100 / 5);
+    }
+
+    #[test]
+    fn test_reverted_transaction_gets_no_refund() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        run_code(&mut sevm, "6001600055");
+        // sstore(0, 0); revert(0, 0)
+        let (success, _, gas_used, _) = run_code(&mut sevm, "600060005560006000fd");
+
+        assert!(!success);
+        assert_eq!(gas_used, 2100);
+    }
+
+    #[test]
+    fn test_staticcall_charges_cold_account_once() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // Two staticcalls to `callee`, the caller (already warm) or a fresh account
+        let gas_used = |callee: &str| {
+            let mut sevm = SEVM::new(&ctx);
+            let call = format!("600060006000600073{}61fffffa50", callee.repeat(20));
+            let (success, _, gas_used, _) = run_code(&mut sevm, &format!("{call}{call}00"));
+            assert!(success);
+            gas_used
+        };
+
+        assert_eq!(gas_used("cc") - gas_used("02"), 2600 - 100);
+    }
+
+    /// Run `hexcode` as a top-level call and return (success, returndata, gas_used, context)
+    fn run_code<'ctx>(sevm: &mut SEVM<'ctx>, hexcode: &str) -> (bool, Vec<u8>, u64, CallContext) {
+        let target = [1u8; 20];
+        sevm.deploy_contract(target, Contract::from_hexcode(hexcode, sevm.ctx).unwrap());
+        sevm.execute_call(
+            target,
+            [2u8; 20],
+            [2u8; 20],
+            BigUint::default(),
+            Vec::new(),
+            1_000_000,
+            false,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_message_builder_derives_call_context() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        let data = ByteVec::from_bytes(vec![0xde, 0xad, 0xbe, 0xef], &ctx).unwrap();
+        let (message, context) = MessageBuilder::new(&ctx)
+            .target([0xaau8; 20])
+            .caller([0xbbu8; 20])
+            .origin([0xccu8; 20])
+            .value(BigUint::from(7u32))
+            .data(data)
+            .gas(50_000)
+            .is_static(true)
+            .call_scheme(0xFA)
+            .build()
+            .unwrap();
+
+        assert_eq!(message.target, [0xaau8; 20]);
+        assert_eq!(message.origin, [0xccu8; 20]);
+        assert_eq!(message.value.as_u64().unwrap(), 7);
+        assert_eq!(message.gas, 50_000);
+
+        assert_eq!(context.message.target, SEVM::address_to_u64(&[0xaau8; 20]));
+        assert_eq!(context.message.caller, SEVM::address_to_u64(&[0xbbu8; 20]));
+        assert_eq!(context.message.value, 7);
+        assert_eq!(context.message.data, vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(context.message.call_scheme, 0xFA);
+        assert!(context.message.is_static);
+        assert!(context.is_stuck());
+        assert_eq!(context.depth, 0);
+    }
+
+    #[test]
+    fn test_call_past_depth_limit_fails_and_continues() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.max_call_depth = 8;
+
+        // sstore(0, sload(0) + 1); pop(call(gas(), address(), 0, 0, 0, 0, 0)); stop
+        let code = format!("600160005401600055{}305af15000", "6000".repeat(5));
+        let (success, _, _, context) = run_code(&mut sevm, &code);
+
+        // Frames at depths 1 through 8 each bump the counter; the call made
+        // from depth 8 fails and that frame still stops normally
+        assert!(success);
+        assert_eq!(context.depth, 1);
+        assert_eq!(context.output.error, None);
+        let slot = CbseBitVec::from_u64(0, 256);
+        assert_eq!(sevm.get_storage([1u8; 20], &slot).as_u64().unwrap(), 8);
+    }
+
+    /// Calls 0xbeef with the 4-byte calldata `selector` and stores the first
+    /// returned word in slot 0
+    fn call_beef_code(selector: &str) -> String {
+        format!("63{}60e01b600052", selector) // mstore(0, selector << 224)
+            + "6020602060046000600061beef5af150" // pop(call(gas(), 0xbeef, 0, 0, 4, 32, 32))
+            + "60205160005500" // sstore(0, mload(32)); stop
+    }
+
+    /// vm.mockCall(0xbeef, 0x12345678, abi.encode(42))
+    fn mock_beef_calldata<'ctx>(ctx: &'ctx Context) -> ByteVec<'ctx> {
+        let word = |v: u64| CbseBitVec::from_u64(v, 256).to_bytes();
+        let mut data = hevm_cheat_code::MOCK_CALL.to_be_bytes().to_vec();
+        data.extend(word(0xbeef));
+        data.extend(word(0x60));
+        data.extend(word(0xa0));
+        data.extend(word(4));
+        data.extend([0x12, 0x34, 0x56, 0x78]);
+        data.extend([0u8; 28]);
+        data.extend(word(32));
+        data.extend(word(42));
+        ByteVec::from_bytes(data, ctx).unwrap()
+    }
+
+    #[test]
+    fn test_mocked_call_returns_canned_bytes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+        sevm.handle_cheatcode(&mut state, &mock_beef_calldata(&ctx))
+            .unwrap();
+
+        // Nothing is deployed at 0xbeef, so only the mock can return 42
+        let (success, _, _, context) = run_code(&mut sevm, &call_beef_code("12345678"));
+
+        assert!(success);
+        let slot = CbseBitVec::from_u64(0, 256);
+        assert_eq!(sevm.get_storage([1u8; 20], &slot).as_u64().unwrap(), 42);
+        match &context.trace[..] {
+            [TraceElement::Call(call), ..] => {
+                assert_eq!(call.message.data, vec![0x12, 0x34, 0x56, 0x78]);
+                assert_eq!(
+                    call.output.data,
+                    Some(CbseBitVec::from_u64(42, 256).to_bytes())
+                );
+            }
+            _ => panic!("expected the mocked call in the trace"),
+        }
+    }
+
+    #[test]
+    fn test_non_matching_call_is_not_mocked() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+        sevm.handle_cheatcode(&mut state, &mock_beef_calldata(&ctx))
+            .unwrap();
+
+        let (success, _, _, _) = run_code(&mut sevm, &call_beef_code("87654321"));
+
+        assert!(success);
+        let slot = CbseBitVec::from_u64(0, 256);
+        assert_eq!(sevm.get_storage([1u8; 20], &slot).as_u64().unwrap(), 0);
+    }
+
+    /// Calls 0xdead, which has no code, with the 4-byte calldata `selector` and
+    /// a 64-byte return buffer. Stores the success flag in slot 0 and the two
+    /// returned words in slots 1 and 2.
+    fn call_unknown_code(selector: &str) -> String {
+        format!("63{}60e01b600052", selector) // mstore(0, selector << 224)
+            + "6040602060046000600061dead5af1" // call(gas(), 0xdead, 0, 0, 4, 32, 64)
+            + "600055" // sstore(0, success)
+            + "602051600155" // sstore(1, mload(32))
+            + "60405160025500" // sstore(2, mload(64)); stop
+    }
+
+    #[test]
+    fn test_trace_filter_records_only_configured_events() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut config = cbse_config::Config::default();
+        config.trace_events = Some("SSTORE".to_string());
+        sevm.trace_filter = trace_filter_from_config(&config).unwrap();
+
+        // PUSH1 1 PUSH1 0 SSTORE; PUSH1 0 SLOAD POP; PUSH1 0 PUSH1 0 LOG0; STOP
+        let (success, _, _, context) = run_code(&mut sevm, "60016000556000545060006000a000");
+        assert!(success);
+        match &context.trace[..] {
+            [TraceElement::Write(write)] => assert_eq!(write.slot, vec![0u8; 32]),
+            other => panic!("expected only the SSTORE, got {:?}", other),
+        }
+
+        // All events are recorded by default
+        let mut sevm = SEVM::new(&ctx);
+        let (_, _, _, context) = run_code(&mut sevm, "60016000556000545060006000a000");
+        assert_eq!(context.trace.len(), 3);
+    }
+
+    #[test]
+    fn test_sstore_trace_records_full_slot_and_value() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut config = cbse_config::Config::default();
+        config.trace_events = Some("SSTORE".to_string());
+        sevm.trace_filter = trace_filter_from_config(&config).unwrap();
+
+        // sstore(0xaa << 248, 0xbb << 248); stop
+        let code = "60bb60f81b60aa60f81b5500";
+        let (success, _, _, context) = run_code(&mut sevm, code);
+        assert!(success);
+        let word = |top: u8| {
+            let mut word = vec![0u8; 32];
+            word[0] = top;
+            word
+        };
+        match &context.trace[..] {
+            [TraceElement::Write(write)] => {
+                assert_eq!(write.slot, word(0xaa));
+                assert_eq!(write.value, word(0xbb));
+            }
+            other => panic!("expected the SSTORE, got {:?}", other),
+        }
+
+        // Nothing is recorded once SSTORE is filtered out
+        config.trace_events = Some("LOG".to_string());
+        let mut sevm = SEVM::new(&ctx);
+        sevm.trace_filter = trace_filter_from_config(&config).unwrap();
+        let (success, _, _, context) = run_code(&mut sevm, code);
+        assert!(success);
+        assert!(context.trace.is_empty());
+    }
+
+    #[test]
+    fn test_listed_unknown_call_returns_symbolic_data() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.unknown_calls = UnknownCalls::new(vec![0x150b7a02], 32);
+
+        let (success, _, _, _) = run_code(&mut sevm, &call_unknown_code("150b7a02"));
+
+        assert!(success);
+        let mut slot = |n: u64| sevm.get_storage([1u8; 20], &CbseBitVec::from_u64(n, 256));
+        assert!(slot(0).is_symbolic());
+        assert!(slot(1).is_symbolic());
+        // Only return_size_of_unknown_calls bytes are returned
+        assert_eq!(slot(2).as_u64().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_unlisted_unknown_call_fails_with_empty_data() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.unknown_calls = UnknownCalls::new(vec![0x150b7a02], 32);
+
+        let (success, _, _, _) = run_code(&mut sevm, &call_unknown_code("12345678"));
+
+        assert!(success);
+        let mut slot = |n: u64| sevm.get_storage([1u8; 20], &CbseBitVec::from_u64(n, 256));
+        assert_eq!(slot(0).as_u64().unwrap(), 0);
+        assert_eq!(slot(1).as_u64().unwrap(), 0);
+    }
+
+    /// Calls vm.expectEmit(), emits LOG1 with a 0xdd..dd topic and then calls
+    /// the contract at [3u8; 20]
+    fn expect_emit_code() -> String {
+        let address = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>()
+        };
+        [
+            // mstore(0, expectEmit.selector << 224)
+            "63440ed10d60e01b600052".to_string(),
+            // pop(call(gas(), HEVM_ADDRESS, 0, 0, 4, 0, 0))
+            format!(
+                "6000600060046000600073{}5af150",
+                address(&cbse_cheatcodes::HEVM_ADDRESS)
+            ),
+            // log1(0, 0, 0xdd..dd)
+            format!("7f{}60006000a1", "dd".repeat(32)),
+            // pop(call(gas(), 0x0303..03, 0, 0, 0, 0, 0)); stop
+            format!("6000600060006000600073{}5af15000", address(&[3u8; 20])),
+        ]
+        .concat()
+    }
+
+    fn run_expect_emit<'ctx>(sevm: &mut SEVM<'ctx>, emitter_code: &str) -> (bool, CallContext) {
+        let emitter = Contract::from_hexcode(emitter_code, sevm.ctx).unwrap();
+        sevm.deploy_contract([3u8; 20], emitter);
+        let (success, _, _, context) = run_code(sevm, &expect_emit_code());
+        (success, context)
+    }
+
+    #[test]
+    fn test_expect_emit_passes_on_matching_log() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // log1(0, 0, 0xdd..dd)
+        let code = format!("7f{}60006000a100", "dd".repeat(32));
+        let (success, context) = run_expect_emit(&mut sevm, &code);
+
+        assert!(success);
+        assert_eq!(context.output.error, None);
+        // The template log is not recorded as an emit of the test contract
+        assert!(!context
+            .trace
+            .iter()
+            .any(|element| matches!(element, TraceElement::Log(_))));
+    }
+
+    #[test]
+    fn test_expect_emit_fails_on_missing_or_mismatched_log() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let (success, context) = run_expect_emit(&mut sevm, "00");
+        assert!(!success);
+        assert_eq!(context.output.error.as_deref(), Some("log != expected log"));
+
+        // log1(0, 0, 0xee..ee)
+        let code = format!("7f{}60006000a100", "ee".repeat(32));
+        let (success, context) = run_expect_emit(&mut sevm, &code);
+        assert!(!success);
+        assert_eq!(context.output.error.as_deref(), Some("log != expected log"));
+    }
+
+    #[test]
+    fn test_return_outputs_memory_slice() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // mstore(0, 42); return(0, 32)
+        let (success, returndata, _, context) = run_code(&mut sevm, "602a60005260206000f3");
+
+        let mut expected = vec![0u8; 32];
+        expected[31] = 42;
+        assert!(success);
+        assert_eq!(returndata, expected);
+        assert_eq!(context.output.data, Some(expected));
+        assert_eq!(context.output.return_scheme, Some(0xF3));
+        assert_eq!(context.output.error, None);
+    }
+
+    #[test]
+    fn test_revert_fails_with_reason_bytes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // mstore(0, 0xdeadbeef << 224); revert(0, 4)
+        let code = format!("7fdeadbeef{}60005260046000fd", "00".repeat(28));
+        let (success, returndata, _, context) = run_code(&mut sevm, &code);
+
+        assert!(!success);
+        assert_eq!(returndata, vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(context.output.return_scheme, Some(0xFD));
+        assert_eq!(context.output.error, None);
+    }
+
+    #[test]
+    fn test_stop_succeeds_with_empty_output() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let (success, returndata, _, context) = run_code(&mut sevm, "602a00");
+
+        assert!(success);
+        assert!(returndata.is_empty());
+        assert_eq!(context.output.return_scheme, Some(0x00));
+    }
+
+    #[test]
+    fn test_invalid_consumes_all_gas() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let (success, returndata, gas_used, context) = run_code(&mut sevm, "fe");
+
+        assert!(!success);
+        assert!(returndata.is_empty());
+        assert_eq!(gas_used, 1_000_000);
+        assert!(context.output.error.is_some());
+    }
+
+    #[test]
+    fn test_access_cost_out_of_gas_halts_the_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        // PUSH1 0; SLOAD; STOP - the cold slot access costs 2100
+        sevm.deploy_contract(target, Contract::from_hexcode("60005400", &ctx).unwrap());
+        let (success, _, gas_used, context) = sevm
+            .execute_call(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                Vec::new(),
+                2_000,
+                false,
+            )
+            .unwrap();
+
+        assert!(!success);
+        assert_eq!(gas_used, 2_000);
+        assert!(context.output.error.is_some());
+    }
+
+    #[test]
+    fn test_sha3_hashes_concrete_memory() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // mstore(0, keccak256(0, 32)); return(0, 32)
+        let (success, returndata, _, _) = run_code(&mut sevm, "602060002060005260206000f3");
+
+        assert!(success);
+        assert_eq!(returndata, cbse_hashes::keccak256(&[0u8; 32]).to_vec());
+        assert_eq!(sevm.keccak_registry.len(), 1);
+    }
+
+    #[test]
+    fn test_mstore_past_memory_limit_halts() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // mstore(1 << 62, 42): must halt rather than allocate 4 EiB
+        let (success, returndata, gas_used, context) =
+            run_code(&mut sevm, "602a6740000000000000005200");
+
+        assert!(!success);
+        assert!(returndata.is_empty());
+        assert_eq!(gas_used, 1_000_000);
+        assert_eq!(
+            context.output.error,
+            Some(ExceptionalHalt::OutOfGas.to_string())
+        );
+    }
+
+    #[test]
+    fn test_stack_underflow_halts_the_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // ADD and POP on an empty stack
+        for code in ["01", "50"] {
+            let (success, returndata, gas_used, context) = run_code(&mut sevm, code);
+
+            assert!(!success, "{}", code);
+            assert!(returndata.is_empty());
+            assert_eq!(gas_used, 1_000_000);
+            assert_eq!(
+                context.output.error,
+                Some(ExceptionalHalt::StackUnderflow.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_stack_overflow_halts_the_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // 1024 pushes fill the stack; the 1025th overflows before the final SSTORE
+        let code = format!("{}55", "5f".repeat(STACK_LIMIT + 1));
+        let (success, returndata, gas_used, context) = run_code(&mut sevm, &code);
+
+        assert!(!success);
+        assert!(returndata.is_empty());
+        assert_eq!(gas_used, 1_000_000);
+        assert_eq!(
+            context.output.error,
+            Some(ExceptionalHalt::StackOverflow.to_string())
+        );
+
+        // A full stack on its own is fine
+        let code = "5f".repeat(STACK_LIMIT);
+        let (success, _, _, context) = run_code(&mut sevm, &code);
+        assert!(success);
+        assert_eq!(context.output.error, None);
+    }
+
+    #[test]
+    fn test_nested_creates_deploy_returned_code() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // inner init code: mstore8(0, 0x2a); return(0, 1)
+        let inner = "602a60005360016000f3";
+        // middle init code: sstore(0, create(0, 22, 10, inner)); return(0, 1)
+        let middle = format!("69{}600052600a60166000f060005560016000f3", inner);
+        // outer: return(create(0, 3, 29, middle))
+        let outer = format!("7c{}600052601d60036000f060005260206000f3", middle);
+
+        let (success, returndata, _, context) = run_code(&mut sevm, &outer);
+        assert!(success);
+
+        let middle_addr: [u8; 20] = returndata[12..32].try_into().unwrap();
+        let inner_addr = sevm
+            .get_storage(middle_addr, &CbseBitVec::from_u64(0, 256))
+            .as_address()
+            .unwrap();
+        assert_ne!(middle_addr, [0u8; 20]);
+        assert_ne!(inner_addr, [0u8; 20]);
+
+        // Each address holds the code its init code returned
+        let middle_code = &sevm.contracts[&middle_addr.into()];
+        assert_eq!(middle_code.len(), 1);
+        assert_eq!(middle_code.get_byte(0).unwrap(), 0x00);
+        let inner_code = &sevm.contracts[&inner_addr.into()];
+        assert_eq!(inner_code.len(), 1);
+        assert_eq!(inner_code.get_byte(0).unwrap(), 0x2a);
+
+        // Both creations are traced under the outer call
+        let [TraceElement::Call(middle_call)] = &context.trace[..] else {
+            panic!("expected one CREATE in the trace");
+        };
+        assert!(middle_call.message.is_create());
+        assert_eq!(middle_call.depth, 2);
+        assert!(matches!(
+            &middle_call.trace[..],
+            [TraceElement::Call(inner_call), ..] if inner_call.depth == 3
+        ));
+    }
+
+    #[test]
+    fn test_straight_line_steps_after_symbolic_branch_skip_solver() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+
+        // jumpi(dest, calldataload(0)), then `steps` straight-line units of
+        // symbolic arithmetic on each side before stopping
+        let solver_calls = |steps: usize| {
+            let unit = "60003560010150".repeat(steps); // pop(calldataload(0) + 1)
+            let dest = 7 + unit.len() / 2;
+            let code = format!("60003560{:02x}57{}005b{}00", dest, unit, unit);
+
+            let mut sevm = SEVM::new(&ctx);
+            let target = [1u8; 20];
+            sevm.deploy_contract(target, Contract::from_hexcode(&code, &ctx).unwrap());
+            let cond = CbseBitVec::symbolic(&ctx, "cond", 256);
+            let mut calldata = ByteVec::new(&ctx);
+            calldata.append(UnwrappedBytes::BitVec(cond)).unwrap();
+            let paths = sevm
+                .explore(
+                    target,
+                    [2u8; 20],
+                    [2u8; 20],
+                    BigUint::default(),
+                    calldata,
+                    1_000_000,
+                    false,
+                )
+                .unwrap();
+            assert_eq!(paths.len(), 2);
+            sevm.run_stats().solver.calls
+        };
+
+        // The branch is checked once; the steps after it reuse that result
+        let branch_only = solver_calls(0);
+        assert!(branch_only > 0);
+        assert_eq!(solver_calls(10), branch_only);
+    }
+
+    #[test]
+    fn test_create_keeps_storage_of_deployed_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // init code: if coinbase() { sstore(1, 1); return(0, 1) } sstore(2, 2); return(0, 2)
+        let init = "41600e57600260025560026000f35b600160015560016000f3";
+        // outer: return(create(0, 7, 25, init))
+        let outer = format!("78{}600052601960076000f060005260206000f3", init);
+
+        let (success, returndata, _, _) = run_code(&mut sevm, &outer);
+        assert!(success);
+
+        // Whichever constructor path was deployed, its writes are the ones kept
+        let created: [u8; 20] = returndata[12..32].try_into().unwrap();
+        let slot = |n: u64| {
+            sevm.get_storage(created, &CbseBitVec::from_u64(n, 256))
+                .as_u64()
+                .unwrap()
+        };
+        match sevm.contracts[&created.into()].len() {
+            1 => assert_eq!((slot(1), slot(2)), (1, 0)),
+            2 => assert_eq!((slot(1), slot(2)), (0, 2)),
+            len => panic!("unexpected code length {}", len),
+        }
+    }
+
+    #[test]
+    fn test_create_rejects_returned_code_over_size_limit() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // init code: return(0, 0x6001); outer: return(create(0, 26, 6, init))
+        let outer = "656160016000f36000526006601a6000f060005260206000f3";
+        let (success, returndata, _, _) = run_code(&mut sevm, outer);
+        assert!(success);
+        assert_eq!(returndata, vec![0u8; 32]);
+
+        sevm.max_code_size = 0x6001;
+        let (_, returndata, _, _) = run_code(&mut sevm, outer);
+        assert_ne!(returndata, vec![0u8; 32]);
+    }
+
+    #[test]
+    fn test_max_memory_size_is_configurable() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.max_memory_size = 64;
+
+        // mstore(0x20, 42) ends exactly at the limit
+        let (success, _, _, _) = run_code(&mut sevm, "602a60205200");
+        assert!(success);
+
+        // mstore(0x21, 42) would expand memory to 96 bytes
+        let (success, _, _, _) = run_code(&mut sevm, "602a60215200");
+        assert!(!success);
+    }
+
+    #[test]
+    fn test_address_biguint_roundtrip() {
+        let mut low = [0u8; 20];
+        low[19] = 0x01;
+        let mut high = low;
+        high[0] = 0xff;
+
+        // Both addresses share their low 8 bytes, so the trace form aliases them
+        assert_eq!(SEVM::address_to_u64(&low), SEVM::address_to_u64(&high));
+        assert_ne!(
+            SEVM::address_to_biguint(&low),
+            SEVM::address_to_biguint(&high)
+        );
+
+        for addr in [low, high, [0u8; 20], [0xffu8; 20]] {
+            assert_eq!(
+                SEVM::biguint_to_address(&SEVM::address_to_biguint(&addr)),
+                addr
+            );
+        }
+
+        // Values wider than 160 bits are truncated
+        let wide = SEVM::address_to_biguint(&high) + (BigUint::from(1u8) << 200u32);
+        assert_eq!(SEVM::biguint_to_address(&wide), high);
+    }
+
+    #[test]
+    fn test_random_cheatcode_returns_fresh_symbols() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        let calldata =
+            ByteVec::from_bytes(hevm_cheat_code::RANDOM_UINT.to_be_bytes().to_vec(), &ctx).unwrap();
+        let mut words = Vec::new();
+        for _ in 0..2 {
+            let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+            match result.get_word(0).unwrap() {
+                UnwrappedBytes::BitVec(bv) => words.push(bv.as_z3(&ctx)),
+                UnwrappedBytes::Bytes(_) => panic!("expected a symbolic result"),
+            }
+        }
+        let (a, b) = (&words[0], &words[1]);
+        assert_ne!(a.to_string(), b.to_string());
+
+        // Both symbols are unconstrained, so they can differ
+        sevm.solver.assert(&a._eq(b).not());
+        assert_eq!(sevm.solver.check(), z3::SatResult::Sat);
+    }
+
+    #[test]
+    fn test_create_uint256_same_name_not_aliased() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        // svm.createUint256("x")
+        let mut calldata = halmos_cheat_code::CREATE_UINT256.to_be_bytes().to_vec();
+        calldata.extend_from_slice(&CbseBitVec::from_u64(0x20, 256).to_bytes());
+        calldata.extend_from_slice(&CbseBitVec::from_u64(1, 256).to_bytes());
+        let mut name = vec![0u8; 32];
+        name[0] = b'x';
+        calldata.extend_from_slice(&name);
+        let calldata = ByteVec::from_bytes(calldata, &ctx).unwrap();
+
+        let mut words = Vec::new();
+        for _ in 0..2 {
+            let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+            match result.get_word(0).unwrap() {
+                UnwrappedBytes::BitVec(bv) => words.push(bv.as_z3(&ctx)),
+                UnwrappedBytes::Bytes(_) => panic!("expected a symbolic result"),
+            }
+        }
+        assert_eq!(sevm.symbols.current(), 2);
+
+        let (a, b) = (&words[0], &words[1]);
+        sevm.solver.assert(&a._eq(b).not());
+        assert_eq!(sevm.solver.check(), z3::SatResult::Sat);
+    }
+
+    #[test]
+    fn test_explore_returns_every_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        // if calldataload(0) == 0 { sstore(0, 1) }; stop
+        let code = "600035600c576001600055005b00";
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let cond = z3::ast::BV::new_const(&ctx, "cond", 256);
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(cond.clone())))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().all(|path| path.success));
+        let (written, skipped): (Vec<_>, Vec<_>) =
+            paths.iter().partition(|path| !path.storage_diff.is_empty());
+        assert_eq!(written.len(), 1);
+        assert_eq!(written[0].storage_diff[0].slot, vec![0u8; 32]);
+        let mut one = vec![0u8; 32];
+        one[31] = 1;
+        assert_eq!(written[0].storage_diff[0].value, one);
+
+        // The storing path requires cond == 0, the other cond != 0
+        let zero = z3::ast::BV::from_u64(&ctx, 0, 256);
+        let implies = |path: &PathResult, claim: z3::ast::Bool| {
+            let solver = Solver::new(&ctx);
+            for constraint in &path.constraints {
+                solver.assert(constraint);
+            }
+            solver.assert(&claim.not());
+            solver.check() == z3::SatResult::Unsat
+        };
+        assert!(implies(written[0], cond._eq(&zero)));
+        assert!(implies(skipped[0], cond._eq(&zero).not()));
+    }
+
+    #[test]
+    fn test_branch_writes_do_not_leak_into_sibling() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        let code = concat!(
+            "600035600e57",                     // jumpi(0x0e, calldataload(0))
+            "6001600155601456",                 // sstore(1, 1); jump(0x14)
+            "5b6001600055",                     // 0x0e: sstore(0, 1)
+            "5b6000546001540160005260206000f3", // 0x14: return sload(0) + sload(1)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let cond = z3::ast::BV::new_const(&ctx, "cond", 256);
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(cond)))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        // Each path sees only its own write
+        assert_eq!(paths.len(), 2);
+        for path in &paths {
+            assert!(path.success);
+            assert_eq!(path.return_data[31], 1);
+            assert_eq!(path.storage_diff.len(), 1);
+        }
+
+        // The SEVM keeps the storage of the first path
+        let first_slot = paths[0].storage_diff[0].slot[31] as u64;
+        let read = |sevm: &mut SEVM<'_>, slot| {
+            let slot = CbseBitVec::from_u64(slot, 256);
+            sevm.get_storage(target, &slot).as_u64().unwrap()
+        };
+        assert_eq!(read(&mut sevm, first_slot), 1);
+        assert_eq!(read(&mut sevm, 1 - first_slot), 0);
+    }
+
+    #[test]
+    fn test_selfdestruct_on_sibling_branch_is_not_applied() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        let code = concat!(
+            "600035600957", // jumpi(0x09, calldataload(0))
+            "6000ff",       // selfdestruct(0)
+            "5b60206000f3", // 0x09: return(0, 32)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let cond = z3::ast::BV::new_const(&ctx, "cond", 256);
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(cond)))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        // Only the path the SEVM keeps decides whether the contract survives
+        assert_eq!(paths.len(), 2);
+        let kept_returned = !paths[0].return_data.is_empty();
+        assert_eq!(sevm.contracts.contains_key(&target), kept_returned);
+    }
+
+    #[test]
+    fn test_branches_share_storage_until_written() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+        let key = Address::from(target);
+        let mut conditions = Vec::new();
+        for i in 0..100 {
+            let (slot, value) = (
+                CbseBitVec::from_u64(i, 256),
+                CbseBitVec::from_u64(i + 1, 256),
+            );
+            sevm.set_storage(target, slot, value, &mut conditions)
+                .unwrap();
+        }
+
+        let state = cheatcode_state(&sevm);
+        let cond = z3::ast::Bool::new_const(&ctx, "c");
+        let branches: Vec<_> = (0..1000)
+            .map(|_| sevm.create_branch(&state, cond.clone(), 0).unwrap())
+            .collect();
+        assert!(branches
+            .iter()
+            .all(|branch| branch.storage.shares(&sevm.storage, &key)));
+
+        // A write on one branch copies its storage, leaving the others shared
+        sevm.storage = branches[0].storage.clone();
+        let slot = CbseBitVec::from_u64(0, 256);
+        sevm.set_storage(
+            target,
+            slot.clone(),
+            CbseBitVec::from_u64(42, 256),
+            &mut conditions,
+        )
+        .unwrap();
+        assert!(!sevm.storage.shares(&branches[1].storage, &key));
+        assert!(branches[1].storage.shares(&branches[999].storage, &key));
+        assert_eq!(sevm.get_storage(target, &slot).as_u64().unwrap(), 42);
+
+        sevm.storage = branches[1].storage.clone();
+        assert_eq!(sevm.get_storage(target, &slot).as_u64().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_mapping_entry_with_symbolic_key() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        // slot = keccak256(calldataload(0) . 1); sstore(slot, 42)
+        // mstore(0, sload(keccak256(calldataload(0) . 1))); return(0, 32)
+        let code = "6000356000526001602052602a6040600020556040600020546000526020\
+                    6000f3";
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::symbolic(&ctx, "k", 256)))
+            .unwrap();
+        let (success, returndata, _, _) = sevm
+            .execute_call_with_calldata(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        let mut expected = vec![0u8; 32];
+        expected[31] = 42;
+        assert!(success);
+        assert_eq!(returndata, expected);
+    }
+
+    #[test]
+    fn test_enable_symbolic_storage() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+        let addr = [2u8; 20];
+        let slot = |v: u64| CbseBitVec::from_u64(v, 256);
+
+        // enableSymbolicStorage(address(0x0202..02))
+        let mut data = halmos_cheat_code::SYMBOLIC_STORAGE.to_be_bytes().to_vec();
+        data.extend([0u8; 12]);
+        data.extend(addr);
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+        sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+
+        // Different slots are unconstrained relative to each other
+        let a = sevm.get_storage(addr, &slot(1)).as_z3(&ctx);
+        let b = sevm.get_storage(addr, &slot(2)).as_z3(&ctx);
+        assert_eq!(
+            state.path.check(&a._eq(&b).not()).unwrap(),
+            z3::SatResult::Sat
+        );
+        assert_eq!(state.path.check(&a._eq(&b)).unwrap(), z3::SatResult::Sat);
+
+        // A written slot reads back the written value
+        sevm.set_storage(addr, slot(1), slot(7), &mut Vec::new())
+            .unwrap();
+        let stored = sevm.get_storage(addr, &slot(1)).as_z3(&ctx);
+        assert_eq!(stored.simplify().as_u64(), Some(7));
+
+        // ...and other slots keep their original symbolic values
+        let b_after = sevm.get_storage(addr, &slot(2)).as_z3(&ctx);
+        assert_eq!(
+            state.path.check(&b_after._eq(&b).not()).unwrap(),
+            z3::SatResult::Unsat
+        );
+    }
+
+    #[test]
+    fn test_storage_diff_lists_changed_slots() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let addr = [1u8; 20];
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+
+        sevm.set_storage(addr, word(1), word(5), &mut Vec::new())
+            .unwrap();
+        let handle = sevm.snapshot_storage(addr);
+
+        sevm.set_storage(addr, word(1), word(7), &mut Vec::new())
+            .unwrap();
+        sevm.set_storage(addr, word(2), word(9), &mut Vec::new())
+            .unwrap();
+        // Rewriting a slot with its current value is not a change
+        sevm.set_storage(addr, word(3), word(0), &mut Vec::new())
+            .unwrap();
+
+        let diff = sevm.storage_diff(addr, handle).unwrap();
+        let diff: Vec<(u64, u64, u64)> = diff
+            .iter()
+            .map(|(slot, old, new)| {
+                (
+                    slot.as_u64().unwrap(),
+                    old.as_u64().unwrap(),
+                    new.as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(diff, vec![(1, 5, 7), (2, 0, 9)]);
+        assert!(sevm.storage_diff([2u8; 20], handle).is_err());
+    }
+
+    #[test]
+    fn test_distinct_mapping_entries_do_not_alias() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let addr = [1u8; 20];
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+
+        // m[1] and m[2] of the mapping at slot 0, and m[k] for a symbolic k
+        let (m1, _) = sevm.keccak_registry.sha3(&ctx, &word(1).concat(&word(0)));
+        let (m2, _) = sevm.keccak_registry.sha3(&ctx, &word(2).concat(&word(0)));
+        let k = CbseBitVec::symbolic(&ctx, "k", 256);
+        let (mk, _) = sevm.keccak_registry.sha3(&ctx, &k.concat(&word(0)));
+
+        sevm.set_storage(addr, m1.clone(), word(11), &mut Vec::new())
+            .unwrap();
+        sevm.set_storage(addr, m2.clone(), word(22), &mut Vec::new())
+            .unwrap();
+        assert_eq!(sevm.get_storage(addr, &m1).as_u64().unwrap(), 11);
+        assert_eq!(sevm.get_storage(addr, &m2).as_u64().unwrap(), 22);
+
+        // m[k] shares the array of m[1] and m[2], so it reads m[1] iff k == 1
+        let read = sevm.get_storage(addr, &mk);
+        let solver = z3::Solver::new(&ctx);
+        solver.assert(&k.as_z3(&ctx)._eq(&word(1).as_z3(&ctx)));
+        solver.assert(&read.as_z3(&ctx)._eq(&word(11).as_z3(&ctx)).not());
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_snapshot_and_restore_storage() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+        let (a, b) = ([2u8; 20], [3u8; 20]);
+        let slot = CbseBitVec::from_u64(0, 256);
+        let value = |v: u64| CbseBitVec::from_u64(v, 256);
+
+        sevm.set_storage(a, slot.clone(), value(1), &mut Vec::new())
+            .unwrap();
+        sevm.set_storage(b, slot.clone(), value(1), &mut Vec::new())
+            .unwrap();
+
+        // snapshotStorage(a) returns the handle as a word
+        let mut data = halmos_cheat_code::SNAPSHOT_STORAGE.to_be_bytes().to_vec();
+        data.extend([0u8; 12]);
+        data.extend(a);
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+        let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        assert_eq!(result.len(), 32);
+        let handle = match result.get_word(0).unwrap() {
+            UnwrappedBytes::Bytes(bytes) => bytes[31] as usize,
+            UnwrappedBytes::BitVec(bv) => bv.as_u64().unwrap() as usize,
+        };
+
+        sevm.set_storage(a, slot.clone(), value(2), &mut Vec::new())
+            .unwrap();
+        sevm.set_storage(b, slot.clone(), value(2), &mut Vec::new())
+            .unwrap();
+
+        sevm.restore_storage(handle).unwrap();
+        let restored_a = sevm.get_storage(a, &slot).as_z3(&ctx).simplify();
+        let restored_b = sevm.get_storage(b, &slot).as_z3(&ctx).simplify();
+        assert_eq!(restored_a.as_u64(), Some(1));
+        assert_eq!(restored_b.as_u64(), Some(2));
+        assert!(sevm.restore_storage(handle + 1).is_err());
+    }
+
+    #[test]
+    fn test_get_code_returns_creation_bytecode() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        BuildOut::instance().set_build_out(serde_json::json!({
+            "GetCode.sol": {"GetCodeTarget": {"bytecode": {"object": "0x602a60005260206000f3"}}}
+        }));
+
+        // getCode("GetCode.sol:GetCodeTarget")
+        let path = b"GetCode.sol:GetCodeTarget";
+        let word = |v: u64| CbseBitVec::from_u64(v, 256).to_bytes();
+        let mut data = hevm_cheat_code::GET_CODE.to_be_bytes().to_vec();
+        data.extend(word(32));
+        data.extend(word(path.len() as u64));
+        data.extend(path);
+        data.extend(vec![0u8; 32 - path.len() % 32]);
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        let result = concrete_bytes(&result).unwrap();
+        let code = vec![0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        assert_eq!(result[..32], word(32)[..]);
+        assert_eq!(result[32..64], word(code.len() as u64)[..]);
+        assert_eq!(result[64..64 + code.len()], code[..]);
+    }
+
+    #[test]
+    fn test_create_uint256_min_max_constrains_path() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        // createUint256("x", 10, 20)
+        let word = |v: u64| {
+            let mut w = vec![0u8; 24];
+            w.extend(v.to_be_bytes());
+            w
+        };
+        let mut data = halmos_cheat_code::CREATE_UINT256_MIN_MAX
+            .to_be_bytes()
+            .to_vec();
+        data.extend(word(96));
+        data.extend(word(10));
+        data.extend(word(20));
+        data.extend(word(1));
+        data.push(b'x');
+        data.extend([0u8; 31]);
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        let value = match result.get_word(0).unwrap() {
+            UnwrappedBytes::BitVec(bv) => bv.as_z3(&ctx),
+            UnwrappedBytes::Bytes(_) => panic!("expected a symbolic result"),
+        };
+
+        let eq = |v: u64| value._eq(&z3::ast::BV::from_u64(&ctx, v, 256));
+        assert_eq!(state.path.check(&eq(5)).unwrap(), z3::SatResult::Unsat);
+        assert_eq!(state.path.check(&eq(15)).unwrap(), z3::SatResult::Sat);
+    }
+
+    #[test]
+    fn test_bad_cheatcode_calldata_is_reported_as_abi_decode_error() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        // label(address(0x1234), "Alice") cut off after two bytes of the name
+        let word = |v: u64| {
+            let mut w = vec![0u8; 24];
+            w.extend(v.to_be_bytes());
+            w
+        };
+        let mut data = hevm_cheat_code::LABEL.to_be_bytes().to_vec();
+        data.extend(word(0x1234));
+        data.extend(word(64));
+        data.extend(word(5));
+        data.extend(b"Al");
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        match sevm.handle_cheatcode(&mut state, &calldata) {
+            Err(CbseException::AbiDecode(msg)) => {
+                assert!(msg.starts_with("bad cheatcode calldata"), "{}", msg)
+            }
+            other => panic!("expected an ABI decoding error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_create_bytes_with_symbolic_length_forks_feasible_candidates() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        // createBytes(len, "x") where the path already requires len > 0
+        let len = z3::ast::BV::new_const(&ctx, "len", 256);
+        state
+            .path
+            .append(len.bvugt(&z3::ast::BV::from_u64(&ctx, 0, 256)), false)
+            .unwrap();
+        let word = |v: u64| {
+            let mut w = vec![0u8; 24];
+            w.extend(v.to_be_bytes());
+            w
+        };
+        let mut calldata =
+            ByteVec::from_bytes(halmos_cheat_code::CREATE_BYTES.to_be_bytes().to_vec(), &ctx)
+                .unwrap();
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(len.clone())))
+            .unwrap();
+        let mut tail = word(64);
+        tail.extend(word(1));
+        tail.push(b'x');
+        tail.extend([0u8; 31]);
+        calldata.append(UnwrappedBytes::Bytes(tail)).unwrap();
+
+        // 0 is infeasible, so the 65-byte candidate is taken and 1024 is forked
+        let result = sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        assert_eq!(result.len(), 64 + 96);
+        let eq = |v: u64| len._eq(&z3::ast::BV::from_u64(&ctx, v, 256));
+        assert_eq!(state.path.check(&eq(65)).unwrap(), z3::SatResult::Sat);
+        assert_eq!(state.path.check(&eq(1024)).unwrap(), z3::SatResult::Unsat);
+
+        assert_eq!(sevm.cheatcode_forks.len(), 1);
+        let (fork, fork_result) = &sevm.cheatcode_forks[0];
+        assert_eq!(fork_result.len(), 64 + 1024);
+        assert_eq!(fork.path.check(&eq(1024)).unwrap(), z3::SatResult::Sat);
+        assert_eq!(fork.path.check(&eq(65)).unwrap(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_create_bytes_with_symbolic_length_forks_every_candidate() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        // b = svm.createBytes(calldataload(0), "x"); return b.length
+        let code = concat!(
+            "63eef5311d60e01b600052", // mstore(0, CREATE_BYTES << 224)
+            "600035600452",           // mstore(4, calldataload(0))
+            "6040602452",             // mstore(0x24, 0x40)
+            "6001604452",             // mstore(0x44, 1)
+            // mstore(0x64, "x")
+            "7f7800000000000000000000000000000000000000000000000000000000000000606452",
+            // pop(call(gas(), SVM_ADDRESS, 0, 0, 0x84, 0x100, 0x40))
+            "6040610100608460006000",
+            "73f3993a62377bcd56ae39d773740a5390411e8bc9",
+            "5af150",
+            "61012051600052", // mstore(0, mload(0x120))
+            "60206000f3",     // return(0, 0x20)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let len = z3::ast::BV::new_const(&ctx, "len", 256);
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(len)))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        let mut lengths: Vec<u64> = paths
+            .iter()
+            .map(|path| {
+                assert!(path.success);
+                CbseBitVec::from_bytes(&path.return_data, 256)
+                    .as_u64()
+                    .unwrap()
+            })
+            .collect();
+        lengths.sort();
+        assert_eq!(lengths, vec![0, 65, 1024]);
+    }
+
+    #[test]
+    fn test_assert_eq_with_symbolic_value_finds_counterexample() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        // assertEq(x, 5)
+        let x = z3::ast::BV::new_const(&ctx, "halmos_x_uint256", 256);
+        let mut calldata = ByteVec::from_bytes(
+            hevm_cheat_code::ASSERT_EQ_UINT256.to_be_bytes().to_vec(),
+            &ctx,
+        )
+        .unwrap();
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(x.clone())))
+            .unwrap();
+        calldata
+            .append(UnwrappedBytes::Bytes(
+                CbseBitVec::from_u64(5, 256).to_bytes(),
+            ))
+            .unwrap();
+
+        sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        let counterexample = sevm.counterexample.clone().unwrap();
+        assert!(counterexample.contains("halmos_x_uint256"));
+        assert!(!counterexample.ends_with(&format!("#x{:064x}", 5)));
+
+        // The path continues assuming the assertion held
+        let eq = |v: u64| x._eq(&z3::ast::BV::from_u64(&ctx, v, 256));
+        assert_eq!(state.path.check(&eq(5)).unwrap(), z3::SatResult::Sat);
+        assert_eq!(state.path.check(&eq(6)).unwrap(), z3::SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_assert_true_on_true_passes() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let mut state = cheatcode_state(&sevm);
+
+        let mut data = hevm_cheat_code::ASSERT_TRUE.to_be_bytes().to_vec();
+        data.extend(CbseBitVec::from_u64(1, 256).to_bytes());
+        let calldata = ByteVec::from_bytes(data, &ctx).unwrap();
+
+        sevm.handle_cheatcode(&mut state, &calldata).unwrap();
+        assert_eq!(sevm.counterexample, None);
+        assert!(state.path.conditions.is_empty());
+    }
+
+    #[test]
+    fn test_assertion_failure_on_any_path_is_reported() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let target = [1u8; 20];
+
+        let code = concat!(
+            "600035600757",           // jumpi(0x07, calldataload(0))
+            "00",                     // stop
+            "5b",                     // 0x07: Panic(0x01)
+            "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+            "6001600452",             // mstore(4, 1)
+            "60246000fd",             // revert(0, 0x24)
+        );
+        sevm.deploy_contract(target, Contract::from_hexcode(code, &ctx).unwrap());
+
+        let cond = z3::ast::BV::new_const(&ctx, "cond", 256);
+        let mut calldata = ByteVec::new(&ctx);
+        calldata
+            .append(UnwrappedBytes::BitVec(CbseBitVec::from_z3(cond)))
+            .unwrap();
+        let paths = sevm
+            .explore(
+                target,
+                [2u8; 20],
+                [2u8; 20],
+                BigUint::default(),
+                calldata,
+                1_000_000,
+                false,
+            )
+            .unwrap();
+
+        // Whichever path finished first, the failing one is checked
+        assert_eq!(paths.len(), 2);
+        let counterexample = sevm.counterexample.clone().unwrap();
+        assert!(counterexample.contains("cond"));
+        assert!(!sevm.inconclusive);
+    }
+
+    #[test]
+    fn test_undecided_counterexample_is_inconclusive() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let call_context = CallContext::new(
+            CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false),
+            CallOutput::new(None, None, Some(0xFD)),
+            0,
+        );
+        let mut state = ExecState::new(&ctx, call_context, Rc::clone(&sevm.solver));
+
+        // Panic(0x01)
+        let mut panic = vec![0x4e, 0x48, 0x7b, 0x71];
+        panic.extend_from_slice(&[0u8; 31]);
+        panic.push(0x01);
+        state.last_return_data = Some(ByteVec::from_bytes(panic, &ctx).unwrap());
+
+        // Factoring x * y == 65521 * 65519 over 16-bit operands, with plain
+        // bvmul rather than the MUL abstraction, cannot finish in 1ms
+        let mask = z3::ast::BV::from_u64(&ctx, 0xffff, 256);
+        let x = z3::ast::BV::new_const(&ctx, "x", 256).bvand(&mask);
+        let y = z3::ast::BV::new_const(&ctx, "y", 256).bvand(&mask);
+        let product = z3::ast::BV::from_u64(&ctx, 65521 * 65519, 256);
+        state.path.append(x.bvmul(&y)._eq(&product), true).unwrap();
+        sevm.solver_timeouts = SolverTimeouts {
+            branching: Some(Duration::from_millis(1)),
+            assertion: Some(Duration::from_millis(1)),
+        };
+
+        let (failed, counterexample) = sevm.check_assertions(&[state]);
+        assert!(failed);
+        assert!(sevm.inconclusive);
+        assert_eq!(counterexample, "Counterexample: unknown (Solver timeout)");
+    }
 }
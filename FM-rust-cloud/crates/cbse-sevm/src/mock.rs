@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Stubbed external calls for `vm.mockCall`
+//!
+//! A mock matches calls to its address whose calldata starts with the mocked
+//! calldata, so mocking just a selector covers every argument and mocking empty
+//! calldata covers every call. When several mocks match, the longest one wins,
+//! as in Foundry.
+
+use std::collections::BTreeMap;
+
+/// Canned return data keyed by (address, calldata prefix)
+#[derive(Debug, Clone, Default)]
+pub struct MockRegistry {
+    mocks: BTreeMap<([u8; 20], Vec<u8>), Vec<u8>>,
+}
+
+impl MockRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `returndata` from calls to `address` whose calldata starts with `calldata`
+    ///
+    /// Replaces an earlier mock of the same address and calldata.
+    pub fn insert(&mut self, address: [u8; 20], calldata: Vec<u8>, returndata: Vec<u8>) {
+        self.mocks.insert((address, calldata), returndata);
+    }
+
+    /// Return data of the most specific mock matching the call, if any
+    pub fn lookup(&self, address: &[u8; 20], calldata: &[u8]) -> Option<&[u8]> {
+        self.mocks
+            .range((*address, Vec::new())..)
+            .take_while(|((mocked, _), _)| mocked == address)
+            .filter(|((_, prefix), _)| calldata.starts_with(prefix))
+            .max_by_key(|((_, prefix), _)| prefix.len())
+            .map(|(_, returndata)| returndata.as_slice())
+    }
+
+    /// Remove every mock (`vm.clearMockedCalls`)
+    pub fn clear(&mut self) {
+        self.mocks.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mocks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_prefers_longest_matching_prefix() {
+        let mut mocks = MockRegistry::new();
+        let (a, b) = ([1u8; 20], [2u8; 20]);
+        mocks.insert(a, vec![], vec![0]);
+        mocks.insert(a, vec![0xaa, 0xbb], vec![1]);
+        mocks.insert(a, vec![0xaa, 0xbb, 0xcc], vec![2]);
+
+        assert_eq!(
+            mocks.lookup(&a, &[0xaa, 0xbb, 0xcc, 0xdd]),
+            Some(&[2u8][..])
+        );
+        assert_eq!(mocks.lookup(&a, &[0xaa, 0xbb, 0x00]), Some(&[1u8][..]));
+        // The empty calldata mock matches anything sent to `a`
+        assert_eq!(mocks.lookup(&a, &[0x12]), Some(&[0u8][..]));
+        assert_eq!(mocks.lookup(&b, &[0xaa, 0xbb]), None);
+
+        mocks.clear();
+        assert!(mocks.is_empty());
+        assert_eq!(mocks.lookup(&a, &[0xaa, 0xbb]), None);
+    }
+}
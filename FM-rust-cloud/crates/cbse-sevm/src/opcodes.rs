@@ -5,120 +5,677 @@
 //! This module implements all EVM opcodes for symbolic execution.
 //! It closely mirrors the Python implementation in halmos/sevm.py
 
-use super::{ExecState, Message, StorageData, SEVM};
-use cbse_bitvec::CbseBitVec;
+use super::{ExecState, Message, PendingCreate, StorageData, BLOCKHASH_WINDOW, SEVM};
+use cbse_bitvec::{CbseBitVec, CbseBool};
 use cbse_bytevec::{ByteVec, UnwrappedBytes};
 use cbse_cheatcodes::{HEVM_ADDRESS, SVM_ADDRESS};
 use cbse_console::CONSOLE_ADDRESS;
+use cbse_constants::EMPTY_KECCAK;
+use cbse_contract::gas::{
+    G_COLD_ACCOUNT_ACCESS, G_COLD_SLOAD, G_SSTORE_RESET, G_SSTORE_SET, G_WARM_ACCESS,
+    R_SSTORE_CLEAR,
+};
 use cbse_contract::Contract;
 use cbse_exceptions::{CbseException, CbseResult, ExceptionalHalt};
 use cbse_hashes::keccak256;
-use cbse_traces::{CallContext, StorageRead, StorageWrite, TraceElement};
+use cbse_traces::{
+    CallContext, CallMessage, CallOutput, StorageRead, StorageWrite, TraceElement, TraceEvent,
+};
+use num_bigint::BigUint;
 use std::collections::HashMap;
 
 // EVM opcodes
-const OP_STOP: u8 = 0x00;
-const OP_ADD: u8 = 0x01;
-const OP_MUL: u8 = 0x02;
-const OP_SUB: u8 = 0x03;
-const OP_DIV: u8 = 0x04;
-const OP_SDIV: u8 = 0x05;
-const OP_MOD: u8 = 0x06;
-const OP_SMOD: u8 = 0x07;
-const OP_ADDMOD: u8 = 0x08;
-const OP_MULMOD: u8 = 0x09;
-const OP_EXP: u8 = 0x0a;
-const OP_SIGNEXTEND: u8 = 0x0b;
-const OP_LT: u8 = 0x10;
-const OP_GT: u8 = 0x11;
-const OP_SLT: u8 = 0x12;
-const OP_SGT: u8 = 0x13;
-const OP_EQ: u8 = 0x14;
-const OP_ISZERO: u8 = 0x15;
-const OP_AND: u8 = 0x16;
-const OP_OR: u8 = 0x17;
-const OP_XOR: u8 = 0x18;
-const OP_NOT: u8 = 0x19;
-const OP_BYTE: u8 = 0x1a;
-const OP_SHL: u8 = 0x1b;
-const OP_SHR: u8 = 0x1c;
-const OP_SAR: u8 = 0x1d;
-const OP_SHA3: u8 = 0x20;
-const OP_ADDRESS: u8 = 0x30;
-const OP_BALANCE: u8 = 0x31;
-const OP_ORIGIN: u8 = 0x32;
-const OP_CALLER: u8 = 0x33;
-const OP_CALLVALUE: u8 = 0x34;
-const OP_CALLDATALOAD: u8 = 0x35;
-const OP_CALLDATASIZE: u8 = 0x36;
-const OP_CALLDATACOPY: u8 = 0x37;
-const OP_CODESIZE: u8 = 0x38;
-const OP_CODECOPY: u8 = 0x39;
-const OP_GASPRICE: u8 = 0x3a;
-const OP_EXTCODESIZE: u8 = 0x3b;
-const OP_EXTCODECOPY: u8 = 0x3c;
-const OP_RETURNDATASIZE: u8 = 0x3d;
-const OP_RETURNDATACOPY: u8 = 0x3e;
-const OP_EXTCODEHASH: u8 = 0x3f;
-const OP_BLOCKHASH: u8 = 0x40;
-const OP_COINBASE: u8 = 0x41;
-const OP_TIMESTAMP: u8 = 0x42;
-const OP_NUMBER: u8 = 0x43;
-const OP_DIFFICULTY: u8 = 0x44;
-const OP_GASLIMIT: u8 = 0x45;
-const OP_CHAINID: u8 = 0x46;
-const OP_SELFBALANCE: u8 = 0x47;
-const OP_BASEFEE: u8 = 0x48;
-const OP_POP: u8 = 0x50;
-const OP_MLOAD: u8 = 0x51;
-const OP_MSTORE: u8 = 0x52;
-const OP_MSTORE8: u8 = 0x53;
-const OP_SLOAD: u8 = 0x54;
-const OP_SSTORE: u8 = 0x55;
-const OP_JUMP: u8 = 0x56;
-const OP_JUMPI: u8 = 0x57;
-const OP_PC: u8 = 0x58;
-const OP_MSIZE: u8 = 0x59;
-const OP_GAS: u8 = 0x5a;
-const OP_JUMPDEST: u8 = 0x5b;
-const OP_PUSH0: u8 = 0x5f;
-const OP_PUSH1: u8 = 0x60;
-const OP_PUSH32: u8 = 0x7f;
-const OP_DUP1: u8 = 0x80;
-const OP_DUP16: u8 = 0x8f;
-const OP_SWAP1: u8 = 0x90;
-const OP_SWAP16: u8 = 0x9f;
-const OP_LOG0: u8 = 0xa0;
-const OP_LOG1: u8 = 0xa1;
-const OP_LOG2: u8 = 0xa2;
-const OP_LOG3: u8 = 0xa3;
-const OP_LOG4: u8 = 0xa4;
-const OP_CREATE: u8 = 0xf0;
-const OP_CALL: u8 = 0xf1;
-const OP_CALLCODE: u8 = 0xf2;
-const OP_RETURN: u8 = 0xf3;
-const OP_DELEGATECALL: u8 = 0xf4;
-const OP_CREATE2: u8 = 0xf5;
-const OP_STATICCALL: u8 = 0xfa;
-const OP_REVERT: u8 = 0xfd;
-const OP_INVALID: u8 = 0xfe;
-const OP_SELFDESTRUCT: u8 = 0xff;
+pub(crate) const OP_STOP: u8 = 0x00;
+pub(crate) const OP_ADD: u8 = 0x01;
+pub(crate) const OP_MUL: u8 = 0x02;
+pub(crate) const OP_SUB: u8 = 0x03;
+pub(crate) const OP_DIV: u8 = 0x04;
+pub(crate) const OP_SDIV: u8 = 0x05;
+pub(crate) const OP_MOD: u8 = 0x06;
+pub(crate) const OP_SMOD: u8 = 0x07;
+pub(crate) const OP_ADDMOD: u8 = 0x08;
+pub(crate) const OP_MULMOD: u8 = 0x09;
+pub(crate) const OP_EXP: u8 = 0x0a;
+pub(crate) const OP_SIGNEXTEND: u8 = 0x0b;
+pub(crate) const OP_LT: u8 = 0x10;
+pub(crate) const OP_GT: u8 = 0x11;
+pub(crate) const OP_SLT: u8 = 0x12;
+pub(crate) const OP_SGT: u8 = 0x13;
+pub(crate) const OP_EQ: u8 = 0x14;
+pub(crate) const OP_ISZERO: u8 = 0x15;
+pub(crate) const OP_AND: u8 = 0x16;
+pub(crate) const OP_OR: u8 = 0x17;
+pub(crate) const OP_XOR: u8 = 0x18;
+pub(crate) const OP_NOT: u8 = 0x19;
+pub(crate) const OP_BYTE: u8 = 0x1a;
+pub(crate) const OP_SHL: u8 = 0x1b;
+pub(crate) const OP_SHR: u8 = 0x1c;
+pub(crate) const OP_SAR: u8 = 0x1d;
+pub(crate) const OP_SHA3: u8 = 0x20;
+pub(crate) const OP_ADDRESS: u8 = 0x30;
+pub(crate) const OP_BALANCE: u8 = 0x31;
+pub(crate) const OP_ORIGIN: u8 = 0x32;
+pub(crate) const OP_CALLER: u8 = 0x33;
+pub(crate) const OP_CALLVALUE: u8 = 0x34;
+pub(crate) const OP_CALLDATALOAD: u8 = 0x35;
+pub(crate) const OP_CALLDATASIZE: u8 = 0x36;
+pub(crate) const OP_CALLDATACOPY: u8 = 0x37;
+pub(crate) const OP_CODESIZE: u8 = 0x38;
+pub(crate) const OP_CODECOPY: u8 = 0x39;
+pub(crate) const OP_GASPRICE: u8 = 0x3a;
+pub(crate) const OP_EXTCODESIZE: u8 = 0x3b;
+pub(crate) const OP_EXTCODECOPY: u8 = 0x3c;
+pub(crate) const OP_RETURNDATASIZE: u8 = 0x3d;
+pub(crate) const OP_RETURNDATACOPY: u8 = 0x3e;
+pub(crate) const OP_EXTCODEHASH: u8 = 0x3f;
+pub(crate) const OP_BLOCKHASH: u8 = 0x40;
+pub(crate) const OP_COINBASE: u8 = 0x41;
+pub(crate) const OP_TIMESTAMP: u8 = 0x42;
+pub(crate) const OP_NUMBER: u8 = 0x43;
+pub(crate) const OP_DIFFICULTY: u8 = 0x44;
+pub(crate) const OP_GASLIMIT: u8 = 0x45;
+pub(crate) const OP_CHAINID: u8 = 0x46;
+pub(crate) const OP_SELFBALANCE: u8 = 0x47;
+pub(crate) const OP_BASEFEE: u8 = 0x48;
+pub(crate) const OP_POP: u8 = 0x50;
+pub(crate) const OP_MLOAD: u8 = 0x51;
+pub(crate) const OP_MSTORE: u8 = 0x52;
+pub(crate) const OP_MSTORE8: u8 = 0x53;
+pub(crate) const OP_SLOAD: u8 = 0x54;
+pub(crate) const OP_SSTORE: u8 = 0x55;
+pub(crate) const OP_JUMP: u8 = 0x56;
+pub(crate) const OP_JUMPI: u8 = 0x57;
+pub(crate) const OP_PC: u8 = 0x58;
+pub(crate) const OP_MSIZE: u8 = 0x59;
+pub(crate) const OP_GAS: u8 = 0x5a;
+pub(crate) const OP_JUMPDEST: u8 = 0x5b;
+pub(crate) const OP_TSTORE: u8 = 0x5d;
+pub(crate) const OP_PUSH0: u8 = 0x5f;
+pub(crate) const OP_PUSH1: u8 = 0x60;
+pub(crate) const OP_PUSH32: u8 = 0x7f;
+pub(crate) const OP_DUP1: u8 = 0x80;
+pub(crate) const OP_DUP16: u8 = 0x8f;
+pub(crate) const OP_SWAP1: u8 = 0x90;
+pub(crate) const OP_SWAP16: u8 = 0x9f;
+pub(crate) const OP_LOG0: u8 = 0xa0;
+pub(crate) const OP_LOG1: u8 = 0xa1;
+pub(crate) const OP_LOG2: u8 = 0xa2;
+pub(crate) const OP_LOG3: u8 = 0xa3;
+pub(crate) const OP_LOG4: u8 = 0xa4;
+pub(crate) const OP_CREATE: u8 = 0xf0;
+pub(crate) const OP_CALL: u8 = 0xf1;
+pub(crate) const OP_CALLCODE: u8 = 0xf2;
+pub(crate) const OP_RETURN: u8 = 0xf3;
+pub(crate) const OP_DELEGATECALL: u8 = 0xf4;
+pub(crate) const OP_CREATE2: u8 = 0xf5;
+pub(crate) const OP_STATICCALL: u8 = 0xfa;
+pub(crate) const OP_REVERT: u8 = 0xfd;
+pub(crate) const OP_INVALID: u8 = 0xfe;
+pub(crate) const OP_SELFDESTRUCT: u8 = 0xff;
+
+/// Change in the refund counter for an SSTORE writing `new` to a slot that
+/// held `original` at the start of the transaction and holds `current` now
+/// (EIP-3529)
+fn sstore_refund(original: &BigUint, current: &BigUint, new: &BigUint) -> i64 {
+    let zero = BigUint::default();
+    let clear = R_SSTORE_CLEAR as i64;
+    if current == new {
+        return 0;
+    }
+
+    // First write to the slot in this transaction
+    if original == current {
+        return if *original != zero && *new == zero {
+            clear
+        } else {
+            0
+        };
+    }
+
+    // The slot is dirty: undo or grant the clearing refund...
+    let mut refund = 0;
+    if *original != zero {
+        if *current == zero {
+            refund -= clear;
+        } else if *new == zero {
+            refund += clear;
+        }
+    }
+    // ...and give back most of the write cost when restoring the original
+    if original == new {
+        let restored = if *original == zero {
+            G_SSTORE_SET - G_WARM_ACCESS
+        } else {
+            G_SSTORE_RESET - G_WARM_ACCESS
+        };
+        refund += restored as i64;
+    }
+    refund
+}
+
+/// Gas for `size` bytes of memory, rounded up to words: 3 per word plus
+/// words^2 / 512 (the yellow paper's C_mem)
+fn memory_cost(size: u64) -> u64 {
+    let words = size / 32;
+    3 * words + words * words / 512
+}
+
+/// Whether execution of the current frame continues after an opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StepOutcome {
+    Continue,
+    Halt,
+}
+
+impl From<bool> for StepOutcome {
+    /// `true` halts, matching the `halted` flags returned by the call helpers
+    fn from(halted: bool) -> Self {
+        if halted {
+            StepOutcome::Halt
+        } else {
+            StepOutcome::Continue
+        }
+    }
+}
+
+/// Opcode handler, given the opcode being executed and the running contract
+type OpcodeHandler<'ctx> = fn(
+    &mut SEVM<'ctx>,
+    u8,
+    &mut ExecState<'ctx>,
+    &Message<'ctx>,
+    &Contract<'ctx>,
+) -> CbseResult<StepOutcome>;
 
 impl<'ctx> SEVM<'ctx> {
-    /// Convert CbseBool to CbseBitVec (0 or 1 as 256-bit value)
-    fn bool_to_bv(&self, b: cbse_bitvec::CbseBool<'ctx>) -> CbseBitVec<'ctx> {
-        use cbse_bitvec::CbseBool;
-        match b {
-            CbseBool::Concrete(true) => CbseBitVec::from_u64(1, 256),
+    /// Apply EVM division-by-zero semantics to `quotient`
+    ///
+    /// EVM division and modulo by zero yield zero, whereas SMT-LIB (and the
+    /// abstraction functions) leave the result unspecified or all-ones.
+    fn zero_if_zero_divisor(
+        &self,
+        divisor: &CbseBitVec<'ctx>,
+        quotient: CbseBitVec<'ctx>,
+    ) -> CbseBitVec<'ctx> {
+        match divisor.is_zero(self.ctx) {
+            CbseBool::Concrete(true) => CbseBitVec::from_u64(0, 256),
+            CbseBool::Concrete(false) => quotient,
+            CbseBool::Symbolic(is_zero) => {
+                let zero = z3::ast::BV::from_u64(self.ctx, 0, 256);
+                CbseBitVec::from_z3(is_zero.ite(&zero, &quotient.as_z3(self.ctx)))
+            }
+        }
+    }
+
+    /// Hash of block `number`: an f_blockhash term inside the BLOCKHASH window
+    /// before the current block, zero for any other number
+    fn blockhash(&self, number: &CbseBitVec<'ctx>) -> CbseBitVec<'ctx> {
+        let current = CbseBitVec::from_u64(self.block.number, 256);
+        let window = CbseBitVec::from_u64(BLOCKHASH_WINDOW, 256);
+        let in_window = number.ult(&current, self.ctx).and(
+            &current.sub(number, self.ctx).ule(&window, self.ctx),
+            self.ctx,
+        );
+        let hash = || {
+            self.blockhash_fn
+                .apply(&[&number.as_z3(self.ctx)])
+                .as_bv()
+                .expect("f_blockhash returns a bitvector")
+        };
+
+        match in_window {
+            CbseBool::Concrete(true) => CbseBitVec::from_z3(hash()),
             CbseBool::Concrete(false) => CbseBitVec::from_u64(0, 256),
-            CbseBool::Symbolic(z3_bool) => {
-                // Use ite: if z3_bool then 1 else 0
-                let one = z3::ast::BV::from_u64(self.ctx, 1, 256);
+            CbseBool::Symbolic(in_window) => {
                 let zero = z3::ast::BV::from_u64(self.ctx, 0, 256);
-                let result = z3_bool.ite(&one, &zero);
-                CbseBitVec::from_z3(result)
+                CbseBitVec::from_z3(in_window.ite(&hash(), &zero))
+            }
+        }
+    }
+
+    /// Deduct `cost` from the remaining gas of `state`
+    fn charge_gas(&self, state: &mut ExecState<'ctx>, cost: u64) -> CbseResult<()> {
+        if state.gas < cost {
+            return Err(CbseException::Halt(ExceptionalHalt::OutOfGas));
+        }
+        state.gas -= cost;
+        Ok(())
+    }
+
+    /// Gas forwarded to a subcall that asks for `requested`: at most all but
+    /// one 64th of what the caller has left (EIP-150). A symbolic request
+    /// gets that maximum.
+    fn call_gas(state: &ExecState<'ctx>, requested: &CbseBitVec<'ctx>) -> u64 {
+        let available = state.gas - state.gas / 64;
+        requested
+            .as_u64()
+            .map_or(available, |gas| gas.min(available))
+    }
+
+    /// Whether CREATE/CREATE2 init code of `size` bytes breaks EIP-3860
+    ///
+    /// The EIP-170 limit on the code it deploys is checked once the init code
+    /// has run (see `finish_create`).
+    fn exceeds_code_size_limits(&self, size: usize) -> bool {
+        size > self.max_initcode_size
+    }
+
+    /// Fund `new_addr` and leave `init_code` to run in a frame of its own
+    /// (see `PendingCreate`); the creating path resumes with the new address,
+    /// or 0 if creation fails.
+    fn start_create(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        new_addr: [u8; 20],
+        init_code: Vec<u8>,
+        value: &CbseBitVec<'ctx>,
+        scheme: u8,
+    ) -> CbseResult<StepOutcome> {
+        // Address collision or insufficient funds - push 0 and continue
+        let balances_before = self.balance.clone();
+        let amount = value.as_biguint().unwrap_or_default();
+        if self.contracts.contains_key(&new_addr)
+            || !self.transfer(message.target, new_addr, &amount)
+        {
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+            state.pc += 1;
+            return Ok(StepOutcome::Continue);
+        }
+
+        // The account has no code while its init code runs
+        let empty_contract = Contract::new(ByteVec::new(self.ctx), self.ctx, None, None, None);
+        self.contracts.insert(new_addr.into(), empty_contract);
+        self.storage.insert(new_addr.into(), StorageData::new());
+
+        self.pending_create = Some(PendingCreate {
+            address: new_addr,
+            init_code,
+            value: amount,
+            scheme,
+            balances_before,
+        });
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// Convert a concrete word to an address (low 160 bits), or None if symbolic
+    fn bv_to_address(bv: &CbseBitVec<'ctx>) -> Option<[u8; 20]> {
+        bv.as_address().ok()
+    }
+
+    /// Look up the code deployed at `addr`.
+    /// The running contract is taken out of `self.contracts` during its call,
+    /// so it is passed in separately as `current`.
+    fn code_at<'a>(
+        &'a self,
+        addr: &[u8; 20],
+        state: &ExecState<'ctx>,
+        current: &'a Contract<'ctx>,
+    ) -> Option<&'a Contract<'ctx>> {
+        if *addr == state.address {
+            Some(current)
+        } else {
+            self.contracts.get(addr)
+        }
+    }
+
+    /// Record a memory access of `size` bytes at `offset` in `memory_size`
+    /// and charge the gas for any expansion
+    ///
+    /// Reads expand memory just like writes do, even though they leave the
+    /// ByteVec untouched, so the size is tracked separately.
+    ///
+    /// Returns false if the access would expand memory past `max_memory_size`
+    /// or costs more gas than is left, in which case nothing is recorded and
+    /// the caller must halt the frame with `memory_limit_halt` rather than
+    /// allocate.
+    pub(crate) fn touch_memory(&self, state: &mut ExecState<'ctx>, offset: u64, size: u64) -> bool {
+        if size == 0 {
+            return true;
+        }
+        let end = offset.saturating_add(size).saturating_add(31) / 32 * 32;
+        if end <= state.memory_size as u64 {
+            return true;
+        }
+        if end > self.max_memory_size as u64 {
+            return false;
+        }
+        let cost = memory_cost(end) - memory_cost(state.memory_size as u64);
+        if cost > state.gas {
+            return false;
+        }
+        state.gas -= cost;
+        state.memory_size = end as usize;
+        true
+    }
+
+    /// `touch_memory` for (offset, size) regions given as stack words
+    ///
+    /// Regions with a symbolic offset or size are not recorded.
+    fn touch_regions(
+        &self,
+        state: &mut ExecState<'ctx>,
+        regions: &[(&CbseBitVec<'ctx>, &CbseBitVec<'ctx>)],
+    ) -> bool {
+        regions
+            .iter()
+            .all(|(offset, size)| match (offset.as_u64(), size.as_u64()) {
+                (Ok(offset), Ok(size)) => self.touch_memory(state, offset, size),
+                _ => true,
+            })
+    }
+
+    /// Halt the frame out of gas, as an oversized memory expansion would on chain
+    fn memory_limit_halt(&self, state: &mut ExecState<'ctx>) -> bool {
+        self.exceptional_halt(state, ExceptionalHalt::OutOfGas)
+    }
+
+    /// Output of RETURN/REVERT: `length` bytes of memory starting at `offset`
+    fn memory_output(
+        state: &ExecState<'ctx>,
+        offset: &CbseBitVec<'ctx>,
+        length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<ByteVec<'ctx>> {
+        let (off, len) = match (offset.as_u64(), length.as_u64()) {
+            (Ok(off), Ok(len)) => (off as usize, len as usize),
+            _ => {
+                return Err(CbseException::NotConcrete(
+                    "symbolic return data offset or size".to_string(),
+                ))
+            }
+        };
+        state.memory.slice(off, off + len)
+    }
+
+    /// Stack arguments taken by the opcodes that open a new call frame
+    fn call_arg_count(opcode: u8) -> Option<usize> {
+        match opcode {
+            OP_CALL => Some(7),
+            OP_DELEGATECALL | OP_STATICCALL => Some(6),
+            OP_CREATE => Some(3),
+            OP_CREATE2 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `opcode` modifies state and is therefore forbidden in a
+    /// static context (EIP-214). CALL only counts when it transfers value.
+    fn is_state_changing(&self, opcode: u8, state: &ExecState<'ctx>) -> CbseResult<bool> {
+        Ok(match opcode {
+            OP_SSTORE
+            | OP_TSTORE
+            | OP_LOG0..=OP_LOG4
+            | OP_CREATE
+            | OP_CREATE2
+            | OP_SELFDESTRUCT => true,
+            OP_CALL => {
+                let value = self.peek(state, 3)?;
+                !matches!(value.as_u64(), Ok(0))
+            }
+            _ => false,
+        })
+    }
+
+    /// Run the cheatcode called with the memory at `args_offset`, copy its
+    /// result to the return region and push success (cheatcodes always succeed).
+    ///
+    /// Paths the cheatcode forked get their own result the same way and move
+    /// past the call, to be explored once the opcode completes.
+    fn call_cheatcode(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        args_offset: &CbseBitVec<'ctx>,
+        args_length: &CbseBitVec<'ctx>,
+        ret_offset: &CbseBitVec<'ctx>,
+        ret_length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<()> {
+        let offset = args_offset.as_u64().unwrap_or(0) as usize;
+        let length = args_length.as_u64().unwrap_or(0) as usize;
+        let calldata = state.memory.slice(offset, offset + length)?;
+
+        if calldata.len() >= 4 {
+            let result = self.handle_cheatcode(state, &calldata)?;
+            Self::write_cheatcode_result(state, &result, ret_offset, ret_length)?;
+
+            for (mut fork, result) in std::mem::take(&mut self.cheatcode_forks) {
+                Self::write_cheatcode_result(&mut fork, &result, ret_offset, ret_length)?;
+                self.push(&mut fork, CbseBitVec::from_u64(1, 256))?;
+                fork.pc += 1;
+                self.forked_states.push(fork);
+            }
+        }
+
+        self.push(state, CbseBitVec::from_u64(1, 256))
+    }
+
+    /// Copy as much of a cheatcode's `result` as fits in the return region to memory
+    fn write_cheatcode_result(
+        state: &mut ExecState<'ctx>,
+        result: &ByteVec<'ctx>,
+        ret_offset: &CbseBitVec<'ctx>,
+        ret_length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<()> {
+        let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
+        let ret_len = ret_length.as_u64().unwrap_or(0) as usize;
+        let write_len = std::cmp::min(result.len(), ret_len);
+        if write_len > 0 {
+            let data = result.slice(0, write_len)?.unwrap()?;
+            state.memory.set_slice(ret_off, ret_off + write_len, data)?;
+        }
+        Ok(())
+    }
+
+    /// Record the result of a subcall in the calling frame: merge its access
+    /// set, append its trace, copy return data to memory and push the status.
+    ///
+    /// Returns true if the frame halted instead because the subcall did not
+    /// emit the log expected by vm.expectEmit.
+    fn finish_subcall(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        success: bool,
+        return_data: &[u8],
+        subcall_context: CallContext,
+        ret_offset: &CbseBitVec<'ctx>,
+        ret_length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<bool> {
+        // Accesses made by a reverted subcall are rolled back (EIP-2929)
+        if let Some(access_set) = self.returned_access.take() {
+            if success {
+                state.access_set = access_set;
+            }
+        }
+        // ...and so are its refunds and self-destructs
+        if let Some(refund) = self.returned_refund.take() {
+            if success {
+                state.refund += refund;
+            }
+        }
+        if let Some(selfdestructs) = self.returned_selfdestructs.take() {
+            if success {
+                state.selfdestructs.extend(selfdestructs);
+            }
+        }
+
+        // An armed vm.expectEmit is checked against the first call after the template
+        let unmet_emit = match &state.expected_emit {
+            Some(expected) if expected.template.is_some() => {
+                let met = expected.is_met_by(&subcall_context);
+                state.expected_emit = None;
+                !met
+            }
+            _ => false,
+        };
+
+        // Add subcall context to parent trace
+        state
+            .context
+            .trace
+            .push(TraceElement::Call(subcall_context));
+
+        if unmet_emit {
+            state.context.output.error = Some("log != expected log".to_string());
+            state.last_return_data = Some(ByteVec::new(self.ctx));
+            return Ok(true);
+        }
+
+        // Write return data to memory
+        if !return_data.is_empty() {
+            let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
+            let ret_len = ret_length.as_u64().unwrap_or(0) as usize;
+            let write_len = std::cmp::min(return_data.len(), ret_len);
+            for i in 0..write_len {
+                let byte_bv = CbseBitVec::from_u64(return_data[i] as u64, 8);
+                state
+                    .memory
+                    .set_byte(ret_off + i, UnwrappedBytes::BitVec(byte_bv))?;
             }
         }
+
+        // Push success flag
+        let success_val = if success { 1 } else { 0 };
+        self.push(state, CbseBitVec::from_u64(success_val, 256))?;
+        Ok(false)
+    }
+
+    /// Complete a call from a `vm.mockCall` stub instead of running `target`
+    ///
+    /// Returns None, leaving the state untouched, if no mock matches, and
+    /// otherwise whether the frame halted (see `finish_subcall`).
+    fn try_mocked_call(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        target: [u8; 20],
+        calldata: &[u8],
+        call_scheme: u8,
+        ret_offset: &CbseBitVec<'ctx>,
+        ret_length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<Option<bool>> {
+        let returndata = match self.mocks.lookup(&target, calldata) {
+            Some(returndata) => returndata.to_vec(),
+            None => return Ok(None),
+        };
+
+        let message = CallMessage::new(
+            Self::address_to_u64(&target),
+            Self::address_to_u64(&state.address),
+            0,
+            calldata.to_vec(),
+            call_scheme,
+            call_scheme == OP_STATICCALL,
+        );
+        let output = CallOutput::new(Some(returndata.clone()), None, Some(OP_RETURN));
+        let context = CallContext::new(message, output, state.context.depth + 1);
+        let halted =
+            self.finish_subcall(state, true, &returndata, context, ret_offset, ret_length)?;
+        Ok(Some(halted))
+    }
+
+    /// Complete a call to an address without code as an uninterpreted call
+    /// (see `UnknownCalls`): success and return data are fresh symbols
+    ///
+    /// Returns false, leaving the state untouched, if the target has code or
+    /// the selector is symbolic or not listed.
+    fn try_uninterpreted_call(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        target: [u8; 20],
+        calldata: &ByteVec<'ctx>,
+        current: &Contract<'ctx>,
+        ret_offset: &CbseBitVec<'ctx>,
+        ret_length: &CbseBitVec<'ctx>,
+    ) -> CbseResult<bool> {
+        if calldata.len() < 4 {
+            return Ok(false);
+        }
+        let selector = match calldata.slice(0, 4)?.unwrap()? {
+            UnwrappedBytes::Bytes(bytes) => bytes.to_vec(),
+            UnwrappedBytes::BitVec(_) => return Ok(false),
+        };
+        if self.code_at(&target, state, current).is_some()
+            || !self.unknown_calls.is_uninterpreted(&selector)
+        {
+            return Ok(false);
+        }
+
+        let id = self.symbols.next_id();
+        let selector = cbse_traces::hexify(&selector);
+        let size = self.unknown_calls.return_size();
+        let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
+        let write_len = std::cmp::min(size, ret_length.as_u64().unwrap_or(0) as usize);
+        if write_len > 0 {
+            let name = format!("f_unknown_{}_ret_{:02}", selector, id);
+            let returndata = CbseBitVec::symbolic(self.ctx, &name, (size * 8) as u32);
+            let returndata = ByteVec::from_data(UnwrappedBytes::BitVec(returndata), self.ctx)?;
+            let data = returndata.slice(0, write_len)?.unwrap()?;
+            state.memory.set_slice(ret_off, ret_off + write_len, data)?;
+        }
+
+        let exit_code = z3::ast::Bool::new_const(self.ctx, format!("call_exit_code_{:02}", id));
+        let success = CbseBool::Symbolic(exit_code).to_bitvec(self.ctx, 256);
+        self.push(state, success)?;
+        Ok(true)
+    }
+
+    /// Charge the EIP-2929 account access cost for `addr` and mark it warm.
+    /// Symbolic addresses are always charged as cold.
+    fn charge_account_access(
+        &self,
+        state: &mut ExecState<'ctx>,
+        addr: &CbseBitVec<'ctx>,
+    ) -> CbseResult<()> {
+        let warm = match Self::bv_to_address(addr) {
+            Some(account) => state.access_set.access_address(account),
+            None => false,
+        };
+        let cost = if warm {
+            G_WARM_ACCESS
+        } else {
+            G_COLD_ACCOUNT_ACCESS
+        };
+        self.charge_gas(state, cost)
+    }
+
+    /// Charge the EIP-2929 storage access cost for `slot` of the current
+    /// contract and mark it warm. Symbolic slots are always charged as cold.
+    fn charge_slot_access(
+        &self,
+        state: &mut ExecState<'ctx>,
+        slot: &CbseBitVec<'ctx>,
+    ) -> CbseResult<()> {
+        let warm = match slot.as_biguint() {
+            Ok(value) => state.access_set.access_slot(state.address, value),
+            Err(_) => false,
+        };
+        let cost = if warm { G_WARM_ACCESS } else { G_COLD_SLOAD };
+        self.charge_gas(state, cost)
+    }
+
+    /// Update the refund counter for an SSTORE of `value` to `slot` of the
+    /// current contract. Writes involving symbolic slots or values are not
+    /// refunded.
+    fn accrue_sstore_refund(
+        &mut self,
+        state: &mut ExecState<'ctx>,
+        slot: &CbseBitVec<'ctx>,
+        value: &CbseBitVec<'ctx>,
+    ) {
+        let Ok(key) = slot.as_biguint() else {
+            return;
+        };
+        let current = self.get_storage(state.address, slot);
+        let original = self
+            .original_storage
+            .entry((state.address.into(), key))
+            .or_insert_with(|| current.clone());
+        if let (Ok(original), Ok(current), Ok(new)) = (
+            original.as_biguint(),
+            current.as_biguint(),
+            value.as_biguint(),
+        ) {
+            state.refund += sstore_refund(&original, &current, &new);
+        }
     }
 
     /// Handle JUMPI with full path branching.
@@ -138,12 +695,10 @@ impl<'ctx> SEVM<'ctx> {
 
         // Pop dest and cond from stack - clone state to avoid mutation
         let mut new_stack = state.stack.clone();
-        let dest_bv = new_stack
-            .pop()
-            .ok_or_else(|| CbseException::Internal("Stack underflow in JUMPI".to_string()))?;
-        let cond_bv = new_stack
-            .pop()
-            .ok_or_else(|| CbseException::Internal("Stack underflow in JUMPI".to_string()))?;
+        let (dest_bv, cond_bv) = match (new_stack.pop(), new_stack.pop()) {
+            (Some(dest), Some(cond)) => (dest, cond),
+            _ => return Err(CbseException::Halt(ExceptionalHalt::StackUnderflow)),
+        };
 
         // Convert destination to usize (must be concrete)
         let dest = dest_bv.as_u64().map_err(|_| {
@@ -153,10 +708,12 @@ impl<'ctx> SEVM<'ctx> {
         // Convert condition to bool - is_zero returns true if cond == 0
         let cond_is_zero = cond_bv.is_zero(self.ctx);
 
-        // We need the opposite: jump if NOT zero
+        // We need the opposite: jump if NOT zero. A symbolic condition that
+        // simplifies to a constant (e.g. `1 < 2`) is decided here, so only one
+        // branch is followed and the solver is never asked about it.
         let cond = match cond_is_zero {
             CbseBool::Concrete(is_zero) => CbseBool::Concrete(!is_zero),
-            CbseBool::Symbolic(z3_bool) => CbseBool::Symbolic(z3_bool.not()),
+            CbseBool::Symbolic(z3_bool) => CbseBool::from_z3(z3_bool.not()),
         };
 
         // Get current pc and create jump id (jid)
@@ -181,23 +738,12 @@ impl<'ctx> SEVM<'ctx> {
                 (*b, !b)
             }
             CbseBool::Symbolic(z3_bool) => {
-                // Check if true branch is satisfiable
-                state.path.solver.push();
-                state.path.solver.assert(z3_bool);
-                let check_true = state.path.solver.check();
-                state.path.solver.pop(1);
-
-                // Check if false branch is satisfiable
-                state.path.solver.push();
-                let not_cond = z3_bool.not();
-                state.path.solver.assert(&not_cond);
-                let check_false = state.path.solver.check();
-                state.path.solver.pop(1);
-
-                let potential_true = check_true == z3::SatResult::Sat;
-                let potential_false = check_false == z3::SatResult::Sat;
-
-                (potential_true, potential_false)
+                // Check both branches in one batch; a branching timeout
+                // (Unknown) keeps the branch alive
+                let feasible = state
+                    .path
+                    .filter_feasible(&[z3_bool.clone(), z3_bool.not()]);
+                (feasible[0], feasible[1])
             }
         };
 
@@ -307,1492 +853,2369 @@ impl<'ctx> SEVM<'ctx> {
         message: &Message<'ctx>,
         contract: &Contract<'ctx>,
     ) -> CbseResult<bool> {
-        match opcode {
-            // 0x00: STOP
-            OP_STOP => {
-                return Ok(true); // Halt execution
-            }
+        // State modifications inside a STATICCALL halt the frame exceptionally
+        if message.is_static && self.is_state_changing(opcode, state)? {
+            return Ok(self.exceptional_halt(state, ExceptionalHalt::WriteInStaticContext));
+        }
 
-            // 0x01: ADD
-            OP_ADD => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.add(&b, self.ctx);
-                self.push(state, result)?;
+        // A call or creation past the depth limit fails without running: its
+        // arguments are consumed and 0 is pushed, as for any failed call
+        if let Some(arg_count) = Self::call_arg_count(opcode) {
+            if state.context.depth >= self.max_call_depth {
+                state.pop_n(arg_count)?;
+                state.last_return_data = Some(ByteVec::new(self.ctx));
+                self.push(state, CbseBitVec::from_u64(0, 256))?;
                 state.pc += 1;
+                return Ok(false);
             }
+        }
 
-            // 0x02: MUL
-            OP_MUL => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.mul(&b, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+        let handler = Self::OPCODE_HANDLERS[opcode as usize];
+        Ok(handler(self, opcode, state, message, contract)? == StepOutcome::Halt)
+    }
 
-            // 0x03: SUB
-            OP_SUB => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.sub(&b, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// Handler for every opcode, indexed by opcode. Opcodes without one fall
+    /// through to `op_unimplemented`.
+    const OPCODE_HANDLERS: [OpcodeHandler<'ctx>; 256] = Self::opcode_handlers();
+
+    const fn opcode_handlers() -> [OpcodeHandler<'ctx>; 256] {
+        let mut table = [Self::op_unimplemented as OpcodeHandler<'ctx>; 256];
+        table[OP_STOP as usize] = Self::op_stop;
+        table[OP_ADD as usize] = Self::op_add;
+        table[OP_MUL as usize] = Self::op_mul;
+        table[OP_SUB as usize] = Self::op_sub;
+        table[OP_DIV as usize] = Self::op_div;
+        table[OP_SDIV as usize] = Self::op_sdiv;
+        table[OP_MOD as usize] = Self::op_mod;
+        table[OP_SMOD as usize] = Self::op_smod;
+        table[OP_ADDMOD as usize] = Self::op_addmod;
+        table[OP_MULMOD as usize] = Self::op_mulmod;
+        table[OP_EXP as usize] = Self::op_exp;
+        table[OP_SIGNEXTEND as usize] = Self::op_signextend;
+        table[OP_LT as usize] = Self::op_lt;
+        table[OP_GT as usize] = Self::op_gt;
+        table[OP_SLT as usize] = Self::op_slt;
+        table[OP_SGT as usize] = Self::op_sgt;
+        table[OP_EQ as usize] = Self::op_eq;
+        table[OP_ISZERO as usize] = Self::op_iszero;
+        table[OP_AND as usize] = Self::op_and;
+        table[OP_OR as usize] = Self::op_or;
+        table[OP_XOR as usize] = Self::op_xor;
+        table[OP_NOT as usize] = Self::op_not;
+        table[OP_BYTE as usize] = Self::op_byte;
+        table[OP_SHL as usize] = Self::op_shl;
+        table[OP_SHR as usize] = Self::op_shr;
+        table[OP_SAR as usize] = Self::op_sar;
+        table[OP_SHA3 as usize] = Self::op_sha3;
+        table[OP_ADDRESS as usize] = Self::op_address;
+        table[OP_BALANCE as usize] = Self::op_balance;
+        table[OP_ORIGIN as usize] = Self::op_origin;
+        table[OP_CALLER as usize] = Self::op_caller;
+        table[OP_CALLVALUE as usize] = Self::op_callvalue;
+        table[OP_CALLDATALOAD as usize] = Self::op_calldataload;
+        table[OP_CALLDATASIZE as usize] = Self::op_calldatasize;
+        table[OP_CALLDATACOPY as usize] = Self::op_calldatacopy;
+        table[OP_CODESIZE as usize] = Self::op_codesize;
+        table[OP_CODECOPY as usize] = Self::op_codecopy;
+        table[OP_GASPRICE as usize] = Self::op_gasprice;
+        table[OP_EXTCODESIZE as usize] = Self::op_extcodesize;
+        table[OP_EXTCODECOPY as usize] = Self::op_extcodecopy;
+        table[OP_RETURNDATASIZE as usize] = Self::op_returndatasize;
+        table[OP_RETURNDATACOPY as usize] = Self::op_returndatacopy;
+        table[OP_EXTCODEHASH as usize] = Self::op_extcodehash;
+        table[OP_BLOCKHASH as usize] = Self::op_blockhash;
+        table[OP_COINBASE as usize] = Self::op_coinbase;
+        table[OP_TIMESTAMP as usize] = Self::op_timestamp;
+        table[OP_NUMBER as usize] = Self::op_number;
+        table[OP_DIFFICULTY as usize] = Self::op_difficulty;
+        table[OP_GASLIMIT as usize] = Self::op_gaslimit;
+        table[OP_CHAINID as usize] = Self::op_chainid;
+        table[OP_SELFBALANCE as usize] = Self::op_selfbalance;
+        table[OP_BASEFEE as usize] = Self::op_basefee;
+        table[OP_POP as usize] = Self::op_pop;
+        table[OP_MLOAD as usize] = Self::op_mload;
+        table[OP_MSTORE as usize] = Self::op_mstore;
+        table[OP_MSTORE8 as usize] = Self::op_mstore8;
+        table[OP_SLOAD as usize] = Self::op_sload;
+        table[OP_SSTORE as usize] = Self::op_sstore;
+        table[OP_JUMP as usize] = Self::op_jump;
+        table[OP_JUMPI as usize] = Self::op_jumpi;
+        table[OP_PC as usize] = Self::op_pc;
+        table[OP_MSIZE as usize] = Self::op_msize;
+        table[OP_GAS as usize] = Self::op_gas;
+        table[OP_JUMPDEST as usize] = Self::op_jumpdest;
+        Self::set_range(&mut table, OP_PUSH0, OP_PUSH32, Self::op_push);
+        Self::set_range(&mut table, OP_DUP1, OP_DUP16, Self::op_dup);
+        Self::set_range(&mut table, OP_SWAP1, OP_SWAP16, Self::op_swap);
+        Self::set_range(&mut table, OP_LOG0, OP_LOG4, Self::op_log);
+        table[OP_CREATE as usize] = Self::op_create;
+        table[OP_CREATE2 as usize] = Self::op_create2;
+        table[OP_CALL as usize] = Self::op_call;
+        table[OP_DELEGATECALL as usize] = Self::op_delegatecall;
+        table[OP_STATICCALL as usize] = Self::op_staticcall;
+        table[OP_RETURN as usize] = Self::op_return;
+        table[OP_REVERT as usize] = Self::op_revert;
+        table[OP_SELFDESTRUCT as usize] = Self::op_selfdestruct;
+        table[OP_INVALID as usize] = Self::op_invalid;
+        table
+    }
 
-            // 0x04: DIV
-            OP_DIV => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = if b.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    a.udiv(&b, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    const fn set_range(
+        table: &mut [OpcodeHandler<'ctx>; 256],
+        first: u8,
+        last: u8,
+        handler: OpcodeHandler<'ctx>,
+    ) {
+        let mut op = first;
+        while op <= last {
+            table[op as usize] = handler;
+            op += 1;
+        }
+    }
 
-            // 0x05: SDIV
-            OP_SDIV => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = if b.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    a.sdiv(&b, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x00: STOP
+    fn op_stop(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        state.last_return_data = Some(ByteVec::new(self.ctx));
+        state.context.output.return_scheme = Some(OP_STOP);
+        Ok(StepOutcome::Halt) // Halt execution
+    }
 
-            // 0x06: MOD
-            OP_MOD => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = if b.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    a.urem(&b, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x01: ADD
+    fn op_add(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let result = a.add(&b, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x07: SMOD
-            OP_SMOD => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = if b.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    a.smod(&b, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x02: MUL
+    fn op_mul(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let result = a.mul_with_abstraction(&b, self.ctx, Some(&self.arith.mul));
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x08: ADDMOD
-            OP_ADDMOD => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let n = self.pop(state)?;
-                let result = if n.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    // (a + b) % n
-                    let sum = a.add(&b, self.ctx);
-                    sum.urem(&n, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x03: SUB
+    fn op_sub(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let result = a.sub(&b, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x09: MULMOD
-            OP_MULMOD => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let n = self.pop(state)?;
-                let result = if n.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(0, 256)
-                } else {
-                    // (a * b) % n
-                    let prod = a.mul(&b, self.ctx);
-                    prod.urem(&n, self.ctx)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x04: DIV
+    fn op_div(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let quotient = a.udiv_with_abstraction(&b, self.ctx, Some(&self.arith.udiv));
+        let result = self.zero_if_zero_divisor(&b, quotient);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x0a: EXP
-            OP_EXP => {
-                let base = self.pop(state)?;
-                let exponent = self.pop(state)?;
-                // For symbolic execution, we need to handle this carefully
-                // For now, use concrete values if available
-                match (base.as_u64(), exponent.as_u64()) {
-                    (Ok(b), Ok(e)) => {
-                        if e > 256 {
-                            // Result would overflow, return 0
-                            self.push(state, CbseBitVec::from_u64(0, 256))?;
-                        } else {
-                            let result = b.saturating_pow(e as u32);
-                            self.push(state, CbseBitVec::from_u64(result, 256))?;
-                        }
-                    }
-                    _ => {
-                        // Symbolic exponentiation - create symbolic result
-                        // In full implementation, this would use Z3's power operation
-                        // For now, return symbolic value
-                        self.push(state, CbseBitVec::from_u64(0, 256))?;
-                    }
-                }
-                state.pc += 1;
-            }
+    /// 0x05: SDIV
+    fn op_sdiv(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let quotient = a.sdiv_with_abstraction(&b, self.ctx, Some(&self.arith.sdiv));
+        let result = self.zero_if_zero_divisor(&b, quotient);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x0b: SIGNEXTEND
-            OP_SIGNEXTEND => {
-                let byte_num = self.pop(state)?;
-                let value = self.pop(state)?;
-
-                // Concrete implementation for now
-                if let Ok(b) = byte_num.as_u64() {
-                    if b < 31 {
-                        let bit_position = (b + 1) * 8;
-                        // Sign extend from bit_position
-                        // This is complex in symbolic execution, simplified for now
-                        self.push(state, value)?;
-                    } else {
-                        self.push(state, value)?;
-                    }
+    /// 0x06: MOD
+    fn op_mod(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let quotient = a.urem_with_abstraction(&b, self.ctx, Some(&self.arith.urem));
+        let result = self.zero_if_zero_divisor(&b, quotient);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x07: SMOD
+    fn op_smod(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let quotient = a.smod_with_abstraction(&b, self.ctx, Some(&self.arith.smod));
+        let result = self.zero_if_zero_divisor(&b, quotient);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x08: ADDMOD
+    fn op_addmod(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let n = self.pop(state)?;
+        let result = if n.is_zero(self.ctx).is_true() {
+            CbseBitVec::from_u64(0, 256)
+        } else {
+            // (a + b) % n
+            let sum = a.add(&b, self.ctx);
+            sum.urem(&n, self.ctx)
+        };
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x09: MULMOD
+    fn op_mulmod(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let n = self.pop(state)?;
+        let result = if n.is_zero(self.ctx).is_true() {
+            CbseBitVec::from_u64(0, 256)
+        } else {
+            // (a * b) % n
+            let prod = a.mul(&b, self.ctx);
+            prod.urem(&n, self.ctx)
+        };
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x0a: EXP
+    fn op_exp(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let base = self.pop(state)?;
+        let exponent = self.pop(state)?;
+        // For symbolic execution, we need to handle this carefully
+        // For now, use concrete values if available
+        match (base.as_u64(), exponent.as_u64()) {
+            (Ok(b), Ok(e)) => {
+                if e > 256 {
+                    // Result would overflow, return 0
+                    self.push(state, CbseBitVec::from_u64(0, 256))?;
                 } else {
-                    self.push(state, value)?;
+                    let result = b.saturating_pow(e as u32);
+                    self.push(state, CbseBitVec::from_u64(result, 256))?;
                 }
-                state.pc += 1;
             }
-
-            // 0x10: LT
-            OP_LT => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let cmp_result = a.ult(&b, self.ctx);
-                let result = self.bool_to_bv(cmp_result);
-                self.push(state, result)?;
-                state.pc += 1;
+            _ => {
+                // Symbolic exponentiation - create symbolic result
+                // In full implementation, this would use Z3's power operation
+                // For now, return symbolic value
+                self.push(state, CbseBitVec::from_u64(0, 256))?;
             }
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x11: GT
-            OP_GT => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let cmp_result = a.ugt(&b, self.ctx);
-                let result = self.bool_to_bv(cmp_result);
-                self.push(state, result)?;
-                state.pc += 1;
+    /// 0x0b: SIGNEXTEND
+    fn op_signextend(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let byte_num = self.pop(state)?;
+        let value = self.pop(state)?;
+
+        // Concrete implementation for now
+        if let Ok(b) = byte_num.as_u64() {
+            if b < 31 {
+                let bit_position = (b + 1) * 8;
+                // Sign extend from bit_position
+                // This is complex in symbolic execution, simplified for now
+                self.push(state, value)?;
+            } else {
+                self.push(state, value)?;
             }
+        } else {
+            self.push(state, value)?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x12: SLT (Signed Less Than)
-            OP_SLT => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let cmp_result = a.slt(&b, self.ctx);
-                let result = self.bool_to_bv(cmp_result);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x10: LT
+    fn op_lt(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let cmp_result = a.ult(&b, self.ctx);
+        let result = cmp_result.to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x13: SGT (Signed Greater Than)
-            OP_SGT => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let cmp_result = a.sgt(&b, self.ctx);
-                let result = self.bool_to_bv(cmp_result);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x11: GT
+    fn op_gt(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let cmp_result = a.ugt(&b, self.ctx);
+        let result = cmp_result.to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x14: EQ
-            OP_EQ => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let cmp_result = a.eq(&b, self.ctx);
-                let result = self.bool_to_bv(cmp_result);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x12: SLT (Signed Less Than)
+    fn op_slt(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let cmp_result = a.slt(&b, self.ctx);
+        let result = cmp_result.to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x15: ISZERO
-            OP_ISZERO => {
-                let a = self.pop(state)?;
-                let result = if a.is_zero(self.ctx).is_true() {
-                    CbseBitVec::from_u64(1, 256)
-                } else {
-                    CbseBitVec::from_u64(0, 256)
-                };
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x13: SGT (Signed Greater Than)
+    fn op_sgt(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let cmp_result = a.sgt(&b, self.ctx);
+        let result = cmp_result.to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x16: AND
-            OP_AND => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.and(&b, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x14: EQ
+    fn op_eq(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let b = self.pop(state)?;
+        let cmp_result = a.eq(&b, self.ctx);
+        let result = cmp_result.to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x17: OR
-            OP_OR => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.or(&b, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x15: ISZERO
+    fn op_iszero(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop(state)?;
+        let result = a.is_zero(self.ctx).to_bitvec(self.ctx, 256);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x18: XOR
-            OP_XOR => {
-                let a = self.pop(state)?;
-                let b = self.pop(state)?;
-                let result = a.xor(&b, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x16: AND
+    fn op_and(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop_word(state)?;
+        let b = self.pop_word(state)?;
+        let result = a.and(&b, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x17: OR
+    fn op_or(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop_word(state)?;
+        let b = self.pop_word(state)?;
+        let result = a.or(&b, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x19: NOT
-            OP_NOT => {
-                let a = self.pop(state)?;
-                let result = a.not(self.ctx);
+    /// 0x18: XOR
+    fn op_xor(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop_word(state)?;
+        let b = self.pop_word(state)?;
+        let result = a.xor(&b, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x19: NOT
+    fn op_not(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let a = self.pop_word(state)?;
+        let result = a.not(self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x1a: BYTE
+    fn op_byte(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let i = self.pop(state)?;
+        let x = self.pop(state)?;
+
+        // Extract byte at position i from x (0 = most significant byte)
+        if let Ok(index) = i.as_u64() {
+            if index < 32 {
+                // Shift right and mask to get the byte
+                let shift_amount = CbseBitVec::from_u64((31 - index) * 8, 256);
+                let shifted = x.lshr(&shift_amount, self.ctx);
+                let mask = CbseBitVec::from_u64(0xFF, 256);
+                let result = shifted.and(&mask, self.ctx);
                 self.push(state, result)?;
-                state.pc += 1;
+            } else {
+                self.push(state, CbseBitVec::from_u64(0, 256))?;
             }
+        } else {
+            // Symbolic index - return 0 for now
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x1a: BYTE
-            OP_BYTE => {
-                let i = self.pop(state)?;
-                let x = self.pop(state)?;
-
-                // Extract byte at position i from x (0 = most significant byte)
-                if let Ok(index) = i.as_u64() {
-                    if index < 32 {
-                        // Shift right and mask to get the byte
-                        let shift_amount = CbseBitVec::from_u64((31 - index) * 8, 256);
-                        let shifted = x.lshr(&shift_amount, self.ctx);
-                        let mask = CbseBitVec::from_u64(0xFF, 256);
-                        let result = shifted.and(&mask, self.ctx);
-                        self.push(state, result)?;
-                    } else {
-                        self.push(state, CbseBitVec::from_u64(0, 256))?;
+    /// 0x1B: SHL
+    fn op_shl(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let shift = self.pop(state)?;
+        let value = self.pop(state)?;
+        let result = value.shl(&shift, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x1C: SHR
+    fn op_shr(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let shift = self.pop(state)?;
+        let value = self.pop(state)?;
+        let result = value.lshr(&shift, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x1d: SAR (Arithmetic right shift)
+    fn op_sar(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let shift = self.pop(state)?;
+        let value = self.pop(state)?;
+        let result = value.ashr(&shift, self.ctx);
+        self.push(state, result)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x20: SHA3 (KECCAK256)
+    fn op_sha3(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if let (Ok(off), Ok(len)) = (offset.as_u64(), length.as_u64()) {
+            if !self.touch_memory(state, off, len) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            let hash = if len == 0 {
+                CbseBitVec::from_bytes(&EMPTY_KECCAK, 256)
+            } else {
+                let (off, len) = (off as usize, len as usize);
+                // Concrete data is hashed for real, reusing the digest of an
+                // input seen before; only genuinely symbolic data needs the
+                // uninterpreted f_sha3 function
+                let concrete = match state.memory.slice(off, off + len)?.unwrap()? {
+                    UnwrappedBytes::Bytes(bytes) => Ok(bytes.to_vec()),
+                    UnwrappedBytes::BitVec(bv) if bv.is_concrete() => Ok(bv.to_bytes()),
+                    UnwrappedBytes::BitVec(bv) => Err(bv),
+                };
+                let (hash, constraints) = match concrete {
+                    Ok(bytes) => {
+                        let digest = self.keccak_cache.digest(&bytes);
+                        self.keccak_registry
+                            .sha3_with_digest(self.ctx, &bytes, digest)
                     }
-                } else {
-                    // Symbolic index - return 0 for now
-                    self.push(state, CbseBitVec::from_u64(0, 256))?;
+                    Err(bv) => self.keccak_registry.sha3(self.ctx, &bv),
+                };
+                for constraint in constraints {
+                    state.path.append(constraint, false)?;
                 }
-                state.pc += 1;
-            }
+                hash
+            };
+            self.push(state, hash)?;
+        } else {
+            // Symbolic offset/length
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x1B: SHL
-            OP_SHL => {
-                let shift = self.pop(state)?;
-                let value = self.pop(state)?;
-                let result = value.shl(&shift, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x30: ADDRESS
+    fn op_address(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let addr_bv = CbseBitVec::from_bytes(&state.address, 160);
+        self.push(state, addr_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x1C: SHR
-            OP_SHR => {
-                let shift = self.pop(state)?;
-                let value = self.pop(state)?;
-                let result = value.lshr(&shift, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x31: BALANCE
+    fn op_balance(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let addr = self.pop(state)?;
+        self.charge_account_access(state, &addr)?;
+        // Symbolic addresses read as zero balance
+        let balance = match addr.as_address() {
+            Ok(account) => self.get_balance(&account),
+            Err(_) => BigUint::default(),
+        };
+        self.push(state, CbseBitVec::from_biguint(balance, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x1d: SAR (Arithmetic right shift)
-            OP_SAR => {
-                let shift = self.pop(state)?;
-                let value = self.pop(state)?;
-                let result = value.ashr(&shift, self.ctx);
-                self.push(state, result)?;
-                state.pc += 1;
-            }
+    /// 0x32: ORIGIN
+    fn op_origin(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let origin_bv = CbseBitVec::from_bytes(&message.origin, 160);
+        self.push(state, origin_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x33: CALLER
+    fn op_caller(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let caller_bv = CbseBitVec::from_bytes(&state.caller, 160);
+        self.push(state, caller_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x34: CALLVALUE
+    fn op_callvalue(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let value_bv = CbseBitVec::from_biguint(state.value.clone(), 256);
+        self.push(state, value_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x20: SHA3 (KECCAK256)
-            OP_SHA3 => {
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                // For now, return a symbolic hash
-                // Full implementation would hash the memory bytes
-                if let (Ok(off), Ok(len)) = (offset.as_u64(), length.as_u64()) {
-                    // In full implementation: hash state.memory[off..off+len]
-                    // For now, create a symbolic hash value
-                    let hash = CbseBitVec::from_u64(0, 256);
-                    self.push(state, hash)?;
+    /// 0x35: CALLDATALOAD
+    fn op_calldataload(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+
+        if let Ok(off) = offset.as_u64() {
+            let word = message.data.get_word(off as usize)?;
+            let word_bv = match word {
+                UnwrappedBytes::BitVec(bv) => bv,
+                UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+            };
+            self.push(state, word_bv)?;
+        } else {
+            // Symbolic offset - create symbolic value
+            let symbolic_word = CbseBitVec::symbolic(self.ctx, "calldata_symbolic", 256);
+            self.push(state, symbolic_word)?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x36: CALLDATASIZE
+    fn op_calldatasize(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let size = CbseBitVec::from_u64(message.data.len() as u64, 256);
+        self.push(state, size)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x37: CALLDATACOPY
+    fn op_calldatacopy(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let dest_offset = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if let (Ok(dest), Ok(off), Ok(len)) =
+            (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
+        {
+            if !self.touch_memory(state, dest, len) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            for i in 0..len {
+                let byte = if (off + i) < message.data.len() as u64 {
+                    message
+                        .data
+                        .get_byte((off + i) as usize)
+                        .unwrap_or(UnwrappedBytes::Bytes(vec![0]))
                 } else {
-                    // Symbolic offset/length
-                    self.push(state, CbseBitVec::from_u64(0, 256))?;
-                }
-                state.pc += 1;
+                    UnwrappedBytes::Bytes(vec![0])
+                };
+                state.memory.set_byte((dest + i) as usize, byte)?;
             }
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x30: ADDRESS
-            OP_ADDRESS => {
-                let addr_bv = CbseBitVec::from_bytes(&state.address, 160);
-                self.push(state, addr_bv)?;
-                state.pc += 1;
-            }
+    /// 0x38: CODESIZE
+    fn op_codesize(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let size = CbseBitVec::from_u64(contract.len() as u64, 256);
+        self.push(state, size)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x31: BALANCE
-            OP_BALANCE => {
-                let addr = self.pop(state)?;
-                // For symbolic execution, return symbolic balance
-                // In full implementation, look up balance for the address
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+    /// 0x39: CODECOPY
+    fn op_codecopy(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let dest_offset = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if let (Ok(dest), Ok(off), Ok(len)) =
+            (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
+        {
+            // Contract::slice zero-pads reads past the end of the code,
+            // and keeps symbolic code bytes (e.g. immutables) intact
+            if !self.touch_memory(state, dest, len) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            let off = off.min(contract.len() as u64) as usize;
+            let (dest, len) = (dest as usize, len as usize);
+            let data = contract.slice(off, len)?;
+            state.memory.set_slice(dest, dest + len, data.unwrap()?)?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x32: ORIGIN
-            OP_ORIGIN => {
-                let origin_bv = CbseBitVec::from_bytes(&message.origin, 160);
-                self.push(state, origin_bv)?;
-                state.pc += 1;
-            }
+    /// 0x3a: GASPRICE
+    fn op_gasprice(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, self.tx.gas_price(self.ctx))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x33: CALLER
-            OP_CALLER => {
-                let caller_bv = CbseBitVec::from_bytes(&state.caller, 160);
-                self.push(state, caller_bv)?;
-                state.pc += 1;
-            }
+    /// 0x3b: EXTCODESIZE
+    fn op_extcodesize(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let addr = self.pop(state)?;
+        self.charge_account_access(state, &addr)?;
+
+        // A symbolic address ends only this path
+        let Some(account) = Self::bv_to_address(&addr) else {
+            let halt = ExceptionalHalt::InvalidParameter("symbolic EXTCODESIZE address".into());
+            return Ok(self.exceptional_halt(state, halt).into());
+        };
+        let size = self
+            .code_at(&account, state, contract)
+            .map_or(0, |code| code.len());
+        self.push(state, CbseBitVec::from_u64(size as u64, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x34: CALLVALUE
-            OP_CALLVALUE => {
-                let value_bv = CbseBitVec::from_u64(state.value, 256);
-                self.push(state, value_bv)?;
-                state.pc += 1;
+    /// 0x3c: EXTCODECOPY
+    fn op_extcodecopy(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let addr = self.pop(state)?;
+        let dest_offset = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+        self.charge_account_access(state, &addr)?;
+
+        // A symbolic address ends only this path
+        let Some(account) = Self::bv_to_address(&addr) else {
+            let halt = ExceptionalHalt::InvalidParameter("symbolic EXTCODECOPY address".into());
+            return Ok(self.exceptional_halt(state, halt).into());
+        };
+
+        // Bytes past the end of the code (or of an absent account) read as zero
+        if let (Ok(dest), Ok(off), Ok(len)) =
+            (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
+        {
+            if !self.touch_memory(state, dest, len) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            let code = self.code_at(&account, state, contract);
+            let code_len = code.map_or(0, |c| c.len()) as u64;
+            let mut data = Vec::with_capacity(len as usize);
+            for i in 0..len {
+                let byte = match code {
+                    Some(c) if off.saturating_add(i) < code_len => {
+                        c.get_byte((off + i) as usize)?
+                    }
+                    _ => 0,
+                };
+                data.push(byte);
             }
+            let dest = dest as usize;
+            state
+                .memory
+                .set_slice(dest, dest + data.len(), UnwrappedBytes::Bytes(data))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x35: CALLDATALOAD
-            OP_CALLDATALOAD => {
-                let offset = self.pop(state)?;
+    /// 0x3d: RETURNDATASIZE
+    fn op_returndatasize(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let size = if let Some(ref data) = state.last_return_data {
+            data.len() as u64
+        } else {
+            0
+        };
+        self.push(state, CbseBitVec::from_u64(size, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                if let Ok(off) = offset.as_u64() {
-                    let word = message.data.get_word(off as usize)?;
-                    let word_bv = match word {
-                        UnwrappedBytes::BitVec(bv) => bv,
-                        UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+    /// 0x3e: RETURNDATACOPY
+    fn op_returndatacopy(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let dest_offset = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if let (Ok(dest), Ok(off), Ok(len)) =
+            (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
+        {
+            if !self.touch_memory(state, dest, len) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            if let Some(ref return_data) = state.last_return_data {
+                for i in 0..len {
+                    let byte = if (off + i) < return_data.len() as u64 {
+                        return_data.get_byte((off + i) as usize)?
+                    } else {
+                        UnwrappedBytes::Bytes(vec![0])
                     };
-                    self.push(state, word_bv)?;
-                } else {
-                    // Symbolic offset - create symbolic value
-                    let symbolic_word = CbseBitVec::symbolic(self.ctx, "calldata_symbolic", 256);
-                    self.push(state, symbolic_word)?;
+                    state.memory.set_byte((dest + i) as usize, byte)?;
                 }
-                state.pc += 1;
             }
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x36: CALLDATASIZE
-            OP_CALLDATASIZE => {
-                let size = CbseBitVec::from_u64(message.data.len() as u64, 256);
-                self.push(state, size)?;
-                state.pc += 1;
-            }
+    /// 0x3f: EXTCODEHASH
+    fn op_extcodehash(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let addr = self.pop(state)?;
+        self.charge_account_access(state, &addr)?;
+
+        // A symbolic address ends only this path
+        let Some(account) = Self::bv_to_address(&addr) else {
+            let halt = ExceptionalHalt::InvalidParameter("symbolic EXTCODEHASH address".into());
+            return Ok(self.exceptional_halt(state, halt).into());
+        };
 
-            // 0x37: CALLDATACOPY
-            OP_CALLDATACOPY => {
-                let dest_offset = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                if let (Ok(dest), Ok(off), Ok(len)) =
-                    (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
-                {
-                    for i in 0..len {
-                        let byte = if (off + i) < message.data.len() as u64 {
-                            message
-                                .data
-                                .get_byte((off + i) as usize)
-                                .unwrap_or(UnwrappedBytes::Bytes(vec![0]))
-                        } else {
-                            UnwrappedBytes::Bytes(vec![0])
-                        };
-                        state.memory.set_byte((dest + i) as usize, byte)?;
-                    }
-                }
-                state.pc += 1;
+        // Nonexistent accounts hash to 0, accounts without code to keccak256("")
+        let hash = match self.code_at(&account, state, contract) {
+            Some(code) => CbseBitVec::from_bytes(&code.code_hash()?, 256),
+            None if self.balance.contains_key(&account) => {
+                CbseBitVec::from_bytes(&EMPTY_KECCAK, 256)
             }
+            None => CbseBitVec::from_u64(0, 256),
+        };
+        self.push(state, hash)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x38: CODESIZE
-            OP_CODESIZE => {
-                let size = CbseBitVec::from_u64(contract.len() as u64, 256);
-                self.push(state, size)?;
-                state.pc += 1;
-            }
+    /// 0x40: BLOCKHASH
+    fn op_blockhash(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let block_num = self.pop_word(state)?;
+        let hash = self.blockhash(&block_num);
+        self.push(state, hash)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x39: CODECOPY
-            OP_CODECOPY => {
-                let dest_offset = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                if let (Ok(dest), Ok(off), Ok(len)) =
-                    (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
-                {
-                    for i in 0..len {
-                        let byte = if (off + i) < contract.len() as u64 {
-                            contract.get_byte((off + i) as usize).unwrap_or(0)
-                        } else {
-                            0
-                        };
-                        let byte_bv = CbseBitVec::from_u64(byte as u64, 8);
-                        state
-                            .memory
-                            .set_byte((dest + i) as usize, UnwrappedBytes::BitVec(byte_bv))?;
-                    }
-                }
-                state.pc += 1;
-            }
+    /// 0x41: COINBASE
+    fn op_coinbase(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, self.block.coinbase(self.ctx))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3a: GASPRICE
-            OP_GASPRICE => {
-                // Return symbolic gas price
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+    /// 0x42: TIMESTAMP
+    fn op_timestamp(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, CbseBitVec::from_u64(1, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3b: EXTCODESIZE
-            OP_EXTCODESIZE => {
-                let _addr = self.pop(state)?;
-                // For symbolic execution, return 1 to indicate code exists
-                // In full implementation, check if address has code
-                self.push(state, CbseBitVec::from_u64(1, 256))?;
-                state.pc += 1;
-            }
+    /// 0x43: NUMBER
+    fn op_number(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, CbseBitVec::from_u64(self.block.number, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3c: EXTCODECOPY
-            OP_EXTCODECOPY => {
-                let _addr = self.pop(state)?;
-                let dest_offset = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                // For symbolic execution, fill with zeros
-                if let (Ok(dest), Ok(_off), Ok(len)) =
-                    (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
-                {
-                    for i in 0..len {
-                        state
-                            .memory
-                            .set_byte((dest + i) as usize, UnwrappedBytes::Bytes(vec![0]))?;
-                    }
-                }
-                state.pc += 1;
-            }
+    /// 0x44: DIFFICULTY
+    fn op_difficulty(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, CbseBitVec::from_u64(0, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3d: RETURNDATASIZE
-            OP_RETURNDATASIZE => {
-                let size = if let Some(ref data) = state.last_return_data {
-                    data.len() as u64
-                } else {
-                    0
-                };
-                self.push(state, CbseBitVec::from_u64(size, 256))?;
-                state.pc += 1;
-            }
+    /// 0x45: GASLIMIT
+    fn op_gaslimit(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, self.block.gas_limit(self.ctx))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3e: RETURNDATACOPY
-            OP_RETURNDATACOPY => {
-                let dest_offset = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                if let (Ok(dest), Ok(off), Ok(len)) =
-                    (dest_offset.as_u64(), offset.as_u64(), length.as_u64())
-                {
-                    if let Some(ref return_data) = state.last_return_data {
-                        for i in 0..len {
-                            let byte = if (off + i) < return_data.len() as u64 {
-                                return_data.get_byte((off + i) as usize)?
-                            } else {
-                                UnwrappedBytes::Bytes(vec![0])
-                            };
-                            state.memory.set_byte((dest + i) as usize, byte)?;
-                        }
-                    }
-                }
-                state.pc += 1;
-            }
+    /// 0x46: CHAINID
+    fn op_chainid(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, CbseBitVec::from_u64(self.block.chainid, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x3f: EXTCODEHASH
-            OP_EXTCODEHASH => {
-                let _addr = self.pop(state)?;
-                // Return a symbolic hash
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+    /// 0x47: SELFBALANCE
+    fn op_selfbalance(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let balance = self.get_balance(&state.address);
+        self.push(state, CbseBitVec::from_biguint(balance, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x40-0x48: Block information opcodes
-            OP_BLOCKHASH => {
-                let _block_num = self.pop(state)?;
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+    /// 0x48: BASEFEE
+    fn op_basefee(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.push(state, CbseBitVec::from_u64(0, 256))?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            OP_COINBASE => {
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+    /// 0x50: POP
+    fn op_pop(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        self.pop(state)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            OP_TIMESTAMP => {
-                self.push(state, CbseBitVec::from_u64(1, 256))?;
-                state.pc += 1;
-            }
+    /// 0x51: MLOAD
+    fn op_mload(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+
+        if let Ok(off) = offset.as_u64() {
+            if !self.touch_memory(state, off, 32) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            // get_word zero-pads any part of the word past the end of memory
+            let word = state.memory.get_word(off as usize)?;
+            let word_bv = match word {
+                UnwrappedBytes::BitVec(bv) => bv,
+                UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
+            };
+            self.push(state, word_bv)?;
+        } else {
+            // Symbolic offset
+            let symbolic_mem = CbseBitVec::symbolic(self.ctx, "memory_symbolic", 256);
+            self.push(state, symbolic_mem)?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x52: MSTORE
+    fn op_mstore(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+        let value = self.pop(state)?;
+
+        if let Ok(off) = offset.as_u64() {
+            if !self.touch_memory(state, off, 32) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            // Narrower stack values (e.g. 160-bit addresses) must fill a full word
+            let word = if value.size() < 256 {
+                value.zero_extend(256, self.ctx)
+            } else {
+                value
+            };
+            state
+                .memory
+                .set_word(off as usize, UnwrappedBytes::BitVec(word))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x53: MSTORE8
+    fn op_mstore8(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+        let value = self.pop(state)?;
+
+        if let Ok(off) = offset.as_u64() {
+            if !self.touch_memory(state, off, 1) {
+                return Ok(self.memory_limit_halt(state).into());
+            }
+            // Write value & 0xff, i.e. the least significant byte
+            let byte_bv = value.byte(value.size_bytes() - 1, self.ctx, 8);
+            state
+                .memory
+                .set_byte(off as usize, UnwrappedBytes::BitVec(byte_bv))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x54: SLOAD
+    fn op_sload(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let slot = self.pop(state)?;
+        self.charge_slot_access(state, &slot)?;
+        let value = self.get_storage(state.address, &slot);
+
+        // Record SLOAD in trace
+        if self.records(TraceEvent::Sload) {
+            state.context.trace.push(TraceElement::Read(StorageRead {
+                slot: slot.to_bytes(),
+                value: value.to_bytes(),
+                transient: false,
+            }));
+        }
+
+        self.push(state, value)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            OP_NUMBER => {
-                self.push(state, CbseBitVec::from_u64(1, 256))?;
-                state.pc += 1;
-            }
+    /// 0x55: SSTORE
+    fn op_sstore(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let slot = self.pop(state)?;
+        let value = self.pop(state)?;
+        self.charge_slot_access(state, &slot)?;
+        self.accrue_sstore_refund(state, &slot, &value);
+
+        // Record SSTORE in trace
+        if self.records(TraceEvent::Sstore) {
+            state.context.trace.push(TraceElement::Write(StorageWrite {
+                slot: slot.to_bytes(),
+                value: value.to_bytes(),
+                transient: false,
+            }));
+        }
 
-            OP_DIFFICULTY => {
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+        // Use symbolic storage with Z3 Arrays
+        // Path conditions from the Store operation will be added to state.path
+        let mut path_conds = Vec::new();
+        self.set_storage(state.address, slot, value, &mut path_conds)?;
 
-            OP_GASLIMIT => {
-                self.push(state, CbseBitVec::from_u64(30_000_000, 256))?;
-                state.pc += 1;
-            }
+        // Add the path conditions from the Store operation
+        for cond in path_conds {
+            state.path.append(cond, false)?;
+        }
 
-            OP_CHAINID => {
-                self.push(state, CbseBitVec::from_u64(1, 256))?;
-                state.pc += 1;
-            }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            OP_SELFBALANCE => {
-                let balance = self.get_balance(&state.address);
-                self.push(state, CbseBitVec::from_u64(balance, 256))?;
-                state.pc += 1;
-            }
+    /// 0x56: JUMP
+    fn op_jump(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let dest = self.pop(state)?;
+        let dest_pc = dest
+            .as_u64()
+            .map_err(|_| CbseException::Internal("Symbolic jump destination".to_string()))?
+            as usize;
+
+        // Verify JUMPDEST
+        if dest_pc >= contract.len() {
+            return Err(CbseException::Internal(
+                "Jump destination out of bounds".to_string(),
+            ));
+        }
 
-            OP_BASEFEE => {
-                self.push(state, CbseBitVec::from_u64(0, 256))?;
-                state.pc += 1;
-            }
+        let dest_opcode = contract.get_byte(dest_pc)?;
+        if dest_opcode != OP_JUMPDEST {
+            return Err(CbseException::Internal(
+                "Invalid jump destination".to_string(),
+            ));
+        }
 
-            // 0x50: POP
-            OP_POP => {
-                self.pop(state)?;
-                state.pc += 1;
-            }
+        state.pc = dest_pc;
+        Ok(StepOutcome::Continue)
+    }
+
+    /// 0x57: JUMPI
+    ///
+    /// Branches as `handle_jumpi` does: `state` continues on the first
+    /// feasible branch and the other, if any, is left in `forked_states` for
+    /// the frame to explore.
+    fn op_jumpi(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let mut branches = self.handle_jumpi(state, message)?.into_iter();
+        let first = branches.next().ok_or_else(|| {
+            CbseException::Internal("JUMPI has no feasible branch left to follow".to_string())
+        })?;
+        *state = first;
+        self.forked_states.extend(branches);
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x51: MLOAD
-            OP_MLOAD => {
-                let offset = self.pop(state)?;
+    /// 0x58: PC
+    fn op_pc(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let pc_bv = CbseBitVec::from_u64(state.pc as u64, 256);
+        self.push(state, pc_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                if let Ok(off) = offset.as_u64() {
-                    let word = state.memory.get_word(off as usize)?;
-                    let word_bv = match word {
-                        UnwrappedBytes::BitVec(bv) => bv,
-                        UnwrappedBytes::Bytes(bytes) => CbseBitVec::from_bytes(&bytes, 256),
-                    };
-                    self.push(state, word_bv)?;
-                } else {
-                    // Symbolic offset
-                    let symbolic_mem = CbseBitVec::symbolic(self.ctx, "memory_symbolic", 256);
-                    self.push(state, symbolic_mem)?;
-                }
-                state.pc += 1;
-            }
+    /// 0x59: MSIZE
+    fn op_msize(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let size = CbseBitVec::from_u64(state.memory_size as u64, 256);
+        self.push(state, size)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x52: MSTORE
-            OP_MSTORE => {
-                let offset = self.pop(state)?;
-                let value = self.pop(state)?;
+    /// 0x5A: GAS
+    fn op_gas(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let gas_bv = CbseBitVec::from_u64(state.gas, 256);
+        self.push(state, gas_bv)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                if let Ok(off) = offset.as_u64() {
-                    state
-                        .memory
-                        .set_word(off as usize, UnwrappedBytes::BitVec(value))?;
-                }
-                state.pc += 1;
-            }
+    /// 0x5B: JUMPDEST
+    fn op_jumpdest(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // No-op
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x53: MSTORE8
-            OP_MSTORE8 => {
-                let offset = self.pop(state)?;
-                let value = self.pop(state)?;
-
-                if let Ok(off) = offset.as_u64() {
-                    let byte_val = (value.as_u64().unwrap_or(0) & 0xFF) as u8;
-                    let byte_bv = CbseBitVec::from_u64(byte_val as u64, 8);
-                    state
-                        .memory
-                        .set_byte(off as usize, UnwrappedBytes::BitVec(byte_bv))?;
+    /// 0x5F-0x7F: PUSH0-PUSH32
+    fn op_push(
+        &mut self,
+        op: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let n = (op - OP_PUSH0) as usize;
+
+        if n == 0 {
+            // PUSH0
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+        } else {
+            // PUSH1-PUSH32
+            let mut bytes = Vec::with_capacity(n);
+            for i in 1..=n {
+                if state.pc + i < contract.len() {
+                    bytes.push(contract.get_byte(state.pc + i)?);
+                } else {
+                    bytes.push(0);
                 }
-                state.pc += 1;
             }
 
-            // 0x54: SLOAD
-            OP_SLOAD => {
-                let slot = self.pop(state)?;
-                let value = self.get_storage(state.address, &slot);
-
-                // Record SLOAD in trace
-                let slot_u64 = slot.as_u64().unwrap_or(0);
-                let value_bytes = value
-                    .as_u64()
-                    .map(|v| v.to_be_bytes().to_vec())
-                    .unwrap_or_else(|_| vec![0; 32]);
-
-                state.context.trace.push(TraceElement::Read(StorageRead {
-                    slot: slot_u64,
-                    value: value_bytes,
-                    transient: false,
-                }));
+            let value = CbseBitVec::from_bytes(&bytes, 256);
+            self.push(state, value)?;
+            state.pc += n;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                self.push(state, value)?;
-                state.pc += 1;
-            }
+    /// 0x80-0x8F: DUP1-DUP16
+    fn op_dup(
+        &mut self,
+        op: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let n = (op - OP_DUP1 + 1) as usize;
+        let value = self.peek(state, n)?;
+        self.push(state, value)?;
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x55: SSTORE
-            OP_SSTORE => {
-                let slot = self.pop(state)?;
-                let value = self.pop(state)?;
-
-                // Record SSTORE in trace
-                let slot_u64 = slot.as_u64().unwrap_or(0);
-                let value_bytes = value
-                    .as_u64()
-                    .map(|v| v.to_be_bytes().to_vec())
-                    .unwrap_or_else(|_| vec![0; 32]);
-
-                state.context.trace.push(TraceElement::Write(StorageWrite {
-                    slot: slot_u64,
-                    value: value_bytes,
-                    transient: false,
-                }));
-
-                // Use symbolic storage with Z3 Arrays
-                // Path conditions from the Store operation will be added to state.path
-                let mut path_conds = Vec::new();
-                self.set_storage(state.address, slot, value, &mut path_conds)?;
-
-                // Add the path conditions from the Store operation
-                for cond in path_conds {
-                    state.path.append(cond, false)?;
-                }
+    /// 0x90-0x9F: SWAP1-SWAP16
+    fn op_swap(
+        &mut self,
+        op: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let n = (op - OP_SWAP1 + 1) as usize;
+        let len = state.stack.len();
+        if len < n + 1 {
+            return Err(CbseException::Halt(ExceptionalHalt::StackUnderflow));
+        }
+        state.stack.swap(len - 1, len - 1 - n);
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                state.pc += 1;
-            }
+    /// 0xA0-0xA4: LOG0-LOG4
+    fn op_log(
+        &mut self,
+        op: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // Calculate number of topics
+        let num_topics = (op - OP_LOG0) as usize;
+
+        // Pop memory location and size
+        let loc = self.pop(state)?;
+        let size = self.pop(state)?;
+        if !self.touch_regions(state, &[(&loc, &size)]) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
 
-            // 0x56: JUMP
-            OP_JUMP => {
-                let dest = self.pop(state)?;
-                let dest_pc = dest
-                    .as_u64()
-                    .map_err(|_| CbseException::Internal("Symbolic jump destination".to_string()))?
-                    as usize;
-
-                // Verify JUMPDEST
-                if dest_pc >= contract.len() {
-                    return Err(CbseException::Internal(
-                        "Jump destination out of bounds".to_string(),
-                    ));
-                }
+        // Get memory location and size as concrete values
+        let loc_concrete = loc.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic LOG memory location not supported".to_string())
+        })? as usize;
 
-                let dest_opcode = contract.get_byte(dest_pc)?;
-                if dest_opcode != OP_JUMPDEST {
-                    return Err(CbseException::Internal(
-                        "Invalid jump destination".to_string(),
-                    ));
-                }
+        let size_concrete = size.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic LOG data size not supported".to_string())
+        })? as usize;
 
-                state.pc = dest_pc;
+        // Pop topics from stack
+        let mut topics = Vec::with_capacity(num_topics);
+        for _ in 0..num_topics {
+            let topic_bv = self.pop(state)?;
+
+            // Convert topic to 32 bytes (topics are Word values)
+            let mut topic_bytes = vec![0u8; 32];
+            if topic_bv.is_concrete() {
+                // Concrete topic - store as big-endian bytes
+                let bytes = topic_bv.to_bytes();
+                topic_bytes[32 - bytes.len()..].copy_from_slice(&bytes);
+            } else {
+                // Symbolic topic - for now use placeholder
+                // Full implementation would need to extract symbolic bytes
+                // This matches Python's behavior of storing symbolic Word values
             }
+            topics.push(topic_bytes);
+        }
 
-            // 0x57: JUMPI
-            OP_JUMPI => {
-                let dest = self.pop(state)?;
-                let cond = self.pop(state)?;
-
-                // Check if condition is concrete or symbolic
-                let cond_bool = cond.is_zero(self.ctx);
-
-                match cond_bool {
-                    cbse_bitvec::CbseBool::Concrete(is_zero) => {
-                        // Concrete condition - take the definite branch
-                        let should_jump = !is_zero;
-
-                        if should_jump {
-                            let dest_pc = dest.as_u64().map_err(|_| {
-                                CbseException::Internal("Symbolic jump destination".to_string())
-                            })? as usize;
-
-                            // Verify JUMPDEST
-                            if dest_pc >= contract.len() {
-                                return Err(CbseException::Internal(
-                                    "Jump destination out of bounds".to_string(),
-                                ));
-                            }
-
-                            let dest_opcode = contract.get_byte(dest_pc)?;
-                            if dest_opcode != OP_JUMPDEST {
-                                return Err(CbseException::Internal(
-                                    "Invalid jump destination".to_string(),
-                                ));
-                            }
-
-                            state.pc = dest_pc;
-                        } else {
-                            state.pc += 1;
-                        }
-                    }
-                    cbse_bitvec::CbseBool::Symbolic(z3_cond) => {
-                        // Symbolic condition - need path branching
-                        // NOTE: Full symbolic JUMPI requires multi-path execution architecture
-                        // For now, we add the constraint and follow one path (concretize)
-                        // TODO: Implement proper path branching with worklist of execution states
-
-                        // Try to check which path is feasible
-                        self.solver.push();
-                        self.solver.assert(&z3_cond);
-                        let can_be_true = self.solver.check() == z3::SatResult::Sat;
-                        self.solver.pop(1);
-
-                        self.solver.push();
-                        self.solver.assert(&z3_cond.not());
-                        let can_be_false = self.solver.check() == z3::SatResult::Sat;
-                        self.solver.pop(1);
-
-                        // For now, follow the "can jump" path if feasible, else fallthrough
-                        // In full implementation, we would create two separate execution states
-                        if can_be_true {
-                            let dest_pc = dest.as_u64().unwrap_or((state.pc + 1) as u64) as usize;
-
-                            if dest_pc < contract.len() {
-                                let dest_opcode = contract.get_byte(dest_pc)?;
-                                if dest_opcode == OP_JUMPDEST {
-                                    // Add constraint that condition is true
-                                    self.solver.assert(&z3_cond);
-                                    state.pc = dest_pc;
-                                } else {
-                                    state.pc += 1;
-                                }
-                            } else {
-                                state.pc += 1;
-                            }
-                        } else if can_be_false {
-                            // Add constraint that condition is false
-                            self.solver.assert(&z3_cond.not());
-                            state.pc += 1;
-                        } else {
-                            // Both paths infeasible - this path is unsat
-                            return Err(CbseException::Internal(
-                                "JUMPI condition leads to unsatisfiable state".to_string(),
-                            ));
-                        }
+        // Extract data from memory
+        let mut data = Vec::with_capacity(size_concrete);
+        for i in 0..size_concrete {
+            let byte = state.memory.get_byte(loc_concrete + i)?;
+            match byte {
+                UnwrappedBytes::BitVec(bv) => {
+                    if let Ok(val) = bv.as_u64() {
+                        data.push(val as u8);
+                    } else {
+                        // Symbolic byte - use 0 as placeholder
+                        data.push(0);
                     }
                 }
+                UnwrappedBytes::Bytes(bytes) => {
+                    // Get first byte from concrete bytes
+                    data.push(bytes.get(0).copied().unwrap_or(0));
+                }
             }
+        }
 
-            // 0x58: PC
-            OP_PC => {
-                let pc_bv = CbseBitVec::from_u64(state.pc as u64, 256);
-                self.push(state, pc_bv)?;
+        // The first log after vm.expectEmit is the expected one, not a real emit
+        if let Some(expected) = &mut state.expected_emit {
+            if expected.template.is_none() {
+                expected.template = Some((topics, data));
                 state.pc += 1;
+                return Ok(StepOutcome::Continue);
             }
+        }
 
-            // 0x59: MSIZE
-            OP_MSIZE => {
-                let size = state.memory.len() as u64;
-                self.push(state, CbseBitVec::from_u64(size, 256))?;
-                state.pc += 1;
-            }
+        // Create EventLog and add to trace
+        if self.records(TraceEvent::Log) {
+            use cbse_traces::EventLog;
+            let log = EventLog::new(message.target, topics, data);
+            state.context.add_trace_element(TraceElement::Log(log));
+        }
 
-            // 0x5A: GAS
-            OP_GAS => {
-                let gas_bv = CbseBitVec::from_u64(state.gas, 256);
-                self.push(state, gas_bv)?;
-                state.pc += 1;
-            }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0x5B: JUMPDEST
-            OP_JUMPDEST => {
-                // No-op
-                state.pc += 1;
-            }
+    /// 0xF0: CREATE
+    fn op_create(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // Pop value, offset, size from stack
+        let value_bv = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let size = self.pop(state)?;
+        if !self.touch_regions(state, &[(&offset, &size)]) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+
+        // Get concrete values
+        let offset_concrete = offset.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic CREATE offset not supported".to_string())
+        })? as usize;
+        let size_concrete = size.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic CREATE size not supported".to_string())
+        })? as usize;
 
-            // 0x5F-0x7F: PUSH0-PUSH32
-            op @ OP_PUSH0..=OP_PUSH32 => {
-                let n = (op - OP_PUSH0) as usize;
+        // Creation fails if the code is over the size limits
+        if self.exceeds_code_size_limits(size_concrete) {
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+            state.pc += 1;
+            return Ok(StepOutcome::Continue);
+        }
 
-                if n == 0 {
-                    // PUSH0
-                    self.push(state, CbseBitVec::from_u64(0, 256))?;
-                } else {
-                    // PUSH1-PUSH32
-                    let mut bytes = Vec::with_capacity(n);
-                    for i in 1..=n {
-                        if state.pc + i < contract.len() {
-                            bytes.push(contract.get_byte(state.pc + i)?);
-                        } else {
-                            bytes.push(0);
-                        }
+        // Extract init code from memory
+        let mut init_code = Vec::with_capacity(size_concrete);
+        for i in 0..size_concrete {
+            let byte = state.memory.get_byte(offset_concrete + i)?;
+            match byte {
+                UnwrappedBytes::BitVec(bv) => {
+                    if let Ok(val) = bv.as_u64() {
+                        init_code.push(val as u8);
+                    } else {
+                        init_code.push(0);
                     }
-
-                    let value = CbseBitVec::from_bytes(&bytes, 256);
-                    self.push(state, value)?;
-                    state.pc += n;
                 }
-                state.pc += 1;
+                UnwrappedBytes::Bytes(bytes) => {
+                    init_code.push(bytes.get(0).copied().unwrap_or(0));
+                }
             }
+        }
 
-            // 0x80-0x8F: DUP1-DUP16
-            op @ OP_DUP1..=OP_DUP16 => {
-                let n = (op - OP_DUP1 + 1) as usize;
-                let value = self.peek(state, n)?;
-                self.push(state, value)?;
-                state.pc += 1;
-            }
+        let new_addr = self.new_address();
+        self.start_create(state, message, new_addr, init_code, &value_bv, OP_CREATE)
+    }
 
-            // 0x90-0x9F: SWAP1-SWAP16
-            op @ OP_SWAP1..=OP_SWAP16 => {
-                let n = (op - OP_SWAP1 + 1) as usize;
-                let len = state.stack.len();
-                if len < n + 1 {
-                    return Err(CbseException::Internal("Stack underflow".to_string()));
-                }
-                state.stack.swap(len - 1, len - 1 - n);
-                state.pc += 1;
-            }
+    /// 0xF5: CREATE2
+    fn op_create2(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // Pop value, offset, size, salt from stack
+        let value_bv = self.pop(state)?;
+        let offset = self.pop(state)?;
+        let size = self.pop(state)?;
+        let salt = self.pop(state)?;
+        if !self.touch_regions(state, &[(&offset, &size)]) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
 
-            // 0xA0-0xA4: LOG0-LOG4
-            op @ OP_LOG0..=OP_LOG4 => {
-                // Check if in static context
-                if message.is_static {
-                    return Err(CbseException::Internal(
-                        "WriteInStaticContext: LOG in static call".to_string(),
-                    ));
-                }
+        // Get concrete values
+        let offset_concrete = offset.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic CREATE2 offset not supported".to_string())
+        })? as usize;
+        let size_concrete = size.as_u64().map_err(|_| {
+            CbseException::Internal("Symbolic CREATE2 size not supported".to_string())
+        })? as usize;
+
+        // Creation fails if the code is over the size limits
+        if self.exceeds_code_size_limits(size_concrete) {
+            self.push(state, CbseBitVec::from_u64(0, 256))?;
+            state.pc += 1;
+            return Ok(StepOutcome::Continue);
+        }
 
-                // Calculate number of topics
-                let num_topics = (op - OP_LOG0) as usize;
-
-                // Pop memory location and size
-                let loc = self.pop(state)?;
-                let size = self.pop(state)?;
-
-                // Get memory location and size as concrete values
-                let loc_concrete = loc.as_u64().map_err(|_| {
-                    CbseException::Internal(
-                        "Symbolic LOG memory location not supported".to_string(),
-                    )
-                })? as usize;
-
-                let size_concrete = size.as_u64().map_err(|_| {
-                    CbseException::Internal("Symbolic LOG data size not supported".to_string())
-                })? as usize;
-
-                // Pop topics from stack
-                let mut topics = Vec::with_capacity(num_topics);
-                for _ in 0..num_topics {
-                    let topic_bv = self.pop(state)?;
-
-                    // Convert topic to 32 bytes (topics are Word values)
-                    let mut topic_bytes = vec![0u8; 32];
-                    if let Ok(val) = topic_bv.as_u64() {
-                        // Concrete topic - store as big-endian bytes
-                        let bytes = val.to_be_bytes();
-                        topic_bytes[24..32].copy_from_slice(&bytes);
+        // Extract init code from memory
+        let mut init_code = Vec::with_capacity(size_concrete);
+        for i in 0..size_concrete {
+            let byte = state.memory.get_byte(offset_concrete + i)?;
+            match byte {
+                UnwrappedBytes::BitVec(bv) => {
+                    if let Ok(val) = bv.as_u64() {
+                        init_code.push(val as u8);
                     } else {
-                        // Symbolic topic - for now use placeholder
-                        // Full implementation would need to extract symbolic bytes
-                        // This matches Python's behavior of storing symbolic Word values
+                        init_code.push(0);
                     }
-                    topics.push(topic_bytes);
                 }
-
-                // Extract data from memory
-                let mut data = Vec::with_capacity(size_concrete);
-                for i in 0..size_concrete {
-                    let byte = state.memory.get_byte(loc_concrete + i)?;
-                    match byte {
-                        UnwrappedBytes::BitVec(bv) => {
-                            if let Ok(val) = bv.as_u64() {
-                                data.push(val as u8);
-                            } else {
-                                // Symbolic byte - use 0 as placeholder
-                                data.push(0);
-                            }
-                        }
-                        UnwrappedBytes::Bytes(bytes) => {
-                            // Get first byte from concrete bytes
-                            data.push(bytes.get(0).copied().unwrap_or(0));
-                        }
-                    }
+                UnwrappedBytes::Bytes(bytes) => {
+                    init_code.push(bytes.get(0).copied().unwrap_or(0));
                 }
+            }
+        }
 
-                // Get contract address from message.target (convert [u8; 20] to u64)
-                // In the trace model, Address is u64, so we take the last 8 bytes
-                let address = u64::from_be_bytes([
-                    message.target[12],
-                    message.target[13],
-                    message.target[14],
-                    message.target[15],
-                    message.target[16],
-                    message.target[17],
-                    message.target[18],
-                    message.target[19],
-                ]);
-
-                // Create EventLog and add to trace
-                use cbse_traces::EventLog;
-                let log = EventLog::new(address, topics, data);
-                state.context.add_trace_element(TraceElement::Log(log));
+        // Compute CREATE2 address deterministically
+        // address = keccak256(0xff || sender || salt || keccak256(init_code))[12:]
 
-                state.pc += 1;
-            }
+        // Hash the init code
+        let init_code_hash = keccak256(&init_code);
 
-            // 0xF0: CREATE
-            OP_CREATE => {
-                // Check if in static context
-                if message.is_static {
-                    return Err(CbseException::Internal(
-                        "WriteInStaticContext: CREATE in static call".to_string(),
-                    ));
-                }
+        // Get salt as 32 bytes
+        let mut salt_bytes = [0u8; 32];
+        if let Ok(salt_val) = salt.as_u64() {
+            let bytes = salt_val.to_be_bytes();
+            salt_bytes[24..32].copy_from_slice(&bytes);
+        } else {
+            // Symbolic salt - use default (could be improved)
+            // Full implementation would handle symbolic salt properly
+        }
 
-                // Pop value, offset, size from stack
-                let value_bv = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let size = self.pop(state)?;
-
-                // Get concrete values
-                let value = value_bv.as_u64().unwrap_or(0);
-                let offset_concrete = offset.as_u64().map_err(|_| {
-                    CbseException::Internal("Symbolic CREATE offset not supported".to_string())
-                })? as usize;
-                let size_concrete = size.as_u64().map_err(|_| {
-                    CbseException::Internal("Symbolic CREATE size not supported".to_string())
-                })? as usize;
-
-                // Extract init code from memory
-                let mut init_code = Vec::with_capacity(size_concrete);
-                for i in 0..size_concrete {
-                    let byte = state.memory.get_byte(offset_concrete + i)?;
-                    match byte {
-                        UnwrappedBytes::BitVec(bv) => {
-                            if let Ok(val) = bv.as_u64() {
-                                init_code.push(val as u8);
-                            } else {
-                                init_code.push(0);
-                            }
-                        }
-                        UnwrappedBytes::Bytes(bytes) => {
-                            init_code.push(bytes.get(0).copied().unwrap_or(0));
-                        }
-                    }
-                }
+        // Construct: 0xff || sender_address || salt || init_code_hash
+        let mut hash_input = Vec::with_capacity(85); // 1 + 20 + 32 + 32
+        hash_input.push(0xff);
+        hash_input.extend_from_slice(&message.target); // sender address (20 bytes)
+        hash_input.extend_from_slice(&salt_bytes); // salt (32 bytes)
+        hash_input.extend_from_slice(&init_code_hash); // init code hash (32 bytes)
 
-                // Generate new address
-                let new_addr = self.new_address();
+        // Hash to get address
+        let address_hash = keccak256(&hash_input);
 
-                // Check for address collision
-                if self.contracts.contains_key(&new_addr) {
-                    // Address collision - push 0 and continue
-                    self.push(state, CbseBitVec::from_u64(0, 256))?;
-                    state.pc += 1;
-                    return Ok(false);
-                }
+        // Take last 20 bytes as address (Ethereum uses rightmost 160 bits)
+        let mut new_addr = [0u8; 20];
+        new_addr.copy_from_slice(&address_hash[12..32]);
 
-                // Create new empty contract at address (will be replaced with deployed code)
-                let empty_bytevec = ByteVec::new(self.ctx);
-                let empty_contract = Contract::new(empty_bytevec, self.ctx, None, None, None);
-                self.contracts.insert(new_addr, empty_contract);
+        self.start_create(state, message, new_addr, init_code, &value_bv, OP_CREATE2)
+    }
 
-                // Initialize storage and balance for new contract
-                self.storage.insert(new_addr, StorageData::new());
+    /// 0xF1: CALL
+    fn op_call(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let gas = self.pop(state)?;
+        let to_addr = self.pop(state)?;
+        let value = self.pop(state)?;
+        let args_offset = self.pop(state)?;
+        let args_length = self.pop(state)?;
+        let ret_offset = self.pop(state)?;
+        let ret_length = self.pop(state)?;
+        let regions = [(&args_offset, &args_length), (&ret_offset, &ret_length)];
+        if !self.touch_regions(state, &regions) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+        self.charge_account_access(state, &to_addr)?;
 
-                // Transfer value from caller to new contract
-                if value > 0 {
-                    let caller_balance = self.get_balance(&message.target);
-                    if caller_balance < value {
-                        // Insufficient funds - push 0 and continue
-                        self.push(state, CbseBitVec::from_u64(0, 256))?;
+        // Extract address
+        if let Some(target) = Self::bv_to_address(&to_addr) {
+            // Check for cheatcode addresses
+            if target == HEVM_ADDRESS || target == SVM_ADDRESS || target == CONSOLE_ADDRESS {
+                self.call_cheatcode(state, &args_offset, &args_length, &ret_offset, &ret_length)?;
+            } else {
+                // Regular contract call
+                let offset = args_offset.as_u64().unwrap_or(0) as usize;
+                let length = args_length.as_u64().unwrap_or(0) as usize;
+                let gas_val = Self::call_gas(state, &gas);
+                let amount = value.as_biguint().unwrap_or_default();
+                let calldata = state.memory.slice(offset, offset + length)?;
+
+                // Mocks only match concrete calldata
+                if let Ok(UnwrappedBytes::Bytes(bytes)) = calldata.unwrap() {
+                    if let Some(halted) = self.try_mocked_call(
+                        state,
+                        target,
+                        &bytes,
+                        OP_CALL,
+                        &ret_offset,
+                        &ret_length,
+                    )? {
                         state.pc += 1;
-                        return Ok(false);
+                        return Ok(halted.into());
                     }
-                    self.set_balance(message.target, caller_balance - value);
-                    let new_balance = self.get_balance(&new_addr);
-                    self.set_balance(new_addr, new_balance + value);
-                }
-
-                // Execute constructor code
-                // In full implementation, this would create a subcall context
-                // For now, we'll simulate success and store the init code as deployed code
-
-                // Create contract from init code
-                let mut deployed_bytevec = ByteVec::new(self.ctx);
-                for (i, &byte) in init_code.iter().enumerate() {
-                    let byte_bv = CbseBitVec::from_u64(byte as u64, 8);
-                    deployed_bytevec.set_byte(i, UnwrappedBytes::BitVec(byte_bv))?;
                 }
-                let deployed_contract = Contract::new(deployed_bytevec, self.ctx, None, None, None);
-                self.contracts.insert(new_addr, deployed_contract);
-
-                // Push new address on stack (as 256-bit value)
-                let addr_val = u64::from_be_bytes([
-                    new_addr[12],
-                    new_addr[13],
-                    new_addr[14],
-                    new_addr[15],
-                    new_addr[16],
-                    new_addr[17],
-                    new_addr[18],
-                    new_addr[19],
-                ]);
-                self.push(state, CbseBitVec::from_u64(addr_val, 256))?;
 
-                state.pc += 1;
-            }
-
-            // 0xF5: CREATE2
-            OP_CREATE2 => {
-                // Check if in static context
-                if message.is_static {
-                    return Err(CbseException::Internal(
-                        "WriteInStaticContext: CREATE2 in static call".to_string(),
-                    ));
+                if self.try_uninterpreted_call(
+                    state,
+                    target,
+                    &calldata,
+                    contract,
+                    &ret_offset,
+                    &ret_length,
+                )? {
+                    state.pc += 1;
+                    return Ok(StepOutcome::Continue);
                 }
 
-                // Pop value, offset, size, salt from stack
-                let value_bv = self.pop(state)?;
-                let offset = self.pop(state)?;
-                let size = self.pop(state)?;
-                let salt = self.pop(state)?;
-
-                // Get concrete values
-                let value = value_bv.as_u64().unwrap_or(0);
-                let offset_concrete = offset.as_u64().map_err(|_| {
-                    CbseException::Internal("Symbolic CREATE2 offset not supported".to_string())
-                })? as usize;
-                let size_concrete = size.as_u64().map_err(|_| {
-                    CbseException::Internal("Symbolic CREATE2 size not supported".to_string())
-                })? as usize;
-
-                // Extract init code from memory
-                let mut init_code = Vec::with_capacity(size_concrete);
-                for i in 0..size_concrete {
-                    let byte = state.memory.get_byte(offset_concrete + i)?;
-                    match byte {
-                        UnwrappedBytes::BitVec(bv) => {
-                            if let Ok(val) = bv.as_u64() {
-                                init_code.push(val as u8);
-                            } else {
-                                init_code.push(0);
-                            }
-                        }
-                        UnwrappedBytes::Bytes(bytes) => {
-                            init_code.push(bytes.get(0).copied().unwrap_or(0));
-                        }
-                    }
+                // An underfunded call fails without running the callee
+                // The value moves before the callee runs, so its SELFBALANCE
+                // includes it. A failed subcall undoes this and every balance
+                // change made further down the call.
+                let balances_before = self.balance.clone();
+                if !self.transfer(state.address, target, &amount) {
+                    self.push(state, CbseBitVec::from_u64(0, 256))?;
+                    state.pc += 1;
+                    return Ok(StepOutcome::Continue);
                 }
 
-                // Compute CREATE2 address deterministically
-                // address = keccak256(0xff || sender || salt || keccak256(init_code))[12:]
-
-                // Hash the init code
-                let init_code_hash = keccak256(&init_code);
-
-                // Get salt as 32 bytes
-                let mut salt_bytes = [0u8; 32];
-                if let Ok(salt_val) = salt.as_u64() {
-                    let bytes = salt_val.to_be_bytes();
-                    salt_bytes[24..32].copy_from_slice(&bytes);
-                } else {
-                    // Symbolic salt - use default (could be improved)
-                    // Full implementation would handle symbolic salt properly
+                // The subcall continues this transaction's access set
+                self.inherited_access = Some(state.access_set.clone());
+
+                // Execute the call - now returns call_context
+                let (success, return_data, gas_used, subcall_context) = self
+                    .execute_call_with_calldata(
+                        target,
+                        state.address,  // caller = current contract address
+                        message.origin, // pass through the original origin
+                        amount,
+                        calldata,
+                        gas_val,
+                        message.is_static,
+                    )?;
+                state.gas -= gas_used.min(state.gas);
+
+                if !success {
+                    self.balance = balances_before;
                 }
 
-                // Construct: 0xff || sender_address || salt || init_code_hash
-                let mut hash_input = Vec::with_capacity(85); // 1 + 20 + 32 + 32
-                hash_input.push(0xff);
-                hash_input.extend_from_slice(&message.target); // sender address (20 bytes)
-                hash_input.extend_from_slice(&salt_bytes); // salt (32 bytes)
-                hash_input.extend_from_slice(&init_code_hash); // init code hash (32 bytes)
-
-                // Hash to get address
-                let address_hash = keccak256(&hash_input);
-
-                // Take last 20 bytes as address (Ethereum uses rightmost 160 bits)
-                let mut new_addr = [0u8; 20];
-                new_addr.copy_from_slice(&address_hash[12..32]);
-
-                // Check for address collision
-                if self.contracts.contains_key(&new_addr) {
-                    // Address collision - push 0 and continue
-                    self.push(state, CbseBitVec::from_u64(0, 256))?;
-                    state.pc += 1;
-                    return Ok(false);
+                if self.finish_subcall(
+                    state,
+                    success,
+                    &return_data,
+                    subcall_context,
+                    &ret_offset,
+                    &ret_length,
+                )? {
+                    return Ok(StepOutcome::Halt);
                 }
+            }
+        } else {
+            // Symbolic address - assume success
+            self.push(state, CbseBitVec::from_u64(1, 256))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                // Create new empty contract at address
-                let empty_bytevec = ByteVec::new(self.ctx);
-                let empty_contract = Contract::new(empty_bytevec, self.ctx, None, None, None);
-                self.contracts.insert(new_addr, empty_contract);
+    /// 0xF4: DELEGATECALL
+    fn op_delegatecall(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // DELEGATECALL: Execute code from target in current contract's context
+        // Preserves msg.sender, msg.value, and storage of caller
+        // Stack: gas, to, args_offset, args_length, ret_offset, ret_length
+
+        let gas = self.pop(state)?;
+        let to_addr = self.pop(state)?;
+        // No value parameter for DELEGATECALL
+        let args_offset = self.pop(state)?;
+        let args_length = self.pop(state)?;
+        let ret_offset = self.pop(state)?;
+        let ret_length = self.pop(state)?;
+        let regions = [(&args_offset, &args_length), (&ret_offset, &ret_length)];
+        if !self.touch_regions(state, &regions) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+        self.charge_account_access(state, &to_addr)?;
+
+        if let Some(code_address) = Self::bv_to_address(&to_addr) {
+            let offset = args_offset.as_u64().unwrap_or(0) as usize;
+            let length = args_length.as_u64().unwrap_or(0) as usize;
+            let gas_val = Self::call_gas(state, &gas);
+            let calldata = state.memory.slice(offset, offset + length)?;
+
+            // Run the callee's code in this frame's context: same address
+            // (and therefore storage), msg.sender and msg.value
+            self.inherited_access = Some(state.access_set.clone());
+            self.delegate_code = Some(code_address);
+            let (success, return_data, gas_used, subcall_context) = self
+                .execute_call_with_calldata(
+                    state.address,
+                    state.caller,
+                    message.origin,
+                    state.value.clone(),
+                    calldata,
+                    gas_val,
+                    message.is_static,
+                )?;
+            state.gas -= gas_used.min(state.gas);
+
+            if self.finish_subcall(
+                state,
+                success,
+                &return_data,
+                subcall_context,
+                &ret_offset,
+                &ret_length,
+            )? {
+                return Ok(StepOutcome::Halt);
+            }
+        } else {
+            // Symbolic address - assume success
+            self.push(state, CbseBitVec::from_u64(1, 256))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-                // Initialize storage for new contract
-                self.storage.insert(new_addr, StorageData::new());
+    /// 0xFA: STATICCALL
+    fn op_staticcall(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // STATICCALL: Read-only call that disallows state modifications
+        // Stack: gas, to, args_offset, args_length, ret_offset, ret_length
+
+        let gas = self.pop(state)?;
+        let to_addr = self.pop(state)?;
+        // No value parameter for STATICCALL (always 0)
+        let args_offset = self.pop(state)?;
+        let args_length = self.pop(state)?;
+        let ret_offset = self.pop(state)?;
+        let ret_length = self.pop(state)?;
+        let regions = [(&args_offset, &args_length), (&ret_offset, &ret_length)];
+        if !self.touch_regions(state, &regions) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+        self.charge_account_access(state, &to_addr)?;
 
-                // Transfer value from caller to new contract
-                if value > 0 {
-                    let caller_balance = self.get_balance(&message.target);
-                    if caller_balance < value {
-                        // Insufficient funds - push 0 and continue
-                        self.push(state, CbseBitVec::from_u64(0, 256))?;
+        // Extract target address
+        if let Some(target) = Self::bv_to_address(&to_addr) {
+            // Check for cheatcode addresses (allowed in static context)
+            if target == HEVM_ADDRESS || target == SVM_ADDRESS || target == CONSOLE_ADDRESS {
+                self.call_cheatcode(state, &args_offset, &args_length, &ret_offset, &ret_length)?;
+            } else {
+                let offset = args_offset.as_u64().unwrap_or(0) as usize;
+                let length = args_length.as_u64().unwrap_or(0) as usize;
+                let gas_val = Self::call_gas(state, &gas);
+                let calldata = state.memory.slice(offset, offset + length)?;
+
+                // Mocks only match concrete calldata
+                if let Ok(UnwrappedBytes::Bytes(bytes)) = calldata.unwrap() {
+                    if let Some(halted) = self.try_mocked_call(
+                        state,
+                        target,
+                        &bytes,
+                        OP_STATICCALL,
+                        &ret_offset,
+                        &ret_length,
+                    )? {
                         state.pc += 1;
-                        return Ok(false);
+                        return Ok(halted.into());
                     }
-                    self.set_balance(message.target, caller_balance - value);
-                    let new_balance = self.get_balance(&new_addr);
-                    self.set_balance(new_addr, new_balance + value);
-                }
-
-                // Create deployed contract from init code
-                let mut deployed_bytevec = ByteVec::new(self.ctx);
-                for (i, &byte) in init_code.iter().enumerate() {
-                    let byte_bv = CbseBitVec::from_u64(byte as u64, 8);
-                    deployed_bytevec.set_byte(i, UnwrappedBytes::BitVec(byte_bv))?;
                 }
-                let deployed_contract = Contract::new(deployed_bytevec, self.ctx, None, None, None);
-                self.contracts.insert(new_addr, deployed_contract);
-
-                // Push new address on stack (as 256-bit value)
-                let addr_val = u64::from_be_bytes([
-                    new_addr[12],
-                    new_addr[13],
-                    new_addr[14],
-                    new_addr[15],
-                    new_addr[16],
-                    new_addr[17],
-                    new_addr[18],
-                    new_addr[19],
-                ]);
-                self.push(state, CbseBitVec::from_u64(addr_val, 256))?;
-
-                state.pc += 1;
-            }
 
-            // 0xF1: CALL
-            OP_CALL => {
-                let gas = self.pop(state)?;
-                let to_addr = self.pop(state)?;
-                let value = self.pop(state)?;
-                let args_offset = self.pop(state)?;
-                let args_length = self.pop(state)?;
-                let ret_offset = self.pop(state)?;
-                let ret_length = self.pop(state)?;
-
-                // Extract address
-                let mut target = [0u8; 20];
-                if let Ok(addr_val) = to_addr.as_u64() {
-                    let addr_bytes = addr_val.to_be_bytes();
-                    target[12..20].copy_from_slice(&addr_bytes);
-
-                    // Check for cheatcode addresses
-                    if target == HEVM_ADDRESS || target == SVM_ADDRESS || target == CONSOLE_ADDRESS
-                    {
-                        // Handle cheatcode
-                        let offset = args_offset.as_u64().unwrap_or(0) as usize;
-                        let length = args_length.as_u64().unwrap_or(0) as usize;
-
-                        let mut calldata = Vec::with_capacity(length);
-                        for i in 0..length {
-                            let byte = state.memory.get_byte(offset + i)?;
-                            match byte {
-                                UnwrappedBytes::Bytes(bytes) => {
-                                    if !bytes.is_empty() {
-                                        calldata.push(bytes[0]);
-                                    } else {
-                                        calldata.push(0);
-                                    }
-                                }
-                                UnwrappedBytes::BitVec(bv) => {
-                                    calldata.push(bv.as_u64().unwrap_or(0) as u8);
-                                }
-                            }
-                        }
-
-                        if calldata.len() >= 4 {
-                            let selector = [calldata[0], calldata[1], calldata[2], calldata[3]];
-                            let result = self.handle_cheatcode(selector, &calldata[4..])?;
-
-                            // Write result to memory
-                            if !result.is_empty() {
-                                let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
-                                let ret_len = ret_length.as_u64().unwrap_or(0) as usize;
-                                let write_len = std::cmp::min(result.len(), ret_len);
-                                for i in 0..write_len {
-                                    let byte_bv = CbseBitVec::from_u64(result[i] as u64, 8);
-                                    state
-                                        .memory
-                                        .set_byte(ret_off + i, UnwrappedBytes::BitVec(byte_bv))?;
-                                }
-                            }
-                        }
-
-                        // Cheatcodes always succeed
-                        self.push(state, CbseBitVec::from_u64(1, 256))?;
-                    } else {
-                        // Regular contract call
-                        let offset = args_offset.as_u64().unwrap_or(0) as usize;
-                        let length = args_length.as_u64().unwrap_or(0) as usize;
-                        let gas_val = gas.as_u64().unwrap_or(30_000_000);
-                        let value_val = value.as_u64().unwrap_or(0);
-
-                        // Extract calldata from memory
-                        let mut calldata = Vec::with_capacity(length);
-                        for i in 0..length {
-                            let byte = state.memory.get_byte(offset + i)?;
-                            match byte {
-                                UnwrappedBytes::Bytes(bytes) => {
-                                    if !bytes.is_empty() {
-                                        calldata.push(bytes[0]);
-                                    } else {
-                                        calldata.push(0);
-                                    }
-                                }
-                                UnwrappedBytes::BitVec(bv) => {
-                                    calldata.push(bv.as_u64().unwrap_or(0) as u8);
-                                }
-                            }
-                        }
-
-                        // Execute the call - now returns call_context
-                        let (success, return_data, _gas_used, subcall_context) = self
-                            .execute_call(
-                                target,
-                                state.address,  // caller = current contract address
-                                message.origin, // pass through the original origin
-                                value_val,
-                                calldata,
-                                gas_val,
-                                false,
-                            )?;
-
-                        // Add subcall context to parent trace
-                        state
-                            .context
-                            .trace
-                            .push(TraceElement::Call(subcall_context));
-
-                        // Write return data to memory
-                        if !return_data.is_empty() {
-                            let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
-                            let ret_len = ret_length.as_u64().unwrap_or(0) as usize;
-                            let write_len = std::cmp::min(return_data.len(), ret_len);
-                            for i in 0..write_len {
-                                let byte_bv = CbseBitVec::from_u64(return_data[i] as u64, 8);
-                                state
-                                    .memory
-                                    .set_byte(ret_off + i, UnwrappedBytes::BitVec(byte_bv))?;
-                            }
-                        }
-
-                        // Push success flag
-                        let success_val = if success { 1 } else { 0 };
-                        self.push(state, CbseBitVec::from_u64(success_val, 256))?;
-                    }
-                } else {
-                    // Symbolic address - assume success
-                    self.push(state, CbseBitVec::from_u64(1, 256))?;
+                self.inherited_access = Some(state.access_set.clone());
+                let (success, return_data, gas_used, subcall_context) = self
+                    .execute_call_with_calldata(
+                        target,
+                        state.address,
+                        message.origin,
+                        BigUint::default(),
+                        calldata,
+                        gas_val,
+                        true, // the callee and everything it calls is read-only
+                    )?;
+                state.gas -= gas_used.min(state.gas);
+
+                if self.finish_subcall(
+                    state,
+                    success,
+                    &return_data,
+                    subcall_context,
+                    &ret_offset,
+                    &ret_length,
+                )? {
+                    return Ok(StepOutcome::Halt);
                 }
-                state.pc += 1;
             }
+        } else {
+            // Symbolic address - assume success
+            self.push(state, CbseBitVec::from_u64(1, 256))?;
+        }
+        state.pc += 1;
+        Ok(StepOutcome::Continue)
+    }
 
-            // 0xF4: DELEGATECALL
-            OP_DELEGATECALL => {
-                // DELEGATECALL: Execute code from target in current contract's context
-                // Preserves msg.sender, msg.value, and storage of caller
-                // Stack: gas, to, args_offset, args_length, ret_offset, ret_length
-
-                let gas = self.pop(state)?;
-                let to_addr = self.pop(state)?;
-                // No value parameter for DELEGATECALL
-                let args_offset = self.pop(state)?;
-                let args_length = self.pop(state)?;
-                let ret_offset = self.pop(state)?;
-                let ret_length = self.pop(state)?;
-
-                // Extract target address
-                let mut target = [0u8; 20];
-                if let Ok(addr_val) = to_addr.as_u64() {
-                    let addr_bytes = addr_val.to_be_bytes();
-                    target[12..20].copy_from_slice(&addr_bytes);
-
-                    // For now, simplified: push success
-                    // Full implementation would execute target's code in caller's context
-                    // with caller's storage and address preserved
-                    self.push(state, CbseBitVec::from_u64(1, 256))?;
-                } else {
-                    // Symbolic address - assume success
-                    self.push(state, CbseBitVec::from_u64(1, 256))?;
-                }
-                state.pc += 1;
-            }
+    /// 0xF3: RETURN
+    fn op_return(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if !self.touch_regions(state, &[(&offset, &length)]) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+        state.last_return_data = Some(Self::memory_output(state, &offset, &length)?);
+        state.context.output.return_scheme = Some(OP_RETURN);
+        Ok(StepOutcome::Halt) // Halt execution
+    }
 
-            // 0xFA: STATICCALL
-            OP_STATICCALL => {
-                // STATICCALL: Read-only call that disallows state modifications
-                // Stack: gas, to, args_offset, args_length, ret_offset, ret_length
-
-                let gas = self.pop(state)?;
-                let to_addr = self.pop(state)?;
-                // No value parameter for STATICCALL (always 0)
-                let args_offset = self.pop(state)?;
-                let args_length = self.pop(state)?;
-                let ret_offset = self.pop(state)?;
-                let ret_length = self.pop(state)?;
-
-                // Extract target address
-                let mut target = [0u8; 20];
-                if let Ok(addr_val) = to_addr.as_u64() {
-                    let addr_bytes = addr_val.to_be_bytes();
-                    target[12..20].copy_from_slice(&addr_bytes);
-
-                    // Check for cheatcode addresses (allowed in static context)
-                    if target == HEVM_ADDRESS || target == SVM_ADDRESS || target == CONSOLE_ADDRESS
-                    {
-                        let offset = args_offset.as_u64().unwrap_or(0) as usize;
-                        let length = args_length.as_u64().unwrap_or(0) as usize;
-
-                        let mut calldata = Vec::with_capacity(length);
-                        for i in 0..length {
-                            let byte = state.memory.get_byte(offset + i)?;
-                            match byte {
-                                UnwrappedBytes::Bytes(bytes) => {
-                                    calldata.push(bytes.get(0).copied().unwrap_or(0));
-                                }
-                                UnwrappedBytes::BitVec(bv) => {
-                                    calldata.push(bv.as_u64().unwrap_or(0) as u8);
-                                }
-                            }
-                        }
-
-                        if calldata.len() >= 4 {
-                            let selector = [calldata[0], calldata[1], calldata[2], calldata[3]];
-                            let result = self.handle_cheatcode(selector, &calldata[4..])?;
-
-                            // Write result to memory
-                            if !result.is_empty() {
-                                let ret_off = ret_offset.as_u64().unwrap_or(0) as usize;
-                                let ret_len = ret_length.as_u64().unwrap_or(0) as usize;
-                                let write_len = std::cmp::min(result.len(), ret_len);
-                                for i in 0..write_len {
-                                    let byte_bv = CbseBitVec::from_u64(result[i] as u64, 8);
-                                    state
-                                        .memory
-                                        .set_byte(ret_off + i, UnwrappedBytes::BitVec(byte_bv))?;
-                                }
-                            }
-                        }
-
-                        self.push(state, CbseBitVec::from_u64(1, 256))?;
-                    } else {
-                        // Regular static call - would need to execute with is_static=true
-                        // For now, simplified: push success
-                        self.push(state, CbseBitVec::from_u64(1, 256))?;
-                    }
-                } else {
-                    // Symbolic address - assume success
-                    self.push(state, CbseBitVec::from_u64(1, 256))?;
-                }
-                state.pc += 1;
-            }
+    /// 0xFD: REVERT
+    fn op_revert(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        let offset = self.pop(state)?;
+        let length = self.pop(state)?;
+
+        if !self.touch_regions(state, &[(&offset, &length)]) {
+            return Ok(self.memory_limit_halt(state).into());
+        }
+        // Revert data is returned like RETURN's, but the call fails
+        state.last_return_data = Some(Self::memory_output(state, &offset, &length)?);
+        state.context.output.return_scheme = Some(OP_REVERT);
+        Ok(StepOutcome::Halt) // Halt execution
+    }
 
-            // 0xF3: RETURN
-            OP_RETURN => {
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                if let (Ok(off), Ok(len)) = (offset.as_u64(), length.as_u64()) {
-                    // Extract return data from memory
-                    let mut return_data = ByteVec::new(self.ctx);
-                    for i in 0..len as usize {
-                        let byte = state.memory.get_byte(off as usize + i)?;
-                        return_data.set_byte(i, byte)?;
-                    }
-                    state.last_return_data = Some(return_data);
-                }
+    /// 0xFF: SELFDESTRUCT
+    fn op_selfdestruct(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // SELFDESTRUCT: Destroy contract and send balance to beneficiary
+        // Pop beneficiary address from stack
+        let beneficiary_bv = self.pop(state)?;
+
+        // A symbolic beneficiary ends only this path
+        let Some(beneficiary) = Self::bv_to_address(&beneficiary_bv) else {
+            let halt =
+                ExceptionalHalt::InvalidParameter("symbolic SELFDESTRUCT beneficiary".into());
+            return Ok(self.exceptional_halt(state, halt).into());
+        };
 
-                return Ok(true); // Halt execution
-            }
+        // Transfer entire balance to beneficiary
+        let self_balance = self.get_balance(&message.target);
+        if self_balance > BigUint::default() {
+            // Set self balance to 0
+            self.set_balance(message.target, 0u64);
 
-            // 0xFD: REVERT
-            OP_REVERT => {
-                let offset = self.pop(state)?;
-                let length = self.pop(state)?;
-
-                // Extract revert data from memory (same as RETURN)
-                if let (Ok(off), Ok(len)) = (offset.as_u64(), length.as_u64()) {
-                    let mut return_data = ByteVec::new(self.ctx);
-                    for i in 0..len as usize {
-                        let byte = state.memory.get_byte(off as usize + i)?;
-                        return_data.set_byte(i, byte)?;
-                    }
-                    state.last_return_data = Some(return_data);
-                }
+            // Add to beneficiary balance
+            let beneficiary_balance = self.get_balance(&beneficiary);
+            self.set_balance(beneficiary, beneficiary_balance + self_balance);
+        }
 
-                return Ok(true); // Halt execution (revert will be detected in execute_call)
-            }
+        // Code and storage are removed once the transaction completes
+        state.selfdestructs.push(message.target);
 
-            // 0xFF: SELFDESTRUCT
-            OP_SELFDESTRUCT => {
-                // SELFDESTRUCT: Destroy contract and send balance to beneficiary
-                // Pop beneficiary address from stack
-                let beneficiary_bv = self.pop(state)?;
-
-                // Get beneficiary address
-                let mut beneficiary = [0u8; 20];
-                if let Ok(addr_val) = beneficiary_bv.as_u64() {
-                    let addr_bytes = addr_val.to_be_bytes();
-                    beneficiary[12..20].copy_from_slice(&addr_bytes);
-                }
+        state.last_return_data = Some(ByteVec::new(self.ctx));
+        state.context.output.return_scheme = Some(OP_SELFDESTRUCT);
+        Ok(StepOutcome::Halt) // Halt execution
+    }
 
-                // Transfer entire balance to beneficiary
-                let self_balance = self.get_balance(&message.target);
-                if self_balance > 0 {
-                    // Set self balance to 0
-                    self.set_balance(message.target, 0);
+    /// 0xFE: INVALID
+    fn op_invalid(
+        &mut self,
+        _opcode: u8,
+        state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        // Exceptional halt: the call fails and all remaining gas is consumed
+        state.context.output.error = Some(ExceptionalHalt::InvalidOpcode(OP_INVALID).to_string());
+        state.last_return_data = Some(ByteVec::new(self.ctx));
+        state.gas = 0;
+        Ok(StepOutcome::Halt)
+    }
 
-                    // Add to beneficiary balance
-                    let beneficiary_balance = self.get_balance(&beneficiary);
-                    self.set_balance(beneficiary, beneficiary_balance + self_balance);
-                }
+    /// Opcodes without an implementation
+    fn op_unimplemented(
+        &mut self,
+        opcode: u8,
+        _state: &mut ExecState<'ctx>,
+        _message: &Message<'ctx>,
+        _contract: &Contract<'ctx>,
+    ) -> CbseResult<StepOutcome> {
+        Err(CbseException::Internal(format!(
+            "Unimplemented opcode: 0x{:02x}",
+            opcode
+        )))
+    }
+}
 
-                // In full implementation, would mark contract for deletion
-                // and remove code after transaction completes
-                // For now, we just halt execution
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MessageBuilder;
+    use std::rc::Rc;
+
+    /// Every `OP_*` constant defined above
+    const DEFINED_OPCODES: &[u8] = &[
+        OP_STOP,
+        OP_ADD,
+        OP_MUL,
+        OP_SUB,
+        OP_DIV,
+        OP_SDIV,
+        OP_MOD,
+        OP_SMOD,
+        OP_ADDMOD,
+        OP_MULMOD,
+        OP_EXP,
+        OP_SIGNEXTEND,
+        OP_LT,
+        OP_GT,
+        OP_SLT,
+        OP_SGT,
+        OP_EQ,
+        OP_ISZERO,
+        OP_AND,
+        OP_OR,
+        OP_XOR,
+        OP_NOT,
+        OP_BYTE,
+        OP_SHL,
+        OP_SHR,
+        OP_SAR,
+        OP_SHA3,
+        OP_ADDRESS,
+        OP_BALANCE,
+        OP_ORIGIN,
+        OP_CALLER,
+        OP_CALLVALUE,
+        OP_CALLDATALOAD,
+        OP_CALLDATASIZE,
+        OP_CALLDATACOPY,
+        OP_CODESIZE,
+        OP_CODECOPY,
+        OP_GASPRICE,
+        OP_EXTCODESIZE,
+        OP_EXTCODECOPY,
+        OP_RETURNDATASIZE,
+        OP_RETURNDATACOPY,
+        OP_EXTCODEHASH,
+        OP_BLOCKHASH,
+        OP_COINBASE,
+        OP_TIMESTAMP,
+        OP_NUMBER,
+        OP_DIFFICULTY,
+        OP_GASLIMIT,
+        OP_CHAINID,
+        OP_SELFBALANCE,
+        OP_BASEFEE,
+        OP_POP,
+        OP_MLOAD,
+        OP_MSTORE,
+        OP_MSTORE8,
+        OP_SLOAD,
+        OP_SSTORE,
+        OP_JUMP,
+        OP_JUMPI,
+        OP_PC,
+        OP_MSIZE,
+        OP_GAS,
+        OP_JUMPDEST,
+        OP_TSTORE,
+        OP_PUSH0,
+        OP_PUSH1,
+        OP_PUSH32,
+        OP_DUP1,
+        OP_DUP16,
+        OP_SWAP1,
+        OP_SWAP16,
+        OP_LOG0,
+        OP_LOG1,
+        OP_LOG2,
+        OP_LOG3,
+        OP_LOG4,
+        OP_CREATE,
+        OP_CALL,
+        OP_CALLCODE,
+        OP_RETURN,
+        OP_DELEGATECALL,
+        OP_CREATE2,
+        OP_STATICCALL,
+        OP_REVERT,
+        OP_INVALID,
+        OP_SELFDESTRUCT,
+    ];
+
+    /// Fresh top-level state for running handlers directly
+    fn test_state<'ctx>(sevm: &SEVM<'ctx>) -> ExecState<'ctx> {
+        let message = CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false);
+        let output = CallOutput::new(None, None, None);
+        let call_context = CallContext::new(message, output, 0);
+        ExecState::new(sevm.ctx, call_context, Rc::clone(&sevm.solver))
+    }
 
-                return Ok(true); // Halt execution
-            }
+    /// Opcodes named above that have no implementation yet
+    const UNIMPLEMENTED: &[u8] = &[OP_TSTORE, OP_CALLCODE];
+
+    #[test]
+    fn test_every_defined_opcode_has_a_handler() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+
+        for &opcode in DEFINED_OPCODES {
+            let mut state = test_state(&sevm);
+
+            // With an empty stack most handlers fail, but none may panic
+            let handler = SEVM::OPCODE_HANDLERS[opcode as usize];
+            let result = handler(&mut sevm, opcode, &mut state, &message, &contract);
+            let unimplemented = matches!(
+                &result,
+                Err(CbseException::Internal(msg)) if msg.starts_with("Unimplemented opcode")
+            );
+            assert_eq!(
+                unimplemented,
+                UNIMPLEMENTED.contains(&opcode),
+                "opcode 0x{:02x}",
+                opcode
+            );
+        }
+    }
 
-            // 0xFE: INVALID
-            OP_INVALID => {
-                return Err(CbseException::Internal("Invalid opcode".to_string()));
-            }
+    #[test]
+    fn test_mload_grows_memory_size_without_writing() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+        let gas = state.gas;
+
+        // MLOAD(0x1000) reads 0x1000..0x1020
+        state.stack.push(CbseBitVec::from_u64(0x1000, 256));
+        sevm.op_mload(OP_MLOAD, &mut state, &message, &contract)
+            .unwrap();
+        assert_eq!(state.memory_size, 0x1020);
+        assert_eq!(state.memory.len(), 0);
+        assert_eq!(gas - state.gas, memory_cost(0x1020));
+
+        // A read below the high-water mark neither grows it nor costs gas
+        state.stack.push(CbseBitVec::from_u64(0, 256));
+        sevm.op_mload(OP_MLOAD, &mut state, &message, &contract)
+            .unwrap();
+        assert_eq!(gas - state.gas, memory_cost(0x1020));
+
+        sevm.op_msize(OP_MSIZE, &mut state, &message, &contract)
+            .unwrap();
+        assert_eq!(state.stack.last().unwrap().as_u64().unwrap(), 0x1020);
+    }
 
-            _ => {
-                return Err(CbseException::Internal(format!(
-                    "Unimplemented opcode: 0x{:02x}",
-                    opcode
-                )));
-            }
+    #[test]
+    fn test_sstore_clearing_accrues_refund() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+        sevm.set_storage(state.address, word(0), word(1), &mut Vec::new())
+            .unwrap();
+
+        // (slot, value, refund counter after the write)
+        let writes = [
+            // Clearing slot 0 earns the clearing refund...
+            (0, 0, 4800),
+            // ...which restoring it takes back, refunding the reset cost instead
+            (0, 1, 2800),
+            // Setting and resetting an empty slot refunds the set cost
+            (1, 5, 2800),
+            (1, 0, 2800 + 19900),
+        ];
+        for (slot, value, refund) in writes {
+            state.stack.push(word(value));
+            state.stack.push(word(slot));
+            sevm.op_sstore(OP_SSTORE, &mut state, &message, &contract)
+                .unwrap();
+            assert_eq!(state.refund, refund, "sstore({}, {})", slot, value);
         }
+    }
+
+    /// Run SHA3 over a 32-byte word stored at memory offset 0
+    fn sha3_word<'ctx>(
+        sevm: &mut SEVM<'ctx>,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        contract: &Contract<'ctx>,
+        word: CbseBitVec<'ctx>,
+    ) -> CbseBitVec<'ctx> {
+        state
+            .memory
+            .set_word(0, UnwrappedBytes::BitVec(word))
+            .unwrap();
+        state.stack.push(CbseBitVec::from_u64(32, 256));
+        state.stack.push(CbseBitVec::from_u64(0, 256));
+        sevm.op_sha3(OP_SHA3, state, message, contract).unwrap();
+        state.stack.pop().unwrap()
+    }
+
+    #[test]
+    fn test_sha3_hashes_concrete_words_directly() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+
+        let word = CbseBitVec::from_u64(0x2a, 256);
+        let hash = sha3_word(&mut sevm, &mut state, &message, &contract, word.clone());
+        let digest = keccak256(&word.to_bytes());
+        assert_eq!(hash.to_bytes(), digest.to_vec());
+        // Still recorded, so storage slots derived from it can be decoded
+        assert!(sevm.keccak_registry.preimage(&digest).is_some());
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let hash = sha3_word(&mut sevm, &mut state, &message, &contract, x);
+        assert!(hash.is_symbolic());
+        assert_eq!(hash.as_z3(&ctx).decl().name(), "f_sha3_256");
+    }
+
+    #[test]
+    fn test_sha3_reuses_cached_digest() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+
+        let word = CbseBitVec::from_u64(0x2a, 256);
+        let first = sha3_word(&mut sevm, &mut state, &message, &contract, word.clone());
+        assert_eq!(sevm.keccak_cache.hits(), 0);
+
+        let second = sha3_word(&mut sevm, &mut state, &message, &contract, word.clone());
+        assert_eq!(sevm.keccak_cache.hits(), 1);
+        assert_eq!(second.to_bytes(), first.to_bytes());
+        assert_eq!(second.to_bytes(), keccak256(&word.to_bytes()).to_vec());
+
+        // Symbolic inputs never reach the cache
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        sha3_word(&mut sevm, &mut state, &message, &contract, x);
+        assert_eq!(sevm.keccak_cache.misses(), 1);
+    }
+
+    /// Run JUMPI to 0x10 on `cond`
+    fn jumpi<'ctx>(
+        sevm: &mut SEVM<'ctx>,
+        state: &mut ExecState<'ctx>,
+        message: &Message<'ctx>,
+        cond: CbseBitVec<'ctx>,
+    ) -> Vec<ExecState<'ctx>> {
+        state.stack.push(cond);
+        state.stack.push(CbseBitVec::from_u64(0x10, 256));
+        sevm.handle_jumpi(state, message).unwrap()
+    }
+
+    #[test]
+    fn test_jumpi_on_constant_condition_does_not_fork() {
+        use z3::ast::BV;
+
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+
+        // ite(1 < 2, 1, 0) is a symbolic term, but always true
+        let (one, two) = (BV::from_u64(&ctx, 1, 256), BV::from_u64(&ctx, 2, 256));
+        let zero = BV::from_u64(&ctx, 0, 256);
+        let cond = CbseBitVec::from_z3(one.bvult(&two).ite(&one, &zero));
+        let next = jumpi(&mut sevm, &mut state, &message, cond);
+        assert_eq!(next.len(), 1);
+        assert_eq!(next[0].pc, 0x10);
+        assert!(next[0].path.conditions.is_empty());
+        assert_eq!(state.path.solver_call_count(), 0);
+
+        // A genuinely symbolic condition still forks after checking both sides
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let next = jumpi(&mut sevm, &mut state, &message, x);
+        assert_eq!(next.len(), 2);
+        assert!(state.path.solver_call_count() > 0);
+    }
 
-        Ok(false) // Continue execution
+    #[test]
+    fn test_jumpi_handler_forks_like_the_frame_loop() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let (message, _) = MessageBuilder::new(&ctx).build().unwrap();
+        let mut state = test_state(&sevm);
+
+        state.stack.push(CbseBitVec::symbolic(&ctx, "x", 256));
+        state.stack.push(CbseBitVec::from_u64(0x10, 256));
+        let halted = sevm
+            .execute_opcode(OP_JUMPI, &mut state, &message, &contract)
+            .unwrap();
+
+        // One branch continues in `state`, the other is left for the frame
+        assert!(!halted);
+        assert_eq!(sevm.forked_states.len(), 1);
+        let mut pcs = [state.pc, sevm.forked_states[0].pc];
+        pcs.sort();
+        assert_eq!(pcs, [1, 0x10]);
     }
 }
@@ -0,0 +1,300 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Concurrent solving of assertion queries
+//!
+//! Z3 contexts are not `Send`, so paths cannot be handed to other threads
+//! directly. Each query travels as its SMT-LIB2 export instead and is parsed
+//! into a context owned by the worker that solves it.
+
+use crate::external_solver::ExternalSolver;
+use cbse_exceptions::CbseException;
+use cbse_solver::SatResult;
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use z3::ast::{Ast, BV};
+use z3::{Context, Params, Solver};
+
+/// Outcome of one assertion query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolvedQuery {
+    pub result: SatResult,
+    /// Values of the query's declared bit-vector constants, when satisfiable
+    pub model: BTreeMap<String, BigUint>,
+    pub error: Option<String>,
+}
+
+impl SolvedQuery {
+    /// Render as the counterexample message shown to the user
+    pub fn describe(&self) -> String {
+        match self.result {
+            SatResult::Sat if self.model.is_empty() => "Counterexample: ∅".to_string(),
+            SatResult::Sat => {
+                let entries: Vec<String> = self
+                    .model
+                    .iter()
+                    .map(|(name, value)| format!("{} = 0x{:x}", name, value))
+                    .collect();
+                format!("Counterexample:\n    {}", entries.join(", "))
+            }
+            SatResult::Unsat => "No counterexample found (path may be infeasible)".to_string(),
+            SatResult::Unknown => format!(
+                "Counterexample: unknown ({})",
+                self.error.as_deref().unwrap_or("solver gave up")
+            ),
+            SatResult::Error => format!(
+                "Counterexample: error ({})",
+                self.error.as_deref().unwrap_or("solver failed")
+            ),
+        }
+    }
+}
+
+/// Solve `queries` on up to `threads` worker threads
+///
+/// Results are returned in query order, so the output does not depend on the
+/// number of threads or on which worker finished first. `timeout` bounds each
+/// in-process query; the external solver has a timeout of its own.
+pub fn solve_queries(
+    queries: &[String],
+    threads: usize,
+    external: Option<&ExternalSolver>,
+    timeout: Option<Duration>,
+) -> Vec<SolvedQuery> {
+    let workers = threads.max(1).min(queries.len());
+    if workers <= 1 {
+        return queries
+            .iter()
+            .map(|query| solve_query(query, external, timeout))
+            .collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<SolvedQuery>>> = Mutex::new(vec![None; queries.len()]);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(query) = queries.get(idx) else {
+                    break;
+                };
+                let solved = solve_query(query, external, timeout);
+                results.lock().unwrap()[idx] = Some(solved);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|solved| solved.expect("every query is claimed by a worker"))
+        .collect()
+}
+
+/// Solve a single query with the external solver, or in-process under
+/// `timeout` if there is none or it could not be run
+pub fn solve_query(
+    smt2: &str,
+    external: Option<&ExternalSolver>,
+    timeout: Option<Duration>,
+) -> SolvedQuery {
+    if let Some(external) = external {
+        let output = external.solve_smt2(smt2);
+        if output.result != SatResult::Error {
+            let model = output
+                .model
+                .map(|model| model.model)
+                .unwrap_or_default()
+                .into_values()
+                .map(|var| (var.full_name, var.value))
+                .collect();
+            return SolvedQuery {
+                result: output.result,
+                model,
+                error: output.error,
+            };
+        }
+        eprintln!(
+            "warning: external solver {} failed: {}",
+            external.command[0],
+            output.error.unwrap_or_default()
+        );
+    }
+
+    solve_in_process(smt2, timeout)
+}
+
+/// Solve a query with Z3 in a context private to the calling thread
+fn solve_in_process(smt2: &str, timeout: Option<Duration>) -> SolvedQuery {
+    let cfg = z3::Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+    if let Some(timeout) = timeout {
+        let mut params = Params::new(&ctx);
+        params.set_u32("timeout", timeout.as_millis().min(u32::MAX as u128) as u32);
+        solver.set_params(&params);
+    }
+    solver.from_string(smt2);
+
+    let result = match solver.check() {
+        z3::SatResult::Sat => SatResult::Sat,
+        z3::SatResult::Unsat => SatResult::Unsat,
+        z3::SatResult::Unknown => SatResult::Unknown,
+    };
+
+    let mut model = BTreeMap::new();
+    if result == SatResult::Sat {
+        if let Some(z3_model) = solver.get_model() {
+            for (name, size) in declared_bitvectors(smt2) {
+                let constant = BV::new_const(&ctx, name.as_str(), size);
+                let value = z3_model
+                    .eval(&constant, true)
+                    .and_then(|value| parse_bv_literal(&value.simplify().to_string()));
+                if let Some(value) = value {
+                    model.insert(name, value);
+                }
+            }
+        }
+    }
+
+    // Reported the same way as an undecided check on the path's own solver
+    let error = (result == SatResult::Unknown).then(|| {
+        let reason = solver
+            .get_reason_unknown()
+            .unwrap_or_else(|| "unknown".to_string());
+        if reason.contains("timeout") || reason.contains("canceled") {
+            CbseException::SolverTimeout.to_string()
+        } else {
+            reason
+        }
+    });
+
+    SolvedQuery {
+        result,
+        model,
+        error,
+    }
+}
+
+/// Names and widths of the `(declare-fun name () (_ BitVec n))` lines in `smt2`
+fn declared_bitvectors(smt2: &str) -> Vec<(String, u32)> {
+    smt2.lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("(declare-fun ")?;
+            let (name, sort) = rest.split_once(" () ")?;
+            let size = sort.strip_prefix("(_ BitVec ")?.strip_suffix("))")?;
+            Some((name.trim_matches('|').to_string(), size.parse().ok()?))
+        })
+        .collect()
+}
+
+/// Parse a Z3 bit-vector literal (`#x..` or `#b..`)
+fn parse_bv_literal(literal: &str) -> Option<BigUint> {
+    if let Some(hex) = literal.strip_prefix("#x") {
+        BigUint::parse_bytes(hex.as_bytes(), 16)
+    } else if let Some(bin) = literal.strip_prefix("#b") {
+        BigUint::parse_bytes(bin.as_bytes(), 2)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Path;
+    use std::rc::Rc;
+
+    /// SMT2 exports of `n` independent paths, path `i` constraining x == i + 1
+    fn independent_queries(n: u64) -> Vec<String> {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        (0..n)
+            .map(|i| {
+                let mut path = Path::new(Rc::new(Solver::new(&ctx)));
+                let x = BV::new_const(&ctx, "halmos_x_uint256", 256);
+                path.append(x._eq(&BV::from_u64(&ctx, i + 1, 256)), true)
+                    .unwrap();
+                path.to_smt2(false)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_solve_queries_all_produce_models() {
+        let queries = independent_queries(6);
+        let solved = solve_queries(&queries, 4, None, None);
+
+        assert_eq!(solved.len(), 6);
+        for (i, query) in solved.iter().enumerate() {
+            assert_eq!(query.result, SatResult::Sat);
+            assert_eq!(query.model["halmos_x_uint256"], BigUint::from(i as u64 + 1));
+            assert!(query.describe().starts_with("Counterexample:\n"));
+        }
+    }
+
+    #[test]
+    fn test_solve_queries_deterministic_across_thread_counts() {
+        let queries = independent_queries(5);
+        let serial = solve_queries(&queries, 1, None, None);
+        for threads in [2, 3, 8] {
+            assert_eq!(solve_queries(&queries, threads, None, None), serial);
+        }
+    }
+
+    #[test]
+    fn test_solve_query_keeps_wide_external_model_values() {
+        if !cfg!(unix) {
+            return;
+        }
+        // Stands in for an external solver answering with x = 2^200
+        let value = format!("#x1{}", "0".repeat(50));
+        let script = format!(
+            "cat > /dev/null; echo sat; \
+             echo '(model (define-fun halmos_x_uint256 () (_ BitVec 256) {}))'",
+            value
+        );
+        let solver = ExternalSolver::new(vec!["sh".into(), "-c".into(), script], None, 0);
+
+        let solved = solve_query(&independent_queries(1)[0], Some(&solver), None);
+        assert_eq!(solved.result, SatResult::Sat);
+        assert_eq!(solved.model["halmos_x_uint256"], BigUint::from(1u32) << 200);
+    }
+
+    #[test]
+    fn test_solve_query_unsat() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut path = Path::new(Rc::new(Solver::new(&ctx)));
+        let x = BV::new_const(&ctx, "x", 8);
+        path.append(x.bvugt(&x), true).unwrap();
+
+        let solved = solve_query(&path.to_smt2(false), None, None);
+        assert_eq!(solved.result, SatResult::Unsat);
+        assert!(solved.model.is_empty());
+    }
+
+    #[test]
+    fn test_solve_query_honours_timeout() {
+        let cfg = z3::Config::new();
+        let ctx = Context::new(&cfg);
+        let mut path = Path::new(Rc::new(Solver::new(&ctx)));
+        // Factoring a product of two 16-bit primes cannot finish in 1ms
+        let mask = BV::from_u64(&ctx, 0xffff, 256);
+        let x = BV::new_const(&ctx, "x", 256).bvand(&mask);
+        let y = BV::new_const(&ctx, "y", 256).bvand(&mask);
+        let product = BV::from_u64(&ctx, 65521 * 65519, 256);
+        path.append(x.bvmul(&y)._eq(&product), true).unwrap();
+
+        let solved = solve_query(&path.to_smt2(false), None, Some(Duration::from_millis(1)));
+        assert_eq!(solved.result, SatResult::Unknown);
+        assert_eq!(
+            solved.describe(),
+            "Counterexample: unknown (Solver timeout)"
+        );
+    }
+}
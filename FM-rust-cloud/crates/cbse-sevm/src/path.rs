@@ -2,11 +2,42 @@
 
 //! Path management for symbolic execution with constraint tracking
 
+use crate::stats::SolverStats;
 use cbse_bitvec::CbseBitVec;
+use cbse_config::Config;
 use cbse_exceptions::{CbseException, CbseResult};
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
-use z3::{ast::Bool as Z3Bool, ast::BV as Z3BV, Context, SatResult, Solver};
+use std::time::{Duration, Instant};
+use z3::{ast::Bool as Z3Bool, ast::BV as Z3BV, Context, Model, Params, SatResult, Solver};
+
+/// Solver time limits, None meaning no limit
+///
+/// Branching checks run on every JUMPI and are kept short; a timeout there
+/// leaves the branch feasible so exploration continues. Assertion checks decide
+/// whether a counterexample exists and get the longer budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverTimeouts {
+    pub branching: Option<Duration>,
+    pub assertion: Option<Duration>,
+}
+
+impl SolverTimeouts {
+    /// Timeouts from `solver_timeout_branching` (ms) and `solver_timeout_assertion` (s)
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            branching: match config.solver_timeout_branching {
+                0 => None,
+                ms => Some(Duration::from_millis(ms)),
+            },
+            assertion: match config.solver_timeout_assertion {
+                0 => None,
+                secs => Some(Duration::from_secs(secs)),
+            },
+        }
+    }
+}
 
 /// Represents a path through symbolic execution with constraint tracking
 ///
@@ -18,12 +49,20 @@ pub struct Path<'ctx> {
     pub solver: Rc<Solver<'ctx>>,
     pub num_scopes: usize,
     pub conditions: Vec<(Z3Bool<'ctx>, bool)>, // Vec of (condition, is_branching)
+    /// Set of conditions already in the path, for structural deduplication
+    pub constraint_set: HashSet<Z3Bool<'ctx>>,
     pub concretization: Concretization<'ctx>,
     pub pending: Vec<Z3Bool<'ctx>>,
     pub related: HashMap<usize, HashSet<usize>>,
     pub var_to_conds: HashMap<String, HashSet<usize>>,
     pub term_to_vars: HashMap<String, HashSet<String>>,
     pub sliced: Option<HashSet<usize>>,
+    /// Cached result of the last feasibility check, cleared when a constraint is added
+    feasible: Cell<Option<bool>>,
+    /// Solver checks issued through this path and its branches
+    solver_stats: Rc<RefCell<SolverStats>>,
+    /// Time limits applied to this path's solver checks
+    pub timeouts: SolverTimeouts,
 }
 
 impl<'ctx> Clone for Path<'ctx> {
@@ -32,12 +71,16 @@ impl<'ctx> Clone for Path<'ctx> {
             solver: self.solver.clone(), // Rc clone - shares solver
             num_scopes: self.num_scopes,
             conditions: self.conditions.clone(),
+            constraint_set: self.constraint_set.clone(),
             concretization: self.concretization.clone(),
             pending: self.pending.clone(),
             related: self.related.clone(),
             var_to_conds: self.var_to_conds.clone(),
             term_to_vars: self.term_to_vars.clone(),
             sliced: self.sliced.clone(),
+            feasible: self.feasible.clone(),
+            solver_stats: Rc::clone(&self.solver_stats),
+            timeouts: self.timeouts,
         }
     }
 }
@@ -86,26 +129,37 @@ impl<'ctx> Default for Concretization<'ctx> {
 impl<'ctx> Path<'ctx> {
     /// Create a new path with the given solver
     pub fn new(solver: Rc<Solver<'ctx>>) -> Self {
+        Self::with_stats(solver, Rc::new(RefCell::new(SolverStats::default())))
+    }
+
+    /// Create a new path whose solver checks are recorded in `solver_stats`
+    pub fn with_stats(solver: Rc<Solver<'ctx>>, solver_stats: Rc<RefCell<SolverStats>>) -> Self {
         Self {
             solver,
             num_scopes: 0,
             conditions: Vec::new(),
+            constraint_set: HashSet::new(),
             concretization: Concretization::new(),
             pending: Vec::new(),
             related: HashMap::new(),
             var_to_conds: HashMap::new(),
             term_to_vars: HashMap::new(),
             sliced: None,
+            feasible: Cell::new(None),
+            solver_stats,
+            timeouts: SolverTimeouts::default(),
         }
     }
 
+    /// Apply `timeouts` to this path and every branch taken from it
+    pub fn with_timeouts(mut self, timeouts: SolverTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
     /// Check if a condition is satisfiable
     pub fn check(&self, cond: &Z3Bool<'ctx>) -> CbseResult<SatResult> {
-        self.solver.push();
-        self.solver.assert(cond);
-        let result = self.solver.check();
-        self.solver.pop(1);
-        Ok(result)
+        Ok(self.check_feasibility(cond))
     }
 
     /// Branch the path with a new condition
@@ -130,12 +184,16 @@ impl<'ctx> Path<'ctx> {
             solver: Rc::clone(&self.solver),
             num_scopes,
             conditions: self.conditions.clone(),
+            constraint_set: self.constraint_set.clone(),
             concretization: self.concretization.clone(),
             pending: vec![cond],
             related: self.related.clone(),
             var_to_conds: self.var_to_conds.clone(),
             term_to_vars: self.term_to_vars.clone(),
             sliced: None,
+            feasible: Cell::new(None),
+            solver_stats: Rc::clone(&self.solver_stats),
+            timeouts: self.timeouts,
         };
 
         Ok(new_path)
@@ -164,6 +222,9 @@ impl<'ctx> Path<'ctx> {
 
         // Add pending conditions
         let pending = std::mem::take(&mut self.pending);
+        if !pending.is_empty() {
+            self.feasible.set(None);
+        }
         for cond in pending {
             self.append(cond, true)?;
         }
@@ -221,18 +282,26 @@ impl<'ctx> Path<'ctx> {
 
     /// Append a condition to the path
     pub fn append(&mut self, cond: Z3Bool<'ctx>, branching: bool) -> CbseResult<()> {
-        // TODO: Simplify condition if needed
-        // For now, skip simplification as it requires Z3 API we don't have access to
+        self.add_constraint(cond, branching)?;
+        Ok(())
+    }
+
+    /// Add a constraint to the path, skipping redundant ones
+    ///
+    /// Conditions that simplify to concrete true are folded away, and conditions
+    /// structurally equal to one already in the path are not sent to the solver again.
+    /// Z3 hash-conses its terms, so structural equality is AST identity.
+    ///
+    /// Returns true if the constraint was added.
+    pub fn add_constraint(&mut self, cond: Z3Bool<'ctx>, branching: bool) -> CbseResult<bool> {
+        use z3::ast::Ast;
 
-        // Skip if condition is trivially true (we can't easily check this without Z3 API)
-        // For now, just add it
+        if cond.simplify().as_bool() == Some(true) {
+            return Ok(false);
+        }
 
-        // Check if already exists (by comparing with existing conditions)
-        let cond_str = format!("{}", cond);
-        for (existing, _) in &self.conditions {
-            if format!("{}", existing) == cond_str {
-                return Ok(());
-            }
+        if self.constraint_set.contains(&cond) {
+            return Ok(false);
         }
 
         // Determine the index for the new condition
@@ -240,6 +309,8 @@ impl<'ctx> Path<'ctx> {
 
         // Add to solver and conditions
         self.solver.assert(&cond);
+        self.feasible.set(None);
+        self.constraint_set.insert(cond.clone());
         self.conditions.push((cond.clone(), branching));
         self.concretization.process_cond(&cond);
 
@@ -252,7 +323,7 @@ impl<'ctx> Path<'ctx> {
             self.var_to_conds.entry(var).or_default().insert(idx);
         }
 
-        Ok(())
+        Ok(true)
     }
 
     /// Extend the path with multiple conditions
@@ -266,10 +337,12 @@ impl<'ctx> Path<'ctx> {
     /// Extend from another path
     pub fn extend_path(&mut self, other: &Path<'ctx>) -> CbseResult<()> {
         self.conditions = other.conditions.clone();
+        self.constraint_set = other.constraint_set.clone();
         self.concretization = other.concretization.clone();
         self.related = other.related.clone();
         self.var_to_conds = other.var_to_conds.clone();
         self.term_to_vars = other.term_to_vars.clone();
+        self.feasible.set(None);
 
         // If the parent path is not sliced, add all constraints to the solver
         if other.sliced.is_none() {
@@ -321,10 +394,33 @@ impl<'ctx> Path<'ctx> {
         result
     }
 
-    /// Generate SMT2 query
-    pub fn to_smt2(&self) -> CbseResult<String> {
-        // Convert solver assertions to SMT2 format
-        Ok(self.solver.to_string())
+    /// Generate an SMT-LIB2 query for this path's constraints
+    ///
+    /// The shared solver holds assertions from sibling paths, so the query is
+    /// rebuilt from `conditions` (restricted to the slice, if any) on a fresh
+    /// solver. The output declares every symbolic constant the constraints
+    /// mention, followed by one `assert` per constraint, and ends with
+    /// `(check-sat)` if `check_sat` is set.
+    pub fn to_smt2(&self, check_sat: bool) -> String {
+        let query = Solver::new(self.solver.get_context());
+        for (idx, (cond, _)) in self.conditions.iter().enumerate() {
+            if self
+                .sliced
+                .as_ref()
+                .map_or(true, |sliced| sliced.contains(&idx))
+            {
+                query.assert(cond);
+            }
+        }
+
+        let mut smt2 = query.to_string();
+        if check_sat {
+            if !smt2.is_empty() && !smt2.ends_with('\n') {
+                smt2.push('\n');
+            }
+            smt2.push_str("(check-sat)\n");
+        }
+        smt2
     }
 
     /// Get a string representation of the path
@@ -349,7 +445,7 @@ impl<'ctx> Path<'ctx> {
     /// Matches Python's model extraction in solve.py at lines 300-400
     pub fn get_model(&self) -> CbseResult<HashMap<String, u64>> {
         // Check if current path is satisfiable
-        if self.solver.check() != SatResult::Sat {
+        if !self.check_assertion()? {
             return Ok(HashMap::new());
         }
 
@@ -411,21 +507,127 @@ impl<'ctx> Path<'ctx> {
     ///
     /// Returns true if there exists a concrete assignment that satisfies all constraints.
     /// This is used to check path feasibility before continuing exploration.
-    pub fn is_feasible(&self) -> bool {
-        self.solver.check() == SatResult::Sat
+    ///
+    /// The result is cached until a new constraint is added, so straight-line
+    /// execution does not re-invoke the solver on an unchanged constraint set.
+    /// The check runs under the branching timeout. If the solver cannot decide,
+    /// SolverTimeout or SolverUnknown is returned and nothing is cached.
+    pub fn is_feasible(&self) -> CbseResult<bool> {
+        if let Some(feasible) = self.feasible.get() {
+            return Ok(feasible);
+        }
+
+        let feasible = match self.timed_check(self.timeouts.branching) {
+            SatResult::Sat => true,
+            SatResult::Unsat => false,
+            SatResult::Unknown => return Err(self.unknown_error()),
+        };
+        self.feasible.set(Some(feasible));
+        Ok(feasible)
+    }
+
+    /// Get the number of solver checks issued through this path and its branches
+    pub fn solver_call_count(&self) -> usize {
+        self.solver_stats.borrow().calls
+    }
+
+    /// Check whether the current assertions are satisfiable under the assertion timeout
+    ///
+    /// An undecided check is an error (SolverTimeout or SolverUnknown), so that
+    /// callers cannot mistake it for unsat.
+    pub fn check_assertion(&self) -> CbseResult<bool> {
+        match self.timed_check(self.timeouts.assertion) {
+            SatResult::Sat => Ok(true),
+            SatResult::Unsat => Ok(false),
+            SatResult::Unknown => Err(self.unknown_error()),
+        }
+    }
+
+    /// Exception for the solver's last Unknown result, based on its reason
+    fn unknown_error(&self) -> CbseException {
+        let reason = self.solver.get_reason_unknown().unwrap_or_default();
+        if reason.contains("timeout") || reason.contains("canceled") {
+            CbseException::SolverTimeout
+        } else {
+            CbseException::SolverUnknown(reason)
+        }
+    }
+
+    /// Run the solver on the current assertions, recording the outcome and timing
+    ///
+    /// The solver is shared between paths, so the timeout is set before every check.
+    fn timed_check(&self, timeout: Option<Duration>) -> SatResult {
+        self.set_timeout(timeout);
+        self.recorded_check()
+    }
+
+    fn set_timeout(&self, timeout: Option<Duration>) {
+        let mut params = Params::new(self.solver.get_context());
+        params.set_u32(
+            "timeout",
+            timeout.map_or(u32::MAX, |t| t.as_millis().min(u32::MAX as u128) as u32),
+        );
+        self.solver.set_params(&params);
+    }
+
+    fn recorded_check(&self) -> SatResult {
+        let started = Instant::now();
+        let result = self.solver.check();
+        self.solver_stats
+            .borrow_mut()
+            .record(result, started.elapsed());
+        result
+    }
+
+    /// Model of the current constraints together with `cond`, or None if unsat
+    ///
+    /// Like `check_feasibility` the condition is only asserted temporarily, but
+    /// the check runs under the assertion timeout since it decides whether a
+    /// counterexample exists.
+    pub fn model_with(&self, cond: &Z3Bool<'ctx>) -> CbseResult<Option<Model<'ctx>>> {
+        self.solver.push();
+        self.solver.assert(cond);
+        let result = match self.timed_check(self.timeouts.assertion) {
+            SatResult::Sat => Ok(self.solver.get_model()),
+            SatResult::Unsat => Ok(None),
+            SatResult::Unknown => Err(self.unknown_error()),
+        };
+        self.solver.pop(1);
+        result
     }
 
     /// Check if a specific condition would be satisfiable with current constraints
     ///
     /// This temporarily adds the condition to the solver, checks satisfiability,
-    /// then removes it. Used for branch feasibility checking.
+    /// then removes it. Used for branch feasibility checking, so it runs under
+    /// the branching timeout and may return Unknown.
     pub fn check_feasibility(&self, cond: &Z3Bool<'ctx>) -> SatResult {
         self.solver.push();
         self.solver.assert(cond);
-        let result = self.solver.check();
+        let result = self.timed_check(self.timeouts.branching);
         self.solver.pop(1);
         result
     }
+
+    /// Feasibility of each of `conds` on top of the current constraints
+    ///
+    /// Same as calling `check_feasibility` per condition, but the solver is
+    /// configured once and every check runs in its own push/pop scope over the
+    /// shared assertion stack. Unknown counts as feasible, so a branching
+    /// timeout keeps the branch alive.
+    pub fn filter_feasible(&self, conds: &[Z3Bool<'ctx>]) -> Vec<bool> {
+        self.set_timeout(self.timeouts.branching);
+        conds
+            .iter()
+            .map(|cond| {
+                self.solver.push();
+                self.solver.assert(cond);
+                let result = self.recorded_check();
+                self.solver.pop(1);
+                result != SatResult::Unsat
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -457,6 +659,117 @@ mod tests {
         assert_eq!(path.conditions.len(), 1);
     }
 
+    #[test]
+    fn test_add_constraint_dedup() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let mut path = Path::new(solver);
+
+        // x > 0, built twice from scratch
+        let x = z3::ast::BV::new_const(&ctx, "x", 256);
+        let zero = z3::ast::BV::from_u64(&ctx, 0, 256);
+        assert!(path.add_constraint(x.bvugt(&zero), true).unwrap());
+        assert!(!path.add_constraint(x.bvugt(&zero), true).unwrap());
+
+        assert_eq!(path.conditions.len(), 1);
+        assert_eq!(path.constraint_set.len(), 1);
+    }
+
+    #[test]
+    fn test_add_constraint_concrete_true() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let mut path = Path::new(solver);
+
+        assert!(!path
+            .add_constraint(z3::ast::Bool::from_bool(&ctx, true), false)
+            .unwrap());
+
+        // 1 == 1 simplifies to true as well
+        let one = z3::ast::BV::from_u64(&ctx, 1, 256);
+        path.append(one._eq(&one), false).unwrap();
+
+        assert!(path.conditions.is_empty());
+        assert!(path.constraint_set.is_empty());
+    }
+
+    #[test]
+    fn test_feasibility_cache() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let mut path = Path::new(solver);
+
+        let x = z3::ast::BV::new_const(&ctx, "x", 256);
+        let zero = z3::ast::BV::from_u64(&ctx, 0, 256);
+        path.append(x.bvugt(&zero), true).unwrap();
+
+        assert!(path.is_feasible().unwrap());
+        assert_eq!(path.solver_call_count(), 1);
+
+        // Straight-line steps: activate with nothing pending, re-check
+        for _ in 0..5 {
+            path.activate().unwrap();
+            assert!(path.is_feasible().unwrap());
+        }
+        assert_eq!(path.solver_call_count(), 1);
+
+        // A duplicate constraint does not invalidate the cache
+        path.append(x.bvugt(&zero), true).unwrap();
+        assert!(path.is_feasible().unwrap());
+        assert_eq!(path.solver_call_count(), 1);
+
+        // A new constraint does
+        path.append(x._eq(&zero), true).unwrap();
+        assert!(!path.is_feasible().unwrap());
+        assert_eq!(path.solver_call_count(), 2);
+    }
+
+    #[test]
+    fn test_feasibility_cache_pending() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let path = Path::new(solver);
+
+        assert!(path.is_feasible().unwrap());
+        assert_eq!(path.solver_call_count(), 1);
+
+        let x = z3::ast::BV::new_const(&ctx, "x", 256);
+        let one = z3::ast::BV::from_u64(&ctx, 1, 256);
+        let mut branched = path.branch(x._eq(&one)).unwrap();
+        branched.activate().unwrap();
+
+        // Pending conditions were added, so the solver must be consulted again
+        assert!(branched.is_feasible().unwrap());
+        assert_eq!(branched.solver_call_count(), 2);
+    }
+
+    #[test]
+    fn test_to_smt2() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let mut path = Path::new(Rc::clone(&solver));
+
+        let x = z3::ast::BV::new_const(&ctx, "x", 256);
+        let zero = z3::ast::BV::from_u64(&ctx, 0, 256);
+        path.append(x.bvugt(&zero), true).unwrap();
+
+        // An assertion made on the shared solver by another path is not part of this one
+        solver.assert(&z3::ast::Bool::new_const(&ctx, "other_path"));
+
+        let smt2 = path.to_smt2(false);
+        assert!(smt2.contains("(declare-fun x () (_ BitVec 256))"));
+        assert!(smt2.contains("(assert (bvugt x"));
+        assert!(!smt2.contains("other_path"));
+        assert!(!smt2.contains("(check-sat)"));
+
+        assert!(path.to_smt2(true).ends_with("(check-sat)\n"));
+    }
+
     #[test]
     fn test_concretization() {
         let mut conc: Concretization = Concretization::new();
@@ -486,7 +799,7 @@ mod tests {
         let mut path = Path::new(solver);
 
         // Initially feasible (no constraints)
-        assert!(path.is_feasible());
+        assert!(path.is_feasible().unwrap());
 
         // Add a simple constraint: x == 5
         let x = z3::ast::BV::new_const(&ctx, "x", 256);
@@ -495,11 +808,137 @@ mod tests {
         path.append(constraint, false).unwrap();
 
         // Still feasible
-        assert!(path.is_feasible());
+        assert!(path.is_feasible().unwrap());
 
         // Check that x == 10 is infeasible given x == 5
         let ten = z3::ast::BV::from_u64(&ctx, 10, 256);
         let new_constraint = x._eq(&ten);
         assert_eq!(path.check_feasibility(&new_constraint), SatResult::Unsat);
     }
+
+    #[test]
+    fn test_filter_feasible_matches_individual_checks() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let solver = Rc::new(Solver::new(&ctx));
+        let mut path = Path::new(solver);
+
+        // x < 10
+        let x = z3::ast::BV::new_const(&ctx, "x", 256);
+        let bv = |n| z3::ast::BV::from_u64(&ctx, n, 256);
+        path.append(x.bvult(&bv(10)), false).unwrap();
+
+        let conds = vec![
+            x._eq(&bv(5)),
+            x._eq(&bv(10)),
+            x._eq(&bv(5)).not(),
+            x.bvugt(&bv(20)),
+            z3::ast::Bool::from_bool(&ctx, true),
+            z3::ast::Bool::from_bool(&ctx, false),
+        ];
+        let calls = path.solver_call_count();
+        let batched = path.filter_feasible(&conds);
+        let individual: Vec<bool> = conds
+            .iter()
+            .map(|cond| path.check_feasibility(cond) != SatResult::Unsat)
+            .collect();
+
+        assert_eq!(batched, vec![true, false, true, false, true, false]);
+        assert_eq!(batched, individual);
+        assert_eq!(path.solver_call_count(), calls + 2 * conds.len());
+
+        // The batch leaves the path's own constraints untouched
+        assert!(path.is_feasible().unwrap());
+        assert!(path.filter_feasible(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_solver_timeouts_from_config() {
+        let mut config = cbse_config::Config::default();
+        config.solver_timeout_branching = 5;
+        config.solver_timeout_assertion = 30;
+        let timeouts = SolverTimeouts::from_config(&config);
+        assert_eq!(timeouts.branching, Some(Duration::from_millis(5)));
+        assert_eq!(timeouts.assertion, Some(Duration::from_secs(30)));
+
+        config.solver_timeout_branching = 0;
+        config.solver_timeout_assertion = 0;
+        assert_eq!(
+            SolverTimeouts::from_config(&config),
+            SolverTimeouts::default()
+        );
+    }
+
+    /// x * y == 65521 * 65519 with 1 < x, y < 2^16: satisfiable, but factoring
+    /// takes the solver well over a millisecond
+    fn factoring_path(ctx: &Context, timeouts: SolverTimeouts) -> Path<'_> {
+        let mut path = Path::new(Rc::new(Solver::new(ctx))).with_timeouts(timeouts);
+        let x = z3::ast::BV::new_const(ctx, "x", 32).zero_ext(32);
+        let y = z3::ast::BV::new_const(ctx, "y", 32).zero_ext(32);
+        let one = z3::ast::BV::from_u64(ctx, 1, 64);
+        let limit = z3::ast::BV::from_u64(ctx, 1 << 16, 64);
+        let product = z3::ast::BV::from_u64(ctx, 65521 * 65519, 64);
+        path.append(x.bvugt(&one), false).unwrap();
+        path.append(y.bvugt(&one), false).unwrap();
+        path.append(x.bvult(&limit), false).unwrap();
+        path.append(y.bvult(&limit), false).unwrap();
+        path.append(x.bvmul(&y)._eq(&product), false).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_branching_timeout_is_reported() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let timeouts = SolverTimeouts {
+            branching: Some(Duration::from_millis(1)),
+            assertion: Some(Duration::from_secs(60)),
+        };
+
+        let path = factoring_path(&ctx, timeouts);
+        let started = Instant::now();
+        assert!(matches!(
+            path.is_feasible(),
+            Err(CbseException::SolverTimeout)
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let branch = path.branch(z3::ast::Bool::from_bool(&ctx, true)).unwrap();
+        assert_eq!(branch.timeouts, timeouts);
+    }
+
+    #[test]
+    fn test_assertion_check_uses_longer_timeout() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let timeouts = SolverTimeouts {
+            branching: Some(Duration::from_millis(1)),
+            assertion: Some(Duration::from_secs(60)),
+        };
+
+        let path = factoring_path(&ctx, timeouts);
+        let branching = path.check_feasibility(&z3::ast::Bool::from_bool(&ctx, true));
+        assert_ne!(branching, SatResult::Unsat);
+        assert!(path.check_assertion().unwrap());
+    }
+
+    #[test]
+    fn test_assertion_timeout_is_not_unsat() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let timeouts = SolverTimeouts {
+            branching: Some(Duration::from_millis(1)),
+            assertion: Some(Duration::from_millis(1)),
+        };
+
+        let path = factoring_path(&ctx, timeouts);
+        assert!(matches!(
+            path.check_assertion(),
+            Err(CbseException::SolverTimeout)
+        ));
+        assert!(matches!(
+            path.get_model(),
+            Err(CbseException::SolverTimeout)
+        ));
+    }
 }
@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Revert reason decoding and panic-code matching
+//!
+//! Solidity reverts with `Error(string)` for `require`/`revert` messages and
+//! `Panic(uint256)` for compiler-inserted checks. Only the panic codes listed in
+//! `--panic-error-codes` count as test failures.
+
+use cbse_config::Config;
+use cbse_constants::PANIC_SELECTOR;
+use cbse_exceptions::{CbseException, CbseResult};
+
+/// Error(string) selector - bytes4(keccak256("Error(string)"))
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xC3, 0x79, 0xA0];
+
+/// Decoded revert data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevertReason {
+    /// Panic(uint256) with a code that fits in a u64
+    Panic(u64),
+    /// Error(string)
+    Error(String),
+    /// Anything else, e.g. a custom error
+    Custom(Vec<u8>),
+}
+
+impl RevertReason {
+    /// Decode revert data, or None if the revert carried no data
+    pub fn decode(returndata: &[u8]) -> Option<Self> {
+        if returndata.is_empty() {
+            return None;
+        }
+
+        let reason = match returndata.get(..4) {
+            Some(selector) if selector == PANIC_SELECTOR => decode_panic(&returndata[4..]),
+            Some(selector) if selector == ERROR_SELECTOR => decode_error(&returndata[4..]),
+            _ => None,
+        };
+        Some(reason.unwrap_or_else(|| RevertReason::Custom(returndata.to_vec())))
+    }
+
+    /// Panic code, if this is a Panic(uint256)
+    pub fn panic_code(&self) -> Option<u64> {
+        match self {
+            RevertReason::Panic(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+fn decode_panic(args: &[u8]) -> Option<RevertReason> {
+    word_as_u64(args.get(..32)?).map(RevertReason::Panic)
+}
+
+fn decode_error(args: &[u8]) -> Option<RevertReason> {
+    let offset = usize::try_from(word_as_u64(args.get(..32)?)?).ok()?;
+    let len_end = offset.checked_add(32)?;
+    let len = usize::try_from(word_as_u64(args.get(offset..len_end)?)?).ok()?;
+    let bytes = args.get(len_end..len_end.checked_add(len)?)?;
+    Some(RevertReason::Error(
+        String::from_utf8_lossy(bytes).into_owned(),
+    ))
+}
+
+/// Value of a 32-byte big-endian word, or None if it doesn't fit in a u64
+fn word_as_u64(word: &[u8]) -> Option<u64> {
+    let (high, low) = word.split_at(24);
+    if high.iter().any(|&b| b != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(low.try_into().ok()?))
+}
+
+/// Panic codes treated as test failures
+///
+/// An empty set matches every code (`--panic-error-codes '*'`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicCodes {
+    codes: Vec<u64>,
+}
+
+impl Default for PanicCodes {
+    /// Only assertion failures, Panic(0x01)
+    fn default() -> Self {
+        Self { codes: vec![0x01] }
+    }
+}
+
+impl PanicCodes {
+    pub fn new(codes: Vec<u64>) -> Self {
+        Self { codes }
+    }
+
+    pub fn from_config(config: &Config) -> CbseResult<Self> {
+        config
+            .parse_panic_error_codes()
+            .map(Self::new)
+            .map_err(|e| CbseException::Internal(format!("invalid panic error codes: {}", e)))
+    }
+
+    pub fn matches(&self, code: u64) -> bool {
+        self.codes.is_empty() || self.codes.contains(&code)
+    }
+
+    /// True if `returndata` is a Panic(uint256) with a matching code
+    pub fn matches_returndata(&self, returndata: &[u8]) -> bool {
+        RevertReason::decode(returndata)
+            .and_then(|reason| reason.panic_code())
+            .map_or(false, |code| self.matches(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panic_data(code: u8) -> Vec<u8> {
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend([0u8; 31]);
+        data.push(code);
+        data
+    }
+
+    #[test]
+    fn test_decode_revert_reasons() {
+        assert_eq!(RevertReason::decode(&[]), None);
+        assert_eq!(
+            RevertReason::decode(&panic_data(0x11)),
+            Some(RevertReason::Panic(0x11))
+        );
+
+        let mut error = ERROR_SELECTOR.to_vec();
+        error.extend([0u8; 31]);
+        error.push(0x20);
+        error.extend([0u8; 31]);
+        error.push(2);
+        error.extend(b"no");
+        error.extend([0u8; 30]);
+        assert_eq!(
+            RevertReason::decode(&error),
+            Some(RevertReason::Error("no".to_string()))
+        );
+
+        // Truncated Panic data is not a panic
+        assert_eq!(
+            RevertReason::decode(&PANIC_SELECTOR),
+            Some(RevertReason::Custom(PANIC_SELECTOR.to_vec()))
+        );
+    }
+
+    #[test]
+    fn test_panic_codes_match_configured_set() {
+        let assertions = PanicCodes::new(vec![0x01]);
+        assert!(assertions.matches_returndata(&panic_data(0x01)));
+        assert!(!assertions.matches_returndata(&panic_data(0x11)));
+
+        let all = PanicCodes::new(Vec::new());
+        assert!(all.matches_returndata(&panic_data(0x01)));
+        assert!(all.matches_returndata(&panic_data(0x11)));
+        assert!(!all.matches_returndata(&[0xde, 0xad]));
+    }
+}
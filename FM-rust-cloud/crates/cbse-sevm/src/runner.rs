@@ -0,0 +1,647 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! High-level entry points for running test functions
+//!
+//! Ties Config, Contract and SEVM together the same way the CLI does, so that
+//! library consumers don't have to assemble the pieces themselves.
+
+use super::{
+    ExternalSolver, PanicCodes, PathResult, RevertReason, RunStats, SolverTimeouts, UnknownCalls,
+    Worklist, SEVM,
+};
+use cbse_bytevec::ByteVec;
+use cbse_cheatcodes::{build_symbolic_calldata, parse_param_types, SolType};
+use cbse_config::Config;
+use cbse_contract::Contract;
+use cbse_exceptions::{CbseException, CbseResult};
+use cbse_traces::TraceEvent;
+use num_bigint::BigUint;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Address the test contract is deployed at (Foundry's default test address)
+pub const TEST_ADDRESS: [u8; 20] = [
+    0x7F, 0xA9, 0x38, 0x5b, 0xE1, 0x02, 0xac, 0x3E, 0xAc, 0x29, 0x74, 0x83, 0xDd, 0x62, 0x33, 0xD6,
+    0x2b, 0x3e, 0x14, 0x96,
+];
+
+/// Sender of top-level test calls (Foundry's default caller)
+pub const TEST_CALLER: [u8; 20] = [
+    0x18, 0x04, 0xc8, 0xAB, 0x1F, 0x12, 0xE6, 0xbb, 0xf3, 0x89, 0x4d, 0x40, 0x83, 0xf3, 0x3e, 0x07,
+    0x30, 0x9d, 0x1f, 0x38,
+];
+
+/// Result of running a single test function
+///
+/// Describes the path `classify_paths` reports: the first failing path, or
+/// the first path if the test passed or every path reverted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestOutcome {
+    /// True if no path hit a configured panic code or a violated assertion
+    /// cheatcode, and some path did not revert for any other reason than a
+    /// panic outside that set
+    pub passed: bool,
+    /// Panic code returned by the test, if it reverted with Panic(uint256)
+    pub panic_code: Option<u64>,
+    /// Counterexample for the failing path, if one was found
+    pub counterexample: Option<String>,
+    /// True if the test failed but the solver timed out or gave up on the
+    /// counterexample query, so the failure is unconfirmed
+    pub inconclusive: bool,
+    pub gas_used: u64,
+    pub returndata: Vec<u8>,
+    /// Calls made before the failing check (invariant tests only)
+    pub call_sequence: Vec<[u8; 4]>,
+    /// Paths, steps and solver usage of the test call
+    pub stats: RunStats,
+}
+
+/// A test contract and its functions, as (signature, selector), to run in a suite
+#[derive(Clone)]
+pub struct SuiteContract<'ctx> {
+    pub name: String,
+    pub contract: Contract<'ctx>,
+    pub functions: Vec<(String, [u8; 4])>,
+}
+
+/// Result of one test function run by `run_suite`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    pub contract: String,
+    pub function: String,
+    pub outcome: TestOutcome,
+}
+
+/// Results of every test function run by `run_suite`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SuiteResult {
+    pub results: Vec<TestResult>,
+    pub total_time: Duration,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Run every selected test function of every selected contract
+///
+/// Contracts are selected by `config.contract` (an exact name) or else
+/// `config.match_contract`, and functions by `config.match_test` or else
+/// `config.function`, the same way the CLI selects them. Each function is
+/// called with symbolic arguments of the types in its signature.
+pub fn run_suite<'ctx>(
+    config: &Config,
+    contracts: &[SuiteContract<'ctx>],
+) -> CbseResult<SuiteResult> {
+    let start = Instant::now();
+    let filters = config
+        .compile_filters()
+        .map_err(|e| CbseException::Internal(format!("{:#}", e)))?;
+
+    let mut suite = SuiteResult::default();
+    for contract in contracts {
+        if !filters.matches_contract(&contract.name) {
+            continue;
+        }
+        for (function, selector) in &contract.functions {
+            if !filters.matches_test(function) {
+                continue;
+            }
+            let param_types = parse_param_types(function)?;
+            let outcome = run_test(config, contract.contract.clone(), *selector, &param_types)?;
+            if outcome.passed {
+                suite.passed += 1;
+            } else {
+                suite.failed += 1;
+            }
+            suite.results.push(TestResult {
+                contract: contract.name.clone(),
+                function: function.clone(),
+                outcome,
+            });
+        }
+    }
+
+    suite.total_time = start.elapsed();
+    Ok(suite)
+}
+
+/// Run the test function identified by `selector` on `contract`
+///
+/// The test is called with a fresh symbol per parameter in `param_types`,
+/// once for each of `config.default_bytes_lengths` if any parameter is
+/// dynamic. It fails if any of those calls fails. Use `run_test_with_args`
+/// to pass specific ABI-encoded arguments.
+pub fn run_test<'ctx>(
+    config: &Config,
+    contract: Contract<'ctx>,
+    selector: [u8; 4],
+    param_types: &[SolType],
+) -> CbseResult<TestOutcome> {
+    let bytes_lengths = bytes_lengths_from_config(config)?;
+    let calldatas =
+        build_symbolic_calldata(selector, param_types, 0, &bytes_lengths, contract.ctx())?;
+
+    let mut last_outcome = None;
+    for calldata in calldatas {
+        let args = calldata.slice(4, calldata.len())?;
+        let outcome = run_test_with_args(config, contract.clone(), selector, args)?;
+        if !outcome.passed {
+            return Ok(outcome);
+        }
+        last_outcome = Some(outcome);
+    }
+    Ok(last_outcome.expect("at least one calldata is built"))
+}
+
+/// Run the test function identified by `selector` with the given encoded arguments
+pub fn run_test_with_args<'ctx>(
+    config: &Config,
+    contract: Contract<'ctx>,
+    selector: [u8; 4],
+    args: ByteVec<'ctx>,
+) -> CbseResult<TestOutcome> {
+    let mut sevm = setup_sevm(config, contract)?;
+    call_test(&mut sevm, selector, args)
+}
+
+/// Run the invariant identified by `invariant` against sequences of calls
+///
+/// Explores every sequence of up to `config.invariant_depth` calls to the
+/// `targets`, given as (selector, parameter types), checking the invariant
+/// after each sequence. Every call gets fresh symbolic arguments, once for
+/// each of `config.default_bytes_lengths` if the target has dynamic
+/// parameters. Sequences are forked through a worklist, so
+/// `config.exploration` decides whether short or long sequences are tried
+/// first.
+///
+/// A sequence stops at a call that reverts. Returns the first violation
+/// found, of the invariant or of an assertion in a target call.
+pub fn run_invariant<'ctx>(
+    config: &Config,
+    contract: Contract<'ctx>,
+    invariant: [u8; 4],
+    targets: &[([u8; 4], Vec<SolType>)],
+) -> CbseResult<TestOutcome> {
+    let ctx = contract.ctx();
+    let bytes_lengths = bytes_lengths_from_config(config)?;
+
+    // A call in a sequence is a target and which of its calldata shapes it uses
+    let mut calls = Vec::new();
+    for (target, (selector, param_types)) in targets.iter().enumerate() {
+        let shapes = build_symbolic_calldata(*selector, param_types, 0, &bytes_lengths, ctx)?;
+        calls.extend((0..shapes.len()).map(|shape| (target, shape)));
+    }
+    let selectors = |sequence: &[(usize, usize)]| -> Vec<[u8; 4]> {
+        sequence
+            .iter()
+            .map(|&(target, _)| targets[target].0)
+            .collect()
+    };
+
+    let mut worklist: Worklist<Vec<(usize, usize)>> =
+        Worklist::with_strategy(config.exploration.parse()?);
+    worklist.push(Vec::new());
+
+    let mut last_outcome = None;
+    'sequences: while let Some(sequence) = worklist.pop() {
+        // Storage lives in the SEVM, so every sequence is replayed from a fresh deployment
+        let mut sevm = setup_sevm(config, contract.clone())?;
+        let mut symbol_id_base = 0;
+        for (step, &(target, shape)) in sequence.iter().enumerate() {
+            let (selector, param_types) = &targets[target];
+            // Each call has symbols of its own, not shared with earlier calls
+            let calldata = build_symbolic_calldata(
+                *selector,
+                param_types,
+                symbol_id_base,
+                &bytes_lengths,
+                ctx,
+            )?
+            .swap_remove(shape);
+            symbol_id_base += param_types.len();
+
+            let mut outcome = call_test(&mut sevm, *selector, calldata.slice(4, calldata.len())?)?;
+            if outcome.passed {
+                continue;
+            }
+            let violated = outcome.counterexample.is_some()
+                || outcome
+                    .panic_code
+                    .map_or(false, |code| sevm.panic_codes.matches(code));
+            if violated {
+                outcome.call_sequence = selectors(&sequence[..=step]);
+                return Ok(outcome);
+            }
+            // The reverted call's writes are still in the SEVM
+            worklist.completed_paths += 1;
+            continue 'sequences;
+        }
+
+        let mut outcome = call_test(&mut sevm, invariant, ByteVec::new(ctx))?;
+        worklist.completed_paths += 1;
+        if !outcome.passed {
+            outcome.call_sequence = selectors(&sequence);
+            return Ok(outcome);
+        }
+
+        if sequence.len() < config.invariant_depth {
+            for call in &calls {
+                let mut next = sequence.clone();
+                next.push(*call);
+                worklist.push(next);
+            }
+        }
+        last_outcome = Some(outcome);
+    }
+
+    Ok(last_outcome.expect("the empty sequence is always checked"))
+}
+
+/// Create an SEVM configured from `config` with `contract` deployed at TEST_ADDRESS
+pub fn setup_sevm<'ctx>(config: &Config, contract: Contract<'ctx>) -> CbseResult<SEVM<'ctx>> {
+    let mut sevm = SEVM::new(contract.ctx());
+    sevm.strategy = config.exploration.parse()?;
+    sevm.ffi = config.ffi;
+    sevm.print_steps = config.print_steps;
+    sevm.print_mem = config.print_mem;
+    sevm.flamegraph = config.flamegraph;
+    sevm.profile_instructions = config.profile_instructions;
+    sevm.external_solver = ExternalSolver::from_config(config)?;
+    sevm.solver_timeouts = SolverTimeouts::from_config(config);
+    sevm.solver_threads = config.get_solver_threads();
+    sevm.max_memory_size = config.max_memory_size;
+    sevm.gas_limit = config.gas_limit;
+    sevm.block.chainid = config.chain_id;
+    sevm.panic_codes = PanicCodes::from_config(config)?;
+    sevm.unknown_calls = UnknownCalls::from_config(config)?;
+    sevm.bytes_lengths = bytes_lengths_from_config(config)?;
+    sevm.trace_filter = trace_filter_from_config(config)?;
+    sevm.deploy_contract(TEST_ADDRESS, contract);
+    Ok(sevm)
+}
+
+/// Lengths tried for dynamic parameters, from `config.default_bytes_lengths`
+fn bytes_lengths_from_config(config: &Config) -> CbseResult<Vec<usize>> {
+    config
+        .parse_default_bytes_lengths()
+        .map_err(|e| CbseException::Internal(format!("invalid default bytes lengths: {}", e)))
+}
+
+/// Trace events to record, from `config.trace_events` (all events by default)
+pub fn trace_filter_from_config(config: &Config) -> CbseResult<HashSet<TraceEvent>> {
+    let events = config
+        .parse_trace_events()
+        .map_err(|e| CbseException::Internal(format!("invalid trace events: {}", e)))?;
+    Ok(events
+        .into_iter()
+        .map(|event| match event {
+            cbse_config::TraceEvent::Log => TraceEvent::Log,
+            cbse_config::TraceEvent::SLoad => TraceEvent::Sload,
+            cbse_config::TraceEvent::SStore => TraceEvent::Sstore,
+        })
+        .collect())
+}
+
+/// Call `selector` on the deployed test contract and classify every path
+fn call_test<'ctx>(
+    sevm: &mut SEVM<'ctx>,
+    selector: [u8; 4],
+    args: ByteVec<'ctx>,
+) -> CbseResult<TestOutcome> {
+    let mut calldata = ByteVec::from_bytes(selector.to_vec(), sevm.ctx)?;
+    calldata.append_bytevec(&args);
+
+    sevm.counterexample = None;
+    sevm.inconclusive = false;
+    sevm.reset_stats();
+    let mut paths = sevm.explore(
+        TEST_ADDRESS,
+        TEST_CALLER,
+        TEST_CALLER, // origin = caller for top-level calls
+        BigUint::default(),
+        calldata,
+        sevm.gas_limit,
+        false,
+    )?;
+
+    let (passed, reported) = classify_paths(sevm, &paths);
+    let path = paths.swap_remove(reported);
+    Ok(TestOutcome {
+        passed,
+        panic_code: panic_code(&path.return_data),
+        counterexample: sevm.counterexample.take(),
+        inconclusive: sevm.inconclusive,
+        gas_used: path.gas_used,
+        returndata: path.return_data,
+        call_sequence: Vec::new(),
+        stats: sevm.run_stats(),
+    })
+}
+
+/// Whether a test whose call completed `paths` passed, and the path to report
+///
+/// A path fails if it reverts with one of `sevm.panic_codes`. The test fails
+/// if any path fails, if a vm.assert* cheatcode recorded a counterexample
+/// (they do not revert), or if every path reverted for a reason other than a
+/// panic. The reported path is the first failing one, or else the first path.
+pub fn classify_paths(sevm: &SEVM<'_>, paths: &[PathResult<'_>]) -> (bool, usize) {
+    let failing = paths.iter().position(|path| {
+        panic_code(&path.return_data).map_or(false, |code| sevm.panic_codes.matches(code))
+    });
+    let all_reverted = paths
+        .iter()
+        .all(|path| !path.success && panic_code(&path.return_data).is_none());
+
+    let passed = failing.is_none() && sevm.counterexample.is_none() && !all_reverted;
+    (passed, failing.unwrap_or(0))
+}
+
+/// Panic code of `returndata`, if it is a Panic(uint256) revert
+fn panic_code(returndata: &[u8]) -> Option<u64> {
+    RevertReason::decode(returndata).and_then(|reason| reason.panic_code())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_test_trivial_pass() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+
+        // check_trivial() { } compiles down to a bare STOP
+        let contract = Contract::from_hexcode("00", &ctx).unwrap();
+        let outcome =
+            run_test(&Config::default(), contract, [0x12, 0x34, 0x56, 0x78], &[]).unwrap();
+
+        assert!(outcome.passed);
+        assert_eq!(outcome.panic_code, None);
+        assert_eq!(outcome.counterexample, None);
+    }
+
+    /// Contract with a three-state counter in slot 0:
+    /// - 0x00000001: 0 -> 1
+    /// - 0x00000002: 1 -> 2
+    /// - 0x00000003: invariant, Panic(0x01) if slot 0 == 2
+    const COUNTER_HEXCODE: &str = concat!(
+        "600035",         // PUSH1 0 CALLDATALOAD
+        "60e01c",         // PUSH1 0xe0 SHR
+        "80600114601b57", // DUP1 PUSH1 1 EQ PUSH1 0x1b JUMPI
+        "80600214602957", // DUP1 PUSH1 2 EQ PUSH1 0x29 JUMPI
+        "600314603b57",   // PUSH1 3 EQ PUSH1 0x3b JUMPI
+        "00",             // STOP
+        // 0x1b: if slot0 == 0 then slot0 = 1
+        "5b600054602757", // JUMPDEST PUSH1 0 SLOAD PUSH1 0x27 JUMPI
+        "6001600055",     // PUSH1 1 PUSH1 0 SSTORE
+        "5b00",           // 0x27: JUMPDEST STOP
+        // 0x29: if slot0 == 1 then slot0 = 2
+        "5b60005460011415603957", // JUMPDEST PUSH1 0 SLOAD PUSH1 1 EQ ISZERO PUSH1 0x39 JUMPI
+        "6002600055",             // PUSH1 2 PUSH1 0 SSTORE
+        "5b00",                   // 0x39: JUMPDEST STOP
+        // 0x3b: assert(slot0 != 2)
+        "5b60005460021415605b57", // JUMPDEST PUSH1 0 SLOAD PUSH1 2 EQ ISZERO PUSH1 0x5b JUMPI
+        "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+        "6001600452",             // mstore(4, 1)
+        "60246000fd",             // revert(0, 0x24)
+        "5b00",                   // 0x5b: JUMPDEST STOP
+    );
+
+    #[test]
+    fn test_run_invariant_depth() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let contract = Contract::from_hexcode(COUNTER_HEXCODE, &ctx).unwrap();
+        let invariant = [0, 0, 0, 3];
+        let targets = [([0, 0, 0, 1], vec![]), ([0, 0, 0, 2], vec![])];
+
+        let shallow = Config {
+            invariant_depth: 1,
+            ..Config::default()
+        };
+        let outcome = run_invariant(&shallow, contract.clone(), invariant, &targets).unwrap();
+        assert!(outcome.passed);
+
+        let deep = Config {
+            invariant_depth: 2,
+            ..Config::default()
+        };
+        let outcome = run_invariant(&deep, contract, invariant, &targets).unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.panic_code, Some(0x01));
+        assert_eq!(outcome.call_sequence, vec![[0, 0, 0, 1], [0, 0, 0, 2]]);
+    }
+
+    #[test]
+    fn test_run_invariant_reports_assertion_in_target_call() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        // target(uint256 x): assert(x != 7); the invariant sees x == 0 and holds
+        let code = concat!(
+            "600435",                 // PUSH1 4 CALLDATALOAD
+            "600714600a57",           // PUSH1 7 EQ PUSH1 0x0a JUMPI
+            "00",                     // STOP
+            "5b",                     // 0x0a: JUMPDEST
+            "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+            "6001600452",             // mstore(4, 1)
+            "60246000fd",             // revert(0, 0x24)
+        );
+        let contract = Contract::from_hexcode(code, &ctx).unwrap();
+        let targets = [([0, 0, 0, 1], vec![SolType::Uint(256)])];
+        let config = Config {
+            invariant_depth: 1,
+            ..Config::default()
+        };
+
+        let outcome = run_invariant(&config, contract, [0, 0, 0, 3], &targets).unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.panic_code, Some(0x01));
+        assert_eq!(outcome.call_sequence, vec![[0, 0, 0, 1]]);
+    }
+
+    /// Contract that always reverts with Panic(code)
+    fn panic_hexcode(code: u8) -> String {
+        format!(
+            "{}60{:02x}600452{}",
+            "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+            code,                     // mstore(4, code)
+            "60246000fd",             // revert(0, 0x24)
+        )
+    }
+
+    #[test]
+    fn test_run_suite_counts_passes_and_failures() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let suite = |name: &str, hexcode: &str| SuiteContract {
+            name: name.to_string(),
+            contract: Contract::from_hexcode(hexcode, &ctx).unwrap(),
+            functions: vec![
+                ("check_it()".to_string(), [0x12, 0x34, 0x56, 0x78]),
+                ("helper()".to_string(), [0x87, 0x65, 0x43, 0x21]),
+            ],
+        };
+        let contracts = [
+            suite("PassTest", "00"),
+            suite("FailTest", &panic_hexcode(0x01)),
+            suite("Skipped", &panic_hexcode(0x01)),
+        ];
+        let config = Config {
+            match_contract: "Test$".to_string(),
+            ..Config::default()
+        };
+
+        let result = run_suite(&config, &contracts).unwrap();
+        assert_eq!((result.passed, result.failed), (1, 1));
+        let run: Vec<(&str, bool)> = result
+            .results
+            .iter()
+            .map(|r| (r.contract.as_str(), r.outcome.passed))
+            .collect();
+        assert_eq!(run, vec![("PassTest", true), ("FailTest", false)]);
+        assert!(result.results.iter().all(|r| r.function == "check_it()"));
+
+        let bad_regex = Config {
+            match_test: "(".to_string(),
+            ..Config::default()
+        };
+        assert!(run_suite(&bad_regex, &contracts).is_err());
+    }
+
+    #[test]
+    fn test_chainid_reads_configured_chain_id() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        // assert(block.chainid == 10)
+        let hexcode = format!("46600a14601c57{}5b00", panic_hexcode(0x01));
+        let contract = Contract::from_hexcode(&hexcode, &ctx).unwrap();
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        let mainnet = Config::default();
+        assert!(
+            !run_test(&mainnet, contract.clone(), selector, &[])
+                .unwrap()
+                .passed
+        );
+
+        let optimism = Config {
+            chain_id: 10,
+            ..Config::default()
+        };
+        assert!(run_test(&optimism, contract, selector, &[]).unwrap().passed);
+    }
+
+    #[test]
+    fn test_gas_limit_bounds_test_calls() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        // mstore(0x20000, 1): expanding memory past 128 KiB costs 45,075 gas
+        let contract = Contract::from_hexcode("60016202000052", &ctx).unwrap();
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        let outcome = run_test(&Config::default(), contract.clone(), selector, &[]).unwrap();
+        assert!(outcome.passed);
+        assert_eq!(outcome.gas_used, 45_075);
+
+        let small = Config {
+            gas_limit: 40_000,
+            ..Config::default()
+        };
+        let outcome = run_test(&small, contract, selector, &[]).unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.gas_used, 40_000);
+    }
+
+    #[test]
+    fn test_only_configured_panic_codes_fail() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        let assert_failure = Contract::from_hexcode(&panic_hexcode(0x01), &ctx).unwrap();
+        let overflow = Contract::from_hexcode(&panic_hexcode(0x11), &ctx).unwrap();
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        let assertions_only = Config {
+            panic_error_codes: "0x01".to_string(),
+            ..Config::default()
+        };
+        let outcome = run_test(&assertions_only, assert_failure.clone(), selector, &[]).unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.panic_code, Some(0x01));
+        let outcome = run_test(&assertions_only, overflow.clone(), selector, &[]).unwrap();
+        assert!(outcome.passed);
+        assert_eq!(outcome.panic_code, Some(0x11));
+
+        let all = Config {
+            panic_error_codes: "*".to_string(),
+            ..Config::default()
+        };
+        assert!(
+            !run_test(&all, assert_failure, selector, &[])
+                .unwrap()
+                .passed
+        );
+        assert!(!run_test(&all, overflow, selector, &[]).unwrap().passed);
+    }
+
+    #[test]
+    fn test_run_test_fails_if_any_path_fails() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        // check(uint256 x): assert(x == 0)
+        let code = concat!(
+            "600435600757",           // PUSH1 4 CALLDATALOAD PUSH1 0x07 JUMPI
+            "00",                     // STOP
+            "5b",                     // 0x07: JUMPDEST
+            "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+            "6001600452",             // mstore(4, 1)
+            "60246000fd",             // revert(0, 0x24)
+        );
+        let contract = Contract::from_hexcode(code, &ctx).unwrap();
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        let outcome = run_test(
+            &Config::default(),
+            contract,
+            selector,
+            &[SolType::Uint(256)],
+        );
+        let outcome = outcome.unwrap();
+        assert!(!outcome.passed);
+        assert_eq!(outcome.panic_code, Some(0x01));
+        assert!(outcome.counterexample.is_some());
+        assert_eq!(outcome.stats.completed_paths, 2);
+    }
+
+    #[test]
+    fn test_run_test_tries_each_bytes_length() {
+        let cfg = z3::Config::new();
+        let ctx = z3::Context::new(&cfg);
+        // check(bytes data): assert(data.length != 65)
+        let code = concat!(
+            "602435",                 // PUSH1 0x24 CALLDATALOAD
+            "604114600a57",           // PUSH1 65 EQ PUSH1 0x0a JUMPI
+            "00",                     // STOP
+            "5b",                     // 0x0a: JUMPDEST
+            "634e487b7160e01b600052", // mstore(0, 0x4e487b71 << 224)
+            "6001600452",             // mstore(4, 1)
+            "60246000fd",             // revert(0, 0x24)
+        );
+        let contract = Contract::from_hexcode(code, &ctx).unwrap();
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        // 65 is one of the default lengths, though not the largest
+        let outcome = run_test(
+            &Config::default(),
+            contract.clone(),
+            selector,
+            &[SolType::Bytes],
+        );
+        assert!(!outcome.unwrap().passed);
+
+        let without_65 = Config {
+            default_bytes_lengths: "0,1024".to_string(),
+            ..Config::default()
+        };
+        let outcome = run_test(&without_65, contract, selector, &[SolType::Bytes]).unwrap();
+        assert!(outcome.passed);
+    }
+}
@@ -2,5 +2,70 @@
 
 //! Execution state management
 
-// This file is intentionally minimal - state is now part of ExecState in lib.rs
-// which better matches the Python implementation structure
+// Most of the state lives in ExecState in lib.rs, which better matches the
+// Python implementation structure. This module holds auxiliary per-transaction
+// state that is carried by ExecState.
+
+use num_bigint::BigUint;
+use std::collections::{HashMap, HashSet};
+
+/// Accessed addresses and storage slots for the current transaction (EIP-2929)
+///
+/// The first access to an address or slot is cold; subsequent accesses in the
+/// same transaction are warm. Symbolic slots are never recorded and are
+/// therefore always charged as cold.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessSet {
+    pub addresses: HashSet<[u8; 20]>,
+    pub slots: HashMap<[u8; 20], HashSet<BigUint>>,
+}
+
+impl AccessSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks `addr` as accessed. Returns true if it was already warm.
+    pub fn access_address(&mut self, addr: [u8; 20]) -> bool {
+        !self.addresses.insert(addr)
+    }
+
+    /// Marks `slot` of `addr` as accessed. Returns true if it was already warm.
+    pub fn access_slot(&mut self, addr: [u8; 20], slot: BigUint) -> bool {
+        !self.slots.entry(addr).or_default().insert(slot)
+    }
+
+    pub fn is_warm_address(&self, addr: &[u8; 20]) -> bool {
+        self.addresses.contains(addr)
+    }
+
+    pub fn is_warm_slot(&self, addr: &[u8; 20], slot: &BigUint) -> bool {
+        self.slots.get(addr).map_or(false, |s| s.contains(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_address() {
+        let mut access = AccessSet::new();
+        let addr = [1u8; 20];
+        assert!(!access.access_address(addr));
+        assert!(access.access_address(addr));
+        assert!(access.is_warm_address(&addr));
+        assert!(!access.is_warm_address(&[2u8; 20]));
+    }
+
+    #[test]
+    fn test_access_slot() {
+        let mut access = AccessSet::new();
+        let addr = [1u8; 20];
+        assert!(!access.access_slot(addr, BigUint::from(0u32)));
+        assert!(access.access_slot(addr, BigUint::from(0u32)));
+        assert!(!access.access_slot(addr, BigUint::from(1u32)));
+        // slots are tracked per address
+        assert!(!access.access_slot([2u8; 20], BigUint::from(0u32)));
+    }
+}
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Execution statistics reported with `--statistics`
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+use z3::SatResult;
+
+/// Outcomes and timings of solver checks
+///
+/// Shared (through an `Rc<RefCell<_>>`) by a path and all of its branches, so
+/// every check issued during a run ends up in the same record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    pub calls: usize,
+    pub sat: usize,
+    pub unsat: usize,
+    pub unknown: usize,
+    /// Wall time of each check, in the order they were issued
+    pub timings: Vec<Duration>,
+}
+
+impl SolverStats {
+    /// Record the outcome of one solver check
+    pub fn record(&mut self, result: SatResult, elapsed: Duration) {
+        self.calls += 1;
+        match result {
+            SatResult::Sat => self.sat += 1,
+            SatResult::Unsat => self.unsat += 1,
+            SatResult::Unknown => self.unknown += 1,
+        }
+        self.timings.push(elapsed);
+    }
+
+    /// Total time spent in the solver
+    pub fn total_time(&self) -> Duration {
+        self.timings.iter().sum()
+    }
+
+    /// Duration of the slowest check, if any were made
+    pub fn slowest(&self) -> Option<Duration> {
+        self.timings.iter().max().copied()
+    }
+}
+
+/// Summary of a symbolic execution run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// Paths that ran to a halting instruction
+    pub completed_paths: usize,
+    /// Paths dropped because their constraints became unsatisfiable
+    pub infeasible_paths: usize,
+    /// Instructions executed across all paths and calls
+    pub steps: usize,
+    pub solver: SolverStats,
+    /// Wall time spent in top-level calls
+    pub wall_time: Duration,
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "paths: {} completed, {} infeasible",
+            self.completed_paths, self.infeasible_paths
+        )?;
+        writeln!(f, "steps: {}", self.steps)?;
+        write!(
+            f,
+            "solver: {} calls ({} sat, {} unsat, {} unknown) in {:.3}s",
+            self.solver.calls,
+            self.solver.sat,
+            self.solver.unsat,
+            self.solver.unknown,
+            self.solver.total_time().as_secs_f64()
+        )?;
+        if let Some(slowest) = self.solver.slowest() {
+            write!(f, ", slowest {:.3}s", slowest.as_secs_f64())?;
+        }
+        writeln!(f)?;
+        write!(f, "time: {:.3}s", self.wall_time.as_secs_f64())
+    }
+}
+
+/// Execution count of one opcode, collected with `--profile-instructions`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstructionProfile {
+    pub opcode: u8,
+    pub mnemonic: String,
+    pub count: usize,
+}
+
+/// File formats accepted by `SEVM::write_instruction_profile`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// Array of `{opcode, mnemonic, count}` objects
+    Json,
+    /// `opcode,mnemonic,count` header followed by one row per opcode
+    Csv,
+}
@@ -2,10 +2,88 @@
 
 //! Storage models for Solidity and generic storage layouts
 
+use crate::KeccakRegistry;
+use cbse_address::Address;
 use cbse_bitvec::CbseBitVec;
 use cbse_exceptions::{CbseException, CbseResult};
+use num_bigint::BigUint;
+use std::borrow::Borrow;
 use std::collections::HashMap;
-use z3::{ast::Array as Z3Array, Context, Sort};
+use std::hash::Hash;
+use std::ops::Index;
+use std::rc::Rc;
+use z3::ast::{Array as Z3Array, Ast, BV as Z3BV};
+use z3::{Context, DeclKind, Sort};
+
+/// Value of `bv` if it is a bitvector numeral
+fn numeral(bv: &Z3BV) -> Option<BigUint> {
+    let text = bv.to_string();
+    if let Some(hex) = text.strip_prefix("#x") {
+        BigUint::parse_bytes(hex.as_bytes(), 16)
+    } else if let Some(bin) = text.strip_prefix("#b") {
+        BigUint::parse_bytes(bin.as_bytes(), 2)
+    } else {
+        None
+    }
+}
+
+/// Wrap a Z3 term, as a concrete value if it is a numeral
+fn fold_numeral(bv: Z3BV) -> CbseBitVec {
+    match numeral(&bv) {
+        Some(value) => CbseBitVec::from_biguint(value, bv.get_size()),
+        None => CbseBitVec::from_z3(bv),
+    }
+}
+
+/// Read `index` from a storage array, folding the result to a concrete word
+/// when it simplifies to a constant (e.g. a read of a concretely written slot)
+fn select_word<'ctx>(array: &Z3Array<'ctx>, index: &Z3BV<'ctx>) -> CbseBitVec<'ctx> {
+    let value = array
+        .select(index)
+        .as_bv()
+        .expect("storage arrays hold bitvectors");
+    fold_numeral(value.simplify())
+}
+
+/// Indices written by a chain of stores, oldest first, and the array the
+/// chain started from
+fn store_chain<'ctx>(array: &Z3Array<'ctx>) -> (Vec<Z3BV<'ctx>>, Z3Array<'ctx>) {
+    let mut indices = Vec::new();
+    let mut current = array.clone();
+    while matches!(current.safe_decl(), Ok(decl) if decl.kind() == DeclKind::STORE) {
+        let children = current.children();
+        let (Some(inner), Some(index)) = (children[0].as_array(), children[1].as_bv()) else {
+            break;
+        };
+        indices.push(index);
+        current = inner;
+    }
+    indices.reverse();
+    (indices, current)
+}
+
+/// Whether two words are the same expression once simplified
+fn same_word<'ctx>(a: &CbseBitVec<'ctx>, b: &CbseBitVec<'ctx>, ctx: &'ctx Context) -> bool {
+    a.as_z3(ctx).simplify() == b.as_z3(ctx).simplify()
+}
+
+/// Initial contents of a storage array from `size_keys`-bit indices to
+/// `value_bits`-bit values: unconstrained for symbolic storage, all zeros
+/// otherwise
+fn unwritten_array<'ctx>(
+    symbolic: bool,
+    name: String,
+    size_keys: usize,
+    value_bits: u32,
+    ctx: &'ctx Context,
+) -> Z3Array<'ctx> {
+    let domain_sort = Sort::bitvector(ctx, size_keys as u32);
+    if symbolic {
+        Z3Array::new_const(ctx, name, &domain_sort, &Sort::bitvector(ctx, value_bits))
+    } else {
+        Z3Array::const_array(ctx, &domain_sort, &Z3BV::from_u64(ctx, 0, value_bits))
+    }
+}
 
 /// Storage data container
 #[derive(Debug, Clone)]
@@ -13,19 +91,106 @@ pub struct StorageData<'ctx> {
     /// Whether this storage uses symbolic values
     pub symbolic: bool,
     /// The actual storage mapping
-    /// For SolidityStorage: (slot, num_keys, size_keys) -> value or array
+    /// For SolidityStorage: (slot, key shape) -> value or array
     mapping: HashMap<StorageKey, StorageValue<'ctx>>,
 }
 
+/// How a key of a decoded Solidity location leads from one location to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum KeyKind {
+    /// Mapping key k: hash(k . loc)
+    Mapping,
+    /// Dynamic array index i: hash(loc) + i
+    Element,
+    /// Offset j of a struct field or static array element: loc + j
+    Field,
+}
+
+/// One key of a decoded Solidity storage location
+#[derive(Debug, Clone)]
+pub struct SlotKey<'ctx> {
+    pub kind: KeyKind,
+    pub value: CbseBitVec<'ctx>,
+}
+
+impl<'ctx> SlotKey<'ctx> {
+    pub fn mapping(value: CbseBitVec<'ctx>) -> Self {
+        Self {
+            kind: KeyKind::Mapping,
+            value,
+        }
+    }
+
+    pub fn element(value: CbseBitVec<'ctx>) -> Self {
+        Self {
+            kind: KeyKind::Element,
+            value,
+        }
+    }
+
+    pub fn field(value: CbseBitVec<'ctx>) -> Self {
+        Self {
+            kind: KeyKind::Field,
+            value,
+        }
+    }
+}
+
+/// Kind and bit size of each key, which identifies the array the keys index
+fn key_shape(keys: &[SlotKey]) -> Vec<(KeyKind, u32)> {
+    keys.iter()
+        .map(|key| (key.kind, key.value.size()))
+        .collect()
+}
+
+/// Array index of a location: its keys concatenated in order
+fn key_index<'ctx>(keys: &[SlotKey<'ctx>]) -> CbseBitVec<'ctx> {
+    keys[1..]
+        .iter()
+        .fold(keys[0].value.clone(), |acc, key| acc.concat(&key.value))
+}
+
+/// Name of the storage array of `slot` indexed by keys of `shape`
+fn array_name(addr: &Address, slot: u64, shape: &[(KeyKind, u32)]) -> String {
+    let size_keys: u32 = shape.iter().map(|&(_, bits)| bits).sum();
+    format!("storage_{}_{}_{}_{}_00", addr, slot, shape.len(), size_keys)
+}
+
 /// Storage key for the mapping
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum StorageKey {
-    /// Solidity storage: (slot, num_keys, size_keys)
-    Solidity(u64, usize, usize),
+    /// Solidity storage: (slot, kind and bit size of each key)
+    Solidity(u64, Vec<(KeyKind, u32)>),
     /// Generic storage: just the size
     Generic(usize),
 }
 
+impl StorageKey {
+    /// Storage location of entry `index` of the array stored under this key
+    ///
+    /// Solidity keys are hashed and added back into their keccak location,
+    /// the inverse of SolidityStorage::decode.
+    fn location<'ctx>(&self, index: &Z3BV<'ctx>, ctx: &'ctx Context) -> CbseBitVec<'ctx> {
+        match self {
+            StorageKey::Generic(_) => fold_numeral(index.simplify()),
+            StorageKey::Solidity(slot, shape) => {
+                let mut high: u32 = shape.iter().map(|&(_, bits)| bits).sum();
+                shape
+                    .iter()
+                    .fold(CbseBitVec::from_u64(*slot, 256), |loc, &(kind, bits)| {
+                        let key = fold_numeral(index.extract(high - 1, high - bits).simplify());
+                        high -= bits;
+                        match kind {
+                            KeyKind::Mapping => KeccakRegistry::hash_term(ctx, &key.concat(&loc)),
+                            KeyKind::Element => KeccakRegistry::hash_term(ctx, &loc).add(&key, ctx),
+                            KeyKind::Field => loc.add(&key, ctx),
+                        }
+                    })
+            }
+        }
+    }
+}
+
 /// Storage value
 #[derive(Debug, Clone)]
 pub enum StorageValue<'ctx> {
@@ -44,6 +209,14 @@ impl<'ctx> StorageData<'ctx> {
         }
     }
 
+    /// Create storage whose unwritten slots hold unconstrained symbolic values
+    pub fn new_symbolic() -> Self {
+        Self {
+            symbolic: true,
+            mapping: HashMap::new(),
+        }
+    }
+
     /// Get a value from storage
     pub fn get(&self, key: &StorageKey) -> Option<&StorageValue<'ctx>> {
         self.mapping.get(key)
@@ -59,6 +232,58 @@ impl<'ctx> StorageData<'ctx> {
         self.mapping.contains_key(key)
     }
 
+    /// Slots whose value differs from `before`, as (slot, old, new)
+    ///
+    /// Slots are reported the way SLOAD/SSTORE address them, so a mapping
+    /// entry shows up under its keccak location. Symbolic slots and values are
+    /// reported as expressions; writes that leave a slot's expression
+    /// unchanged are not listed.
+    pub fn diff(
+        &self,
+        before: &StorageData<'ctx>,
+        ctx: &'ctx Context,
+    ) -> Vec<(CbseBitVec<'ctx>, CbseBitVec<'ctx>, CbseBitVec<'ctx>)> {
+        let mut keys: Vec<&StorageKey> = self.mapping.keys().collect();
+        keys.sort();
+
+        let mut changes = Vec::new();
+        for key in keys {
+            match &self.mapping[key] {
+                StorageValue::Value(new) => {
+                    let old = match before.get(key) {
+                        Some(StorageValue::Value(old)) => old.clone(),
+                        _ => CbseBitVec::from_u64(0, 256),
+                    };
+                    if let StorageKey::Solidity(slot, ..) = key {
+                        if !same_word(&old, new, ctx) {
+                            changes.push((CbseBitVec::from_u64(*slot, 256), old, new.clone()));
+                        }
+                    }
+                }
+                StorageValue::Array(array) => {
+                    let (indices, initial) = store_chain(array);
+                    let old_array = match before.get(key) {
+                        Some(StorageValue::Array(old_array)) => old_array.clone(),
+                        _ => initial,
+                    };
+                    let mut seen: Vec<Z3BV> = Vec::new();
+                    for index in indices {
+                        if seen.contains(&index) {
+                            continue;
+                        }
+                        let old = select_word(&old_array, &index);
+                        let new = select_word(array, &index);
+                        if !same_word(&old, &new, ctx) {
+                            changes.push((key.location(&index, ctx), old, new));
+                        }
+                        seen.push(index);
+                    }
+                }
+            }
+        }
+        changes
+    }
+
     /// Compute a hash digest of the storage (for state comparison)
     pub fn digest(&self) -> u64 {
         // Simple hash based on the number of entries
@@ -73,12 +298,89 @@ impl<'ctx> Default for StorageData<'ctx> {
     }
 }
 
+/// Storage of every contract, shared copy-on-write between paths
+///
+/// Cloning is O(1), so branching a path does not copy storage. The first
+/// write after a clone copies the map of contracts (one pointer each) and the
+/// storage of the contract written to; other contracts stay shared.
+#[derive(Debug, Clone, Default)]
+pub struct StorageMap<'ctx> {
+    contracts: Rc<HashMap<Address, Rc<StorageData<'ctx>>>>,
+}
+
+impl<'ctx> StorageMap<'ctx> {
+    /// Create storage without any contracts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the storage of a contract
+    pub fn get<Q>(&self, addr: &Q) -> Option<&StorageData<'ctx>>
+    where
+        Address: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.contracts.get(addr).map(|data| data.as_ref())
+    }
+
+    /// Check if a contract has storage
+    pub fn contains_key<Q>(&self, addr: &Q) -> bool
+    where
+        Address: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.contracts.contains_key(addr)
+    }
+
+    /// Storage of `addr` for writing, created empty if missing
+    pub fn entry_mut(&mut self, addr: Address) -> &mut StorageData<'ctx> {
+        let data = Rc::make_mut(&mut self.contracts).entry(addr).or_default();
+        Rc::make_mut(data)
+    }
+
+    /// Set the storage of a contract
+    pub fn insert(&mut self, addr: Address, data: StorageData<'ctx>) {
+        Rc::make_mut(&mut self.contracts).insert(addr, Rc::new(data));
+    }
+
+    /// Drop the storage of a contract
+    pub fn remove<Q>(&mut self, addr: &Q)
+    where
+        Address: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.contracts.contains_key(addr) {
+            Rc::make_mut(&mut self.contracts).remove(addr);
+        }
+    }
+
+    /// Whether the storage of `addr` is the same allocation in both maps
+    pub fn shares(&self, other: &StorageMap<'ctx>, addr: &Address) -> bool {
+        match (self.contracts.get(addr), other.contracts.get(addr)) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl<'ctx, Q> Index<&Q> for StorageMap<'ctx>
+where
+    Address: Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+{
+    type Output = StorageData<'ctx>;
+
+    fn index(&self, addr: &Q) -> &StorageData<'ctx> {
+        self.get(addr).expect("no storage for address")
+    }
+}
+
 /// Solidity storage model
 ///
 /// Handles Solidity-specific storage layout with:
 /// - Scalar values at specific slots
 /// - Mappings with keccak-based slot calculation
-/// - Arrays with sequential slot allocation
+/// - Dynamic arrays whose elements follow the keccak of their slot
 pub struct SolidityStorage;
 
 impl SolidityStorage {
@@ -87,78 +389,81 @@ impl SolidityStorage {
         StorageData::new()
     }
 
-    /// Create an empty Z3 Array for a given slot and keys
-    /// Returns an Array from (concat of keys) -> BitVec(256)
+    /// Storage array of `slot` for keys of `shape`, before any write
+    ///
+    /// The array sort is BitVec(k) -> BitVec(value_bits), where k is the
+    /// summed width of the keys. An entry is indexed by its keys concatenated
+    /// in decode order, the outermost key in the high bits, so the same keys
+    /// always select the same entry. Unwritten entries read as zero, or as
+    /// unconstrained but stable values in symbolic storage.
     pub fn empty<'ctx>(
-        addr: &[u8; 20],
+        addr: &Address,
         slot: u64,
-        num_keys: usize,
-        size_keys: usize,
+        shape: &[(KeyKind, u32)],
+        value_bits: u32,
+        symbolic: bool,
         ctx: &'ctx Context,
     ) -> Z3Array<'ctx> {
-        let name = format!("storage_{:?}_{}_{}_{}_00", addr, slot, num_keys, size_keys);
-
-        // Create domain sort (BitVec of size_keys bits)
-        let domain_sort = Sort::bitvector(ctx, size_keys as u32);
-        // Create range sort (BitVec of 256 bits for EVM word)
-        let range_sort = Sort::bitvector(ctx, 256);
-
-        // Create Z3 Array
-        Z3Array::new_const(ctx, name, &domain_sort, &range_sort)
+        let size_keys: u32 = shape.iter().map(|&(_, bits)| bits).sum();
+        let name = array_name(addr, slot, shape);
+        unwritten_array(symbolic, name, size_keys as usize, value_bits, ctx)
     }
 
-    /// Initialize storage location if not yet initialized
+    /// Initialize the storage of a decoded location if not yet initialized
+    ///
+    /// `slot` is the base slot from `decode` and `shape` the kind and width of
+    /// each of its keys, which together pick the array (see `empty`) holding
+    /// `value_bits`-bit values. A location without keys holds a single value.
     pub fn init<'ctx>(
-        storage: &mut HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &mut StorageMap<'ctx>,
+        addr: Address,
         slot: u64,
-        num_keys: usize,
-        size_keys: usize,
+        shape: &[(KeyKind, u32)],
+        value_bits: u32,
         ctx: &'ctx Context,
     ) -> CbseResult<()> {
-        let storage_addr = storage.entry(addr).or_insert_with(StorageData::new);
-
-        let key = StorageKey::Solidity(slot, num_keys, size_keys);
+        let storage_addr = storage.entry_mut(addr);
+        let key = StorageKey::Solidity(slot, shape.to_vec());
+        if storage_addr.contains(&key) {
+            return Ok(());
+        }
 
-        if !storage_addr.contains(&key) {
-            if size_keys > 0 {
-                // Mapping type: use Z3 Array
-                let array = Self::empty(&addr, slot, num_keys, size_keys, ctx);
-                storage_addr.set(key, StorageValue::Array(array));
+        let value = if shape.is_empty() {
+            let value = if storage_addr.symbolic {
+                CbseBitVec::symbolic(ctx, &array_name(&addr, slot, shape), value_bits)
             } else {
-                // Scalar type: initialize with zero or symbolic
-                let value = if storage_addr.symbolic {
-                    CbseBitVec::symbolic(
-                        ctx,
-                        &format!("storage_{:?}_{}_{}_{}_00", addr, slot, num_keys, size_keys),
-                        256,
-                    )
-                } else {
-                    CbseBitVec::from_u64(0, 256)
-                };
-                storage_addr.set(key, StorageValue::Value(value));
-            }
-        }
+                CbseBitVec::from_u64(0, value_bits)
+            };
+            StorageValue::Value(value)
+        } else {
+            StorageValue::Array(Self::empty(
+                &addr,
+                slot,
+                shape,
+                value_bits,
+                storage_addr.symbolic,
+                ctx,
+            ))
+        };
+        storage_addr.set(key, value);
 
         Ok(())
     }
 
     /// Load a value from storage
     pub fn load<'ctx>(
-        storage: &HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &StorageMap<'ctx>,
+        addr: Address,
         slot: u64,
-        keys: &[CbseBitVec<'ctx>],
+        keys: &[SlotKey<'ctx>],
         ctx: &'ctx Context,
     ) -> CbseResult<CbseBitVec<'ctx>> {
-        let num_keys = keys.len();
-        let size_keys: usize = keys.iter().map(|k| k.size() as usize).sum();
-
         let storage_addr = storage
             .get(&addr)
             .ok_or_else(|| CbseException::Internal("Storage address not found".to_string()))?;
 
-        let key = StorageKey::Solidity(slot, num_keys, size_keys);
+        let shape = key_shape(keys);
+        let key = StorageKey::Solidity(slot, shape.clone());
 
         match storage_addr.get(&key) {
             Some(StorageValue::Value(v)) => {
@@ -173,21 +478,14 @@ impl SolidityStorage {
                     ));
                 }
 
-                // Concatenate keys to form the array index
-                let concat_key = if keys.len() == 1 {
-                    keys[0].clone()
-                } else {
-                    // Concatenate multiple keys
-                    let mut result = keys[0].clone();
-                    for key in &keys[1..] {
-                        result = result.concat(key);
-                    }
-                    result
-                };
-
                 // Use Z3 Select operation: Select(array, index)
-                let value = array.select(&concat_key.as_z3(ctx));
-                Ok(CbseBitVec::from_z3(value.as_bv().unwrap()))
+                Ok(select_word(array, &key_index(keys).as_z3(ctx)))
+            }
+            None if storage_addr.symbolic && !keys.is_empty() => {
+                // Symbolic storage: read from the (unwritten) base array, so each
+                // slot is an unconstrained but stable value
+                let array = Self::empty(&addr, slot, &shape, 256, true, ctx);
+                Ok(select_word(&array, &key_index(keys).as_z3(ctx)))
             }
             None => {
                 // Uninitialized storage returns zero
@@ -198,107 +496,165 @@ impl SolidityStorage {
 
     /// Store a value to storage
     pub fn store<'ctx>(
-        storage: &mut HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &mut StorageMap<'ctx>,
+        addr: Address,
         slot: u64,
-        keys: &[CbseBitVec<'ctx>],
+        keys: &[SlotKey<'ctx>],
         value: CbseBitVec<'ctx>,
         ctx: &'ctx Context,
     ) -> CbseResult<()> {
-        let num_keys = keys.len();
-        let size_keys: usize = keys.iter().map(|k| k.size() as usize).sum();
-
-        let storage_addr = storage.entry(addr).or_insert_with(StorageData::new);
-
-        let key = StorageKey::Solidity(slot, num_keys, size_keys);
+        let shape = key_shape(keys);
+        Self::init(storage, addr, slot, &shape, value.size(), ctx)?;
+        let storage_addr = storage.entry_mut(addr);
+        let key = StorageKey::Solidity(slot, shape);
 
-        if num_keys == 0 {
+        if keys.is_empty() {
             // Scalar storage: store the value directly
             storage_addr.set(key, StorageValue::Value(value));
-        } else {
-            // Mapping storage: use Z3 Store to create updated array
-
-            // Get the current array or create a new one
-            let current_array = if let Some(StorageValue::Array(arr)) = storage_addr.get(&key) {
-                arr.clone()
-            } else {
-                // Create empty array if not exists
-                Self::empty(&addr, slot, num_keys, size_keys, ctx)
-            };
-
-            // Concatenate keys to form the array index
-            let concat_key = if keys.len() == 1 {
-                keys[0].clone()
-            } else {
-                let mut result = keys[0].clone();
-                for key in &keys[1..] {
-                    result = result.concat(key);
-                }
-                result
-            };
-
-            // Use Z3 Store operation: Store(array, index, value)
-            let new_array = current_array.store(&concat_key.as_z3(ctx), &value.as_z3(ctx));
+            return Ok(());
+        }
 
-            // Store the new array
-            storage_addr.set(key, StorageValue::Array(new_array));
+        // Mapping storage: use Z3 Store to create updated array
+        let current_array = match storage_addr.get(&key) {
+            Some(StorageValue::Array(array)) => array.clone(),
+            _ => {
+                return Err(CbseException::Internal(format!(
+                    "slot {} with keys is not stored as an array",
+                    slot
+                )))
+            }
+        };
+        if current_array.get_sort().array_range() != Some(Sort::bitvector(ctx, value.size())) {
+            return Err(CbseException::Internal(format!(
+                "cannot store a {}-bit value in the array of slot {}",
+                value.size(),
+                slot
+            )));
         }
 
+        // Use Z3 Store operation: Store(array, index, value)
+        let new_array = current_array.store(&key_index(keys).as_z3(ctx), &value.as_z3(ctx));
+        storage_addr.set(key, StorageValue::Array(new_array));
+
         Ok(())
     }
 
     /// Decode a storage location into (slot, keys)
     /// This handles Solidity's storage layout rules following Python implementation
     ///
-    /// Solidity storage layout patterns:
-    /// 1. m[k]: hash(k . m) where k is 256-bit → sha3_512
-    /// 2. a[i]: hash(a) + i → sha3_256
-    /// 3. m[k]: hash(k . m) where k is non-256-bit → generic sha3 with concat
-    /// 4. Array indexing: base + offset → bvadd
-    /// 5. Concrete values: lookup in keccak registry for reverse mapping
+    /// Solidity storage layout patterns recognized:
+    /// 1. m[k]: hash(k . m), a `f_sha3_<bits>` application over concat(k, m)
+    /// 2. Nested mappings m[k1][k2]: hash(k2 . hash(k1 . m))
+    /// 3. Dynamic array elements a[i]: hash(a) + i, with the array's data
+    ///    region starting at hash(a)
+    /// 4. Struct fields after a mapping entry: hash(k . m) + j
+    /// 5. Concrete values: a keccak registry preimage, possibly offset, if the
+    ///    value is near a known hash, so concrete and symbolic keys share one
+    ///    array, otherwise a plain slot
     ///
-    /// Returns: (base_slot, [key1, key2, ...]) where keys are in order
+    /// Returns: (base_slot, [key1, key2, ...]) where keys are in order, or
+    /// None for locations that follow none of these patterns (arbitrary
+    /// symbolic slots)
     pub fn decode<'ctx>(
         loc: &CbseBitVec<'ctx>,
+        registry: &KeccakRegistry<'ctx>,
         ctx: &'ctx Context,
-    ) -> CbseResult<(u64, Vec<CbseBitVec<'ctx>>)> {
-        Self::decode_recursive(loc, ctx)
+    ) -> Option<(u64, Vec<SlotKey<'ctx>>)> {
+        Self::decode_recursive(&loc.as_z3(ctx), registry, ctx)
     }
 
-    /// Recursive helper for decode that returns a tuple of decoded components
+    /// Recursive helper for decode on Z3 terms
     /// Mirrors Python's cls.decode(ex, loc) which returns tuple
     fn decode_recursive<'ctx>(
-        loc: &CbseBitVec<'ctx>,
+        loc: &Z3BV<'ctx>,
+        registry: &KeccakRegistry<'ctx>,
         ctx: &'ctx Context,
-    ) -> CbseResult<(u64, Vec<CbseBitVec<'ctx>>)> {
-        use z3::ast::Ast;
-
+    ) -> Option<(u64, Vec<SlotKey<'ctx>>)> {
         // Simplify the location first (Python: loc = normalize(loc))
-        let loc_bv = loc.as_z3(ctx);
-        let simplified = loc_bv.simplify();
-
-        // Check if this is a concrete value
-        // In Z3, we can check if it's a numeral by trying to get its u64 value
-        if let Some(val) = simplified.as_u64() {
-            // Just a concrete slot (keccak registry not yet implemented)
-            return Ok((val, Vec::new()));
+        let loc = loc.simplify();
+        Self::decode_hashed(&loc, registry, ctx)
+            .or_else(|| loc.as_u64().map(|slot| (slot, Vec::new())))
+    }
+
+    /// Decode a location derived from a keccak hash, which has at least one key
+    fn decode_hashed<'ctx>(
+        loc: &Z3BV<'ctx>,
+        registry: &KeccakRegistry<'ctx>,
+        ctx: &'ctx Context,
+    ) -> Option<(u64, Vec<SlotKey<'ctx>>)> {
+        if let Some(value) = numeral(loc) {
+            let (preimage, delta) = registry.preimage_near(ctx, &value)?;
+            let (slot, mut keys) = Self::decode_preimage(&preimage, registry, ctx)?;
+            Self::add_offset(&mut keys, Z3BV::from_u64(ctx, delta, 256), ctx);
+            return Some((slot, keys));
+        }
+
+        let decl = loc.safe_decl().ok()?;
+        if decl.name().starts_with("f_sha3_") {
+            return Self::decode_preimage(&loc.nth_child(0)?.as_bv()?, registry, ctx);
+        }
+        if decl.kind() != DeclKind::BADD {
+            return None;
+        }
+
+        // hash + offset: exactly one summand is the hashed base
+        let terms: Vec<Z3BV> = loc.children().iter().filter_map(|c| c.as_bv()).collect();
+        let mut decoded = terms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, term)| Some((i, Self::decode_hashed(term, registry, ctx)?)));
+        let (base, (slot, mut keys)) = decoded.next()?;
+        if decoded.next().is_some() {
+            return None;
+        }
+        let offset = terms
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != base)
+            .fold(Z3BV::from_u64(ctx, 0, 256), |acc, (_, term)| {
+                acc.bvadd(term)
+            });
+        Self::add_offset(&mut keys, offset, ctx);
+        Some((slot, keys))
+    }
+
+    /// Keys of the location hash(preimage)
+    ///
+    /// A 256-bit preimage is the slot of a dynamic array and hash(preimage)
+    /// its element 0; a longer one is a mapping key followed by the mapping's
+    /// slot.
+    fn decode_preimage<'ctx>(
+        preimage: &Z3BV<'ctx>,
+        registry: &KeccakRegistry<'ctx>,
+        ctx: &'ctx Context,
+    ) -> Option<(u64, Vec<SlotKey<'ctx>>)> {
+        let size = preimage.get_size();
+        if size < 256 {
+            return None;
         }
 
-        // For now, simplified implementation: return the location as a single key
-        // Full Z3 App introspection would require accessing internal Z3 AST structure
-        // which is not easily exposed in z3-sys Rust bindings.
-        //
-        // TODO: For complete implementation, we would need to:
-        // 1. Parse string representation of the expression
-        // 2. Or use z3-sys FFI to access Z3_get_app_decl, Z3_get_app_num_args, etc.
-        // 3. Pattern match on:
-        //    - f_sha3_512(concat(key, base)) for mapping[key]
-        //    - f_sha3_256(base) for array indexing
-        //    - bvadd(base, offset) for array offset calculations
-        //    - concat operations for non-256-bit keys
-        //
-        // For basic functionality, treating location as single key works for simple storage
-        Ok((0, vec![loc.clone()]))
+        let base = preimage.extract(255, 0);
+        let (slot, mut keys) = Self::decode_recursive(&base, registry, ctx)?;
+        keys.push(if size == 256 {
+            SlotKey::element(CbseBitVec::from_u64(0, 256))
+        } else {
+            SlotKey::mapping(fold_numeral(preimage.extract(size - 1, 256).simplify()))
+        });
+        Some((slot, keys))
+    }
+
+    /// Move a decoded location `offset` slots further
+    fn add_offset<'ctx>(keys: &mut Vec<SlotKey<'ctx>>, offset: Z3BV<'ctx>, ctx: &'ctx Context) {
+        let offset = fold_numeral(offset.simplify());
+        if offset.as_u64().ok() == Some(0) {
+            return;
+        }
+        match keys.last_mut() {
+            Some(key) if key.kind != KeyKind::Mapping => {
+                key.value = fold_numeral(key.value.add(&offset, ctx).as_z3(ctx).simplify());
+            }
+            _ => keys.push(SlotKey::field(offset)),
+        }
     }
 }
 
@@ -315,8 +671,8 @@ impl GenericStorage {
     }
 
     /// Create an empty array for storage
-    pub fn empty<'ctx>(addr: &[u8; 20], size: usize, ctx: &'ctx Context) -> Z3Array<'ctx> {
-        let name = format!("storage_{:?}_{}", addr, size);
+    pub fn empty<'ctx>(addr: &Address, size: usize, ctx: &'ctx Context) -> Z3Array<'ctx> {
+        let name = format!("storage_{}_{}", addr, size);
         let domain_sort = Sort::bitvector(ctx, size as u32);
         let range_sort = Sort::bitvector(ctx, 256);
         Z3Array::new_const(ctx, name, &domain_sort, &range_sort)
@@ -324,12 +680,12 @@ impl GenericStorage {
 
     /// Initialize storage if needed
     pub fn init<'ctx>(
-        storage: &mut HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &mut StorageMap<'ctx>,
+        addr: Address,
         size_keys: usize,
         ctx: &'ctx Context,
     ) -> CbseResult<()> {
-        let storage_addr = storage.entry(addr).or_insert_with(StorageData::new);
+        let storage_addr = storage.entry_mut(addr);
 
         let key = StorageKey::Generic(size_keys);
 
@@ -343,8 +699,8 @@ impl GenericStorage {
 
     /// Load from generic storage
     pub fn load<'ctx>(
-        storage: &HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &StorageMap<'ctx>,
+        addr: Address,
         loc: &CbseBitVec<'ctx>,
         ctx: &'ctx Context,
     ) -> CbseResult<CbseBitVec<'ctx>> {
@@ -357,33 +713,38 @@ impl GenericStorage {
         let key = StorageKey::Generic(size_keys);
 
         match storage_addr.get(&key) {
-            Some(StorageValue::Array(_array_name)) => {
-                // Return symbolic value for now
-                Ok(CbseBitVec::symbolic(
-                    ctx,
-                    &format!("storage_load_{}", size_keys),
-                    256,
-                ))
-            }
+            Some(StorageValue::Array(array)) => Ok(select_word(array, &loc.as_z3(ctx))),
             Some(StorageValue::Value(v)) => Ok(v.clone()),
+            None if storage_addr.symbolic => Ok(select_word(
+                &Self::empty(&addr, size_keys, ctx),
+                &loc.as_z3(ctx),
+            )),
             None => Ok(CbseBitVec::from_u64(0, 256)),
         }
     }
 
     /// Store to generic storage
     pub fn store<'ctx>(
-        storage: &mut HashMap<[u8; 20], StorageData<'ctx>>,
-        addr: [u8; 20],
+        storage: &mut StorageMap<'ctx>,
+        addr: Address,
         loc: &CbseBitVec<'ctx>,
         value: CbseBitVec<'ctx>,
-        _ctx: &'ctx Context,
+        ctx: &'ctx Context,
     ) -> CbseResult<()> {
         let size_keys = loc.size() as usize;
 
-        let storage_addr = storage.entry(addr).or_insert_with(StorageData::new);
+        let storage_addr = storage.entry_mut(addr);
         let key = StorageKey::Generic(size_keys);
 
-        storage_addr.set(key, StorageValue::Value(value));
+        let current_array = match storage_addr.get(&key) {
+            Some(StorageValue::Array(array)) => array.clone(),
+            _ => {
+                let name = format!("storage_{}_{}", addr, size_keys);
+                unwritten_array(storage_addr.symbolic, name, size_keys, 256, ctx)
+            }
+        };
+        let new_array = current_array.store(&loc.as_z3(ctx), &value.as_z3(ctx));
+        storage_addr.set(key, StorageValue::Array(new_array));
 
         Ok(())
     }
@@ -404,7 +765,7 @@ mod tests {
     fn test_storage_data() {
         let mut storage: StorageData = StorageData::new();
 
-        let key = StorageKey::Solidity(0, 0, 0);
+        let key = StorageKey::Solidity(0, Vec::new());
         let value = StorageValue::Value(CbseBitVec::from_u64(42, 256));
 
         storage.set(key.clone(), value);
@@ -416,11 +777,11 @@ mod tests {
     fn test_solidity_storage() {
         let cfg = Config::new();
         let ctx = Context::new(&cfg);
-        let mut storage = HashMap::new();
-        let addr = [1u8; 20];
+        let mut storage = StorageMap::new();
+        let addr = Address([1u8; 20]);
 
         // Initialize storage
-        SolidityStorage::init(&mut storage, addr, 0, 0, 0, &ctx).unwrap();
+        SolidityStorage::init(&mut storage, addr, 0, &[], 256, &ctx).unwrap();
 
         // Store a value
         let value = CbseBitVec::from_u64(100, 256);
@@ -431,12 +792,183 @@ mod tests {
         assert_eq!(loaded.as_u64().unwrap(), 100);
     }
 
+    #[test]
+    fn test_init_mapping_is_width_consistent() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut storage = StorageMap::new();
+        let addr = Address([1u8; 20]);
+        let shape = [(KeyKind::Mapping, 256)];
+
+        SolidityStorage::init(&mut storage, addr, 0, &shape, 256, &ctx).unwrap();
+        let word_sort = Sort::bitvector(&ctx, 256);
+        match storage[&addr].get(&StorageKey::Solidity(0, shape.to_vec())) {
+            Some(StorageValue::Array(array)) => {
+                assert_eq!(array.get_sort(), Sort::array(&ctx, &word_sort, &word_sort))
+            }
+            other => panic!("expected a storage array, got {:?}", other),
+        }
+
+        // Unwritten entries read as zero words, written ones as what was stored
+        let k = [SlotKey::mapping(CbseBitVec::symbolic(&ctx, "k", 256))];
+        let loaded = SolidityStorage::load(&storage, addr, 0, &k, &ctx).unwrap();
+        assert_eq!((loaded.size(), loaded.as_u64().unwrap()), (256, 0));
+        let seven = CbseBitVec::from_u64(7, 256);
+        SolidityStorage::store(&mut storage, addr, 0, &k, seven, &ctx).unwrap();
+        let loaded = SolidityStorage::load(&storage, addr, 0, &k, &ctx).unwrap();
+        assert_eq!((loaded.size(), loaded.as_u64().unwrap()), (256, 7));
+
+        // A value of another width is rejected instead of mixing sorts
+        let narrow = CbseBitVec::from_u64(7, 128);
+        assert!(SolidityStorage::store(&mut storage, addr, 0, &k, narrow, &ctx).is_err());
+    }
+
+    #[test]
+    fn test_symbolic_storage_load() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut storage = StorageMap::new();
+        let addr = Address([1u8; 20]);
+        let slot = |v: u64| [SlotKey::mapping(CbseBitVec::from_u64(v, 256))];
+
+        storage.insert(addr, StorageData::new_symbolic());
+        let a = SolidityStorage::load(&storage, addr, 0, &slot(1), &ctx).unwrap();
+        let b = SolidityStorage::load(&storage, addr, 0, &slot(1), &ctx).unwrap();
+        assert!(a.is_symbolic());
+        assert_eq!(a.as_z3(&ctx).to_string(), b.as_z3(&ctx).to_string());
+
+        // Concrete storage still reads unwritten slots as zero
+        storage.insert(addr, StorageData::new());
+        let zero = SolidityStorage::load(&storage, addr, 0, &slot(1), &ctx).unwrap();
+        assert_eq!(zero.as_u64().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_decode_mapping_slots() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+        let key = CbseBitVec::symbolic(&ctx, "k", 256);
+
+        // m[k] for the mapping at slot 3
+        let (slot, _) = registry.sha3(&ctx, &key.concat(&word(3)));
+        let (base, keys) = SolidityStorage::decode(&slot, &registry, &ctx).unwrap();
+        assert_eq!(base, 3);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].kind, KeyKind::Mapping);
+        assert_eq!(keys[0].value.as_z3(&ctx), key.as_z3(&ctx));
+
+        // m[5][k]: the concrete inner hash is found through the registry
+        let (inner, _) = registry.sha3(&ctx, &word(5).concat(&word(3)));
+        assert!(inner.is_concrete());
+        let (slot, _) = registry.sha3(&ctx, &key.concat(&inner));
+        let (base, keys) = SolidityStorage::decode(&slot, &registry, &ctx).unwrap();
+        assert_eq!(base, 3);
+        assert_eq!(keys[0].value.as_u64().unwrap(), 5);
+        assert_eq!(keys[1].value.as_z3(&ctx), key.as_z3(&ctx));
+
+        // Plain slots decode to themselves, other symbolic slots not at all
+        let (base, keys) = SolidityStorage::decode(&word(7), &registry, &ctx).unwrap();
+        assert_eq!(base, 7);
+        assert!(keys.is_empty());
+        assert!(SolidityStorage::decode(&key, &registry, &ctx).is_none());
+    }
+
+    #[test]
+    fn test_diff_reports_mapping_entries_at_their_keccak_slot() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut storage = StorageMap::new();
+        let addr = Address([1u8; 20]);
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+
+        storage.insert(addr, StorageData::new());
+        let before = storage[&addr].clone();
+        let keys = [SlotKey::mapping(word(5))];
+        SolidityStorage::store(&mut storage, addr, 3, &keys, word(42), &ctx).unwrap();
+
+        let diff = storage[&addr].diff(&before, &ctx);
+        assert_eq!(diff.len(), 1);
+        let (slot, old, new) = &diff[0];
+        let expected = KeccakRegistry::hash_term(&ctx, &word(5).concat(&word(3)));
+        assert_eq!(slot.as_biguint().unwrap(), expected.as_biguint().unwrap());
+        assert_eq!(old.as_u64().unwrap(), 0);
+        assert_eq!(new.as_u64().unwrap(), 42);
+    }
+
+    #[test]
+    fn test_decode_array_elements() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+        let i = CbseBitVec::symbolic(&ctx, "i", 256);
+
+        // a[i] for the dynamic array at slot 2: its data starts at hash(2)
+        let (start, _) = registry.sha3(&ctx, &word(2));
+        let (base, keys) = SolidityStorage::decode(&start.add(&i, &ctx), &registry, &ctx).unwrap();
+        assert_eq!(base, 2);
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].kind, KeyKind::Element);
+        assert_eq!(keys[0].value.as_z3(&ctx).simplify(), i.as_z3(&ctx));
+
+        // a[3] is found in the data region through the registry
+        let (base, keys) =
+            SolidityStorage::decode(&start.add(&word(3), &ctx), &registry, &ctx).unwrap();
+        assert_eq!(base, 2);
+        assert_eq!(keys[0].kind, KeyKind::Element);
+        assert_eq!(keys[0].value.as_u64().unwrap(), 3);
+
+        // m[k].f: a field after a mapping entry
+        let k = CbseBitVec::symbolic(&ctx, "k", 256);
+        let (entry, _) = registry.sha3(&ctx, &k.concat(&word(4)));
+        let (base, keys) =
+            SolidityStorage::decode(&entry.add(&word(1), &ctx), &registry, &ctx).unwrap();
+        assert_eq!(base, 4);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[1].kind, KeyKind::Field);
+        assert_eq!(keys[1].value.as_u64().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_array_elements_round_trip_through_storage() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut registry = KeccakRegistry::new();
+        let mut storage = StorageMap::new();
+        let addr = Address([1u8; 20]);
+        let word = |v: u64| CbseBitVec::from_u64(v, 256);
+        storage.insert(addr, StorageData::new());
+        let before = storage[&addr].clone();
+
+        // Writing a[i] is visible to a read of a[3] exactly when i == 3
+        let i = CbseBitVec::symbolic(&ctx, "i", 256);
+        let (start, _) = registry.sha3(&ctx, &word(2));
+        let (base, keys) = SolidityStorage::decode(&start.add(&i, &ctx), &registry, &ctx).unwrap();
+        SolidityStorage::store(&mut storage, addr, base, &keys, word(42), &ctx).unwrap();
+
+        let (base, keys) =
+            SolidityStorage::decode(&start.add(&word(3), &ctx), &registry, &ctx).unwrap();
+        let loaded = SolidityStorage::load(&storage, addr, base, &keys, &ctx).unwrap();
+        let solver = z3::Solver::new(&ctx);
+        solver.assert(&i.as_z3(&ctx)._eq(&word(3).as_z3(&ctx)));
+        solver.assert(&loaded.as_z3(&ctx)._eq(&word(42).as_z3(&ctx)).not());
+        assert_eq!(solver.check(), z3::SatResult::Unsat);
+
+        // The diff reports the element at its data region location
+        let diff = storage[&addr].diff(&before, &ctx);
+        assert_eq!(diff.len(), 1);
+        let expected = start.add(&i, &ctx).as_z3(&ctx).simplify();
+        assert_eq!(diff[0].0.as_z3(&ctx).simplify(), expected);
+    }
+
     #[test]
     fn test_generic_storage() {
         let cfg = Config::new();
         let ctx = Context::new(&cfg);
-        let mut storage = HashMap::new();
-        let addr = [2u8; 20];
+        let mut storage = StorageMap::new();
+        let addr = Address([2u8; 20]);
 
         // Initialize
         GenericStorage::init(&mut storage, addr, 256, &ctx).unwrap();
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Uninterpreted calls to addresses without code
+//!
+//! A call to an address with no deployed contract normally fails with empty
+//! return data. Calls whose selector is listed in `--uninterpreted-unknown-calls`
+//! instead succeed or fail symbolically and return fresh symbolic data of
+//! `--return-size-of-unknown-calls` bytes, so that callbacks such as
+//! `onERC721Received` on arbitrary receivers are explored both ways.
+
+use cbse_config::Config;
+use cbse_exceptions::{CbseException, CbseResult};
+
+/// Selectors of unknown calls modeled as uninterpreted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCalls {
+    selectors: Vec<u32>,
+    return_size: usize,
+}
+
+impl Default for UnknownCalls {
+    /// No uninterpreted calls: every call to a missing contract fails
+    fn default() -> Self {
+        Self::new(Vec::new(), 32)
+    }
+}
+
+impl UnknownCalls {
+    pub fn new(selectors: Vec<u32>, return_size: usize) -> Self {
+        Self {
+            selectors,
+            return_size,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> CbseResult<Self> {
+        let selectors = config.parse_uninterpreted_unknown_calls().map_err(|e| {
+            CbseException::Internal(format!("invalid uninterpreted unknown calls: {}", e))
+        })?;
+        Ok(Self::new(selectors, config.return_size_of_unknown_calls))
+    }
+
+    /// True if a call with `calldata` to an address without code is uninterpreted
+    pub fn is_uninterpreted(&self, calldata: &[u8]) -> bool {
+        calldata
+            .get(..4)
+            .and_then(|selector| selector.try_into().ok())
+            .map_or(false, |selector| {
+                self.selectors.contains(&u32::from_be_bytes(selector))
+            })
+    }
+
+    /// Bytes of symbolic return data
+    pub fn return_size(&self) -> usize {
+        self.return_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_listed_selectors_are_uninterpreted() {
+        let unknown = UnknownCalls::new(vec![0x150b7a02], 64);
+        assert!(unknown.is_uninterpreted(&[0x15, 0x0b, 0x7a, 0x02, 0xff]));
+        assert!(!unknown.is_uninterpreted(&[0x15, 0x0b, 0x7a, 0x03]));
+        assert!(!unknown.is_uninterpreted(&[0x15, 0x0b, 0x7a]));
+        assert_eq!(unknown.return_size(), 64);
+
+        assert!(!UnknownCalls::default().is_uninterpreted(&[0x15, 0x0b, 0x7a, 0x02]));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let mut config = Config::default();
+        config.return_size_of_unknown_calls = 96;
+        let unknown = UnknownCalls::from_config(&config).unwrap();
+        assert!(unknown.is_uninterpreted(&[0x16, 0x26, 0xba, 0x7e]));
+        assert_eq!(unknown.return_size(), 96);
+
+        config.uninterpreted_unknown_calls = "nope".to_string();
+        assert!(UnknownCalls::from_config(&config).is_err());
+    }
+}
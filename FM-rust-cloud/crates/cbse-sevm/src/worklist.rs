@@ -2,13 +2,61 @@
 
 //! Worklist for managing execution paths in symbolic execution
 
-/// Worklist for depth-first search path exploration
+use cbse_exceptions::CbseException;
+use std::collections::VecDeque;
+
+/// Path exploration strategy
 ///
-/// Manages a stack of execution states to explore, using DFS strategy.
+/// DFS tends to reach deep bugs quickly, while BFS covers shallow paths first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    /// Depth-first search (last in, first out)
+    #[default]
+    Dfs,
+    /// Breadth-first search (first in, first out)
+    Bfs,
+}
+
+impl Strategy {
+    pub fn value(&self) -> &'static str {
+        match self {
+            Strategy::Dfs => "dfs",
+            Strategy::Bfs => "bfs",
+        }
+    }
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = CbseException;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dfs" => Ok(Strategy::Dfs),
+            "bfs" => Ok(Strategy::Bfs),
+            _ => Err(CbseException::Internal(format!(
+                "Invalid exploration strategy: {}",
+                s
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+/// Worklist for path exploration
+///
+/// Manages the pending execution states to explore, ordered by the
+/// configured `Strategy` (DFS by default).
 #[derive(Debug)]
 pub struct Worklist<T> {
-    /// Stack of execution states
-    stack: Vec<T>,
+    /// Pending execution states
+    stack: VecDeque<T>,
+    /// Exploration order
+    strategy: Strategy,
     /// Count of completed paths
     pub completed_paths: usize,
 }
@@ -16,20 +64,36 @@ pub struct Worklist<T> {
 impl<T> Worklist<T> {
     /// Create a new empty worklist
     pub fn new() -> Self {
+        Self::with_strategy(Strategy::Dfs)
+    }
+
+    /// Create a new empty worklist with the given exploration strategy
+    pub fn with_strategy(strategy: Strategy) -> Self {
         Self {
-            stack: Vec::new(),
+            stack: VecDeque::new(),
+            strategy,
             completed_paths: 0,
         }
     }
 
+    /// Get the exploration strategy
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
     /// Push an execution state onto the worklist
     pub fn push(&mut self, item: T) {
-        self.stack.push(item);
+        self.stack.push_back(item);
     }
 
-    /// Pop an execution state from the worklist (DFS - last in, first out)
+    /// Pop the next execution state from the worklist
+    ///
+    /// DFS pops the most recently pushed item, BFS the oldest one.
     pub fn pop(&mut self) -> Option<T> {
-        self.stack.pop()
+        match self.strategy {
+            Strategy::Dfs => self.stack.pop_back(),
+            Strategy::Bfs => self.stack.pop_front(),
+        }
     }
 
     /// Get the number of pending items in the worklist
@@ -111,6 +175,48 @@ mod tests {
         assert_eq!(worklist.pop(), None);
     }
 
+    #[test]
+    fn test_worklist_bfs() {
+        let mut worklist: Worklist<i32> = Worklist::with_strategy(Strategy::Bfs);
+
+        worklist.push(1);
+        worklist.push(2);
+        worklist.push(3);
+
+        // BFS: first in, first out
+        assert_eq!(worklist.pop(), Some(1));
+        assert_eq!(worklist.pop(), Some(2));
+
+        worklist.push(4);
+
+        assert_eq!(worklist.pop(), Some(3));
+        assert_eq!(worklist.pop(), Some(4));
+        assert_eq!(worklist.pop(), None);
+    }
+
+    #[test]
+    fn test_worklist_dfs_interleaved() {
+        let mut worklist: Worklist<i32> = Worklist::with_strategy(Strategy::Dfs);
+
+        worklist.push(1);
+        worklist.push(2);
+        assert_eq!(worklist.pop(), Some(2));
+
+        worklist.push(3);
+        assert_eq!(worklist.pop(), Some(3));
+        assert_eq!(worklist.pop(), Some(1));
+        assert_eq!(worklist.pop(), None);
+    }
+
+    #[test]
+    fn test_strategy_parse() {
+        assert_eq!("dfs".parse::<Strategy>().unwrap(), Strategy::Dfs);
+        assert_eq!("BFS".parse::<Strategy>().unwrap(), Strategy::Bfs);
+        assert!("random".parse::<Strategy>().is_err());
+        assert_eq!(Strategy::default(), Strategy::Dfs);
+        assert_eq!(Worklist::<i32>::new().strategy(), Strategy::Dfs);
+    }
+
     #[test]
     fn test_worklist_completed_count() {
         let mut worklist: Worklist<i32> = Worklist::new();
@@ -7,6 +7,15 @@
 //! - DELEGATECALL (proxy pattern)
 //! - STATICCALL (read-only calls)
 //! - SELFDESTRUCT (contract destruction)
+//! - MSTORE8/MLOAD (byte-level memory access)
+//! - SLOAD warm/cold access costs (EIP-2929)
+//! - EXTCODESIZE/EXTCODEHASH/EXTCODECOPY (external code introspection)
+//! - DELEGATECALL storage and address context
+//! - STATICCALL write protection
+//! - CODESIZE/CODECOPY (own code introspection)
+//! - PC/MSIZE/GAS (execution introspection)
+//! - CREATE/CREATE2 code size limits (EIP-170/EIP-3860)
+//! - ORIGIN/GASPRICE/GASLIMIT/COINBASE (transaction and block environment)
 
 #[cfg(test)]
 mod new_opcode_tests {
@@ -16,6 +25,7 @@ mod new_opcode_tests {
     use cbse_hashes::keccak256;
     use cbse_sevm::SEVM;
     use cbse_traces::{CallContext, CallMessage, CallOutput};
+    use num_bigint::BigUint;
     use z3::{Config, Context};
 
     #[test]
@@ -49,7 +59,15 @@ mod new_opcode_tests {
         // Execute call
         let caller = [0u8; 20];
         let origin = [0u8; 20];
-        let result = sevm.execute_call(contract_addr, caller, origin, 0, vec![], 1000000, false);
+        let result = sevm.execute_call(
+            contract_addr,
+            caller,
+            origin,
+            BigUint::default(),
+            vec![],
+            1000000,
+            false,
+        );
 
         // Check that execution completed without error
         assert!(result.is_ok(), "LOG0 execution should succeed");
@@ -87,7 +105,15 @@ mod new_opcode_tests {
 
         let caller = [0u8; 20];
         let origin = [0u8; 20];
-        let result = sevm.execute_call(contract_addr, caller, origin, 0, vec![], 1000000, false);
+        let result = sevm.execute_call(
+            contract_addr,
+            caller,
+            origin,
+            BigUint::default(),
+            vec![],
+            1000000,
+            false,
+        );
         assert!(result.is_ok(), "LOG1 execution should succeed");
     }
 
@@ -126,7 +152,15 @@ mod new_opcode_tests {
 
         let caller = [0u8; 20];
         let origin = [0u8; 20];
-        let result = sevm.execute_call(contract_addr, caller, origin, 0, vec![], 1000000, false);
+        let result = sevm.execute_call(
+            contract_addr,
+            caller,
+            origin,
+            BigUint::default(),
+            vec![],
+            1000000,
+            false,
+        );
         assert!(result.is_ok(), "LOG4 execution should succeed");
     }
 
@@ -231,16 +265,16 @@ mod new_opcode_tests {
         let addr2 = [2u8; 20];
 
         // Set initial balance
-        sevm.set_balance(addr1, 1000);
-        assert_eq!(sevm.get_balance(&addr1), 1000);
-        assert_eq!(sevm.get_balance(&addr2), 0);
+        sevm.set_balance(addr1, 1000u64);
+        assert_eq!(sevm.get_balance_u64(&addr1), 1000);
+        assert_eq!(sevm.get_balance_u64(&addr2), 0);
 
         // Transfer
-        sevm.set_balance(addr1, 600);
-        sevm.set_balance(addr2, 400);
+        sevm.set_balance(addr1, 600u64);
+        sevm.set_balance(addr2, 400u64);
 
-        assert_eq!(sevm.get_balance(&addr1), 600);
-        assert_eq!(sevm.get_balance(&addr2), 400);
+        assert_eq!(sevm.get_balance_u64(&addr1), 600);
+        assert_eq!(sevm.get_balance_u64(&addr2), 400);
 
         println!("✓ Balance transfer works correctly");
     }
@@ -256,18 +290,18 @@ mod new_opcode_tests {
         let beneficiary = [2u8; 20];
 
         // Contract has 1000 wei
-        sevm.set_balance(contract_addr, 1000);
-        sevm.set_balance(beneficiary, 500);
+        sevm.set_balance(contract_addr, 1000u64);
+        sevm.set_balance(beneficiary, 500u64);
 
         // Simulate SELFDESTRUCT
         let contract_balance = sevm.get_balance(&contract_addr);
-        sevm.set_balance(contract_addr, 0);
+        sevm.set_balance(contract_addr, 0u64);
         let beneficiary_balance = sevm.get_balance(&beneficiary);
         sevm.set_balance(beneficiary, beneficiary_balance + contract_balance);
 
         // Verify transfer
-        assert_eq!(sevm.get_balance(&contract_addr), 0);
-        assert_eq!(sevm.get_balance(&beneficiary), 1500);
+        assert_eq!(sevm.get_balance_u64(&contract_addr), 0);
+        assert_eq!(sevm.get_balance_u64(&beneficiary), 1500);
 
         println!("✓ SELFDESTRUCT balance transfer works correctly");
     }
@@ -340,18 +374,18 @@ mod new_opcode_tests {
         use cbse_traces::EventLog;
 
         // LOG0 (no topics)
-        let log0 = EventLog::new(0x1234, vec![], vec![0xAB, 0xCD]);
+        let log0 = EventLog::new([0x12; 20], vec![], vec![0xAB, 0xCD]);
         assert_eq!(log0.topics.len(), 0);
         assert_eq!(log0.data, vec![0xAB, 0xCD]);
-        assert_eq!(log0.address, 0x1234);
+        assert_eq!(log0.address, [0x12; 20]);
 
         // LOG1 (1 topic)
-        let log1 = EventLog::new(0x1234, vec![vec![0x01; 32]], vec![0xAB, 0xCD]);
+        let log1 = EventLog::new([0x12; 20], vec![vec![0x01; 32]], vec![0xAB, 0xCD]);
         assert_eq!(log1.topics.len(), 1);
 
         // LOG4 (4 topics)
         let log4 = EventLog::new(
-            0x1234,
+            [0x12; 20],
             vec![
                 vec![0x01; 32],
                 vec![0x02; 32],
@@ -409,7 +443,7 @@ mod new_opcode_tests {
         use cbse_traces::{EventLog, TraceElement};
 
         // Test creating trace elements
-        let log = EventLog::new(0x1234, vec![vec![0x01; 32]], vec![0xAB, 0xCD]);
+        let log = EventLog::new([0x12; 20], vec![vec![0x01; 32]], vec![0xAB, 0xCD]);
         let trace_elem = TraceElement::Log(log);
 
         match trace_elem {
@@ -447,4 +481,521 @@ mod new_opcode_tests {
 
         println!("✓ DELEGATECALL context preservation verified");
     }
+
+    /// Deploy raw bytecode at a fixed address and call it with empty calldata.
+    /// Returns (success, returndata, gas_used).
+    fn run_bytecode(sevm: &mut SEVM, ctx: &Context, bytecode: &[u8]) -> (bool, Vec<u8>, u64) {
+        let mut bytevec = ByteVec::new(ctx);
+        for (i, &byte) in bytecode.iter().enumerate() {
+            let byte_bv = CbseBitVec::from_u64(byte as u64, 8);
+            bytevec
+                .set_byte(i, cbse_bytevec::UnwrappedBytes::BitVec(byte_bv))
+                .unwrap();
+        }
+
+        let contract_addr = [1u8; 20];
+        let contract = Contract::new(bytevec, ctx, None, None, None);
+        sevm.deploy_contract(contract_addr, contract);
+
+        let caller = [0u8; 20];
+        let (success, returndata, gas_used, _) = sevm
+            .execute_call(
+                contract_addr,
+                caller,
+                caller,
+                BigUint::default(),
+                vec![],
+                1000000,
+                false,
+            )
+            .unwrap();
+        (success, returndata, gas_used)
+    }
+
+    #[test]
+    fn test_mstore8_writes_single_byte() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x60, 0x00, // PUSH1 0
+            0x19, // NOT (0xff..ff)
+            0x60, 0x00, // PUSH1 0
+            0x52, // MSTORE
+            0x61, 0x12, 0x34, // PUSH2 0x1234
+            0x60, 0x01, // PUSH1 1
+            0x53, // MSTORE8 (writes 0x34 at offset 1)
+            0x60, 0x20, // PUSH1 32
+            0x60, 0x00, // PUSH1 0
+            0xf3, // RETURN
+        ];
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+
+        let mut expected = vec![0xffu8; 32];
+        expected[1] = 0x34;
+        assert_eq!(returndata, expected);
+    }
+
+    #[test]
+    fn test_mload_zero_pads_past_written_region() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x60, 0x42, // PUSH1 0x42
+            0x60, 0x00, // PUSH1 0
+            0x52, // MSTORE (memory[0..32] = 0x00..42)
+            0x60, 0x10, // PUSH1 16
+            0x51, // MLOAD (memory[16..48], half past the end)
+            0x60, 0x40, // PUSH1 64
+            0x52, // MSTORE
+            0x60, 0x20, // PUSH1 32
+            0x60, 0x40, // PUSH1 64
+            0xf3, // RETURN
+        ];
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+
+        let mut expected = vec![0u8; 32];
+        expected[15] = 0x42;
+        assert_eq!(returndata, expected);
+    }
+
+    #[test]
+    fn test_sload_same_slot_cold_then_warm() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x60, 0x00, // PUSH1 0
+            0x54, // SLOAD (cold)
+            0x50, // POP
+            0x60, 0x00, // PUSH1 0
+            0x54, // SLOAD (warm)
+            0x50, // POP
+            0x00, // STOP
+        ];
+
+        let (_, _, gas_used) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        assert_eq!(gas_used, 2100 + 100);
+    }
+
+    #[test]
+    fn test_sload_different_slots_both_cold() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x60, 0x00, // PUSH1 0
+            0x54, // SLOAD (cold)
+            0x50, // POP
+            0x60, 0x01, // PUSH1 1
+            0x54, // SLOAD (cold)
+            0x50, // POP
+            0x00, // STOP
+        ];
+
+        let (_, _, gas_used) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        assert_eq!(gas_used, 2100 + 2100);
+    }
+
+    /// Deploy `code` at [2u8; 20] as the target of the EXTCODE* tests
+    fn deploy_target(sevm: &mut SEVM, ctx: &Context, code: &str) {
+        let contract = Contract::from_hexcode(code, ctx).unwrap();
+        sevm.deploy_contract([2u8; 20], contract);
+    }
+
+    /// PUSH20 addr <opcode> PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    fn query_bytecode(addr: [u8; 20], opcode: u8) -> Vec<u8> {
+        let mut bytecode = vec![0x73];
+        bytecode.extend_from_slice(&addr);
+        bytecode.extend_from_slice(&[opcode, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        bytecode
+    }
+
+    #[test]
+    fn test_extcodesize() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        deploy_target(&mut sevm, &ctx, "6001600055");
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &query_bytecode([2u8; 20], 0x3b));
+        assert_eq!(returndata[31], 5);
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &query_bytecode([3u8; 20], 0x3b));
+        assert_eq!(returndata, vec![0u8; 32]);
+    }
+
+    #[test]
+    fn test_extcodehash() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        deploy_target(&mut sevm, &ctx, "6001600055");
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &query_bytecode([2u8; 20], 0x3f));
+        assert_eq!(
+            returndata,
+            keccak256(&[0x60, 0x01, 0x60, 0x00, 0x55]).to_vec()
+        );
+
+        // Nonexistent account
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &query_bytecode([3u8; 20], 0x3f));
+        assert_eq!(returndata, vec![0u8; 32]);
+
+        // Existing account without code
+        sevm.set_balance([4u8; 20], 1u64);
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &query_bytecode([4u8; 20], 0x3f));
+        assert_eq!(returndata, keccak256(&[]).to_vec());
+    }
+
+    #[test]
+    fn test_extcodecopy() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        deploy_target(&mut sevm, &ctx, "6001600055");
+
+        // EXTCODECOPY(addr, dest=0, offset=0, size=8), RETURN(0, 8)
+        let mut bytecode = vec![0x60, 0x08, 0x60, 0x00, 0x60, 0x00, 0x73];
+        bytecode.extend_from_slice(&[2u8; 20]);
+        bytecode.extend_from_slice(&[0x3c, 0x60, 0x08, 0x60, 0x00, 0xf3]);
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        assert_eq!(returndata, vec![0x60, 0x01, 0x60, 0x00, 0x55, 0, 0, 0]);
+
+        // Absent account copies zeros
+        let mut bytecode = vec![0x60, 0x04, 0x60, 0x00, 0x60, 0x00, 0x73];
+        bytecode.extend_from_slice(&[3u8; 20]);
+        bytecode.extend_from_slice(&[0x3c, 0x60, 0x04, 0x60, 0x00, 0xf3]);
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        assert_eq!(returndata, vec![0u8; 4]);
+    }
+
+    #[test]
+    fn test_delegatecall_uses_caller_storage_and_context() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // Library: sstore(0, 0x2a); return(address(), caller())
+        let mut library_addr = [0u8; 20];
+        library_addr[19] = 0x02;
+        let library = Contract::from_hexcode("602a600055306000523360205260406000f3", &ctx).unwrap();
+        sevm.deploy_contract(library_addr, library);
+
+        let bytecode = vec![
+            0x60, 0x40, // PUSH1 64 (ret_length)
+            0x60, 0x00, // PUSH1 0 (ret_offset)
+            0x60, 0x00, // PUSH1 0 (args_length)
+            0x60, 0x00, // PUSH1 0 (args_offset)
+            0x60, 0x02, // PUSH1 library
+            0x62, 0x0f, 0x42, 0x40, // PUSH3 1000000 (gas)
+            0xf4, // DELEGATECALL
+            0x50, // POP
+            0x60, 0x40, // PUSH1 64
+            0x60, 0x00, // PUSH1 0
+            0xf3, // RETURN
+        ];
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+
+        // ADDRESS inside the library is the delegating contract
+        let mut expected_address = vec![0u8; 12];
+        expected_address.extend_from_slice(&[1u8; 20]);
+        assert_eq!(returndata[0..32], expected_address[..]);
+
+        // CALLER inside the library is the delegating contract's caller
+        assert_eq!(returndata[32..64], [0u8; 32]);
+
+        // The write landed in the delegating contract's storage
+        let slot = CbseBitVec::from_u64(0, 256);
+        let value = sevm.get_storage([1u8; 20], &slot);
+        assert_eq!(value.as_z3(&ctx).simplify().as_u64(), Some(0x2a));
+        let value = sevm.get_storage(library_addr, &slot);
+        assert_eq!(value.as_z3(&ctx).simplify().as_u64(), Some(0));
+    }
+
+    /// STATICCALL the code at 0x..03 and return the call status as a word
+    fn staticcall_status(callee_hexcode: &str) -> u8 {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let mut callee_addr = [0u8; 20];
+        callee_addr[19] = 0x03;
+        let callee = Contract::from_hexcode(callee_hexcode, &ctx).unwrap();
+        sevm.deploy_contract(callee_addr, callee);
+
+        let bytecode = vec![
+            0x60, 0x00, // PUSH1 0 (ret_length)
+            0x60, 0x00, // PUSH1 0 (ret_offset)
+            0x60, 0x00, // PUSH1 0 (args_length)
+            0x60, 0x00, // PUSH1 0 (args_offset)
+            0x60, 0x03, // PUSH1 callee
+            0x62, 0x0f, 0x42, 0x40, // PUSH3 1000000 (gas)
+            0xfa, // STATICCALL
+            0x60, 0x00, // PUSH1 0
+            0x52, // MSTORE
+            0x60, 0x20, // PUSH1 32
+            0x60, 0x00, // PUSH1 0
+            0xf3, // RETURN
+        ];
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        returndata[31]
+    }
+
+    #[test]
+    fn test_staticcall_sstore_fails() {
+        // sstore(0, 1)
+        assert_eq!(staticcall_status("6001600055"), 0);
+    }
+
+    #[test]
+    fn test_staticcall_log_fails() {
+        // log0(0, 0)
+        assert_eq!(staticcall_status("60006000a0"), 0);
+    }
+
+    #[test]
+    fn test_staticcall_read_succeeds() {
+        // mstore(0, sload(0)); return(0, 32)
+        assert_eq!(staticcall_status("60005460005260206000f3"), 1);
+    }
+
+    #[test]
+    fn test_codesize() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x38, // CODESIZE
+            0x60, 0x00, // PUSH1 0
+            0x52, // MSTORE
+            0x60, 0x20, // PUSH1 32
+            0x60, 0x00, // PUSH1 0
+            0xf3, // RETURN
+        ];
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        assert_eq!(returndata[31] as usize, bytecode.len());
+    }
+
+    #[test]
+    fn test_codecopy_zero_pads_over_read() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let bytecode = vec![
+            0x60, 0x10, // PUSH1 16 (size, past the end of the 12-byte code)
+            0x60, 0x00, // PUSH1 0 (offset)
+            0x60, 0x00, // PUSH1 0 (dest)
+            0x39, // CODECOPY
+            0x60, 0x10, // PUSH1 16
+            0x60, 0x00, // PUSH1 0
+            0xf3, // RETURN
+        ];
+
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &bytecode);
+        let mut expected = bytecode.clone();
+        expected.resize(16, 0);
+        assert_eq!(returndata, expected);
+    }
+
+    /// Run `bytecode` followed by PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN and
+    /// return the stored word's low 8 bytes
+    fn run_and_return_top(bytecode: &[u8]) -> u64 {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let mut code = bytecode.to_vec();
+        code.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        let (_, returndata, _) = run_bytecode(&mut sevm, &ctx, &code);
+        u64::from_be_bytes(returndata[24..32].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_pc_reports_offset() {
+        let bytecode = vec![
+            0x60, 0x01, // PUSH1 1
+            0x50, // POP
+            0x5b, // JUMPDEST
+            0x58, // PC (at offset 4)
+        ];
+        assert_eq!(run_and_return_top(&bytecode), 4);
+    }
+
+    #[test]
+    fn test_msize_rounds_up_to_word() {
+        // Untouched memory
+        assert_eq!(run_and_return_top(&[0x59]), 0);
+
+        // MSTORE8 at offset 0x41 touches byte 65, so memory is three words
+        let bytecode = vec![
+            0x60, 0xff, // PUSH1 0xff
+            0x60, 0x41, // PUSH1 0x41
+            0x53, // MSTORE8
+            0x59, // MSIZE
+        ];
+        assert_eq!(run_and_return_top(&bytecode), 96);
+
+        // MSTORE at 0x100 ends at 0x120
+        let bytecode = vec![
+            0x60, 0x2a, // PUSH1 42
+            0x61, 0x01, 0x00, // PUSH2 0x100
+            0x52, // MSTORE
+            0x59, // MSIZE
+        ];
+        assert_eq!(run_and_return_top(&bytecode), 0x120);
+    }
+
+    #[test]
+    fn test_msize_grows_on_read() {
+        let bytecode = vec![
+            0x60, 0x50, // PUSH1 0x50
+            0x51, // MLOAD (reads 0x50..0x70)
+            0x50, // POP
+            0x59, // MSIZE
+        ];
+        assert_eq!(run_and_return_top(&bytecode), 0x80);
+    }
+
+    #[test]
+    fn test_gas_reports_remaining() {
+        let bytecode = vec![
+            0x60, 0x00, // PUSH1 0
+            0x54, // SLOAD (cold)
+            0x50, // POP
+            0x5a, // GAS
+        ];
+        assert_eq!(run_and_return_top(&bytecode), 1_000_000 - 2100);
+    }
+
+    /// Run CREATE (or CREATE2 with salt 0) on `size` bytes of zeroed memory
+    /// and return the low 8 bytes of the pushed address
+    fn create_with_size(sevm: &mut SEVM, ctx: &Context, create2: bool, size: u16) -> u64 {
+        let [hi, lo] = size.to_be_bytes();
+        let mut code = vec![
+            0x60, 0x00, // PUSH1 0 (salt, ignored by CREATE)
+            0x61, hi, lo, // PUSH2 size
+            0x60, 0x00, // PUSH1 0 (offset)
+            0x60, 0x00, // PUSH1 0 (value)
+        ];
+        code.push(if create2 { 0xf5 } else { 0xf0 });
+        code.extend_from_slice(&[0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3]);
+        let (_, returndata, _) = run_bytecode(sevm, ctx, &code);
+        u64::from_be_bytes(returndata[24..32].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_create_rejects_code_over_size_limit() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+
+        for create2 in [false, true] {
+            let mut sevm = SEVM::new(&ctx);
+            assert_ne!(create_with_size(&mut sevm, &ctx, create2, 0xc000), 0);
+            assert_eq!(create_with_size(&mut sevm, &ctx, create2, 0xc001), 0);
+        }
+    }
+
+    #[test]
+    fn test_create_size_limits_are_configurable() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // A chain without EIP-170 still caps init code under EIP-3860
+        sevm.max_code_size = usize::MAX;
+        assert_ne!(create_with_size(&mut sevm, &ctx, false, 0x6001), 0);
+        assert_ne!(create_with_size(&mut sevm, &ctx, false, 0xc000), 0);
+        assert_eq!(create_with_size(&mut sevm, &ctx, false, 0xc001), 0);
+    }
+
+    #[test]
+    fn test_origin_is_top_level_sender_in_nested_calls() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // inner: return(origin())
+        let inner = Contract::from_hexcode("3260005260206000f3", &ctx).unwrap();
+        sevm.deploy_contract([0xbbu8; 20], inner);
+
+        // outer: call(1000000, inner, 0, 0, 0, 0, 32); return(0, 32)
+        let outer = format!(
+            "60206000600060006000\
+             73{}620f4240f15060206000f3",
+            "bb".repeat(20)
+        );
+        sevm.deploy_contract([1u8; 20], Contract::from_hexcode(&outer, &ctx).unwrap());
+
+        let sender = [0x0au8; 20];
+        let (success, returndata, _, _) = sevm
+            .execute_call(
+                [1u8; 20],
+                sender,
+                sender,
+                BigUint::default(),
+                vec![],
+                1_000_000,
+                false,
+            )
+            .unwrap();
+        assert!(success);
+        assert_eq!(returndata[12..32], sender);
+    }
+
+    #[test]
+    fn test_env_opcodes_read_configured_values() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.block.coinbase = Some([0xccu8; 20]);
+        sevm.block.gas_limit = Some(12_345);
+        sevm.tx.gas_price = Some(7);
+
+        let return_top = [0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+        let mut results = Vec::new();
+        for opcode in [0x41, 0x45, 0x3a] {
+            let mut code = vec![opcode];
+            code.extend_from_slice(&return_top);
+            let (success, returndata, _) = run_bytecode(&mut sevm, &ctx, &code);
+            assert!(success);
+            results.push(returndata);
+        }
+
+        assert_eq!(results[0][12..32], [0xccu8; 20]);
+        assert_eq!(
+            u64::from_be_bytes(results[1][24..32].try_into().unwrap()),
+            12_345
+        );
+        assert_eq!(
+            u64::from_be_bytes(results[2][24..32].try_into().unwrap()),
+            7
+        );
+    }
+
+    #[test]
+    fn test_unset_coinbase_and_gas_price_are_symbolic() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let sevm = SEVM::new(&ctx);
+
+        assert!(sevm.block.coinbase(&ctx).is_symbolic());
+        assert!(sevm.tx.gas_price(&ctx).is_symbolic());
+        assert_eq!(
+            sevm.block.gas_limit(&ctx).as_u64().unwrap(),
+            cbse_constants::GAS_LIMIT_DEFAULT
+        );
+    }
 }
@@ -0,0 +1,323 @@
+// SPDX-License-Identifier: AGPL-3.0
+
+//! Tests for opcodes that only operate on the stack:
+//! - LT/GT/SLT/SGT/EQ/ISZERO (comparisons)
+//! - AND/OR/XOR/NOT (bitwise logic)
+//! - ADD/SUB/MUL/DIV/SDIV/MOD/SMOD (arithmetic)
+//! - BLOCKHASH (recent block hashes)
+
+#[cfg(test)]
+mod stack_opcode_tests {
+    use cbse_bitvec::CbseBitVec;
+    use cbse_bytevec::ByteVec;
+    use cbse_contract::Contract;
+    use cbse_sevm::{ExecState, Message, SEVM};
+    use cbse_traces::{CallContext, CallMessage, CallOutput};
+    use num_bigint::BigUint;
+    use std::rc::Rc;
+    use z3::ast::{Ast, BV};
+    use z3::{Config, Context};
+
+    /// Execute `opcode` on a stack holding `operands` (first operand on top)
+    /// and return the single resulting word
+    fn apply<'ctx>(
+        sevm: &mut SEVM<'ctx>,
+        opcode: u8,
+        operands: Vec<CbseBitVec<'ctx>>,
+    ) -> CbseBitVec<'ctx> {
+        let ctx = sevm.ctx;
+        let call_context = CallContext::new(
+            CallMessage::new(0, 0, 0, Vec::new(), 0xF1, false),
+            CallOutput::new(None, None, None),
+            0,
+        );
+        let mut state = ExecState::new(ctx, call_context, Rc::clone(&sevm.solver));
+        state.stack = operands.into_iter().rev().collect();
+
+        let message = Message {
+            target: [1u8; 20],
+            caller: [0u8; 20],
+            origin: [0u8; 20],
+            value: CbseBitVec::from_u64(0, 256),
+            data: ByteVec::new(ctx),
+            gas: 1_000_000,
+            is_static: false,
+        };
+        let contract = Contract::from_hexcode("00", ctx).unwrap();
+
+        sevm.execute_opcode(opcode, &mut state, &message, &contract)
+            .unwrap();
+        assert_eq!(state.stack.len(), 1);
+        state.stack.pop().unwrap()
+    }
+
+    fn word<'ctx>(value: u64) -> CbseBitVec<'ctx> {
+        CbseBitVec::from_u64(value, 256)
+    }
+
+    /// Two's complement encoding of -value
+    fn neg<'ctx>(value: u64) -> CbseBitVec<'ctx> {
+        CbseBitVec::from_biguint((BigUint::from(1u8) << 256) - value, 256)
+    }
+
+    fn as_u64(bv: &CbseBitVec) -> u64 {
+        assert_eq!(bv.size(), 256);
+        bv.as_u64().unwrap()
+    }
+
+    /// Evaluate `bv` with the symbol `x` replaced by `value`
+    fn eval_at(ctx: &Context, bv: &CbseBitVec, value: u64) -> Option<u64> {
+        let x = BV::new_const(ctx, "x", 256);
+        bv.as_z3(ctx)
+            .substitute(&[(&x, &BV::from_u64(ctx, value, 256))])
+            .simplify()
+            .as_u64()
+    }
+
+    #[test]
+    fn test_lt_gt_operand_order() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // top = a, next = b: LT pushes a < b
+        assert_eq!(as_u64(&apply(&mut sevm, 0x10, vec![word(1), word(2)])), 1);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x10, vec![word(2), word(1)])), 0);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x11, vec![word(2), word(1)])), 1);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x11, vec![word(1), word(1)])), 0);
+    }
+
+    #[test]
+    fn test_signed_comparisons_with_negatives() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // -1 < 1 signed, but 2^256-1 > 1 unsigned
+        assert_eq!(as_u64(&apply(&mut sevm, 0x12, vec![neg(1), word(1)])), 1);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x13, vec![neg(1), word(1)])), 0);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x10, vec![neg(1), word(1)])), 0);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x13, vec![neg(1), neg(2)])), 1);
+    }
+
+    #[test]
+    fn test_eq() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        assert_eq!(as_u64(&apply(&mut sevm, 0x14, vec![word(7), word(7)])), 1);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x14, vec![word(7), neg(7)])), 0);
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let result = apply(&mut sevm, 0x14, vec![x, word(3)]);
+        assert_eq!(result.size(), 256);
+        assert_eq!(eval_at(&ctx, &result, 3), Some(1));
+        assert_eq!(eval_at(&ctx, &result, 4), Some(0));
+    }
+
+    #[test]
+    fn test_iszero() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        assert_eq!(as_u64(&apply(&mut sevm, 0x15, vec![word(0)])), 1);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x15, vec![word(5)])), 0);
+
+        // A symbolic operand stays symbolic instead of being treated as nonzero
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let result = apply(&mut sevm, 0x15, vec![x]);
+        assert!(result.is_symbolic());
+        assert_eq!(eval_at(&ctx, &result, 0), Some(1));
+        assert_eq!(eval_at(&ctx, &result, 9), Some(0));
+    }
+
+    #[test]
+    fn test_bitwise_concrete() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        assert_eq!(
+            as_u64(&apply(&mut sevm, 0x16, vec![word(0b1100), word(0b1010)])),
+            0b1000
+        );
+        assert_eq!(
+            as_u64(&apply(&mut sevm, 0x17, vec![word(0b1100), word(0b1010)])),
+            0b1110
+        );
+        assert_eq!(
+            as_u64(&apply(&mut sevm, 0x18, vec![word(0b1100), word(0b1010)])),
+            0b0110
+        );
+    }
+
+    #[test]
+    fn test_not_zero_is_all_ones() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let result = apply(&mut sevm, 0x19, vec![word(0)]);
+        assert_eq!(result.size(), 256);
+        assert_eq!(
+            result.as_biguint().unwrap(),
+            (BigUint::from(1u8) << 256) - 1u8
+        );
+    }
+
+    #[test]
+    fn test_bitwise_mixed_symbolic() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let x = || CbseBitVec::symbolic(&ctx, "x", 256);
+
+        let and = apply(&mut sevm, 0x16, vec![x(), word(0xff)]);
+        assert_eq!(and.size(), 256);
+        assert_eq!(eval_at(&ctx, &and, 0x1234), Some(0x34));
+
+        let or = apply(&mut sevm, 0x17, vec![word(0xf0), x()]);
+        assert_eq!(eval_at(&ctx, &or, 0x0f), Some(0xff));
+
+        let xor = apply(&mut sevm, 0x18, vec![x(), x()]);
+        assert_eq!(eval_at(&ctx, &xor, 0xdead), Some(0));
+
+        let not = apply(&mut sevm, 0x19, vec![x()]);
+        let not_not = apply(&mut sevm, 0x19, vec![not]);
+        assert_eq!(eval_at(&ctx, &not_not, 42), Some(42));
+    }
+
+    #[test]
+    fn test_bitwise_widens_narrow_operands() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        // A 160-bit symbolic address masked with a 256-bit word
+        let addr = CbseBitVec::symbolic(&ctx, "addr", 160);
+        let result = apply(&mut sevm, 0x16, vec![addr, word(u64::MAX)]);
+        assert_eq!(result.size(), 256);
+
+        let result = apply(&mut sevm, 0x19, vec![CbseBitVec::from_u64(0, 160)]);
+        assert_eq!(result.size(), 256);
+    }
+
+    #[test]
+    fn test_arithmetic_concrete_folding() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        assert_eq!(as_u64(&apply(&mut sevm, 0x01, vec![neg(1), word(2)])), 1);
+        let sub = apply(&mut sevm, 0x03, vec![word(2), word(5)]);
+        assert_eq!(sub.as_biguint().unwrap(), neg(3).as_biguint().unwrap());
+        assert_eq!(as_u64(&apply(&mut sevm, 0x02, vec![word(6), word(7)])), 42);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x04, vec![word(7), word(2)])), 3);
+        assert_eq!(as_u64(&apply(&mut sevm, 0x06, vec![word(7), word(2)])), 1);
+
+        // -7 sdiv 2 == -3 and -7 smod 2 == -1 (truncating towards zero)
+        let sdiv = apply(&mut sevm, 0x05, vec![neg(7), word(2)]);
+        assert_eq!(sdiv.as_biguint().unwrap(), neg(3).as_biguint().unwrap());
+        let smod = apply(&mut sevm, 0x07, vec![neg(7), word(2)]);
+        assert_eq!(smod.as_biguint().unwrap(), neg(1).as_biguint().unwrap());
+    }
+
+    #[test]
+    fn test_division_by_zero_is_zero() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        for opcode in [0x04, 0x05, 0x06, 0x07] {
+            assert_eq!(as_u64(&apply(&mut sevm, opcode, vec![word(7), word(0)])), 0);
+
+            // Symbolic divisor: zero only when the divisor turns out to be zero
+            let x = CbseBitVec::symbolic(&ctx, "x", 256);
+            let result = apply(&mut sevm, opcode, vec![word(7), x]);
+            assert_eq!(eval_at(&ctx, &result, 0), Some(0));
+        }
+    }
+
+    #[test]
+    fn test_mul_by_power_of_two_is_shift() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let result = apply(&mut sevm, 0x02, vec![x, word(8)]);
+        let term = result.as_z3(&ctx).to_string();
+        assert!(term.contains("bvshl"), "{}", term);
+        assert!(!term.contains("f_evm_bvmul_256"), "{}", term);
+        assert_eq!(eval_at(&ctx, &result, 3), Some(24));
+
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let result = apply(&mut sevm, 0x04, vec![x, word(8)]);
+        assert!(result.as_z3(&ctx).to_string().contains("bvlshr"));
+        assert_eq!(eval_at(&ctx, &result, 80), Some(10));
+    }
+
+    #[test]
+    fn test_symbolic_operands_use_abstractions() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+
+        for (opcode, name) in [
+            (0x02, "f_evm_bvmul_256"),
+            (0x04, "f_evm_bvudiv_256"),
+            (0x05, "f_evm_bvsdiv_256"),
+            (0x06, "f_evm_bvurem_256"),
+            (0x07, "f_evm_bvsrem_256"),
+        ] {
+            let x = CbseBitVec::symbolic(&ctx, "x", 256);
+            let y = CbseBitVec::symbolic(&ctx, "y", 256);
+            let result = apply(&mut sevm, opcode, vec![x, y]);
+            assert_eq!(result.size(), 256);
+            let term = result.as_z3(&ctx).to_string();
+            assert!(term.contains(name), "{}", term);
+        }
+    }
+
+    #[test]
+    fn test_blockhash_same_number_same_hash() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.block.number = 1000;
+
+        let first = apply(&mut sevm, 0x40, vec![word(990)]);
+        let second = apply(&mut sevm, 0x40, vec![word(990)]);
+        assert!(first.is_symbolic());
+        assert_eq!(first.as_z3(&ctx), second.as_z3(&ctx));
+        assert!(first.as_z3(&ctx).to_string().contains("f_blockhash"));
+
+        let other = apply(&mut sevm, 0x40, vec![word(991)]);
+        assert_ne!(first.as_z3(&ctx), other.as_z3(&ctx));
+    }
+
+    #[test]
+    fn test_blockhash_outside_window_is_zero() {
+        let cfg = Config::new();
+        let ctx = Context::new(&cfg);
+        let mut sevm = SEVM::new(&ctx);
+        sevm.block.number = 1000;
+
+        // 256 blocks back is the oldest hash still available
+        assert!(apply(&mut sevm, 0x40, vec![word(744)]).is_symbolic());
+        for number in [743, 0, 1000, 2000] {
+            assert_eq!(as_u64(&apply(&mut sevm, 0x40, vec![word(number)])), 0);
+        }
+
+        // A symbolic number is zero wherever it falls outside the window
+        let x = CbseBitVec::symbolic(&ctx, "x", 256);
+        let result = apply(&mut sevm, 0x40, vec![x]);
+        assert_eq!(result.size(), 256);
+        assert_eq!(eval_at(&ctx, &result, 5), Some(0));
+        assert_eq!(eval_at(&ctx, &result, 1000), Some(0));
+        assert_eq!(eval_at(&ctx, &result, 990), None);
+    }
+}
@@ -2,6 +2,7 @@
 
 //! SMT solver integration and model parsing
 
+use num_bigint::BigUint;
 use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
@@ -42,7 +43,7 @@ pub struct ModelVariable {
     pub solidity_type: String,
     pub smt_type: String,
     pub size_bits: usize,
-    pub value: u128,
+    pub value: BigUint,
 }
 
 impl ModelVariable {
@@ -52,7 +53,7 @@ impl ModelVariable {
         solidity_type: String,
         smt_type: String,
         size_bits: usize,
-        value: u128,
+        value: BigUint,
     ) -> Self {
         Self {
             full_name,
@@ -212,27 +213,27 @@ impl SolverOutput {
 }
 
 /// Parse constant value from SMT output
-pub fn parse_const_value(value: &str) -> Result<u128, String> {
-    if value.starts_with("#b") {
+///
+/// Values are as wide as their sort, so a 256-bit value is kept in full.
+pub fn parse_const_value(value: &str) -> Result<BigUint, String> {
+    if let Some(digits) = value.strip_prefix("#b") {
         // Binary: #b1010
-        u128::from_str_radix(&value[2..], 2)
-            .map_err(|e| format!("Failed to parse binary value: {}", e))
-    } else if value.starts_with("#x") {
+        BigUint::parse_bytes(digits.as_bytes(), 2)
+            .ok_or_else(|| format!("Failed to parse binary value: {}", value))
+    } else if let Some(digits) = value.strip_prefix("#x") {
         // Hex: #xFF
-        u128::from_str_radix(&value[2..], 16)
-            .map_err(|e| format!("Failed to parse hex value: {}", e))
-    } else if value.starts_with("bv") {
+        BigUint::parse_bytes(digits.as_bytes(), 16)
+            .ok_or_else(|| format!("Failed to parse hex value: {}", value))
+    } else if let Some(digits) = value.strip_prefix("bv") {
         // Decimal: bv42
-        value[2..]
-            .parse()
-            .map_err(|e| format!("Failed to parse bv value: {}", e))
+        BigUint::parse_bytes(digits.as_bytes(), 10)
+            .ok_or_else(|| format!("Failed to parse bv value: {}", value))
     } else if value.contains("bv") {
         // Pattern: (_ bv123 256)
         for token in value.split_whitespace() {
-            if token.starts_with("bv") {
-                return token[2..]
-                    .parse()
-                    .map_err(|e| format!("Failed to parse bv token: {}", e));
+            if let Some(digits) = token.strip_prefix("bv") {
+                return BigUint::parse_bytes(digits.as_bytes(), 10)
+                    .ok_or_else(|| format!("Failed to parse bv token: {}", token));
             }
         }
         Err(format!("No bv token found in: {}", value))
@@ -278,7 +279,7 @@ pub fn parse_model_str(smtlib_str: &str) -> ModelVariables {
         let full_name = captures[1].trim().to_string();
         let smt_type = format!("{} {}", &captures[2], &captures[3]);
         let size_bits: usize = captures[3].parse().unwrap_or(0);
-        let value = parse_const_value(&captures[4]).unwrap_or(0);
+        let value = parse_const_value(&captures[4]).unwrap_or_default();
 
         // Extract variable name and type from full name
         // Format: halmos_varname_type or p_varname_type
@@ -463,6 +464,86 @@ pub fn solve_external(
     }
 }
 
+/// Solve an SMT-LIB2 query by piping it to the solver's stdin
+///
+/// `query` is sent as-is, so it must contain its own `(check-sat)` (and
+/// `(get-model)` if a model is wanted). On timeout the solver is killed and
+/// the result is `Unknown` with `EXIT_TIMEDOUT`, as in `solve_external`.
+pub fn solve_stdin(
+    solver_command: &[String],
+    query: &str,
+    timeout: Option<Duration>,
+    path_id: usize,
+) -> SolverOutput {
+    let source = "<stdin>".to_string();
+
+    let Some((program, args)) = solver_command.split_first() else {
+        return SolverOutput::from_error("Empty solver command".to_string(), path_id, source);
+    };
+
+    let mut child = match Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            return SolverOutput::from_error(
+                format!("Failed to spawn solver: {}", e),
+                path_id,
+                source,
+            )
+        }
+    };
+
+    // Closing stdin (by dropping it) tells the solver the query is complete
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(query.as_bytes()) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return SolverOutput::from_error(
+                format!("Failed to write query: {}", e),
+                path_id,
+                source,
+            );
+        }
+    }
+
+    if let Some(timeout_duration) = timeout {
+        match wait_timeout::ChildExt::wait_timeout(&mut child, timeout_duration) {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return SolverOutput {
+                    result: SatResult::Unknown,
+                    returncode: EXIT_TIMEDOUT,
+                    path_id,
+                    query_file: source,
+                    model: None,
+                    unsat_core: None,
+                    error: Some("Solver timeout".to_string()),
+                };
+            }
+            Err(e) => {
+                return SolverOutput::from_error(format!("Wait error: {}", e), path_id, source)
+            }
+        }
+    }
+
+    match child.wait_with_output() {
+        Ok(output) => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let returncode = output.status.code().unwrap_or(-1);
+            SolverOutput::from_result(&stdout, &stderr, returncode, path_id, source)
+        }
+        Err(e) => SolverOutput::from_error(format!("Wait error: {}", e), path_id, source),
+    }
+}
+
 /// Check if query contains unsat core
 pub fn check_unsat_cores(query: &SMTQuery, unsat_cores: &[Vec<String>]) -> bool {
     for core in unsat_cores {
@@ -508,20 +589,55 @@ mod tests {
 
     #[test]
     fn test_parse_const_value_binary() {
-        assert_eq!(parse_const_value("#b1010").unwrap(), 10);
-        assert_eq!(parse_const_value("#b11111111").unwrap(), 255);
+        assert_eq!(parse_const_value("#b1010").unwrap(), BigUint::from(10u32));
+        assert_eq!(
+            parse_const_value("#b11111111").unwrap(),
+            BigUint::from(255u32)
+        );
     }
 
     #[test]
     fn test_parse_const_value_hex() {
-        assert_eq!(parse_const_value("#xFF").unwrap(), 255);
-        assert_eq!(parse_const_value("#x10").unwrap(), 16);
+        assert_eq!(parse_const_value("#xFF").unwrap(), BigUint::from(255u32));
+        assert_eq!(parse_const_value("#x10").unwrap(), BigUint::from(16u32));
     }
 
     #[test]
     fn test_parse_const_value_decimal() {
-        assert_eq!(parse_const_value("bv42").unwrap(), 42);
-        assert_eq!(parse_const_value("(_ bv123 256)").unwrap(), 123);
+        assert_eq!(parse_const_value("bv42").unwrap(), BigUint::from(42u32));
+        assert_eq!(
+            parse_const_value("(_ bv123 256)").unwrap(),
+            BigUint::from(123u32)
+        );
+    }
+
+    #[test]
+    fn test_parse_const_value_above_u128() {
+        let max = BigUint::from(1u32) << 256;
+        let max = max - 1u32;
+        assert_eq!(
+            parse_const_value(&format!("#x{}", "f".repeat(64))).unwrap(),
+            max
+        );
+        assert_eq!(
+            parse_const_value(&format!("#b1{}", "0".repeat(200))).unwrap(),
+            BigUint::from(1u32) << 200
+        );
+        assert_eq!(
+            parse_const_value(&format!("(_ bv{} 256)", max)).unwrap(),
+            max
+        );
+    }
+
+    #[test]
+    fn test_parse_model_str_keeps_wide_values() {
+        let output = format!(
+            "sat\n(model\n  (define-fun halmos_x_uint256 () (_ BitVec 256)\n    #x1{})\n)",
+            "0".repeat(40)
+        );
+        let model = parse_model_str(&output);
+        let var = &model["halmos_x_uint256"];
+        assert_eq!(var.value, BigUint::from(1u32) << 160);
     }
 
     #[test]
@@ -532,9 +648,9 @@ mod tests {
             "uint256".to_string(),
             "BitVec 256".to_string(),
             256,
-            42,
+            BigUint::from(42u32),
         );
-        assert_eq!(var.value, 42);
+        assert_eq!(var.value, BigUint::from(42u32));
         assert_eq!(var.size_bits, 256);
     }
 
@@ -613,6 +729,14 @@ mod tests {
         assert!(output.model.is_none());
     }
 
+    #[test]
+    fn test_solve_stdin_missing_binary() {
+        let command = vec!["cbse-no-such-solver".to_string()];
+        let output = solve_stdin(&command, "(check-sat)\n", None, 0);
+        assert_eq!(output.result, SatResult::Error);
+        assert!(output.error.unwrap().contains("Failed to spawn solver"));
+    }
+
     #[test]
     fn test_solver_output_from_error() {
         let output = SolverOutput::from_error("test error".to_string(), 1, "test.smt2".to_string());
@@ -2,7 +2,9 @@
 
 //! Trace rendering and visualization
 
+use cbse_mapper::Mapper;
 use colored::*;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::fmt;
 use std::io::{self, Write};
@@ -11,7 +13,7 @@ use std::io::{self, Write};
 pub type Address = u64;
 
 /// Trace event types
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TraceEvent {
     Log,
     Sload,
@@ -21,13 +23,14 @@ pub enum TraceEvent {
 /// Event log entry
 #[derive(Debug, Clone)]
 pub struct EventLog {
-    pub address: Address,
+    /// Full 20-byte address of the emitting contract
+    pub address: [u8; 20],
     pub topics: Vec<Vec<u8>>,
     pub data: Vec<u8>,
 }
 
 impl EventLog {
-    pub fn new(address: Address, topics: Vec<Vec<u8>>, data: Vec<u8>) -> Self {
+    pub fn new(address: [u8; 20], topics: Vec<Vec<u8>>, data: Vec<u8>) -> Self {
         Self {
             address,
             topics,
@@ -37,17 +40,19 @@ impl EventLog {
 }
 
 /// Storage read operation
+///
+/// Slot and value are 32-byte big-endian words; symbolic words are zero.
 #[derive(Debug, Clone)]
 pub struct StorageRead {
-    pub slot: Address,
+    pub slot: Vec<u8>,
     pub value: Vec<u8>,
     pub transient: bool,
 }
 
-/// Storage write operation
+/// Storage write operation, encoded as for `StorageRead`
 #[derive(Debug, Clone)]
 pub struct StorageWrite {
-    pub slot: Address,
+    pub slot: Vec<u8>,
     pub value: Vec<u8>,
     pub transient: bool,
 }
@@ -140,6 +145,121 @@ impl CallContext {
     pub fn add_trace_element(&mut self, element: TraceElement) {
         self.trace.push(element);
     }
+
+    /// Whether the call returned normally (no error and not a REVERT)
+    pub fn is_success(&self) -> bool {
+        self.output.error.is_none() && self.output.return_scheme != Some(0xFD)
+    }
+
+    /// Render this call and its sub-calls as an indented tree, one line per call
+    ///
+    /// Addresses are shown by their deployed/labelled name and selectors by
+    /// the function name registered with `mapper`, when known.
+    pub fn render_tree(&self, mapper: &Mapper, depth: usize) -> String {
+        let message = &self.message;
+        let addr_str = format!("0x{:x}", message.target);
+        let target = mapper.deploy_addresses.get_deployed_contract(&addr_str);
+        let contract_name = (target != addr_str).then_some(target.as_str());
+
+        let call_str = if message.is_create() {
+            format!("<{} bytes of initcode>", byte_length(&message.data))
+        } else if message.data.len() < 4 {
+            hexify(&message.data)
+        } else {
+            let selector = hexify(&message.data[..4]);
+            let args = &message.data[4..];
+            let function = mapper.lookup_selector(&selector, contract_name);
+            if args.is_empty() {
+                format!("{}()", function)
+            } else {
+                format!("{}({})", function, hexify(args))
+            }
+        };
+
+        let value_str = if message.value > 0 {
+            format!(" (value: {})", message.value)
+        } else {
+            String::new()
+        };
+
+        let status = match (&self.output.error, self.is_success()) {
+            (Some(error), _) => format!("revert: {}", error),
+            (None, true) => "success".to_string(),
+            (None, false) => "revert".to_string(),
+        };
+
+        let mut tree = format!(
+            "{}{} {}::{}{} → {}\n",
+            "    ".repeat(depth),
+            mnemonic(message.call_scheme),
+            target,
+            call_str,
+            value_str,
+            status
+        );
+        for element in &self.trace {
+            if let TraceElement::Call(sub_call) = element {
+                tree.push_str(&sub_call.render_tree(mapper, depth + 1));
+            }
+        }
+        tree
+    }
+}
+
+/// Placeholder for values that were not concrete when the trace was recorded
+pub const SYMBOLIC_PLACEHOLDER: &str = "<symbolic>";
+
+/// Machine-readable projection of a `CallContext`, used for JSON output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TraceNode {
+    pub target: String,
+    pub caller: String,
+    pub value: u64,
+    pub call_scheme: String,
+    pub selector: Option<String>,
+    pub output: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub children: Vec<TraceNode>,
+}
+
+impl From<&CallContext> for TraceNode {
+    fn from(context: &CallContext) -> Self {
+        let message = &context.message;
+        let selector =
+            (!message.is_create() && message.data.len() >= 4).then(|| hexify(&message.data[..4]));
+        let output = match context.output.data {
+            Some(ref data) => hexify(data),
+            None => SYMBOLIC_PLACEHOLDER.to_string(),
+        };
+        let children = context
+            .trace
+            .iter()
+            .filter_map(|element| match element {
+                TraceElement::Call(sub_call) => Some(TraceNode::from(sub_call)),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            target: format!("0x{:x}", message.target),
+            caller: format!("0x{:x}", message.caller),
+            value: message.value,
+            call_scheme: mnemonic(message.call_scheme).to_string(),
+            selector,
+            output,
+            success: context.is_success(),
+            error: context.output.error.clone(),
+            children,
+        }
+    }
+}
+
+impl CallContext {
+    /// Serialize this call and its sub-calls as a JSON `TraceNode` tree
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&TraceNode::from(self)).expect("TraceNode is always serializable")
+    }
 }
 
 /// Call sequence
@@ -178,6 +298,7 @@ impl Default for DeployAddressMapper {
 /// Get mnemonic for opcode
 pub fn mnemonic(opcode: u8) -> &'static str {
     match opcode {
+        0x00 => "STOP",
         0xF0 => "CREATE",
         0xF1 => "CALL",
         0xF2 => "CALLCODE",
@@ -215,12 +336,20 @@ pub fn rendered_address(addr: Address, mapper: &DeployAddressMapper) -> String {
     mapper.get_deployed_contract(&addr_str)
 }
 
-/// Render slot value
-pub fn rendered_slot(slot: Address) -> String {
-    if slot < 0x10000 {
-        format!("{}", slot).magenta().to_string()
+/// Render a big-endian slot: decimal below 0x10000, hex otherwise
+pub fn rendered_slot(slot: &[u8]) -> String {
+    let start = slot.iter().position(|&b| b != 0).unwrap_or(slot.len());
+    let significant = &slot[start..];
+    if significant.len() <= 2 {
+        let value = significant
+            .iter()
+            .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+        format!("{}", value).magenta().to_string()
     } else {
-        format!("0x{:x}", slot).magenta().to_string()
+        let digits = hex::encode(significant);
+        format!("0x{}", digits.trim_start_matches('0'))
+            .magenta()
+            .to_string()
     }
 }
 
@@ -243,7 +372,7 @@ pub fn rendered_log(log: &EventLog) -> String {
 
 /// Render storage write
 pub fn rendered_sstore(update: &StorageWrite) -> String {
-    let slot_str = rendered_slot(update.slot);
+    let slot_str = rendered_slot(&update.slot);
     let opcode = if update.transient { "TSTORE" } else { "SSTORE" };
     format!(
         "{} @{} ← {}",
@@ -255,7 +384,7 @@ pub fn rendered_sstore(update: &StorageWrite) -> String {
 
 /// Render storage read
 pub fn rendered_sload(read: &StorageRead) -> String {
-    let slot_str = rendered_slot(read.slot);
+    let slot_str = rendered_slot(&read.slot);
     let opcode = if read.transient { "TLOAD" } else { "SLOAD" };
     format!("{} @{} → {}", opcode.cyan(), slot_str, hexify(&read.value))
 }
@@ -502,16 +631,27 @@ mod tests {
 
     #[test]
     fn test_rendered_slot_small() {
-        let slot = rendered_slot(42);
+        let slot = rendered_slot(&[42]);
         assert!(slot.contains("42"));
     }
 
     #[test]
     fn test_rendered_slot_large() {
-        let slot = rendered_slot(0x123456);
+        let slot = rendered_slot(&[0x12, 0x34, 0x56]);
         assert!(slot.contains("0x123456"));
     }
 
+    #[test]
+    fn test_rendered_slot_full_word() {
+        let mut word = vec![0u8; 32];
+        word[31] = 7;
+        assert!(rendered_slot(&word).contains('7'));
+
+        // Hashed slots, as used by mappings, keep all of their digits
+        let hashed = [0xabu8; 32];
+        assert!(rendered_slot(&hashed).contains(&"ab".repeat(32)));
+    }
+
     #[test]
     fn test_rendered_calldata_empty() {
         assert_eq!(rendered_calldata(&[], None), "0x");
@@ -539,9 +679,8 @@ mod tests {
 
     #[test]
     fn test_event_log() {
-        let address = 0x1234567890abcdefu64;
         let log = EventLog {
-            address,
+            address: [0x12; 20],
             topics: vec![vec![0x12, 0x34], vec![0x56, 0x78]],
             data: vec![0xAB, 0xCD],
         };
@@ -555,7 +694,7 @@ mod tests {
     #[test]
     fn test_storage_read() {
         let read = StorageRead {
-            slot: 42,
+            slot: vec![42],
             value: vec![0x12, 0x34],
             transient: false,
         };
@@ -568,7 +707,7 @@ mod tests {
     #[test]
     fn test_storage_read_transient() {
         let read = StorageRead {
-            slot: 42,
+            slot: vec![42],
             value: vec![0x12, 0x34],
             transient: true,
         };
@@ -579,7 +718,7 @@ mod tests {
     #[test]
     fn test_storage_write() {
         let write = StorageWrite {
-            slot: 10,
+            slot: vec![10],
             value: vec![0xFF, 0xEE],
             transient: false,
         };
@@ -592,7 +731,7 @@ mod tests {
     #[test]
     fn test_storage_write_transient() {
         let write = StorageWrite {
-            slot: 10,
+            slot: vec![10],
             value: vec![0xFF, 0xEE],
             transient: true,
         };
@@ -624,6 +763,92 @@ mod tests {
         assert!(!ctx.is_stuck());
     }
 
+    #[test]
+    fn test_render_tree_nested_calls() {
+        let mut mapper = Mapper::new();
+        mapper
+            .deploy_addresses
+            .add_deployed_contract("0xaaaa", "Vault");
+        mapper.add_node(
+            Some("Vault"),
+            cbse_mapper::AstNode::new(
+                "FunctionDefinition".to_string(),
+                "deposit".to_string(),
+                "0xd0e30db0".to_string(),
+            ),
+        );
+
+        let inner = CallContext::new(
+            CallMessage::new(
+                0xbbbb,
+                0xaaaa,
+                0,
+                vec![0x12, 0x34, 0x56, 0x78, 0x01],
+                0xFA,
+                true,
+            ),
+            CallOutput::new(Some(vec![]), None, Some(0xFD)),
+            2,
+        );
+        let mut outer = CallContext::new(
+            CallMessage::new(0xaaaa, 0x1, 5, vec![0xd0, 0xe3, 0x0d, 0xb0], 0xF1, false),
+            CallOutput::new(Some(vec![]), None, Some(0xF3)),
+            1,
+        );
+        outer.add_trace_element(TraceElement::Call(inner));
+
+        let tree = outer.render_tree(&mapper, 0);
+        let lines: Vec<&str> = tree.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "CALL Vault::deposit() (value: 5) → success",
+                "    STATICCALL 0xbbbb::0x12345678(0x01) → revert",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_call_context_to_json() {
+        let ctx = CallContext::new(
+            CallMessage::new(
+                0xaaaa,
+                0x1,
+                7,
+                vec![0xd0, 0xe3, 0x0d, 0xb0, 0x01],
+                0xF1,
+                false,
+            ),
+            CallOutput::new(Some(vec![0x2a]), None, Some(0xF3)),
+            1,
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&ctx.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "target": "0xaaaa",
+                "caller": "0x1",
+                "value": 7,
+                "call_scheme": "CALL",
+                "selector": "0xd0e30db0",
+                "output": "0x2a",
+                "success": true,
+                "error": null,
+                "children": [],
+            })
+        );
+    }
+
+    #[test]
+    fn test_trace_node_symbolic_output() {
+        let msg = CallMessage::new(0xaaaa, 0x1, 0, vec![], 0xF1, false);
+        let ctx = CallContext::new(msg, CallOutput::new(None, None, None), 1);
+        let node = TraceNode::from(&ctx);
+        assert_eq!(node.output, SYMBOLIC_PLACEHOLDER);
+        assert_eq!(node.selector, None);
+    }
+
     #[test]
     fn test_call_context_add_trace_element() {
         let msg = CallMessage::new(0, 0, 0, vec![], 0xF1, false);
@@ -632,9 +857,8 @@ mod tests {
 
         assert_eq!(ctx.trace.len(), 0);
 
-        let address = 0x1234567890abcdefu64;
         let log = EventLog {
-            address,
+            address: [0x12; 20],
             topics: vec![],
             data: vec![],
         };
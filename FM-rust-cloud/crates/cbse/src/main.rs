@@ -4,6 +4,7 @@
 //! Main entry point matching Python's halmos/__main__.py
 
 use anyhow::{Context as AnyhowContext, Result};
+use cbse_cheatcodes::{build_symbolic_calldata, parse_param_types};
 use cbse_config::Config;
 use cbse_constants::{
     VERBOSITY_TRACE_CONSTRUCTOR, VERBOSITY_TRACE_COUNTEREXAMPLE, VERBOSITY_TRACE_PATHS,
@@ -11,11 +12,13 @@ use cbse_constants::{
 };
 use cbse_contract::Contract;
 use cbse_protocol::{VerificationAttestation, VerificationResult};
-use cbse_sevm::SEVM;
-use cbse_traces::{render_trace, DeployAddressMapper, TraceEvent};
+use cbse_sevm::{
+    classify_paths, setup_sevm, PathResult, ProfileFormat, RevertReason, SEVM, TEST_ADDRESS,
+    TEST_CALLER,
+};
+use cbse_traces::{render_trace, TraceEvent};
 use clap::Parser;
 use colored::Colorize;
-use regex::Regex;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
@@ -53,6 +56,15 @@ fn _main() -> Result<MainResult> {
         });
     }
 
+    config.validate()?;
+
+    // Dump options that differ from their defaults
+    if config.debug_config {
+        for (field, default, value) in config.non_default_fields() {
+            eprintln!("[config] {} = {} (default: {})", field, value, default);
+        }
+    }
+
     // Handle worker mode (remote execution worker)
     if config.worker_mode {
         return run_worker_mode(&config);
@@ -100,8 +112,7 @@ fn _main() -> Result<MainResult> {
     let build_out = parse_build_out(&artifacts_path, &config)?;
 
     // Compile regex patterns for filtering
-    let contract_regex = make_contract_regex(&config)?;
-    let test_regex = make_test_regex(&config)?;
+    let filters = config.compile_filters()?;
 
     // Find and run test contracts
     let mut total_passed = 0;
@@ -114,7 +125,7 @@ fn _main() -> Result<MainResult> {
         for (filename, contracts_map) in files_map {
             for (contract_name, (contract_json, contract_type, _natspec)) in contracts_map {
                 // Filter by contract name regex
-                if !contract_regex.is_match(contract_name) {
+                if !filters.matches_contract(contract_name) {
                     continue;
                 }
 
@@ -131,7 +142,7 @@ fn _main() -> Result<MainResult> {
 
                 let test_functions: Vec<String> = method_identifiers
                     .keys()
-                    .filter(|name| test_regex.is_match(name))
+                    .filter(|name| filters.matches_test(name))
                     .cloned()
                     .collect();
 
@@ -199,6 +210,7 @@ fn _main() -> Result<MainResult> {
 
     // Handle Prover Mode
     if config.prover_mode {
+        // Each test's verdict already covers every path it explored
         let passed = total_failed == 0;
 
         let details = serde_json::to_string(&test_results_map).unwrap_or_default();
@@ -213,34 +225,34 @@ fn _main() -> Result<MainResult> {
             details,
         };
 
-        if let Some(key) = &config.private_key {
-            let attestation = VerificationAttestation::sign(
-                verification_result,
-                key,
-                env!("CARGO_PKG_VERSION").to_string(),
-            )
-            .map_err(|e| anyhow::anyhow!("Failed to sign attestation: {}", e))?;
-
-            // Print JSON attestation to stdout for the Coordinator to pick up
-            println!("{}", serde_json::to_string(&attestation).unwrap());
-
-            return Ok(MainResult {
-                exitcode: if passed { 0 } else { 1 },
-                total_passed,
-                total_failed,
-                total_found,
-                duration: start_time.elapsed(),
-            });
+        // Config::validate guarantees the key is present in prover mode
+        let key = config
+            .private_key
+            .as_deref()
+            .context("--prover-mode requires --private-key")?;
+        let attestation = VerificationAttestation::sign(
+            verification_result,
+            key,
+            env!("CARGO_PKG_VERSION").to_string(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to sign attestation: {}", e))?;
+
+        let attestation_json = serde_json::to_string(&attestation)?;
+        if let Some(json_path) = &config.json_output {
+            fs::write(json_path, &attestation_json)?;
+            eprintln!("Attestation written to: {}", json_path.display());
         } else {
-            eprintln!("{}", "Error: --prover-mode requires --private-key".red());
-            return Ok(MainResult {
-                exitcode: 1,
-                total_passed: 0,
-                total_failed: 0,
-                total_found: 0,
-                duration: start_time.elapsed(),
-            });
+            // Print JSON attestation to stdout for the Coordinator to pick up
+            println!("{}", attestation_json);
         }
+
+        return Ok(MainResult {
+            exitcode: if passed { 0 } else { 1 },
+            total_passed,
+            total_failed,
+            total_found,
+            duration: start_time.elapsed(),
+        });
     }
 
     // Print summary
@@ -304,21 +316,8 @@ fn run_contract_tests(
     let contract = Contract::from_hexcode(bytecode_hex, &ctx)
         .context("Failed to create contract from bytecode")?;
 
-    // Initialize SEVM
-    let mut sevm = SEVM::new(&ctx);
-
-    // Deploy test contract at Foundry test address
-    let test_address: [u8; 20] = [
-        0x7F, 0xA9, 0x38, 0x5b, 0xE1, 0x02, 0xac, 0x3E, 0xAc, 0x29, 0x74, 0x83, 0xDd, 0x62, 0x33,
-        0xD6, 0x2b, 0x3e, 0x14, 0x96,
-    ];
-    sevm.deploy_contract(test_address, contract);
-
-    // Caller address (Foundry caller)
-    let caller_address: [u8; 20] = [
-        0x18, 0x04, 0xc8, 0xAB, 0x1F, 0x12, 0xE6, 0xbb, 0xf3, 0x89, 0x4d, 0x40, 0x83, 0xf3, 0x3e,
-        0x07, 0x30, 0x9d, 0x1f, 0x38,
-    ];
+    // Initialize SEVM with the test contract deployed at the Foundry test address
+    let mut sevm = setup_sevm(config, contract)?;
 
     // Run each test function
     for test_name in test_functions {
@@ -341,27 +340,59 @@ fn run_contract_tests(
             ))?;
 
         // Convert selector to bytes (first 4 bytes of calldata)
-        let selector_bytes =
-            hex::decode(selector_str).context("Failed to decode function selector")?;
-
-        // Build calldata: selector + encoded parameters (empty for parameterless tests)
-        let mut calldata = selector_bytes;
-        // TODO: For fuzz tests, generate symbolic parameters here
-
-        // Execute the test function with SEVM
-        let exec_result = sevm.execute_call(
-            test_address,
-            caller_address,
-            caller_address, // origin = caller for top-level calls
-            0,              // value
-            calldata.clone(),
-            u64::MAX, // unlimited gas
-            false,    // not static
-        );
+        let selector: [u8; 4] = hex::decode(selector_str)
+            .context("Failed to decode function selector")?
+            .try_into()
+            .ok()
+            .context(format!("Function selector {} is not 4 bytes", selector_str))?;
+
+        // Build calldata: selector + a fresh symbol per parameter, once per
+        // bytes length if the test takes dynamic parameters
+        let param_types = parse_param_types(test_name)?;
+        let calldatas =
+            build_symbolic_calldata(selector, &param_types, 0, &sevm.bytes_lengths, &ctx)?;
+
+        // Execute the test function with SEVM; the first calldata that fails
+        // decides the result
+        let mut exec_result = Ok(Vec::new());
+        for calldata in calldatas {
+            sevm.reset_stats();
+            sevm.inconclusive = false;
+            sevm.counterexample = None;
+            exec_result = sevm.explore(
+                TEST_ADDRESS,
+                TEST_CALLER,
+                TEST_CALLER,        // origin = caller for top-level calls
+                Default::default(), // value
+                calldata,
+                sevm.gas_limit,
+                false, // not static
+            );
+            if config.statistics {
+                println!("{}", sevm.run_stats());
+            }
+            let failed = match &exec_result {
+                Ok(paths) => !classify_paths(&sevm, paths).0,
+                Err(_) => true,
+            };
+            if failed {
+                break;
+            }
+        }
 
-        // Analyze execution results
+        // Analyze execution results; the verdict covers every path, and the
+        // first failing one (or else the first path) is reported
         let (exitcode, num_paths) = match exec_result {
-            Ok((success, returndata, gas_used, call_context)) => {
+            Ok(paths) => {
+                let (passed, reported) = classify_paths(&sevm, &paths);
+                let num_paths = paths.len();
+                let PathResult {
+                    success,
+                    return_data: returndata,
+                    gas_used,
+                    context: call_context,
+                    ..
+                } = &paths[reported];
                 if config.verbose >= 2 {
                     println!(
                         "    Success: {}, Gas: {}, Return: {} bytes",
@@ -374,17 +405,36 @@ fn run_contract_tests(
                 // Check for assertion failures in returndata
                 // Solidity assertions revert with Panic(uint256)
                 // Panic codes: 0x01 = assert(false), 0x11 = arithmetic overflow, etc.
-                let has_panic = check_for_panic(&returndata, config);
+                // Only the configured codes count as failures
+                let panic_code =
+                    RevertReason::decode(returndata).and_then(|reason| reason.panic_code());
+                let has_panic = panic_code.map_or(false, |code| sevm.panic_codes.matches(code));
+                if has_panic && config.verbose >= 2 {
+                    println!("    Panic code: 0x{:02x}", panic_code.unwrap_or_default());
+                }
+
+                // vm.assert* cheatcodes record a counterexample without reverting
+                let failed_assertion = has_panic || sevm.counterexample.is_some();
 
                 // Determine result and render trace on failure
-                let (exitcode, should_show_trace) = if success && !has_panic {
+                let (exitcode, should_show_trace) = if passed {
                     (Exitcode::Pass as i32, false)
-                } else if has_panic {
+                } else if failed_assertion && sevm.inconclusive {
                     if config.verbose >= 1 {
-                        println!("    {} Assertion failed (Panic detected)", "✗".red());
-                        if returndata.len() >= 36 {
-                            let panic_code = returndata[35];
-                            println!("    Panic code: 0x{:02x}", panic_code);
+                        println!(
+                            "    {} Assertion failure unconfirmed (solver timeout or unknown)",
+                            "?".yellow()
+                        );
+                    }
+                    (Exitcode::Timeout as i32, true)
+                } else if failed_assertion {
+                    if config.verbose >= 1 {
+                        println!("    {} Assertion failed", "✗".red());
+                        if let Some(code) = panic_code.filter(|_| has_panic) {
+                            println!("    Panic code: 0x{:02x}", code);
+                        }
+                        if let Some(counterexample) = &sevm.counterexample {
+                            println!("    {}", counterexample);
                         }
                     }
                     (Exitcode::Counterexample as i32, true)
@@ -401,12 +451,13 @@ fn run_contract_tests(
                     || config.verbose >= VERBOSITY_TRACE_PATHS
                 {
                     println!("    {}", "Trace:".cyan());
-                    let mapper = DeployAddressMapper::new();
                     let trace_events = vec![TraceEvent::Sload, TraceEvent::Sstore, TraceEvent::Log];
-                    let _ = render_trace(&call_context, &mapper, &trace_events, &mut io::stdout());
+                    let _ =
+                        render_trace(call_context, &sevm.labels, &trace_events, &mut io::stdout());
                 }
 
-                (exitcode, (1, 1, 0))
+                let num_success = paths.iter().filter(|path| path.success).count();
+                (exitcode, (num_paths, num_success, 0))
             }
             Err(e) => {
                 if config.verbose >= 1 {
@@ -436,50 +487,18 @@ fn run_contract_tests(
         results.push(test_result);
     }
 
-    Ok(results)
-}
-
-/// Check if returndata contains a Panic error
-fn check_for_panic(returndata: &[u8], config: &Config) -> bool {
-    // Panic selector is 0x4e487b71 (keccak256("Panic(uint256)")[:4])
-    const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
-
-    if returndata.len() < 36 {
-        return false;
+    if config.flamegraph {
+        sevm.write_flamegraph(format!("{}.folded", contract_name))?;
     }
 
-    // Check if selector matches Panic
-    if &returndata[0..4] != PANIC_SELECTOR {
-        return false;
-    }
-
-    // Extract panic code (next 32 bytes as uint256)
-    let panic_code = if returndata.len() >= 36 {
-        // Read last byte of the uint256 (most codes are < 256)
-        returndata[35]
-    } else {
-        return false;
-    };
-
-    // Parse panic error codes from config
-    // Default is "0x01" (assertion failure)
-    let panic_codes: Vec<u8> = config
-        .panic_error_codes
-        .split(',')
-        .filter_map(|s| {
-            let trimmed = s.trim().strip_prefix("0x").unwrap_or(s.trim());
-            u8::from_str_radix(trimmed, 16).ok()
-        })
-        .collect();
-
-    // Check if panic code matches configured codes
-    let matches = panic_codes.contains(&panic_code);
-
-    if matches && config.verbose >= 2 {
-        println!("    Panic code: 0x{:02x}", panic_code);
+    if config.profile_instructions {
+        sevm.write_instruction_profile(
+            format!("{}.profile.json", contract_name),
+            ProfileFormat::Json,
+        )?;
     }
 
-    matches
+    Ok(results)
 }
 
 /// Parse build output directory (matches Python parse_build_out)
@@ -593,30 +612,6 @@ fn get_contract_type_from_ast(ast: &Value, contract_name: &str) -> (Option<Strin
     (None, None)
 }
 
-/// Build contract name matching regex
-fn make_contract_regex(config: &Config) -> Result<Regex> {
-    let pattern = if !config.contract.is_empty() {
-        format!("^{}$", regex::escape(&config.contract))
-    } else if !config.match_contract.is_empty() {
-        config.match_contract.clone()
-    } else {
-        ".*".to_string()
-    };
-
-    Ok(Regex::new(&pattern)?)
-}
-
-/// Build test function matching regex
-fn make_test_regex(config: &Config) -> Result<Regex> {
-    let pattern = if !config.match_test.is_empty() {
-        config.match_test.clone()
-    } else {
-        config.function.clone()
-    };
-
-    Ok(Regex::new(&pattern)?)
-}
-
 /// Run forge build command
 fn run_forge_build(config: &Config) -> Result<()> {
     let mut cmd = Command::new("forge");
@@ -764,8 +759,7 @@ fn run_ssh_mode(config: &Config, start_time: Instant) -> Result<MainResult> {
     let build_out = parse_build_out(&artifacts_path, config)?;
 
     // Compile regex patterns
-    let contract_regex = make_contract_regex(config)?;
-    let test_regex = make_test_regex(config)?;
+    let filters = config.compile_filters()?;
 
     // Collect contracts and tests to run
     let mut job_artifact = JobArtifact::new();
@@ -774,7 +768,7 @@ fn run_ssh_mode(config: &Config, start_time: Instant) -> Result<MainResult> {
     for (_compiler_version, files_map) in &build_out {
         for (filename, contracts_map) in files_map {
             for (contract_name, (contract_json, contract_type, _natspec)) in contracts_map {
-                if !contract_regex.is_match(contract_name) {
+                if !filters.matches_contract(contract_name) {
                     continue;
                 }
                 if contract_type != "contract" {
@@ -789,7 +783,7 @@ fn run_ssh_mode(config: &Config, start_time: Instant) -> Result<MainResult> {
 
                 let test_functions: Vec<String> = method_identifiers
                     .keys()
-                    .filter(|name| test_regex.is_match(name))
+                    .filter(|name| filters.matches_test(name))
                     .cloned()
                     .collect();
 
@@ -1021,7 +1015,7 @@ fn run_worker_mode(config: &Config) -> Result<MainResult> {
                 test_address,
                 caller_address,
                 caller_address,
-                0,
+                Default::default(),
                 selector_bytes,
                 u64::MAX,
                 false,
@@ -1069,12 +1063,11 @@ fn run_worker_mode(config: &Config) -> Result<MainResult> {
                         // Print trace if requested
                         if verbose >= 2 || exec_config.print_states {
                             println!("    {}", "Trace:".cyan());
-                            let mapper = DeployAddressMapper::new();
                             let trace_events =
                                 vec![TraceEvent::Sload, TraceEvent::Sstore, TraceEvent::Log];
                             let _ = render_trace(
                                 &call_context,
-                                &mapper,
+                                &sevm.labels,
                                 &trace_events,
                                 &mut io::stdout(),
                             );
@@ -1089,12 +1082,11 @@ fn run_worker_mode(config: &Config) -> Result<MainResult> {
                         // Print trace for reverts if requested
                         if verbose >= 2 || exec_config.print_failed_states {
                             println!("    {}", "Trace:".cyan());
-                            let mapper = DeployAddressMapper::new();
                             let trace_events =
                                 vec![TraceEvent::Sload, TraceEvent::Sstore, TraceEvent::Log];
                             let _ = render_trace(
                                 &call_context,
-                                &mapper,
+                                &sevm.labels,
                                 &trace_events,
                                 &mut io::stdout(),
                             );